@@ -13,4 +13,10 @@ pub struct AddLiquidityArgs {
     pub token_1: String,
     pub amount_1: Nat,
     pub tx_id_1: Option<TxId>,
+    pub tick_lower: Option<i32>,  // Concentrated pools - lower tick of a new position
+    pub tick_upper: Option<i32>,  // Concentrated pools - upper tick of a new position
+    pub position_id: Option<u64>, // Concentrated pools - add to an existing position of the user
+    // minimum LP token amount to receive, liquidity for Concentrated pools. fails and returns the tokens if below
+    pub min_lp_token_amount: Option<Nat>,
+    pub deadline: Option<u64>, // timestamp in nanoseconds. fails and returns the tokens if executed after the deadline
 }
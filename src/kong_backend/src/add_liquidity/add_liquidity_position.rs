@@ -0,0 +1,132 @@
+use candid::Nat;
+
+use super::add_liquidity_args::AddLiquidityArgs;
+use super::add_liquidity_transfer_from::check_min_lp_token_amount;
+
+use crate::helpers::concentrated_helpers::{amounts_for_liquidity, liquidity_for_amounts, sqrt_price_at_tick};
+use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_subtract};
+use crate::helpers::pool_helpers::{check_pool_action, PoolAction};
+use crate::stable_pool::concentrated_pool::{check_ticks, liquidity_delta, update_position};
+use crate::stable_pool::{pool_map, stable_pool::StablePool};
+use crate::stable_position::{position_map, stable_position::StablePosition};
+use crate::stable_request::{request_map, status::StatusCode};
+
+/// tick range of the position to add liquidity to for Concentrated pools
+/// either position_id of an existing position of the user or tick_lower and tick_upper for a new position
+fn position_ticks(pool: &StablePool, args: &AddLiquidityArgs, user_id: Option<u32>) -> Result<(i32, i32), String> {
+    let (tick_lower, tick_upper) = match args.position_id {
+        Some(position_id) => {
+            if args.tick_lower.is_some() || args.tick_upper.is_some() {
+                Err("Specify either position_id or tick_lower and tick_upper".to_string())?
+            }
+            let position = position_map::get_by_position_id(position_id)
+                .filter(|position| Some(position.user_id) == user_id && position.pool_id == pool.pool_id)
+                .ok_or(format!("Position #{} not found", position_id))?;
+            (position.tick_lower, position.tick_upper)
+        }
+        None => match (args.tick_lower, args.tick_upper) {
+            (Some(tick_lower), Some(tick_upper)) => (tick_lower, tick_upper),
+            _ => Err("Tick_lower and tick_upper are required for concentrated liquidity pools".to_string())?,
+        },
+    };
+    check_ticks(pool, tick_lower, tick_upper)?;
+    Ok((tick_lower, tick_upper))
+}
+
+/// calculate the amounts (amount_0 and amount_1) to be added to the position at the current price of the pool
+/// calculate the liquidity for the position
+///
+/// returns (pool, amount_0, amount_1, liquidity)
+pub fn calculate_position_amounts(
+    pool: StablePool,
+    args: &AddLiquidityArgs,
+    amount_0: &Nat,
+    amount_1: &Nat,
+    user_id: Option<u32>,
+) -> Result<(StablePool, Nat, Nat, Nat), String> {
+    check_pool_action(pool.suspended_at, PoolAction::AddLiquidity)?;
+
+    let (tick_lower, tick_upper) = position_ticks(&pool, args, user_id)?;
+    let sqrt_price = &pool.concentrated.as_ref().ok_or("Pool price not initialized")?.sqrt_price_x96;
+    let sqrt_price_lower = sqrt_price_at_tick(tick_lower).ok_or("Invalid tick_lower")?;
+    let sqrt_price_upper = sqrt_price_at_tick(tick_upper).ok_or("Invalid tick_upper")?;
+
+    let mut liquidity =
+        liquidity_for_amounts(sqrt_price, &sqrt_price_lower, &sqrt_price_upper, amount_0, amount_1).ok_or("Invalid liquidity")?;
+    let (mut add_amount_0, mut add_amount_1) =
+        amounts_for_liquidity(sqrt_price, &sqrt_price_lower, &sqrt_price_upper, &liquidity, true).ok_or("Invalid liquidity")?;
+    // amounts are rounded up in favour of the pool which can go over the user amounts by a unit
+    if add_amount_0 > *amount_0 || add_amount_1 > *amount_1 {
+        liquidity = nat_subtract(&liquidity, &Nat::from(1_u8)).ok_or("Invalid liquidity")?;
+        (add_amount_0, add_amount_1) =
+            amounts_for_liquidity(sqrt_price, &sqrt_price_lower, &sqrt_price_upper, &liquidity, true).ok_or("Invalid liquidity")?;
+        if add_amount_0 > *amount_0 || add_amount_1 > *amount_1 {
+            Err("Incorrect ratio of amount_0 and amount_1".to_string())?
+        }
+    }
+    if nat_is_zero(&liquidity) {
+        Err("Insufficient amounts for the tick range".to_string())?
+    }
+
+    Ok((pool, add_amount_0, add_amount_1, liquidity))
+}
+
+/// update the Concentrated pool and the user's position with the new liquidity amounts
+/// ensure we have the latest state of the pool before adding the new amounts
+///
+/// returns (pool, amount_0, amount_1, liquidity, position_id)
+pub fn update_position_liquidity(
+    request_id: u64,
+    user_id: u32,
+    pool: &StablePool,
+    args: &AddLiquidityArgs,
+    add_amount_0: &Nat,
+    add_amount_1: &Nat,
+    ts: u64,
+) -> Result<(StablePool, Nat, Nat, Nat, Option<u64>), String> {
+    request_map::update_status(request_id, StatusCode::CalculatePoolAmounts, None);
+
+    let pool = pool_map::get_by_pool_id(pool.pool_id).ok_or("Pool not found")?;
+    let (mut pool, amount_0, amount_1, liquidity) = match calculate_position_amounts(pool, args, add_amount_0, add_amount_1, Some(user_id))
+    {
+        Ok(amounts) => {
+            request_map::update_status(request_id, StatusCode::CalculatePoolAmountsSuccess, None);
+            amounts
+        }
+        Err(e) => {
+            request_map::update_status(request_id, StatusCode::CalculatePoolAmountsFailed, Some(&e));
+            Err(e)?
+        }
+    };
+    check_min_lp_token_amount(request_id, args, &liquidity)?;
+
+    request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmount, None);
+    let mut position = match args.position_id {
+        Some(position_id) => position_map::get_by_position_id(position_id).ok_or(format!("Position #{} not found", position_id))?,
+        None => {
+            let (tick_lower, tick_upper) = position_ticks(&pool, args, Some(user_id))?;
+            StablePosition::new(user_id, pool.pool_id, tick_lower, tick_upper, ts)
+        }
+    };
+    if let Err(e) = update_position(&mut pool, &mut position, &liquidity_delta(&liquidity, true), ts) {
+        request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountFailed, Some(&e));
+        Err(e)?
+    }
+    let position_id = match args.position_id {
+        Some(position_id) => {
+            position_map::update(&position);
+            position_id
+        }
+        None => position_map::insert(&position),
+    };
+    request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None);
+
+    request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
+    pool.balance_0 = nat_add(&pool.balance_0, &amount_0);
+    pool.balance_1 = nat_add(&pool.balance_1, &amount_1);
+    pool.set_tvl();
+    pool_map::update(&pool);
+    request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
+
+    Ok((pool, amount_0, amount_1, liquidity, Some(position_id)))
+}
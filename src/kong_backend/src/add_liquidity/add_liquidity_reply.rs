@@ -20,6 +20,7 @@ pub struct AddLiquidityReply {
     pub symbol_1: String,
     pub amount_1: Nat,
     pub add_lp_token_amount: Nat,
+    pub position_id: Option<u64>,
     pub transfer_ids: Vec<TransferIdReply>,
     pub claim_ids: Vec<u64>,
     pub ts: u64,
@@ -49,6 +49,7 @@ pub fn to_add_liquidity_reply(add_liquidity_tx: &AddLiquidityTx) -> AddLiquidity
         symbol_1,
         amount_1: add_liquidity_tx.amount_1.clone(),
         add_lp_token_amount: add_liquidity_tx.add_lp_token_amount.clone(),
+        position_id: add_liquidity_tx.position_id,
         transfer_ids: to_transfer_ids(&add_liquidity_tx.transfer_ids),
         claim_ids: add_liquidity_tx.claim_ids.clone(),
         ts: add_liquidity_tx.ts,
@@ -71,6 +72,7 @@ pub fn to_add_liquidity_reply_failed(pool_id: u32, request_id: u64, transfer_ids
         symbol_1,
         amount_1: nat_zero(),
         add_lp_token_amount: nat_zero(),
+        position_id: None,
         transfer_ids: to_transfer_ids(transfer_ids),
         claim_ids: claim_ids.to_vec(),
         ts,
@@ -0,0 +1,338 @@
+use candid::Nat;
+use ic_cdk::update;
+
+use super::add_liquidity::TokenIndex;
+use super::add_liquidity_args::AddLiquidityArgs;
+use super::add_liquidity_reply::AddLiquidityReply;
+use super::add_liquidity_reply_helpers::{to_add_liquidity_reply, to_add_liquidity_reply_failed};
+use super::add_liquidity_single_args::AddLiquiditySingleArgs;
+use super::add_liquidity_transfer_from::{
+    archive_to_kong_data, calculate_pool_amounts, check_min_lp_token_amount, return_tokens, transfer_from_token, update_liquidity_pool,
+};
+
+use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_zero};
+use crate::ic::{get_time::get_time, guards::not_in_maintenance_mode, id::caller_id};
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_pool::{pool_map, stable_pool::StablePool};
+use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{token::Token, token_map};
+use crate::stable_tx::{add_liquidity_tx::AddLiquidityTx, stable_tx::StableTx, tx_map};
+use crate::stable_user::user_map;
+use crate::swap::swap_amounts::pool_swap_amounts;
+use crate::swap::swap_calc::SwapCalc;
+use crate::swap::update_liquidity_pool::{apply_swaps, swap_pool_balances};
+
+/// Add liquidity to a pool with a single token
+///
+/// - part of the amount is swapped through the pool for the other token and both are added to the pool
+///   in the same request
+/// - before calling add_liquidity_single, the user must create an icrc2_approve_transaction for the token to
+///   allow the backend canister to icrc2_transfer_from. Note, the approve transaction must account for the gas fee
+///
+/// Arguments: AddLiquiditySingleArgs
+///  token: token to add eg. "ckBTC". must be token_0 or token_1 of the pool
+///  amount: amount of the token to add (nat) eg. 100_000_000 is 1 ckBTC
+///  pool: pool to add liquidity to eg. "ckBTC_ckUSDT"
+///
+/// Returns: AddLiquidityReply
+///  amount_0 and amount_1 are the amounts of token_0 and token_1 added to the pool after the swap
+#[update(guard = "not_in_maintenance_mode")]
+pub async fn add_liquidity_single(args: AddLiquiditySingleArgs) -> Result<AddLiquidityReply, String> {
+    let (user_id, pool, token_index) = check_arguments(&args)?;
+    let ts = get_time();
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::AddLiquiditySingle(args.clone()), ts));
+
+    let result = match process_add_liquidity_single(request_id, user_id, &pool, &token_index, &args, ts).await {
+        Ok(reply) => {
+            request_map::update_status(request_id, StatusCode::Success, None);
+            Ok(reply)
+        }
+        Err(e) => {
+            request_map::update_status(request_id, StatusCode::Failed, None);
+            Err(e)
+        }
+    };
+    _ = archive_to_kong_data(request_id);
+
+    result
+}
+
+fn check_arguments(args: &AddLiquiditySingleArgs) -> Result<(u32, StablePool, TokenIndex), String> {
+    if nat_is_zero(&args.amount) {
+        Err("Invalid zero amount".to_string())?
+    }
+
+    let pool = pool_map::get_by_token(&args.pool)?;
+    if pool.curve.is_concentrated() {
+        Err("Concentrated liquidity pools require a tick range".to_string())?
+    }
+
+    let token = token_map::get_by_token(&args.token)?;
+    let token_index = if token.token_id() == pool.token_id_0 {
+        TokenIndex::Token0
+    } else if token.token_id() == pool.token_id_1 {
+        TokenIndex::Token1
+    } else {
+        Err(format!("Token {} is not in pool {}", args.token, args.pool))?
+    };
+    if !token.is_icrc2() {
+        Err("Token must support ICRC2".to_string())?
+    }
+    if pool.token_0().is_removed() {
+        Err("Token_0 is suspended or removed".to_string())?
+    }
+    if pool.token_1().is_removed() {
+        Err("Token_1 is suspended or removed".to_string())?
+    }
+
+    // calculate the amounts with the current state of the pool
+    let (_, _, _, add_lp_token_amount) = calculate_single_amounts(&pool, &token_index, &args.amount)?;
+    if let Some(min_lp_token_amount) = &args.min_lp_token_amount {
+        if add_lp_token_amount < *min_lp_token_amount {
+            Err(format!(
+                "LP token amount {} below minimum {}",
+                add_lp_token_amount, min_lp_token_amount
+            ))?
+        }
+    }
+
+    // make sure user is registered, if not create a new user
+    let user_id = user_map::insert(None)?;
+
+    Ok((user_id, pool, token_index))
+}
+
+/// calculate the amount to swap so that the amount left and the amount received are at the ratio of the pool after the swap
+///
+/// returns (swap, add_amount_0, add_amount_1, add_lp_token_amount)
+fn calculate_single_amounts(pool: &StablePool, token_index: &TokenIndex, amount: &Nat) -> Result<(SwapCalc, Nat, Nat, Nat), String> {
+    if nat_is_zero(&nat_add(&pool.balance_0, &pool.lp_fee_0)) || nat_is_zero(&nat_add(&pool.balance_1, &pool.lp_fee_1)) {
+        Err("Pool has no liquidity".to_string())?
+    }
+
+    // the more that is swapped, the less is left of the paid token and the more of it is in the pool
+    // binary search for the largest swap amount that still leaves the paid token at or above the ratio of the pool
+    let mut low = nat_zero();
+    let mut high = amount.clone();
+    while nat_add(&low, &Nat::from(1_u8)) < high {
+        let mid = nat_divide(&nat_add(&low, &high), &Nat::from(2_u8)).ok_or("Invalid swap amount")?;
+        let (_, add_amount_0, add_amount_1, swap_pool) = swap_single_amount(pool, token_index, amount, &mid)?;
+        let reserve_0 = nat_add(&swap_pool.balance_0, &swap_pool.lp_fee_0);
+        let reserve_1 = nat_add(&swap_pool.balance_1, &swap_pool.lp_fee_1);
+        let swap_more = match token_index {
+            TokenIndex::Token0 => nat_multiply(&add_amount_0, &reserve_1) >= nat_multiply(&add_amount_1, &reserve_0),
+            TokenIndex::Token1 => nat_multiply(&add_amount_1, &reserve_0) >= nat_multiply(&add_amount_0, &reserve_1),
+        };
+        if swap_more {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    let (swap, add_amount_0, add_amount_1, swap_pool) = swap_single_amount(pool, token_index, amount, &low)?;
+    let (_, _, _, add_lp_token_amount) = calculate_pool_amounts(swap_pool, &add_amount_0, &add_amount_1)?;
+    if nat_is_zero(&add_lp_token_amount) {
+        Err("Amount is too small to add liquidity".to_string())?
+    }
+
+    Ok((swap, add_amount_0, add_amount_1, add_lp_token_amount))
+}
+
+/// swap swap_amount of amount through the pool
+///
+/// returns (swap, amount_0, amount_1, pool) where amount_0 and amount_1 are the amounts held after the swap
+fn swap_single_amount(
+    pool: &StablePool,
+    token_index: &TokenIndex,
+    amount: &Nat,
+    swap_amount: &Nat,
+) -> Result<(SwapCalc, Nat, Nat, StablePool), String> {
+    let swap = match token_index {
+        TokenIndex::Token0 => pool_swap_amounts(pool, true, swap_amount)?,
+        TokenIndex::Token1 => pool_swap_amounts(pool, false, swap_amount)?,
+    };
+    let mut swap_pool = pool.clone();
+    swap_pool_balances(&mut swap_pool, &swap);
+    let amount_left = nat_subtract(amount, swap_amount).ok_or("Invalid swap amount")?;
+    let receive_amount = swap.receive_amount_with_fees_and_gas();
+    let (amount_0, amount_1) = match token_index {
+        TokenIndex::Token0 => (amount_left, receive_amount),
+        TokenIndex::Token1 => (receive_amount, amount_left),
+    };
+
+    Ok((swap, amount_0, amount_1, swap_pool))
+}
+
+async fn process_add_liquidity_single(
+    request_id: u64,
+    user_id: u32,
+    pool: &StablePool,
+    token_index: &TokenIndex,
+    args: &AddLiquiditySingleArgs,
+    ts: u64,
+) -> Result<AddLiquidityReply, String> {
+    let token = match token_index {
+        TokenIndex::Token0 => pool.token_0(),
+        TokenIndex::Token1 => pool.token_1(),
+    };
+    // amounts to return to the user if anything fails before the swap
+    let (return_amount_0, return_amount_1) = match token_index {
+        TokenIndex::Token0 => (Some(&args.amount), None),
+        TokenIndex::Token1 => (None, Some(&args.amount)),
+    };
+
+    let caller_id = caller_id();
+    let kong_backend = kong_settings_map::get().kong_backend;
+    let mut transfer_ids = Vec::new();
+
+    request_map::update_status(request_id, StatusCode::Start, None);
+
+    if args.deadline.is_some_and(|deadline| get_time() > deadline) {
+        request_map::update_status(request_id, StatusCode::DeadlineExceeded, None);
+        return Err(format!("Req #{} failed. Deadline exceeded", request_id));
+    }
+
+    // transfer_from the token. if this fails, nothing to return so just return the error
+    transfer_from_token(
+        request_id,
+        &caller_id,
+        token_index,
+        &token,
+        &args.amount,
+        &kong_backend,
+        &mut transfer_ids,
+        ts,
+    )
+    .await
+    .map_err(|e| format!("Token transfer_from failed. {}", e))?;
+
+    // deadline could have passed while waiting for transfer_from. return the token back to user
+    if args.deadline.is_some_and(|deadline| get_time() > deadline) {
+        request_map::update_status(request_id, StatusCode::DeadlineExceeded, None);
+        return_tokens(
+            request_id,
+            user_id,
+            &caller_id,
+            pool,
+            return_amount_0,
+            return_amount_1,
+            &mut transfer_ids,
+            ts,
+        )
+        .await;
+        return Err(format!("Req #{} failed. Deadline exceeded", request_id));
+    }
+
+    // re-calculate the swap with the latest state of the pool
+    request_map::update_status(request_id, StatusCode::CalculatePoolAmounts, None);
+    let pool = &pool_map::get_by_pool_id(pool.pool_id).unwrap_or(pool.clone());
+    let (swap, add_amount_0, add_amount_1, add_lp_token_amount) = match calculate_single_amounts(pool, token_index, &args.amount) {
+        Ok(amounts) => {
+            request_map::update_status(request_id, StatusCode::CalculatePoolAmountsSuccess, None);
+            amounts
+        }
+        Err(e) => {
+            request_map::update_status(request_id, StatusCode::CalculatePoolAmountsFailed, Some(&e));
+            return_tokens(
+                request_id,
+                user_id,
+                &caller_id,
+                pool,
+                return_amount_0,
+                return_amount_1,
+                &mut transfer_ids,
+                ts,
+            )
+            .await;
+            return Err(format!("Req #{} failed. {}", request_id, e));
+        }
+    };
+
+    let add_args = AddLiquidityArgs {
+        token_0: pool.token_0().address_with_chain(),
+        amount_0: add_amount_0.clone(),
+        tx_id_0: None,
+        token_1: pool.token_1().address_with_chain(),
+        amount_1: add_amount_1.clone(),
+        tx_id_1: None,
+        tick_lower: None,
+        tick_upper: None,
+        position_id: None,
+        min_lp_token_amount: args.min_lp_token_amount.clone(),
+        deadline: args.deadline,
+    };
+    // check the minimum before the swap so the token can still be returned as paid
+    if let Err(e) = check_min_lp_token_amount(request_id, &add_args, &add_lp_token_amount) {
+        return_tokens(
+            request_id,
+            user_id,
+            &caller_id,
+            pool,
+            return_amount_0,
+            return_amount_1,
+            &mut transfer_ids,
+            ts,
+        )
+        .await;
+        return Err(format!("Req #{} failed. {}", request_id, e));
+    }
+
+    // swap part of the token through the pool. the received amount stays in Kong to be added to the pool
+    apply_swaps(request_id, &[swap]);
+    request_map::update_status(request_id, StatusCode::SwapSuccess, None);
+
+    let (mut pool, amount_0, amount_1, add_lp_token_amount, position_id) =
+        match update_liquidity_pool(request_id, user_id, pool, &add_args, &add_amount_0, &add_amount_1, ts) {
+            Ok((pool, amount_0, amount_1, add_lp_token_amount, position_id)) => {
+                (pool, amount_0, amount_1, add_lp_token_amount, position_id)
+            }
+            Err(e) => {
+                // LP amounts are incorrect. return both tokens held after the swap back to user
+                return_tokens(
+                    request_id,
+                    user_id,
+                    &caller_id,
+                    pool,
+                    Some(&add_amount_0),
+                    Some(&add_amount_1),
+                    &mut transfer_ids,
+                    ts,
+                )
+                .await;
+                return Err(format!("Req #{} failed. {}", request_id, e));
+            }
+        };
+
+    // rounding of the swap amount can leave dust of one token which is added to the pool
+    let dust_amount_0 = nat_subtract(&add_amount_0, &amount_0).unwrap_or(nat_zero());
+    let dust_amount_1 = nat_subtract(&add_amount_1, &amount_1).unwrap_or(nat_zero());
+    if !nat_is_zero(&dust_amount_0) || !nat_is_zero(&dust_amount_1) {
+        pool.balance_0 = nat_add(&pool.balance_0, &dust_amount_0);
+        pool.balance_1 = nat_add(&pool.balance_1, &dust_amount_1);
+        pool.set_tvl();
+        pool_map::update(&pool);
+    }
+
+    // succcesful, add tx and update request with reply
+    let add_liquidity_tx = AddLiquidityTx::new_success(
+        pool.pool_id,
+        user_id,
+        request_id,
+        &add_amount_0,
+        &add_amount_1,
+        &add_lp_token_amount,
+        position_id,
+        &transfer_ids,
+        &Vec::new(),
+        ts,
+    );
+    let tx_id = tx_map::insert(&StableTx::AddLiquidity(add_liquidity_tx.clone()));
+    let reply = match tx_map::get_by_user_and_token_id(Some(tx_id), None, None, None).first() {
+        Some(StableTx::AddLiquidity(add_liquidity_tx)) => to_add_liquidity_reply(add_liquidity_tx),
+        _ => to_add_liquidity_reply_failed(pool.pool_id, request_id, &transfer_ids, &Vec::new(), ts),
+    };
+    request_map::update_reply(request_id, Reply::AddLiquidity(reply.clone()));
+
+    Ok(reply)
+}
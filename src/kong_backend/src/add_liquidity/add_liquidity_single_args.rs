@@ -0,0 +1,14 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the arguments of the `add_liquidity_single` function.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct AddLiquiditySingleArgs {
+    pub token: String, // token to add. must be token_0 or token_1 of the pool
+    pub amount: Nat,
+    pub pool: String, // pool to add liquidity to eg. "ckBTC_ckUSDT"
+    // minimum LP token amount to receive. fails and returns the token if below
+    pub min_lp_token_amount: Option<Nat>,
+    pub deadline: Option<u64>, // timestamp in nanoseconds. fails and returns the token if executed after the deadline
+}
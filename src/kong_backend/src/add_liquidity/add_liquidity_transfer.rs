@@ -328,10 +328,33 @@ async fn process_add_liquidity(
         };
     }
 
+    // the transfers could have been made well before add_liquidity is executed
+    if args.deadline.is_some_and(|deadline| get_time() > deadline) {
+        request_map::update_status(request_id, StatusCode::DeadlineExceeded, None);
+        return_tokens(
+            request_id,
+            user_id,
+            &caller_id,
+            Some(pool.pool_id),
+            token_0,
+            &transfer_0,
+            add_amount_0,
+            token_1,
+            &transfer_1,
+            add_amount_1,
+            &mut transfer_ids,
+            ts,
+        )
+        .await;
+        Err(format!("Req #{} failed. Deadline exceeded", request_id))?
+    }
+
     // re-calculate with latest pool state and make sure amounts are valid
-    let (pool, amount_0, amount_1, add_lp_token_amount) =
-        match update_liquidity_pool(request_id, user_id, &pool, add_amount_0, add_amount_1, ts) {
-            Ok((pool, amount_0, amount_1, add_lp_token_amount)) => (pool, amount_0, amount_1, add_lp_token_amount),
+    let (pool, amount_0, amount_1, add_lp_token_amount, position_id) =
+        match update_liquidity_pool(request_id, user_id, &pool, args, add_amount_0, add_amount_1, ts) {
+            Ok((pool, amount_0, amount_1, add_lp_token_amount, position_id)) => {
+                (pool, amount_0, amount_1, add_lp_token_amount, position_id)
+            }
             Err(e) => {
                 // LP amounts are incorrect. return token_0 and token_1 back to user
                 return_tokens(
@@ -361,6 +384,7 @@ async fn process_add_liquidity(
         &amount_0,
         &amount_1,
         &add_lp_token_amount,
+        position_id,
         &transfer_ids,
         &Vec::new(),
         ts,
@@ -3,12 +3,15 @@ use icrc_ledger_types::icrc1::account::Account;
 
 use super::add_liquidity::TokenIndex;
 use super::add_liquidity_args::AddLiquidityArgs;
+use super::add_liquidity_position::{calculate_position_amounts, update_position_liquidity};
 use super::add_liquidity_reply::AddLiquidityReply;
 use super::add_liquidity_reply_helpers::{to_add_liquidity_reply, to_add_liquidity_reply_failed};
 
+use crate::flash_loans::flash_loan_lock::check_flash_loan_lock;
 use crate::helpers::nat_helpers::{
     nat_add, nat_divide, nat_is_zero, nat_multiply, nat_sqrt, nat_subtract, nat_to_decimal_precision, nat_zero,
 };
+use crate::helpers::pool_helpers::{check_pool_action, PoolAction};
 use crate::ic::{
     address::Address,
     get_time::get_time,
@@ -17,6 +20,7 @@ use crate::ic::{
 };
 use crate::stable_claim::{claim_map, stable_claim::StableClaim};
 use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_lp_block::lp_block_map;
 use crate::stable_lp_token::{lp_token_map, stable_lp_token::StableLPToken};
 use crate::stable_pool::{pool_map, stable_pool::StablePool};
 use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
@@ -29,9 +33,9 @@ use crate::stable_user::user_map;
 pub async fn add_liquidity_transfer_from(args: AddLiquidityArgs) -> Result<AddLiquidityReply, String> {
     let (user_id, pool, add_amount_0, add_amount_1) = check_arguments(&args).await?;
     let ts = get_time();
-    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::AddLiquidity(args), ts));
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::AddLiquidity(args.clone()), ts));
 
-    let result = match process_add_liquidity(request_id, user_id, &pool, &add_amount_0, &add_amount_1, ts).await {
+    let result = match process_add_liquidity(request_id, user_id, &pool, &args, &add_amount_0, &add_amount_1, ts).await {
         Ok(reply) => {
             request_map::update_status(request_id, StatusCode::Success, None);
             Ok(reply)
@@ -52,7 +56,7 @@ pub async fn add_liquidity_transfer_from_async(args: AddLiquidityArgs) -> Result
     let request_id = request_map::insert(&StableRequest::new(user_id, &Request::AddLiquidity(args.clone()), ts));
 
     ic_cdk::spawn(async move {
-        match process_add_liquidity(request_id, user_id, &pool, &add_amount_0, &add_amount_1, ts).await {
+        match process_add_liquidity(request_id, user_id, &pool, &args, &add_amount_0, &add_amount_1, ts).await {
             Ok(_) => request_map::update_status(request_id, StatusCode::Success, None),
             Err(_) => request_map::update_status(request_id, StatusCode::Failed, None),
         };
@@ -63,7 +67,7 @@ pub async fn add_liquidity_transfer_from_async(args: AddLiquidityArgs) -> Result
 }
 
 async fn check_arguments(args: &AddLiquidityArgs) -> Result<(u32, StablePool, Nat, Nat), String> {
-    if nat_is_zero(&args.amount_0) || nat_is_zero(&args.amount_1) {
+    if nat_is_zero(&args.amount_0) && nat_is_zero(&args.amount_1) {
         Err("Invalid zero amounts".to_string())?
     }
 
@@ -74,7 +78,25 @@ async fn check_arguments(args: &AddLiquidityArgs) -> Result<(u32, StablePool, Na
 
     // add_amount_0 and add_amount_1 are the amounts to be added to the pool with the current state
     // these are the amounts that will be transferred to the pool
-    let (pool, add_amount_0, add_amount_1, _) = calculate_amounts(&args.token_0, &args.amount_0, &args.token_1, &args.amount_1)?;
+    let pool = pool_map::get_by_tokens(&args.token_0, &args.token_1)?;
+    let (pool, add_amount_0, add_amount_1, add_lp_token_amount) = if pool.curve.is_concentrated() {
+        // positions of Concentrated pools out of range only need one token
+        let user_id = user_map::get_by_caller().ok().flatten().map(|user| user.user_id);
+        calculate_position_amounts(pool, args, &args.amount_0, &args.amount_1, user_id)?
+    } else {
+        if nat_is_zero(&args.amount_0) || nat_is_zero(&args.amount_1) {
+            Err("Invalid zero amounts".to_string())?
+        }
+        calculate_amounts(&args.token_0, &args.amount_0, &args.token_1, &args.amount_1)?
+    };
+    if let Some(min_lp_token_amount) = &args.min_lp_token_amount {
+        if add_lp_token_amount < *min_lp_token_amount {
+            Err(format!(
+                "LP token amount {} below minimum {}",
+                add_lp_token_amount, min_lp_token_amount
+            ))?
+        }
+    }
 
     let token_0 = pool.token_0();
     if token_0.is_removed() {
@@ -105,6 +127,19 @@ async fn check_arguments(args: &AddLiquidityArgs) -> Result<(u32, StablePool, Na
 pub fn calculate_amounts(token_0: &str, amount_0: &Nat, token_1: &str, amount_1: &Nat) -> Result<(StablePool, Nat, Nat, Nat), String> {
     // Pool - make sure pool exists, refresh balances of the pool to make sure we have the latest state
     let pool = pool_map::get_by_tokens(token_0, token_1)?;
+    if pool.curve.is_concentrated() {
+        Err("Concentrated liquidity pools require a tick range".to_string())?
+    }
+    calculate_pool_amounts(pool, amount_0, amount_1)
+}
+
+/// calculate the ratio of amounts (amount_0 and amount_1) to be added to the given state of the pool
+///
+/// returns (pool, amount_0, amount_1, add_lp_token_amount)
+pub fn calculate_pool_amounts(pool: StablePool, amount_0: &Nat, amount_1: &Nat) -> Result<(StablePool, Nat, Nat, Nat), String> {
+    check_flash_loan_lock(&pool)?;
+    check_pool_action(pool.suspended_at, PoolAction::AddLiquidity)?;
+
     // Token0
     let token_0 = pool.token_0();
     // reserve_0 is the total balance of token_0 in the pool = balance_0 + lp_fee_0
@@ -189,6 +224,7 @@ async fn process_add_liquidity(
     request_id: u64,
     user_id: u32,
     pool: &StablePool,
+    args: &AddLiquidityArgs,
     add_amount_0: &Nat,
     add_amount_1: &Nat,
     ts: u64,
@@ -204,6 +240,11 @@ async fn process_add_liquidity(
 
     request_map::update_status(request_id, StatusCode::Start, None);
 
+    if args.deadline.is_some_and(|deadline| get_time() > deadline) {
+        request_map::update_status(request_id, StatusCode::DeadlineExceeded, None);
+        return Err(format!("Req #{} failed. Deadline exceeded", request_id));
+    }
+
     // transfer_from token_0. if this fails, nothing to return so just return the error
     transfer_from_token(
         request_id,
@@ -245,10 +286,29 @@ async fn process_add_liquidity(
         return Err(format!("Req #{}. Token_1 transfer_from failed. {}", request_id, e));
     };
 
+    // deadline could have passed while waiting for transfer_from. return token_0 and token_1 back to user
+    if args.deadline.is_some_and(|deadline| get_time() > deadline) {
+        request_map::update_status(request_id, StatusCode::DeadlineExceeded, None);
+        return_tokens(
+            request_id,
+            user_id,
+            &caller_id,
+            pool,
+            Some(add_amount_0),
+            Some(add_amount_1),
+            &mut transfer_ids,
+            ts,
+        )
+        .await;
+        return Err(format!("Req #{} failed. Deadline exceeded", request_id));
+    }
+
     // re-calculate with latest pool state and make sure amounts are valid
-    let (pool, amount_0, amount_1, add_lp_token_amount) =
-        match update_liquidity_pool(request_id, user_id, pool, add_amount_0, add_amount_1, ts) {
-            Ok((pool, amount_0, amount_1, add_lp_token_amount)) => (pool, amount_0, amount_1, add_lp_token_amount),
+    let (pool, amount_0, amount_1, add_lp_token_amount, position_id) =
+        match update_liquidity_pool(request_id, user_id, pool, args, add_amount_0, add_amount_1, ts) {
+            Ok((pool, amount_0, amount_1, add_lp_token_amount, position_id)) => {
+                (pool, amount_0, amount_1, add_lp_token_amount, position_id)
+            }
             Err(e) => {
                 // LP amounts are incorrect. return token_0 and token_1 back to user
                 return_tokens(
@@ -274,6 +334,7 @@ async fn process_add_liquidity(
         &amount_0,
         &amount_1,
         &add_lp_token_amount,
+        position_id,
         &transfer_ids,
         &Vec::new(),
         ts,
@@ -301,6 +362,11 @@ pub async fn transfer_from_token(
 ) -> Result<(), String> {
     let token_id = token.token_id();
 
+    // nothing to transfer. positions of Concentrated pools out of range only need one token
+    if nat_is_zero(amount) {
+        return Ok(());
+    }
+
     match token_index {
         TokenIndex::Token0 => request_map::update_status(request_id, StatusCode::SendToken0, None),
         TokenIndex::Token1 => request_map::update_status(request_id, StatusCode::SendToken1, None),
@@ -338,14 +404,23 @@ pub async fn transfer_from_token(
 
 /// update the liquidity pool with the new liquidity amounts
 /// ensure we have the latest state of the pool before adding the new amounts
+///
+/// returns (pool, amount_0, amount_1, add_lp_token_amount, position_id)
+/// for Concentrated pools add_lp_token_amount is the liquidity added to the position
+#[allow(clippy::type_complexity)]
 pub fn update_liquidity_pool(
     request_id: u64,
     user_id: u32,
     pool: &StablePool,
+    args: &AddLiquidityArgs,
     add_amount_0: &Nat,
     add_amount_1: &Nat,
     ts: u64,
-) -> Result<(StablePool, Nat, Nat, Nat), String> {
+) -> Result<(StablePool, Nat, Nat, Nat, Option<u64>), String> {
+    if pool.curve.is_concentrated() {
+        return update_position_liquidity(request_id, user_id, pool, args, add_amount_0, add_amount_1, ts);
+    }
+
     request_map::update_status(request_id, StatusCode::CalculatePoolAmounts, None);
 
     let token_0 = pool.token_0().address_with_chain();
@@ -357,6 +432,8 @@ pub fn update_liquidity_pool(
         Ok((mut pool, amount_0, amount_1, add_lp_token_amount)) => {
             request_map::update_status(request_id, StatusCode::CalculatePoolAmountsSuccess, None);
 
+            check_min_lp_token_amount(request_id, args, &add_lp_token_amount)?;
+
             request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
 
             pool.balance_0 = nat_add(&pool.balance_0, &amount_0);
@@ -368,7 +445,7 @@ pub fn update_liquidity_pool(
             // update user's LP token amount
             update_lp_token(request_id, user_id, pool.lp_token_id, &add_lp_token_amount, ts);
 
-            Ok((pool, amount_0, amount_1, add_lp_token_amount))
+            Ok((pool, amount_0, amount_1, add_lp_token_amount, None))
         }
         Err(e) => {
             request_map::update_status(request_id, StatusCode::CalculatePoolAmountsFailed, Some(&e));
@@ -377,6 +454,19 @@ pub fn update_liquidity_pool(
     }
 }
 
+/// make sure the LP token amount is not below the minimum LP token amount of the user
+/// must be called before the pool is updated so the tokens can be returned
+pub fn check_min_lp_token_amount(request_id: u64, args: &AddLiquidityArgs, add_lp_token_amount: &Nat) -> Result<(), String> {
+    if let Some(min_lp_token_amount) = &args.min_lp_token_amount {
+        if add_lp_token_amount < min_lp_token_amount {
+            let e = format!("LP token amount {} below minimum {}", add_lp_token_amount, min_lp_token_amount);
+            request_map::update_status(request_id, StatusCode::LPTokenAmountBelowMinimum, Some(&e));
+            Err(e)?
+        }
+    }
+    Ok(())
+}
+
 /// update the user's LP token amount
 /// ensure we have the latest state of the LP token before adding the new amounts
 fn update_lp_token(request_id: u64, user_id: u32, lp_token_id: u32, add_lp_token_amount: &Nat, ts: u64) {
@@ -392,13 +482,17 @@ fn update_lp_token(request_id: u64, user_id: u32, lp_token_id: u32, add_lp_token
                 ..lp_token.clone()
             };
             lp_token_map::update(&new_user_lp_token);
+            lp_block_map::insert_mint(lp_token_id, user_id, add_lp_token_amount, ts);
             request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None);
         }
         None => {
             // new entry
             let new_user_lp_token = StableLPToken::new(user_id, lp_token_id, add_lp_token_amount.clone(), ts);
             match lp_token_map::insert(&new_user_lp_token) {
-                Ok(_) => request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None),
+                Ok(_) => {
+                    lp_block_map::insert_mint(lp_token_id, user_id, add_lp_token_amount, ts);
+                    request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None)
+                }
                 Err(e) => request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountFailed, Some(&e)),
             };
         }
@@ -406,7 +500,7 @@ fn update_lp_token(request_id: u64, user_id: u32, lp_token_id: u32, add_lp_token
 }
 
 #[allow(clippy::too_many_arguments)]
-async fn return_tokens(
+pub async fn return_tokens(
     request_id: u64,
     user_id: u32,
     to_principal_id: &Account,
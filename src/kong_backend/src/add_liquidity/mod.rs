@@ -1,7 +1,10 @@
 #[allow(clippy::module_inception)]
 pub mod add_liquidity;
 pub mod add_liquidity_args;
+pub mod add_liquidity_position;
 pub mod add_liquidity_reply;
 pub mod add_liquidity_reply_helpers;
+pub mod add_liquidity_single;
+pub mod add_liquidity_single_args;
 pub mod add_liquidity_transfer;
 pub mod add_liquidity_transfer_from;
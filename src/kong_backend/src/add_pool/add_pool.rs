@@ -8,23 +8,27 @@ use super::add_pool_reply_helpers::{to_add_pool_reply, to_add_pool_reply_failed}
 
 use crate::add_token::add_token::{add_ic_token, add_lp_token};
 use crate::chains::chains::{IC_CHAIN, LP_CHAIN};
+use crate::helpers::concentrated_helpers::{liquidity_for_amounts, sqrt_price_at_tick, sqrt_price_from_amounts, tick_at_sqrt_price};
 use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_multiply, nat_sqrt, nat_subtract, nat_to_decimal_precision, nat_zero};
 use crate::ic::{
     address::Address,
-    ckusdt::is_ckusdt,
+    ckusdt::ckusdt_amount,
     get_time::get_time,
-    guards::not_in_maintenance_mode,
-    icp::is_icp,
+    guards::{caller_is_kingkong, not_in_maintenance_mode},
     id::caller_id,
     transfer::{icrc1_transfer, icrc2_transfer_from},
     verify::verify_transfer,
 };
 use crate::stable_claim::{claim_map, stable_claim::StableClaim};
 use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_lp_block::lp_block_map;
 use crate::stable_lp_token::lp_token_map;
 use crate::stable_lp_token::stable_lp_token::StableLPToken;
+use crate::stable_pool::concentrated_pool::{full_range_ticks, liquidity_delta, update_position, ConcentratedState};
+use crate::stable_pool::pool_curve::{PoolCurve, MAX_AMP, MAX_TICK_SPACING, MIN_AMP, MIN_TICK_SPACING};
 use crate::stable_pool::pool_map;
 use crate::stable_pool::stable_pool::StablePool;
+use crate::stable_position::{position_map, stable_position::StablePosition};
 use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
 use crate::stable_token::lp_token::LP_DECIMALS;
 use crate::stable_token::stable_token::StableToken;
@@ -54,8 +58,20 @@ enum TokenIndex {
 /// * `Err(String)` - An error message if the operation fails.
 #[update(guard = "not_in_maintenance_mode")]
 pub async fn add_pool(args: AddPoolArgs) -> Result<AddPoolReply, String> {
-    let (user_id, token_0, add_amount_0, tx_id_0, token_1, add_amount_1, tx_id_1, lp_fee_bps, kong_fee_bps, add_lp_token_amount) =
-        check_arguments(&args).await?;
+    let (
+        user_id,
+        token_0,
+        add_amount_0,
+        tx_id_0,
+        token_1,
+        add_amount_1,
+        tx_id_1,
+        lp_fee_bps,
+        kong_fee_bps,
+        curve,
+        add_lp_token_amount,
+        creation_fee,
+    ) = check_arguments(&args).await?;
     let ts = get_time();
     let request_id = request_map::insert(&StableRequest::new(user_id, &Request::AddPool(args), ts));
 
@@ -70,7 +86,9 @@ pub async fn add_pool(args: AddPoolArgs) -> Result<AddPoolReply, String> {
         tx_id_1.as_ref(),
         lp_fee_bps,
         kong_fee_bps,
+        curve,
         &add_lp_token_amount,
+        creation_fee.as_ref(),
         ts,
     )
     .await
@@ -97,7 +115,7 @@ pub async fn add_pool(args: AddPoolArgs) -> Result<AddPoolReply, String> {
 ///
 /// # Returns
 ///
-/// * `Ok((user_id, token_0, amount_0, tx_id_0, token_1, add_amount_1, tx_id_1, lp_fee_bps))`
+/// * `Ok((user_id, token_0, amount_0, tx_id_0, token_1, add_amount_1, tx_id_1, lp_fee_bps, kong_fee_bps, curve, add_lp_token_amount, creation_fee))`
 /// *   `user_id` - The user id.
 /// *   `token_0` - The first token.
 /// *   `amount_0` - The amount of the first token.
@@ -107,11 +125,29 @@ pub async fn add_pool(args: AddPoolArgs) -> Result<AddPoolReply, String> {
 /// *   `tx_id_1` - The transaction id of the second token for icrc1_transfer.
 /// *   `lp_fee_bps` - The liquidity pool fee basis points.
 /// *   `kong_fee_bps` - The liquidity pool Kong fee basis points.
+/// *   `curve` - The AMM curve of the pool.
 /// *   `add_lp_token_amount` - The amount of LP token to be added to the pool.
+/// *   `creation_fee` - The pool creation fee token, amount and treasury account.
 /// * `Err(String)` - An error message if the operation fails.
 async fn check_arguments(
     args: &AddPoolArgs,
-) -> Result<(u32, StableToken, Nat, Option<Nat>, StableToken, Nat, Option<Nat>, u8, u8, Nat), String> {
+) -> Result<
+    (
+        u32,
+        StableToken,
+        Nat,
+        Option<Nat>,
+        StableToken,
+        Nat,
+        Option<Nat>,
+        u8,
+        u8,
+        PoolCurve,
+        Nat,
+        Option<(StableToken, Nat, Account)>,
+    ),
+    String,
+> {
     if nat_is_zero(&args.amount_0) || nat_is_zero(&args.amount_1) {
         Err("Invalid zero amounts".to_string())?
     }
@@ -127,6 +163,20 @@ async fn check_arguments(
         Err(format!("LP fee cannot be less than Kong fee of {}", kong_fee_bps))?
     }
 
+    let curve = args.curve.unwrap_or_default();
+    match curve {
+        PoolCurve::StableSwap { amp } if !(MIN_AMP..=MAX_AMP).contains(&amp) => {
+            Err(format!("Amplification coefficient must be between {} and {}", MIN_AMP, MAX_AMP))?
+        }
+        PoolCurve::Concentrated { tick_spacing } if !(MIN_TICK_SPACING..=MAX_TICK_SPACING).contains(&tick_spacing) => {
+            Err(format!(
+                "Tick spacing must be between {} and {}",
+                MIN_TICK_SPACING, MAX_TICK_SPACING
+            ))?;
+        }
+        _ => (),
+    }
+
     // check tx_id_0 and tx_id_1 are valid block index Nat
     let tx_id_0 = match &args.tx_id_0 {
         Some(tx_id_0) => match tx_id_0 {
@@ -143,20 +193,36 @@ async fn check_arguments(
         None => None,
     };
 
-    // make sure token_1 is ckUSDT or ICP
-    let token_1 = match args.token_1.as_str() {
-        token if is_ckusdt(token) => token_map::get_ckusdt()?,
-        token if is_icp(token) => token_map::get_icp()?,
+    // make sure token_1 is a quote token (ie. ckUSDT or ICP)
+    let token_1 = match token_map::get_by_token(&args.token_1) {
+        Ok(token) if token_map::is_quote_token_id(token.token_id()) => token,
         _ => Err(format!(
-            "Token_1 must be {} or {}",
-            kong_settings_map::get().ckusdt_symbol,
-            kong_settings_map::get().icp_symbol
+            "Token_1 must be {}",
+            token_map::get_quote_tokens()
+                .iter()
+                .map(|token| token.symbol())
+                .collect::<Vec<String>>()
+                .join(", ")
         ))?,
     };
 
+    // pool creation policy. kingkong can create any pool without fee
+    let is_kingkong = caller_is_kingkong().is_ok();
+    if !is_kingkong {
+        check_min_pool_tvl(&token_1, &args.amount_1)?;
+    }
+    let creation_fee = if is_kingkong { None } else { pool_creation_fee()? };
+
     // token_0, check if it exists already or needs to be added
     // leave token_0 check latest as possible as token will be added to the system
-    let token_0 = match token_map::get_by_token(&args.token_0) {
+    let existing_token_0 = token_map::get_by_token(&args.token_0);
+    if !is_kingkong {
+        match &existing_token_0 {
+            Ok(token) => check_token_allowed(&token.address_with_chain())?,
+            Err(_) => check_token_allowed(&args.token_0)?,
+        }
+    }
+    let token_0 = match existing_token_0 {
         Ok(token) => token, // token_0 exists already
         Err(_) => {
             // token_0 needs to be added. Only IC tokens of format IC.CanisterId supported
@@ -179,7 +245,7 @@ async fn check_arguments(
         Err(format!("Pool {} already exists", pool_map::symbol(&token_0, &token_1)))?
     }
 
-    let (add_amount_0, add_amount_1, add_lp_token_amount) = calculate_amounts(&token_0, &args.amount_0, &token_1, &args.amount_1)?;
+    let (add_amount_0, add_amount_1, add_lp_token_amount) = calculate_amounts(&token_0, &args.amount_0, &token_1, &args.amount_1, &curve)?;
 
     // make sure user is registered, if not create a new user
     let user_id = user_map::insert(None)?;
@@ -194,11 +260,75 @@ async fn check_arguments(
         tx_id_1,
         lp_fee_bps,
         kong_fee_bps,
+        curve,
         add_lp_token_amount,
+        creation_fee,
     ))
 }
 
-pub fn calculate_amounts(token_0: &StableToken, amount_0: &Nat, token_1: &StableToken, amount_1: &Nat) -> Result<(Nat, Nat, Nat), String> {
+/// initial TVL of the pool must be at least min_pool_tvl. new pools are balanced so TVL is twice the value of token_1
+fn check_min_pool_tvl(token_1: &StableToken, amount_1: &Nat) -> Result<(), String> {
+    let min_pool_tvl = kong_settings_map::get().min_pool_tvl;
+    if nat_is_zero(&min_pool_tvl) {
+        return Ok(());
+    }
+    let tvl = nat_multiply(&ckusdt_amount(token_1, amount_1)?, &Nat::from(2_u8));
+    if tvl < min_pool_tvl {
+        Err(format!(
+            "Initial TVL of {} is below minimum of {} {}",
+            tvl,
+            min_pool_tvl,
+            kong_settings_map::get().ckusdt_symbol
+        ))?
+    }
+    Ok(())
+}
+
+/// token must be in the allow list if it is set and not in the deny list
+fn check_token_allowed(address_with_chain: &str) -> Result<(), String> {
+    let kong_settings = kong_settings_map::get();
+    if kong_settings.token_deny_list.iter().any(|token| token == address_with_chain) {
+        Err(format!("Token {} is not allowed", address_with_chain))?
+    }
+    if !kong_settings.token_allow_list.is_empty() && !kong_settings.token_allow_list.iter().any(|token| token == address_with_chain) {
+        Err(format!("Token {} is not in the allow list", address_with_chain))?
+    }
+    Ok(())
+}
+
+/// token, amount and treasury account of the pool creation fee if set
+fn pool_creation_fee() -> Result<Option<(StableToken, Nat, Account)>, String> {
+    let kong_settings = kong_settings_map::get();
+    let Some(creation_fee) = kong_settings.pool_creation_fee else {
+        return Ok(None);
+    };
+    let treasury = kong_settings.treasury.ok_or("Treasury for pool creation fee not set")?;
+    let token = token_map::get_by_token_id(creation_fee.token_id).ok_or("Pool creation fee token not found")?;
+    Ok(Some((token, creation_fee.amount, treasury)))
+}
+
+pub fn calculate_amounts(
+    token_0: &StableToken,
+    amount_0: &Nat,
+    token_1: &StableToken,
+    amount_1: &Nat,
+    curve: &PoolCurve,
+) -> Result<(Nat, Nat, Nat), String> {
+    if let PoolCurve::Concentrated { tick_spacing } = curve {
+        // initial price is the ratio of the amounts and the first position is full range
+        // the LP token amount is the liquidity of the position
+        let sqrt_price = sqrt_price_from_amounts(amount_0, amount_1)
+            .filter(|sqrt_price| tick_at_sqrt_price(sqrt_price).is_some())
+            .ok_or("Invalid ratio of amount_0 and amount_1")?;
+        let (tick_lower, tick_upper) = full_range_ticks(*tick_spacing);
+        let sqrt_price_lower = sqrt_price_at_tick(tick_lower).ok_or("Invalid tick_lower")?;
+        let sqrt_price_upper = sqrt_price_at_tick(tick_upper).ok_or("Invalid tick_upper")?;
+        let liquidity = liquidity_for_amounts(&sqrt_price, &sqrt_price_lower, &sqrt_price_upper, amount_0, amount_1)
+            .filter(|liquidity| !nat_is_zero(liquidity))
+            .ok_or("Invalid liquidity")?;
+        return Ok((amount_0.clone(), amount_1.clone(), liquidity));
+    }
+
     // new pool as there are no balances - take user amounts as initial ratio
     // initialize LP tokens as sqrt(amount_0 * amount_1)
     // convert the amounts to the same decimal precision as the LP token
@@ -221,7 +351,9 @@ async fn process_add_pool(
     tx_id_1: Option<&Nat>,
     lp_fee_bps: u8,
     kong_fee_bps: u8,
+    curve: PoolCurve,
     add_lp_token_amount: &Nat,
+    creation_fee: Option<&(StableToken, Nat, Account)>,
     ts: u64,
 ) -> Result<AddPoolReply, String> {
     let caller_id = caller_id();
@@ -292,6 +424,35 @@ async fn process_add_pool(
         };
     }
 
+    // pool creation fee is sent from the caller to the treasury
+    if let Some((fee_token, fee_amount, treasury)) = creation_fee {
+        request_map::update_status(request_id, StatusCode::SendPoolCreationFee, None);
+        match icrc2_transfer_from(fee_token, fee_amount, &caller_id, treasury).await {
+            Ok(block_id) => {
+                let message = format!("Block id #{}", block_id);
+                request_map::update_status(request_id, StatusCode::SendPoolCreationFeeSuccess, Some(&message));
+            }
+            Err(e) => {
+                request_map::update_status(request_id, StatusCode::SendPoolCreationFeeFailed, Some(&e));
+                return_tokens(
+                    request_id,
+                    user_id,
+                    &caller_id,
+                    &transfer_0,
+                    token_0,
+                    amount_0,
+                    &transfer_1,
+                    token_1,
+                    amount_1,
+                    &mut transfer_ids,
+                    ts,
+                )
+                .await;
+                Err(format!("Req #{} failed. Pool creation fee: {}", request_id, e))?
+            }
+        }
+    }
+
     // add LP token
     request_map::update_status(request_id, StatusCode::AddLPToken, None);
     // default to None for LP token metadata
@@ -328,6 +489,7 @@ async fn process_add_pool(
         lp_fee_bps,
         kong_fee_bps,
         lp_token.token_id(),
+        curve,
     ) {
         Ok(pool) => {
             request_map::update_status(request_id, StatusCode::AddPoolSuccess, None);
@@ -354,7 +516,7 @@ async fn process_add_pool(
     };
 
     // update pool with new balances
-    update_liquidity_pool(request_id, user_id, &pool, amount_0, amount_1, add_lp_token_amount, ts);
+    let position_id = update_liquidity_pool(request_id, user_id, &pool, amount_0, amount_1, add_lp_token_amount, ts);
 
     // successful, add tx and update request with reply
     let add_pool_tx = AddPoolTx::new_success(
@@ -364,6 +526,7 @@ async fn process_add_pool(
         amount_0,
         amount_1,
         add_lp_token_amount,
+        position_id,
         &transfer_ids,
         &Vec::new(),
         ts,
@@ -490,6 +653,7 @@ async fn transfer_from_token(
     }
 }
 
+/// returns the position_id for Concentrated pools
 fn update_liquidity_pool(
     request_id: u64,
     user_id: u32,
@@ -498,7 +662,7 @@ fn update_liquidity_pool(
     amount_1: &Nat,
     add_lp_token_amount: &Nat,
     ts: u64,
-) {
+) -> Option<u64> {
     request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
 
     let mut update_pool = StablePool {
@@ -506,12 +670,42 @@ fn update_liquidity_pool(
         balance_1: nat_add(&pool.balance_1, amount_1),
         ..pool.clone()
     };
+
+    if let PoolCurve::Concentrated { tick_spacing } = pool.curve {
+        // mint the full range position of the pool creator
+        request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmount, None);
+        let (tick_lower, tick_upper) = full_range_ticks(tick_spacing);
+        let mut position = StablePosition::new(user_id, pool.pool_id, tick_lower, tick_upper, ts);
+        let result = sqrt_price_from_amounts(amount_0, amount_1)
+            .ok_or("Invalid ratio of amount_0 and amount_1".to_string())
+            .and_then(|sqrt_price| ConcentratedState::new(&sqrt_price))
+            .and_then(|state| {
+                update_pool.concentrated = Some(state);
+                update_position(&mut update_pool, &mut position, &liquidity_delta(add_lp_token_amount, true), ts)
+            });
+        update_pool.set_tvl();
+        pool_map::update(&update_pool);
+        request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
+        return match result {
+            Ok(()) => {
+                let position_id = position_map::insert(&position);
+                request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None);
+                Some(position_id)
+            }
+            Err(e) => {
+                request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountFailed, Some(&e));
+                None
+            }
+        };
+    }
+
     update_pool.set_tvl();
     pool_map::update(&update_pool);
     request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
 
     // update user's LP token amount
     update_lp_token(request_id, user_id, pool.lp_token_id, add_lp_token_amount, ts);
+    None
 }
 
 fn update_lp_token(request_id: u64, user_id: u32, lp_token_id: u32, add_lp_token_amount: &Nat, ts: u64) {
@@ -527,13 +721,17 @@ fn update_lp_token(request_id: u64, user_id: u32, lp_token_id: u32, add_lp_token
                 ..lp_token.clone()
             };
             lp_token_map::update(&new_user_lp_token);
+            lp_block_map::insert_mint(lp_token_id, user_id, add_lp_token_amount, ts);
             request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None);
         }
         None => {
             // new entry
             let new_user_lp_token = StableLPToken::new(user_id, lp_token_id, add_lp_token_amount.clone(), ts);
             match lp_token_map::insert(&new_user_lp_token) {
-                Ok(_) => request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None),
+                Ok(_) => {
+                    lp_block_map::insert_mint(lp_token_id, user_id, add_lp_token_amount, ts);
+                    request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None)
+                }
                 Err(e) => request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountFailed, Some(&e)),
             };
         }
@@ -657,8 +855,15 @@ async fn return_token(
 }
 
 // add_pool() taken
-fn add_new_pool(token_id_0: u32, token_id_1: u32, lp_fee_bps: u8, kong_fee_bps: u8, lp_token_id: u32) -> Result<StablePool, String> {
-    let pool = StablePool::new(token_id_0, token_id_1, lp_fee_bps, kong_fee_bps, lp_token_id);
+fn add_new_pool(
+    token_id_0: u32,
+    token_id_1: u32,
+    lp_fee_bps: u8,
+    kong_fee_bps: u8,
+    lp_token_id: u32,
+    curve: PoolCurve,
+) -> Result<StablePool, String> {
+    let pool = StablePool::new(token_id_0, token_id_1, lp_fee_bps, kong_fee_bps, lp_token_id, curve);
     let pool_id = pool_map::insert(&pool)?;
 
     // Retrieves the inserted pool by its pool_id
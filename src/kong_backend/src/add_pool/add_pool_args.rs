@@ -1,6 +1,7 @@
 use candid::{CandidType, Nat};
 use serde::{Deserialize, Serialize};
 
+use crate::stable_pool::pool_curve::PoolCurve;
 use crate::stable_transfer::tx_id::TxId;
 
 /// Data structure for the arguments of the `add_pool` function.
@@ -14,4 +15,5 @@ pub struct AddPoolArgs {
     pub amount_1: Nat,
     pub tx_id_1: Option<TxId>,
     pub lp_fee_bps: Option<u8>,
+    pub curve: Option<PoolCurve>, // defaults to ConstantProduct
 }
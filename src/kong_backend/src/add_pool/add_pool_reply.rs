@@ -28,6 +28,7 @@ pub struct AddPoolReply {
     pub lp_fee_bps: u8,
     pub lp_token_symbol: String,
     pub add_lp_token_amount: Nat,
+    pub position_id: Option<u64>,
     pub transfer_ids: Vec<TransferIdReply>,
     pub claim_ids: Vec<u64>,
     #[serde(default = "false_bool")]
@@ -82,6 +82,7 @@ pub fn to_add_pool_reply(add_pool_tx: &AddPoolTx) -> AddPoolReply {
         lp_fee_bps,
         lp_token_symbol,
         add_lp_token_amount: add_pool_tx.add_lp_token_amount.clone(),
+        position_id: add_pool_tx.position_id,
         transfer_ids: to_transfer_ids(&add_pool_tx.transfer_ids),
         claim_ids: add_pool_tx.claim_ids.clone(),
         is_removed: add_pool_tx.is_removed,
@@ -122,6 +123,7 @@ pub fn to_add_pool_reply_failed(
         lp_fee_bps: 0,
         lp_token_symbol: "LP token not added".to_string(),
         add_lp_token_amount: nat_zero(),
+        position_id: None,
         transfer_ids: to_transfer_ids(transfer_ids),
         claim_ids: claim_ids.to_vec(),
         is_removed: false,
@@ -35,21 +35,19 @@ pub async fn update_ic_token(token: &str) -> Result<StableToken, String> {
 
     let mut ic_token = ICToken::new(&canister_id).await?;
     ic_token.token_id = token_id;
+    // keep the verification method of the token as it may have been set by kingkong
+    if let StableToken::IC(ref token) = stable_token {
+        ic_token.verification_method = token.verification_method.or(ic_token.verification_method);
+    }
 
     token_map::update(&StableToken::IC(ic_token.clone()));
 
-    // update _ckUSDT pool for symbol
-    let ckusdt = token_map::get_ckusdt()?;
-    if let Ok(StableToken::LP(mut lp_token)) = token_map::get_by_token(&format!("LP.{}_{}", symbol, ckusdt.symbol())) {
-        lp_token.symbol = token::symbol(&StableToken::IC(ic_token.clone()), &ckusdt);
-        token_map::update(&StableToken::LP(lp_token));
-    }
-
-    // update _ICP pool for symbol
-    let icp = token_map::get_icp()?;
-    if let Ok(StableToken::LP(mut lp_token)) = token_map::get_by_token(&format!("LP.{}_{}", symbol, icp.symbol())) {
-        lp_token.symbol = token::symbol(&StableToken::IC(ic_token), &icp);
-        token_map::update(&StableToken::LP(lp_token));
+    // update pools of symbol quoted in each quote token (ie. _ckUSDT and _ICP)
+    for quote_token in token_map::get_quote_tokens() {
+        if let Ok(StableToken::LP(mut lp_token)) = token_map::get_by_token(&format!("LP.{}_{}", symbol, quote_token.symbol())) {
+            lp_token.symbol = token::symbol(&StableToken::IC(ic_token.clone()), &quote_token);
+            token_map::update(&StableToken::LP(lp_token));
+        }
     }
 
     token_map::get_by_token_id(token_id).ok_or_else(|| format!("Failed to update token {}", token))
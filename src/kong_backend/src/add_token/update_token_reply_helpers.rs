@@ -17,6 +17,7 @@ pub fn to_update_token_reply(token: &StableToken) -> Result<UpdateTokenReply, St
             icrc1: ic_token.icrc1,
             icrc2: ic_token.icrc2,
             icrc3: ic_token.icrc3,
+            verification_method: ic_token.verification_method().to_string(),
             is_removed: token.is_removed(),
         })),
         _ => Err("Unsupported token type".to_string()),
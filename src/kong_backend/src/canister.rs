@@ -13,6 +13,7 @@ use super::{APP_NAME, APP_VERSION};
 
 use crate::add_liquidity::add_liquidity_args::AddLiquidityArgs;
 use crate::add_liquidity::add_liquidity_reply::AddLiquidityReply;
+use crate::add_liquidity::add_liquidity_single_args::AddLiquiditySingleArgs;
 use crate::add_liquidity_amounts::add_liquidity_amounts_reply::AddLiquidityAmountsReply;
 use crate::add_pool::add_pool_args::AddPoolArgs;
 use crate::add_pool::add_pool_reply::AddPoolReply;
@@ -22,32 +23,64 @@ use crate::add_token::update_token_args::UpdateTokenArgs;
 use crate::add_token::update_token_reply::UpdateTokenReply;
 use crate::claims::claims::process_claims;
 use crate::helpers::nat_helpers::{nat_to_decimals_f64, nat_to_f64};
+use crate::helpers::time_helpers::format_timestamp;
 use crate::ic::canister_address::KONG_BACKEND;
 use crate::ic::id::caller_principal_id;
 use crate::ic::logging::info_log;
+use crate::limit_orders::process_limit_orders::process_limit_orders;
+use crate::pool_updates::process_pool_updates::process_pool_updates;
 use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_lp_token::lp_token_index::create_lp_token_index;
+use crate::stable_pool::low_tvl::suspend_low_tvl_pools;
+use crate::stable_pool::rolling_stats::update_rolling_stats;
 use crate::stable_request::request_archive::archive_request_map;
 use crate::stable_token::token::Token;
 use crate::stable_token::token_map;
 use crate::stable_transfer::transfer_archive::archive_transfer_map;
 use crate::stable_tx::tx_archive::archive_tx_map;
+use crate::stable_user::fee_tiers::update_fee_tiers;
 use crate::stable_user::principal_id_map::create_principal_id_map;
+use crate::stable_user::referee_count_map::create_referee_count_map;
 use crate::swap::swap_args::SwapArgs;
+use crate::webhooks::process_webhooks::process_webhooks;
 
 // list of query calls
 // a bit hard-coded but shouldn't change often
-static QUERY_METHODS: [&str; 11] = [
+static QUERY_METHODS: [&str; 34] = [
     "icrc1_name",
     "icrc10_supported_standards",
     "tokens",
     "pools",
+    "pool_updates",
     "get_user",
     "user_balances",
+    "claims",
     "requests",
     "txs",
+    "requests_history",
+    "txs_history",
+    "transfers_history",
+    "claims_history",
+    "webhook",
     "add_liquidity_amounts",
     "remove_liquidity_amounts",
     "swap_amounts",
+    "swap_amounts_exact_output",
+    "limit_orders",
+    "twap",
+    "protocol_fees",
+    "referral_stats",
+    "lp_icrc1_name",
+    "lp_icrc1_symbol",
+    "lp_icrc1_decimals",
+    "lp_icrc1_fee",
+    "lp_icrc1_metadata",
+    "lp_icrc1_total_supply",
+    "lp_icrc1_balance_of",
+    "lp_icrc1_supported_standards",
+    "lp_icrc2_allowance",
+    "lp_icrc3_get_blocks",
+    "lp_icrc3_supported_block_types",
 ];
 
 #[init]
@@ -55,6 +88,8 @@ async fn init() {
     info_log(&format!("{} canister has been initialized", APP_NAME));
 
     create_principal_id_map();
+    create_referee_count_map();
+    create_lp_token_index();
 
     set_timer_processes().await;
 }
@@ -67,6 +102,8 @@ fn pre_upgrade() {
 #[post_upgrade]
 async fn post_upgrade() {
     create_principal_id_map();
+    create_referee_count_map();
+    create_lp_token_index();
 
     set_timer_processes().await;
 
@@ -81,6 +118,40 @@ async fn set_timer_processes() {
         });
     });
 
+    // start the background timer to fill and expire limit orders
+    let _ = set_timer_interval(Duration::from_secs(kong_settings_map::get().limit_orders_interval_secs), || {
+        ic_cdk::spawn(async {
+            process_limit_orders().await;
+        });
+    });
+
+    // start the background timer to roll the 24h, 7d and 30d pool stats and suspend pools with low TVL
+    let _ = set_timer_interval(Duration::from_secs(kong_settings_map::get().stats_interval_secs), || {
+        ic_cdk::spawn(async {
+            update_rolling_stats();
+            suspend_low_tvl_pools();
+        });
+    });
+
+    // start the background timer to apply scheduled pool updates
+    let _ = set_timer_interval(Duration::from_secs(kong_settings_map::get().pool_updates_interval_secs), || {
+        ic_cdk::spawn(async {
+            process_pool_updates();
+        });
+    });
+
+    // start the background timer to deliver webhooks of finished requests
+    let _ = set_timer_interval(Duration::from_secs(kong_settings_map::get().webhooks_interval_secs), || {
+        process_webhooks();
+    });
+
+    // start the background timer to promote and demote users' fee tiers
+    let _ = set_timer_interval(Duration::from_secs(kong_settings_map::get().fee_tiers_interval_secs), || {
+        ic_cdk::spawn(async {
+            update_fee_tiers();
+        });
+    });
+
     // start the background timer to archive request map
     let _ = set_timer_interval(Duration::from_secs(kong_settings_map::get().requests_archive_interval_secs), || {
         ic_cdk::spawn(async {
@@ -170,12 +241,22 @@ fn icrc21_canister_call_consent_message(consent_msg_request: ConsentMessageReque
                 Some(address) => address,
                 None => caller_principal_id(),
             };
+            let exact_output = swap_args.exact_output.unwrap_or(false);
+            let pay_amount = if exact_output {
+                format!("Max. amount {}", pay_amount)
+            } else {
+                pay_amount.to_string()
+            };
             let receive_token = match swap_args.receive_amount {
                 Some(amount) => {
                     let receive_amount = nat_to_f64(&amount).ok_or_else(|| ErrorInfo {
                         description: "Failed to convert receive amount to f64".to_string(),
                     })?;
-                    format!("Min. amount {} {}", receive_amount, swap_args.receive_token)
+                    if exact_output {
+                        format!("Exact amount {} {}", receive_amount, swap_args.receive_token)
+                    } else {
+                        format!("Min. amount {} {}", receive_amount, swap_args.receive_token)
+                    }
                 }
                 None => {
                     let max_slippage = swap_args.max_slippage.unwrap_or(kong_settings_map::get().default_max_slippage);
@@ -183,6 +264,11 @@ fn icrc21_canister_call_consent_message(consent_msg_request: ConsentMessageReque
                 }
             };
 
+            let deadline = match swap_args.deadline {
+                Some(deadline) => format!("\n\n**Deadline:**\n{}", format_timestamp(deadline)),
+                None => String::new(),
+            };
+
             ConsentMessage::GenericDisplayMessage(format!(
                 "# Approve KongSwap swap
                 
@@ -193,8 +279,8 @@ fn icrc21_canister_call_consent_message(consent_msg_request: ConsentMessageReque
 {}
 
 **Receive address:**
-{}",
-                pay_amount, swap_args.pay_token, receive_token, to_address
+{}{}",
+                pay_amount, swap_args.pay_token, receive_token, to_address, deadline
             ))
         }
         "add_liquidity" | "add_liquidity_async" => {
@@ -221,6 +307,11 @@ fn icrc21_canister_call_consent_message(consent_msg_request: ConsentMessageReque
             let amount_1 = nat_to_decimals_f64(decimals_1, &add_liquidity_args.amount_1).ok_or_else(|| ErrorInfo {
                 description: "Failed to convert token_1 amount to f64".to_string(),
             })?;
+            let deadline = match add_liquidity_args.deadline {
+                Some(deadline) => format!("\n\n**Deadline:**\n{}", format_timestamp(deadline)),
+                None => String::new(),
+            };
+
             ConsentMessage::GenericDisplayMessage(format!(
                 "# Approve KongSwap add liquidity
 
@@ -228,8 +319,8 @@ fn icrc21_canister_call_consent_message(consent_msg_request: ConsentMessageReque
 {} {}
 
 **Token 1:**
-{} {}",
-                amount_0, add_liquidity_args.token_0, amount_1, add_liquidity_args.token_1
+{} {}{}",
+                amount_0, add_liquidity_args.token_0, amount_1, add_liquidity_args.token_1, deadline
             ))
         }
         "add_pool" => {
@@ -0,0 +1,62 @@
+use ic_cdk::update;
+
+use super::claim_reply::ClaimReply;
+use super::claims::{archive_to_kong_data, process_claim};
+
+use crate::ic::{address::Address, address_helpers::get_address, get_time::get_time, guards::not_in_maintenance_mode, id::caller_id};
+use crate::stable_claim::claim_map;
+use crate::stable_claim::stable_claim::ClaimStatus;
+use crate::stable_request::{request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::stable_token::StableToken;
+use crate::stable_token::token_map;
+use crate::stable_user::user_map;
+
+/// retry sending out one of the caller's claims now instead of waiting for the claims process
+/// to_address - optional, new address to send the claim to if the original can't receive it
+#[update(guard = "not_in_maintenance_mode")]
+pub async fn claim(claim_id: u64, to_address: Option<String>) -> Result<ClaimReply, String> {
+    let (user_id, token, to_address) = check_arguments(claim_id, to_address.as_deref())?;
+    // set the claim status to claiming to prevent reentrancy. fails if the claim is already being sent or claimed
+    claim_map::update_claiming_status(
+        claim_id,
+        &[ClaimStatus::Unclaimed, ClaimStatus::UnclaimedOverride, ClaimStatus::TooManyAttempts],
+    )
+    .ok_or(format!("Claim #{} is already being claimed", claim_id))?;
+    let claim = claim_map::update_to_address(claim_id, &to_address).ok_or(format!("Claim #{} not found", claim_id))?;
+    let ts = get_time();
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::Claim(claim_id), ts));
+
+    let result = match process_claim(request_id, claim_id, &token, &claim.amount, &to_address, ts).await {
+        Ok(reply) => {
+            request_map::update_status(request_id, StatusCode::Success, None);
+            Ok(reply)
+        }
+        Err(e) => {
+            request_map::update_status(request_id, StatusCode::Failed, None);
+            Err(e)
+        }
+    };
+    let _ = archive_to_kong_data(request_id);
+
+    result
+}
+
+fn check_arguments(claim_id: u64, to_address: Option<&str>) -> Result<(u32, StableToken, Address), String> {
+    let user_id = user_map::get_by_caller()?.ok_or("User not found")?.user_id;
+    let claim = claim_map::get_by_claim_id(claim_id)
+        .filter(|claim| claim.user_id == user_id)
+        .ok_or(format!("Claim #{} not found", claim_id))?;
+    match claim.status {
+        ClaimStatus::Unclaimed | ClaimStatus::UnclaimedOverride | ClaimStatus::TooManyAttempts => (),
+        _ => Err(format!("Claim #{} is {}", claim_id, claim.status))?,
+    }
+    let token = token_map::get_by_token_id(claim.token_id).ok_or(format!("Token of claim #{} not found", claim_id))?;
+
+    // use specified address, or the claim's address, or default to caller's principal id
+    let to_address = match to_address {
+        Some(address) => get_address(&token, address)?,
+        None => claim.to_address.unwrap_or(Address::PrincipalId(caller_id())),
+    };
+
+    Ok((user_id, token, to_address))
+}
@@ -11,7 +11,7 @@ use crate::ic::{
     transfer::{icp_transfer, icrc1_transfer},
 };
 use crate::stable_claim::claim_map;
-use crate::stable_claim::stable_claim::{ClaimStatus, StableClaim};
+use crate::stable_claim::stable_claim::ClaimStatus;
 use crate::stable_kong_settings::kong_settings_map;
 use crate::stable_memory::CLAIM_MAP;
 use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
@@ -29,13 +29,13 @@ pub async fn process_claims() {
     let ts = get_time();
 
     // get all unclaimed claims
-    let claims: Vec<StableClaim> = CLAIM_MAP.with(|m| {
+    let claim_ids: Vec<u64> = CLAIM_MAP.with(|m| {
         m.borrow()
             .iter()
             .rev()
             .filter_map(|(_, v)| {
                 if v.status == ClaimStatus::Unclaimed || v.status == ClaimStatus::UnclaimedOverride {
-                    Some(v)
+                    Some(v.claim_id)
                 } else {
                     None
                 }
@@ -44,7 +44,13 @@ pub async fn process_claims() {
     });
 
     let mut consecutive_errors = 0_u8;
-    for claim in &claims {
+    for claim_id in claim_ids {
+        // re-read the claim as it may have been claimed by the user while sending the previous claims
+        let Some(claim) = claim_map::get_by_claim_id(claim_id)
+            .filter(|claim| claim.status == ClaimStatus::Unclaimed || claim.status == ClaimStatus::UnclaimedOverride)
+        else {
+            continue;
+        };
         if let Some(to_address) = &claim.to_address {
             if claim.attempt_request_id.len() > 50 && claim.status != ClaimStatus::UnclaimedOverride {
                 // if claim has more than 50 attempts, update status to too_many_attempts and investigate manually
@@ -67,9 +73,13 @@ pub async fn process_claims() {
                 None => continue, // continue to next claim if token not found
             };
 
+            // set the claim status to claiming to prevent reentrancy before sending the claim
+            if claim_map::update_claiming_status(claim_id, &[ClaimStatus::Unclaimed, ClaimStatus::UnclaimedOverride]).is_none() {
+                continue;
+            }
+
             // create new request with CLAIMS_TIMER_USER_ID as user_id
-            let request_id = request_map::insert(&StableRequest::new(CLAIMS_TIMER_USER_ID, &Request::Claim(claim.claim_id), ts));
-            let claim_id = claim.claim_id;
+            let request_id = request_map::insert(&StableRequest::new(CLAIMS_TIMER_USER_ID, &Request::Claim(claim_id), ts));
             match process_claim(request_id, claim_id, &token, &claim.amount, to_address, ts).await {
                 Ok(_) => {
                     request_map::update_status(request_id, StatusCode::Success, None);
@@ -90,7 +100,8 @@ pub async fn process_claims() {
     }
 }
 
-async fn process_claim(
+/// send the claim. the claim status must already be set to claiming by the caller
+pub async fn process_claim(
     request_id: u64,
     claim_id: u64,
     token: &StableToken,
@@ -148,9 +159,6 @@ async fn send_claim(
     transfer_ids: &mut Vec<u64>,
     ts: u64,
 ) -> Result<(), String> {
-    // set the claim status to claiming to prevent reentrancy before sending the claim
-    claim_map::update_claiming_status(claim_id);
-
     request_map::update_status(request_id, StatusCode::ClaimToken, None);
 
     let amount_with_gas = nat_subtract(amount, &token.fee()).unwrap_or(nat_zero());
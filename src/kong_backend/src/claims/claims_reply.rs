@@ -0,0 +1,17 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the reply of the `claims` function.
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimsReply {
+    pub claim_id: u64,
+    pub status: String,
+    pub chain: String,
+    pub symbol: String,
+    pub canister_id: String,
+    pub amount: Nat,
+    pub fee: Nat,
+    pub to_address: Option<String>,
+    pub attempts: u32,
+    pub ts: u64,
+}
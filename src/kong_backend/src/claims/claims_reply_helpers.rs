@@ -0,0 +1,21 @@
+use super::claims_reply::ClaimsReply;
+
+use crate::stable_claim::stable_claim::StableClaim;
+use crate::stable_token::token::Token;
+use crate::stable_token::token_map;
+
+pub fn to_claims_reply(claim: &StableClaim) -> Option<ClaimsReply> {
+    let token = token_map::get_by_token_id(claim.token_id)?;
+    Some(ClaimsReply {
+        claim_id: claim.claim_id,
+        status: claim.status.to_string(),
+        chain: token.chain(),
+        symbol: token.symbol(),
+        canister_id: token.address(),
+        amount: claim.amount.clone(),
+        fee: token.fee(),
+        to_address: claim.to_address.as_ref().map(|to_address| to_address.to_string()),
+        attempts: claim.attempt_request_id.len() as u32,
+        ts: claim.ts,
+    })
+}
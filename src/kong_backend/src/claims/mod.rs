@@ -1,3 +1,7 @@
+pub mod claim;
 pub mod claim_reply;
 #[allow(clippy::module_inception)]
 pub mod claims;
+pub mod claims_reply;
+pub mod claims_reply_helpers;
+pub mod user_claims;
@@ -0,0 +1,25 @@
+use ic_cdk::query;
+
+use super::claims_reply::ClaimsReply;
+use super::claims_reply_helpers::to_claims_reply;
+
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_claim::claim_map;
+use crate::stable_claim::stable_claim::ClaimStatus;
+use crate::stable_user::user_map;
+
+/// user's claims that have not been sent out yet, most recent first
+#[query(guard = "not_in_maintenance_mode")]
+pub fn claims(principal_id: String) -> Result<Vec<ClaimsReply>, String> {
+    let user_id = user_map::get_by_principal_id(&principal_id)
+        .ok()
+        .flatten()
+        .ok_or("User not found")?
+        .user_id;
+
+    Ok(claim_map::get_by_user_id(user_id)
+        .iter()
+        .filter(|claim| claim.status != ClaimStatus::Claimed)
+        .filter_map(to_claims_reply)
+        .collect())
+}
@@ -0,0 +1,50 @@
+use ic_cdk::update;
+
+use crate::ic::guards::caller_is_kingkong;
+use crate::stable_pool::dynamic_fee::{DynamicFee, DEFAULT_DYNAMIC_FEE_WINDOW};
+use crate::stable_pool::pool_map;
+
+const MIN_DYNAMIC_FEE_WINDOW: u8 = 2;
+const MAX_DYNAMIC_FEE_WINDOW: u8 = 100;
+
+/// enable the dynamic LP fee of a pool bounded by min_lp_fee_bps and max_lp_fee_bps
+/// window is the number of recent swap prices used for the volatility. the recorded prices are reset
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn set_pool_dynamic_fee(symbol: String, min_lp_fee_bps: u8, max_lp_fee_bps: u8, window: Option<u8>) -> Result<String, String> {
+    let mut pool = pool_map::get_by_token(&symbol)?;
+    if min_lp_fee_bps > max_lp_fee_bps {
+        Err("Minimum LP fee cannot be more than maximum LP fee".to_string())?
+    }
+    if max_lp_fee_bps == u8::MAX {
+        Err(format!("Maximum LP fee must be less than {}", u8::MAX))?
+    }
+    let window = window.unwrap_or(DEFAULT_DYNAMIC_FEE_WINDOW);
+    if !(MIN_DYNAMIC_FEE_WINDOW..=MAX_DYNAMIC_FEE_WINDOW).contains(&window) {
+        Err(format!(
+            "Window must be between {} and {}",
+            MIN_DYNAMIC_FEE_WINDOW, MAX_DYNAMIC_FEE_WINDOW
+        ))?
+    }
+    pool.dynamic_fee = Some(DynamicFee::new(min_lp_fee_bps, max_lp_fee_bps, window));
+    pool_map::update(&pool);
+
+    Ok(format!(
+        "Dynamic LP fee of {} to {} bps enabled for pool {}",
+        min_lp_fee_bps,
+        max_lp_fee_bps,
+        pool.symbol()
+    ))
+}
+
+/// disable the dynamic LP fee of a pool. swaps are charged lp_fee_bps again
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn remove_pool_dynamic_fee(symbol: String) -> Result<String, String> {
+    let mut pool = pool_map::get_by_token(&symbol)?;
+    if pool.dynamic_fee.is_none() {
+        Err(format!("Dynamic LP fee not enabled for pool {}", pool.symbol()))?
+    }
+    pool.dynamic_fee = None;
+    pool_map::update(&pool);
+
+    Ok(format!("Dynamic LP fee disabled for pool {}", pool.symbol()))
+}
@@ -0,0 +1,38 @@
+use ic_cdk::update;
+
+use crate::ic::get_time::get_time;
+use crate::ic::guards::caller_is_kingkong;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_user::user_map;
+
+/// grant a promotional fee tier to a user until expires_at (nanoseconds)
+/// the user returns to the fee tier of their swap volume when it expires
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn grant_fee_tier(principal_id: String, fee_tier: String, expires_at: u64) -> Result<String, String> {
+    let fee_tier = kong_settings_map::get()
+        .fee_tiers
+        .into_iter()
+        .find(|tier| tier.name == fee_tier)
+        .ok_or(format!("Fee tier {} not found", fee_tier))?;
+    if fee_tier.fee_level > 100 {
+        Err(format!("Fee tier {} has invalid fee level {}", fee_tier.name, fee_tier.fee_level))?
+    }
+    if expires_at <= get_time() {
+        Err("Expiry must be in the future".to_string())?
+    }
+    let mut user = user_map::get_by_principal_id(&principal_id)?.ok_or(format!("User {} not found", principal_id))?;
+    if fee_tier.fee_level <= user.volume_fee_level {
+        Err(format!(
+            "User {} already reached fee tier {} by volume",
+            principal_id, fee_tier.name
+        ))?
+    }
+    user.fee_level = fee_tier.fee_level;
+    user.fee_level_expires_at = Some(expires_at);
+    user_map::update(&user);
+
+    Ok(format!(
+        "Fee tier {} granted to user {} until {}",
+        fee_tier.name, principal_id, expires_at
+    ))
+}
@@ -0,0 +1,57 @@
+use candid::Principal;
+use ic_cdk::update;
+
+use crate::ic::guards::caller_is_kingkong;
+use crate::stable_memory::KONG_SETTINGS;
+use crate::stable_pool::pool_map;
+
+/// enable or disable flash loans of a pool
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn set_pool_flash_loan(symbol: String, enabled: bool) -> Result<String, String> {
+    let mut pool = pool_map::get_by_token(&symbol)?;
+    if pool.curve.is_concentrated() {
+        Err("Flash loans are not supported for concentrated liquidity pools".to_string())?
+    }
+    pool.flash_loan_enabled = enabled;
+    pool_map::update(&pool);
+
+    Ok(format!(
+        "Flash loans {} for pool {}",
+        if enabled { "enabled" } else { "disabled" },
+        pool.symbol()
+    ))
+}
+
+/// allow a canister to take flash loans
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn add_flash_loan_canister(canister_id: String) -> Result<String, String> {
+    let canister_id = Principal::from_text(&canister_id).map_err(|e| format!("Invalid canister id: {}", e))?;
+
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let mut kong_settings = map.get().clone();
+        if kong_settings.flash_loan_canisters.contains(&canister_id) {
+            Err(format!("Canister {} already allowed to take flash loans", canister_id))?
+        }
+        kong_settings.flash_loan_canisters.push(canister_id);
+        map.set(kong_settings).map_err(|_| "Failed to update Kong settings".to_string())?;
+        Ok(format!("Canister {} allowed to take flash loans", canister_id))
+    })
+}
+
+/// remove a canister from taking flash loans
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn remove_flash_loan_canister(canister_id: String) -> Result<String, String> {
+    let canister_id = Principal::from_text(&canister_id).map_err(|e| format!("Invalid canister id: {}", e))?;
+
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let mut kong_settings = map.get().clone();
+        if !kong_settings.flash_loan_canisters.contains(&canister_id) {
+            Err(format!("Canister {} not allowed to take flash loans", canister_id))?
+        }
+        kong_settings.flash_loan_canisters.retain(|c| *c != canister_id);
+        map.set(kong_settings).map_err(|_| "Failed to update Kong settings".to_string())?;
+        Ok(format!("Canister {} removed from flash loans", canister_id))
+    })
+}
@@ -3,12 +3,20 @@ use std::collections::BTreeMap;
 
 use crate::helpers::nat_helpers::nat_zero;
 use crate::ic::guards::caller_is_kingkong;
+use crate::stable_lp_token::lp_token_index::create_lp_token_index;
 use crate::stable_lp_token::lp_token_map;
 use crate::stable_lp_token::stable_lp_token::{StableLPToken, StableLPTokenId};
 use crate::stable_memory::LP_TOKEN_MAP;
 
 const MAX_LP_TOKENS: usize = 1_000;
 
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn update_lp_token_index() -> Result<String, String> {
+    create_lp_token_index();
+
+    Ok("LP token index updated".to_string())
+}
+
 #[query(hidden = true, guard = "caller_is_kingkong")]
 fn max_lp_token_idx() -> u64 {
     LP_TOKEN_MAP.with(|m| m.borrow().last_key_value().map_or(0, |(k, _)| k.0))
@@ -51,15 +59,14 @@ fn update_lp_tokens(stable_lp_tokens: String) -> Result<String, String> {
 
 #[update(hidden = true, guard = "caller_is_kingkong")]
 fn remove_zero_lp_tokens() -> Result<String, String> {
-    LP_TOKEN_MAP.with(|m| {
-        let mut map = m.borrow_mut();
-        let keys_to_remove: Vec<_> = map
+    let keys_to_remove: Vec<_> = LP_TOKEN_MAP.with(|m| {
+        m.borrow()
             .iter()
             .filter_map(|(k, v)| if v.amount == nat_zero() { Some(k) } else { None })
-            .collect();
-        keys_to_remove.iter().for_each(|k| {
-            map.remove(k);
-        });
+            .collect()
+    });
+    keys_to_remove.iter().for_each(|k| {
+        lp_token_map::remove(k.0);
     });
 
     Ok("Zero LP tokens removed".to_string())
@@ -1,9 +1,15 @@
 mod canister_withdraw;
 mod check_pools;
 mod claims;
+mod dynamic_fees;
+mod fee_tiers;
+mod flash_loans;
 mod kong_settings;
 mod lp_tokens;
+mod pool_creation;
 mod pools;
+mod protocol_fees;
+mod quote_tokens;
 mod requests;
 mod status;
 mod tokens;
@@ -0,0 +1,96 @@
+use candid::Nat;
+use ic_cdk::update;
+
+use crate::chains::chains::IC_CHAIN;
+use crate::ic::guards::caller_is_kingkong;
+use crate::ic::icp::is_icp;
+use crate::stable_kong_settings::pool_creation_fee::PoolCreationFee;
+use crate::stable_memory::KONG_SETTINGS;
+use crate::stable_token::token::Token;
+use crate::stable_token::token_map;
+
+const KONG_SYMBOL: &str = "KONG";
+
+/// set the minimum initial TVL of new pools, and the TVL threshold and time after which pools are suspended
+/// TVLs are in ckUSDT. 0 disables the check
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn set_pool_creation_policy(min_pool_tvl: Nat, low_tvl_threshold: Nat, low_tvl_suspend_secs: u64) -> Result<String, String> {
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let mut kong_settings = map.get().clone();
+        kong_settings.min_pool_tvl = min_pool_tvl;
+        kong_settings.low_tvl_threshold = low_tvl_threshold;
+        kong_settings.low_tvl_suspend_secs = low_tvl_suspend_secs;
+        map.set(kong_settings).map_err(|_| "Failed to update Kong settings".to_string())?;
+        Ok("Pool creation policy updated".to_string())
+    })
+}
+
+/// set the fee to create a pool in ICP or KONG, sent to the treasury. None removes the fee
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn set_pool_creation_fee(token: Option<String>, amount: Nat) -> Result<String, String> {
+    let pool_creation_fee = match token {
+        Some(token) => {
+            let token = token_map::get_by_token(&token)?;
+            if !is_icp(&token.symbol()) && token.symbol() != KONG_SYMBOL {
+                Err(format!("Pool creation fee must be in ICP or {}", KONG_SYMBOL))?
+            }
+            Some(PoolCreationFee {
+                token_id: token.token_id(),
+                amount,
+            })
+        }
+        None => None,
+    };
+
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let mut kong_settings = map.get().clone();
+        if pool_creation_fee.is_some() && kong_settings.treasury.is_none() {
+            Err("Treasury not set".to_string())?
+        }
+        kong_settings.pool_creation_fee = pool_creation_fee;
+        map.set(kong_settings).map_err(|_| "Failed to update Kong settings".to_string())?;
+        Ok("Pool creation fee updated".to_string())
+    })
+}
+
+/// set the tokens allowed in new pools. empty list allows all tokens
+/// tokens are Chain.Address or Address of IC tokens
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn set_token_allow_list(tokens: Vec<String>) -> Result<String, String> {
+    let tokens = to_addresses_with_chain(&tokens)?;
+
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let mut kong_settings = map.get().clone();
+        kong_settings.token_allow_list = tokens;
+        map.set(kong_settings).map_err(|_| "Failed to update Kong settings".to_string())?;
+        Ok("Token allow list updated".to_string())
+    })
+}
+
+/// set the tokens not allowed in new pools
+/// tokens are Chain.Address or Address of IC tokens
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn set_token_deny_list(tokens: Vec<String>) -> Result<String, String> {
+    let tokens = to_addresses_with_chain(&tokens)?;
+
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let mut kong_settings = map.get().clone();
+        kong_settings.token_deny_list = tokens;
+        map.set(kong_settings).map_err(|_| "Failed to update Kong settings".to_string())?;
+        Ok("Token deny list updated".to_string())
+    })
+}
+
+fn to_addresses_with_chain(tokens: &[String]) -> Result<Vec<String>, String> {
+    tokens
+        .iter()
+        .map(|token| {
+            let address = token_map::get_address(token).ok_or(format!("Invalid token address {}", token))?;
+            Ok(format!("{}.{}", IC_CHAIN, address))
+        })
+        .collect()
+}
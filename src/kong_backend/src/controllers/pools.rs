@@ -8,9 +8,10 @@ use crate::ic::guards::caller_is_kingkong;
 use crate::remove_liquidity::remove_liquidity::remove_liquidity_from_pool;
 use crate::remove_liquidity::remove_liquidity_args::RemoveLiquidityArgs;
 use crate::stable_lp_token::lp_token_map;
-use crate::stable_memory::{LP_TOKEN_MAP, POOL_MAP};
+use crate::stable_memory::POOL_MAP;
 use crate::stable_pool::pool_map;
 use crate::stable_pool::stable_pool::{StablePool, StablePoolId};
+use crate::stable_position::position_map;
 use crate::stable_token::token::Token;
 use crate::stable_user::user_map;
 
@@ -63,34 +64,42 @@ async fn remove_lps_from_pool(symbol: String) -> Result<String, String> {
     let lp_token_id = pool.lp_token_id;
 
     // list of all LP positions to remove
-    // (user_id, principal_id, lp token amount)
-    let lp_users = LP_TOKEN_MAP.with(|m| {
-        m.borrow()
-            .iter()
-            .filter_map(|(_, v)| {
-                if v.token_id == lp_token_id {
-                    let user = user_map::get_by_user_id(v.user_id)?;
-                    Some((user.user_id, user.principal_id, v.amount))
-                } else {
-                    None
-                }
+    // (user_id, principal_id, lp token amount, position_id)
+    let mut lp_users = lp_token_map::get_by_token_id_holders(lp_token_id)
+        .into_iter()
+        .filter_map(|v| {
+            let user = user_map::get_by_user_id(v.user_id)?;
+            Some((user.user_id, user.principal_id, v.amount, None))
+        })
+        .collect::<Vec<_>>();
+    // Concentrated pools hold liquidity in positions
+    if pool.curve.is_concentrated() {
+        lp_users = position_map::get_by_pool_id(pool.pool_id)
+            .into_iter()
+            .filter_map(|position| {
+                let user = user_map::get_by_user_id(position.user_id)?;
+                Some((user.user_id, user.principal_id, position.liquidity, Some(position.position_id)))
             })
-            .collect::<Vec<_>>()
-    });
+            .collect();
+    }
 
     // remove_liquidity for each user
     let token_0 = pool.token_0().address_with_chain();
     let token_1 = pool.token_1().address_with_chain();
     let mut results = Vec::new();
-    for (user_id, principal_id, remove_lp_token_amount) in lp_users {
+    for (user_id, principal_id, remove_lp_token_amount, position_id) in lp_users {
         // skip if user has no LP position
-        if remove_lp_token_amount == nat_zero() {
+        if remove_lp_token_amount == nat_zero() && position_id.is_none() {
             continue;
         }
         let args = RemoveLiquidityArgs {
             token_0: token_0.clone(),
             token_1: token_1.clone(),
             remove_lp_token_amount,
+            position_id,
+            min_amount_0: None,
+            min_amount_1: None,
+            deadline: None,
         };
         match Principal::from_text(principal_id) {
             Ok(principal) => {
@@ -141,10 +150,20 @@ fn suspend_pool(symbol: String) -> Result<String, String> {
     Ok(format!("Pool {} suspended", symbol))
 }
 
+/// unsuspend pool suspended for low TVL, otherwise set is_removed to false
 #[update(hidden = true, guard = "caller_is_kingkong")]
 fn unsuspend_pool(symbol: String) -> Result<String, String> {
     let pool = pool_map::get_by_token(&symbol)?;
-    pool_map::unremove(pool.pool_id)?;
+    if pool.suspended_at.is_some() {
+        // reset low_tvl_since so the pool gets a new grace period
+        pool_map::update(&StablePool {
+            low_tvl_since: None,
+            suspended_at: None,
+            ..pool
+        });
+    } else {
+        pool_map::unremove(pool.pool_id)?;
+    }
 
     Ok(format!("Pool {} unsuspended", symbol))
 }
@@ -0,0 +1,20 @@
+use ic_cdk::update;
+use icrc_ledger_types::icrc1::account::Account;
+use std::str::FromStr;
+
+use crate::ic::guards::caller_is_kingkong;
+use crate::stable_memory::KONG_SETTINGS;
+
+/// set the treasury account protocol fees are collected to
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn set_treasury(treasury: String) -> Result<String, String> {
+    let treasury = Account::from_str(&treasury).map_err(|e| format!("Invalid treasury account: {}", e))?;
+
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let mut kong_settings = map.get().clone();
+        kong_settings.treasury = Some(treasury);
+        map.set(kong_settings).map_err(|_| "Failed to update Kong settings".to_string())?;
+        Ok(format!("Treasury set to {}", treasury))
+    })
+}
@@ -0,0 +1,48 @@
+use ic_cdk::update;
+
+use crate::ic::guards::caller_is_kingkong;
+use crate::stable_memory::KONG_SETTINGS;
+use crate::stable_token::token::Token;
+use crate::stable_token::token_map;
+
+/// add a quote token new pools can be paired against and multi-step swaps route through
+/// quote tokens are in order of priority, the token is added last
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn add_quote_token(token: String) -> Result<String, String> {
+    let token = token_map::get_by_token(&token)?;
+    if token.is_removed() {
+        Err(format!("Token {} is suspended or removed", token.symbol()))?
+    }
+
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let mut kong_settings = map.get().clone();
+        if kong_settings.quote_token_ids.contains(&token.token_id()) {
+            Err(format!("Token {} is already a quote token", token.symbol()))?
+        }
+        kong_settings.quote_token_ids.push(token.token_id());
+        map.set(kong_settings).map_err(|_| "Failed to update Kong settings".to_string())?;
+        Ok(format!("Quote token {} added", token.symbol()))
+    })
+}
+
+/// remove a quote token. existing pools are not affected but no new pools can be paired against it
+/// ckUSDT can not be removed as it is used to value tokens
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn remove_quote_token(token: String) -> Result<String, String> {
+    let token = token_map::get_by_token(&token)?;
+
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let mut kong_settings = map.get().clone();
+        if token.token_id() == kong_settings.ckusdt_token_id {
+            Err(format!("Token {} can not be removed as a quote token", token.symbol()))?
+        }
+        if !kong_settings.quote_token_ids.contains(&token.token_id()) {
+            Err(format!("Token {} is not a quote token", token.symbol()))?
+        }
+        kong_settings.quote_token_ids.retain(|token_id| *token_id != token.token_id());
+        map.set(kong_settings).map_err(|_| "Failed to update Kong settings".to_string())?;
+        Ok(format!("Quote token {} removed", token.symbol()))
+    })
+}
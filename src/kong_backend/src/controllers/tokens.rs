@@ -3,8 +3,10 @@ use std::collections::BTreeMap;
 
 use crate::ic::guards::caller_is_kingkong;
 use crate::stable_memory::TOKEN_MAP;
+use crate::stable_token::ic_token::ICToken;
 use crate::stable_token::stable_token::{StableToken, StableTokenId};
 use crate::stable_token::token_map;
+use crate::stable_token::verification_method::VerificationMethod;
 
 const MAX_TOKENS: usize = 1_000;
 
@@ -47,3 +49,23 @@ fn update_tokens(stable_tokens: String) -> Result<String, String> {
 
     Ok("Tokens updated".to_string())
 }
+
+/// override how transfers are verified on the token's ledger. None to use the default for the token
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn set_verification_method(token: String, verification_method: Option<VerificationMethod>) -> Result<String, String> {
+    let StableToken::IC(ic_token) = token_map::get_by_token(&token)? else {
+        Err("Verification method only supported for IC tokens")?
+    };
+
+    let ic_token = ICToken {
+        verification_method,
+        ..ic_token
+    };
+    token_map::update(&StableToken::IC(ic_token.clone()));
+
+    Ok(format!(
+        "{} verification method set to {}",
+        ic_token.symbol,
+        ic_token.verification_method()
+    ))
+}
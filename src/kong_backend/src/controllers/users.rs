@@ -4,6 +4,7 @@ use std::collections::BTreeMap;
 use crate::ic::guards::caller_is_kingkong;
 use crate::stable_memory::{PRINCIPAL_ID_MAP, USER_MAP};
 use crate::stable_user::principal_id_map::create_principal_id_map;
+use crate::stable_user::referee_count_map::create_referee_count_map;
 use crate::stable_user::stable_user::{StableUser, StableUserId};
 
 const MAX_USERS: usize = 1_000;
@@ -63,6 +64,7 @@ fn update_users(stable_users_json: String) -> Result<String, String> {
     });
 
     create_principal_id_map();
+    create_referee_count_map();
 
     Ok("Users updated".to_string())
 }
@@ -78,6 +80,7 @@ fn update_user(stable_user_json: String) -> Result<String, String> {
         let mut map = user_map.borrow_mut();
         map.insert(StableUserId(user.user_id), user);
     });
+    create_referee_count_map();
 
     Ok("User updated".to_string())
 }
@@ -88,6 +91,7 @@ fn remove_user(user_id: u32) -> Result<String, String> {
         let mut map = user_map.borrow_mut();
         map.remove(&StableUserId(user_id));
     });
+    create_referee_count_map();
 
     Ok("User removed".to_string())
 }
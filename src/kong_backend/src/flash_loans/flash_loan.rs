@@ -0,0 +1,277 @@
+use candid::Nat;
+use ic_cdk::update;
+use icrc_ledger_types::icrc1::account::Account;
+
+use super::flash_loan_args::FlashLoanArgs;
+use super::flash_loan_callback_args::FlashLoanCallbackArgs;
+use super::flash_loan_lock::FlashLoanLock;
+use super::flash_loan_reply::FlashLoanReply;
+
+use crate::helpers::flash_loan_helpers::{
+    credit_flash_loan_fee, flash_loan_fee, flash_loan_repay_amount, max_flash_loan_amount, write_off_flash_loan,
+};
+use crate::helpers::nat_helpers::nat_is_zero;
+use crate::ic::logging::error_log;
+use crate::ic::{
+    get_time::get_time,
+    guards::not_in_maintenance_mode,
+    id::caller,
+    transfer::{icrc1_transfer, icrc2_transfer_from},
+};
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_pool::{pool_map, stable_pool::StablePool};
+use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{stable_token::StableToken, token::Token, token_map};
+use crate::stable_transfer::{stable_transfer::StableTransfer, transfer_map, tx_id::TxId};
+use crate::stable_user::user_map;
+
+/// borrow amount of token from a pool within one call
+///
+/// - only for canisters whitelisted by King Kong and pools with flash loans enabled
+/// - Kong transfers amount to the calling canister and then calls its callback_method with FlashLoanCallbackArgs.
+///   before returning, the callback must approve Kong for repay_amount (amount + fee + gas fee) plus the gas fee of the transfer_from
+/// - Kong collects repay_amount with icrc2_transfer_from() after the callback returns. the fee is credited to the LP fees of the pool
+/// - the pool is locked against swaps and liquidity changes until the loan is repaid
+/// - a loan is at most flash_loan_max_bps of the pool's balance. an unrepaid loan is charged to Kong's fees of the pool,
+///   and any shortfall is owed by the treasury, so the LPs' balance is not affected
+#[update(guard = "not_in_maintenance_mode")]
+pub async fn flash_loan(args: FlashLoanArgs) -> Result<FlashLoanReply, String> {
+    let (user_id, pool, token) = check_arguments(&args)?;
+    let _lock = FlashLoanLock::new(&pool)?;
+    let ts = get_time();
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::FlashLoan(args.clone()), ts));
+
+    let result = match process_flash_loan(request_id, &pool, &token, &args, ts).await {
+        Ok(reply) => {
+            request_map::update_status(request_id, StatusCode::Success, None);
+            Ok(reply)
+        }
+        Err(e) => {
+            request_map::update_status(request_id, StatusCode::Failed, None);
+            Err(e)
+        }
+    };
+    _ = archive_to_kong_data(request_id);
+
+    result
+}
+
+fn check_arguments(args: &FlashLoanArgs) -> Result<(u32, StablePool, StableToken), String> {
+    if !kong_settings_map::get().flash_loan_canisters.contains(&caller()) {
+        Err("Caller is not allowed to take flash loans".to_string())?
+    }
+
+    let pool = pool_map::get_by_token(&args.pool)?;
+    if pool.is_removed || pool.suspended_at.is_some() {
+        Err("Pool is suspended or removed".to_string())?
+    }
+    if !pool.flash_loan_enabled {
+        Err(format!("Flash loans are not enabled for pool {}", args.pool))?
+    }
+    if pool.curve.is_concentrated() {
+        Err("Flash loans are not supported for concentrated liquidity pools".to_string())?
+    }
+
+    let token = token_map::get_by_token(&args.token)?;
+    let balance = if token.token_id() == pool.token_id_0 {
+        &pool.balance_0
+    } else if token.token_id() == pool.token_id_1 {
+        &pool.balance_1
+    } else {
+        Err(format!("Token {} is not in pool {}", args.token, args.pool))?
+    };
+    if token.is_removed() {
+        Err("Token is suspended or removed".to_string())?
+    }
+    if !token.is_icrc2() {
+        Err(format!("Token {} does not support ICRC-2 for repayment", token.symbol()))?
+    }
+    if nat_is_zero(&args.amount) {
+        Err("Invalid zero amount".to_string())?
+    }
+    let max_amount = max_flash_loan_amount(balance, kong_settings_map::get().flash_loan_max_bps);
+    if args.amount > max_amount {
+        Err(format!("Flash loan exceeds the maximum of {} {}", max_amount, token.symbol()))?
+    }
+
+    // make sure user is registered, if not create a new user
+    let user_id = user_map::insert(None)?;
+
+    Ok((user_id, pool, token))
+}
+
+async fn process_flash_loan(
+    request_id: u64,
+    pool: &StablePool,
+    token: &StableToken,
+    args: &FlashLoanArgs,
+    ts: u64,
+) -> Result<FlashLoanReply, String> {
+    let borrower = caller();
+    let is_token_0 = token.token_id() == pool.token_id_0;
+    let fee = flash_loan_fee(&args.amount, kong_settings_map::get().flash_loan_fee_bps).ok_or("Invalid flash loan fee")?;
+    // the borrower also covers the gas fee of the transfer out
+    let repay_amount = flash_loan_repay_amount(&args.amount, &fee, &token.fee());
+
+    request_map::update_status(request_id, StatusCode::Start, None);
+
+    // send the amount to the borrower
+    request_map::update_status(
+        request_id,
+        if is_token_0 {
+            StatusCode::ReceiveToken0
+        } else {
+            StatusCode::ReceiveToken1
+        },
+        None,
+    );
+    match icrc1_transfer(&args.amount, &Account::from(borrower), token, None).await {
+        Ok(block_id) => {
+            transfer_map::insert(&StableTransfer {
+                transfer_id: 0,
+                request_id,
+                is_send: false,
+                amount: args.amount.clone(),
+                token_id: token.token_id(),
+                tx_id: TxId::BlockIndex(block_id),
+                ts,
+            });
+            request_map::update_status(
+                request_id,
+                if is_token_0 {
+                    StatusCode::ReceiveToken0Success
+                } else {
+                    StatusCode::ReceiveToken1Success
+                },
+                None,
+            );
+        }
+        Err(e) => {
+            request_map::update_status(
+                request_id,
+                if is_token_0 {
+                    StatusCode::ReceiveToken0Failed
+                } else {
+                    StatusCode::ReceiveToken1Failed
+                },
+                Some(&e),
+            );
+            Err(format!("Req #{} failed. {}", request_id, e))?
+        }
+    }
+
+    // call back the borrower to use and repay the loan
+    request_map::update_status(request_id, StatusCode::FlashLoanCallback, None);
+    let callback_args = FlashLoanCallbackArgs {
+        request_id,
+        chain: token.chain(),
+        address: token.address(),
+        symbol: token.symbol(),
+        amount: args.amount.clone(),
+        fee: fee.clone(),
+        repay_amount: repay_amount.clone(),
+        data: args.data.clone(),
+    };
+    match ic_cdk::call::<(FlashLoanCallbackArgs,), ()>(borrower, &args.callback_method, (callback_args,)).await {
+        Ok(()) => request_map::update_status(request_id, StatusCode::FlashLoanCallbackSuccess, None),
+        // the borrower could still have approved the repayment so collect it regardless
+        Err(e) => request_map::update_status(request_id, StatusCode::FlashLoanCallbackFailed, Some(&e.1)),
+    };
+
+    // collect the repayment from the borrower. the gas fee of the transfer out is covered by the repay_amount
+    request_map::update_status(request_id, StatusCode::VerifyFlashLoanRepayment, None);
+    let kong_backend = kong_settings_map::get().kong_backend;
+    match icrc2_transfer_from(token, &repay_amount, &Account::from(borrower), &kong_backend).await {
+        Ok(block_id) => {
+            transfer_map::insert(&StableTransfer {
+                transfer_id: 0,
+                request_id,
+                is_send: true,
+                amount: repay_amount.clone(),
+                token_id: token.token_id(),
+                tx_id: TxId::BlockIndex(block_id),
+                ts,
+            });
+            request_map::update_status(request_id, StatusCode::VerifyFlashLoanRepaymentSuccess, None);
+        }
+        Err(e) => {
+            request_map::update_status(request_id, StatusCode::VerifyFlashLoanRepaymentFailed, Some(&e));
+            // the amount is lost, charge it to Kong's fees of the pool so the LPs do not absorb the loss
+            request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
+            let shortfall = match write_off_pool_flash_loan(pool.pool_id, is_token_0, &args.amount) {
+                Ok(shortfall) => {
+                    request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
+                    shortfall
+                }
+                Err(e) => {
+                    request_map::update_status(request_id, StatusCode::UpdatePoolAmountsFailed, Some(&e));
+                    args.amount.clone()
+                }
+            };
+            error_log(&format!(
+                "Req #{}. Flash loan of {} {} from pool {} not repaid. Shortfall owed by the treasury {} {}. {}",
+                request_id,
+                args.amount,
+                token.symbol(),
+                pool.symbol(),
+                shortfall,
+                token.symbol(),
+                e
+            ));
+            Err(format!("Req #{} failed. Flash loan not repaid. {}", request_id, e))?
+        }
+    }
+
+    // credit the fee to the LPs
+    request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
+    credit_pool_flash_loan_fee(pool.pool_id, is_token_0, &fee);
+    request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
+
+    let reply = FlashLoanReply {
+        request_id,
+        status: "Success".to_string(),
+        pool_symbol: pool.symbol(),
+        chain: token.chain(),
+        address: token.address(),
+        symbol: token.symbol(),
+        amount: args.amount.clone(),
+        fee,
+        repay_amount,
+        ts,
+    };
+    request_map::update_reply(request_id, Reply::FlashLoan(reply.clone()));
+
+    Ok(reply)
+}
+
+/// charge an unrepaid flash loan to Kong's fees of the pool
+/// refresh with the latest state of the pool. returns the shortfall owed by the treasury
+fn write_off_pool_flash_loan(pool_id: u32, is_token_0: bool, amount: &Nat) -> Result<Nat, String> {
+    let mut pool = pool_map::get_by_pool_id(pool_id).ok_or("Pool not found")?;
+    let shortfall = write_off_flash_loan(&mut pool, is_token_0, amount);
+    pool.set_tvl();
+    pool_map::update(&pool);
+    Ok(shortfall)
+}
+
+/// add the fee of a flash loan to the LP fees of the pool
+/// refresh with the latest state of the pool
+fn credit_pool_flash_loan_fee(pool_id: u32, is_token_0: bool, fee: &Nat) {
+    let Some(mut pool) = pool_map::get_by_pool_id(pool_id) else {
+        return; // should not get here
+    };
+    credit_flash_loan_fee(&mut pool, is_token_0, fee);
+    pool.set_tvl();
+    pool_map::update(&pool);
+}
+
+fn archive_to_kong_data(request_id: u64) -> Result<(), String> {
+    if !kong_settings_map::get().archive_to_kong_data {
+        return Ok(());
+    }
+
+    let request = request_map::get_by_request_id(request_id).ok_or(format!("Failed to archive. request_id #{} not found", request_id))?;
+    request_map::archive_to_kong_data(&request)?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the arguments of the `flash_loan` function.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct FlashLoanArgs {
+    pub pool: String,  // pool to borrow from eg. "ckBTC_ckUSDT"
+    pub token: String, // token to borrow. must be token_0 or token_1 of the pool
+    pub amount: Nat,
+    pub callback_method: String, // method of the calling canister called with FlashLoanCallbackArgs after the amount is sent
+    pub data: Vec<u8>,           // passed as is to the callback
+}
@@ -0,0 +1,16 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the arguments of the callback method of the borrowing canister.
+/// Kong must be approved to transfer repay_amount (plus the gas fee) before the callback returns
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct FlashLoanCallbackArgs {
+    pub request_id: u64,
+    pub chain: String,
+    pub address: String,
+    pub symbol: String,
+    pub amount: Nat,
+    pub fee: Nat,
+    pub repay_amount: Nat, // amount + fee + gas fee of the transfer to the borrower
+    pub data: Vec<u8>,
+}
@@ -0,0 +1,39 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+use crate::stable_pool::stable_pool::StablePool;
+
+thread_local! {
+    // pools with a flash loan in progress. swaps and liquidity changes of a pool are locked until the loan is repaid
+    static FLASH_LOAN_POOLS: RefCell<BTreeSet<u32>> = RefCell::default();
+}
+
+/// holds the flash loan lock of a pool. released when dropped, including when the callback traps
+pub struct FlashLoanLock {
+    pool_id: u32,
+}
+
+impl FlashLoanLock {
+    pub fn new(pool: &StablePool) -> Result<Self, String> {
+        let pool_id = pool.pool_id;
+        if !FLASH_LOAN_POOLS.with(|m| m.borrow_mut().insert(pool_id)) {
+            Err(format!("Flash loan of pool {} already in progress", pool.symbol()))?
+        }
+        Ok(Self { pool_id })
+    }
+}
+
+impl Drop for FlashLoanLock {
+    fn drop(&mut self) {
+        FLASH_LOAN_POOLS.with(|m| m.borrow_mut().remove(&self.pool_id));
+    }
+}
+
+/// error if the pool has a flash loan in progress
+/// the reserves of the pool are not final until the loan is repaid so they can not be used for swaps or liquidity
+pub fn check_flash_loan_lock(pool: &StablePool) -> Result<(), String> {
+    if FLASH_LOAN_POOLS.with(|m| m.borrow().contains(&pool.pool_id)) {
+        Err(format!("Pool {} is locked by a flash loan in progress", pool.symbol()))?
+    }
+    Ok(())
+}
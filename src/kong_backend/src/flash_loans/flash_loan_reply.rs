@@ -0,0 +1,16 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct FlashLoanReply {
+    pub request_id: u64,
+    pub status: String,
+    pub pool_symbol: String,
+    pub chain: String,
+    pub address: String,
+    pub symbol: String,
+    pub amount: Nat,
+    pub fee: Nat,
+    pub repay_amount: Nat,
+    pub ts: u64,
+}
@@ -0,0 +1,6 @@
+#[allow(clippy::module_inception)]
+pub mod flash_loan;
+pub mod flash_loan_args;
+pub mod flash_loan_callback_args;
+pub mod flash_loan_lock;
+pub mod flash_loan_reply;
@@ -0,0 +1,340 @@
+use candid::Nat;
+use num_bigint::BigUint;
+use num_traits::One;
+use std::cmp;
+
+use super::nat_helpers::{nat_add, nat_divide, nat_divide_ceil, nat_is_zero, nat_multiply, nat_sqrt, nat_subtract, nat_zero};
+
+// Uniswap v3 style concentrated liquidity math
+// prices are stored as sqrt(price) in Q64.96 fixed point where price = amount_1 / amount_0 in token units
+// tick i is the price 1.0001^i, so sqrt_price = 1.0001^(i / 2)
+
+pub const MIN_TICK: i32 = -887_272;
+pub const MAX_TICK: i32 = 887_272;
+
+pub fn q96() -> Nat {
+    Nat::from(BigUint::one() << 96)
+}
+
+pub fn q128() -> Nat {
+    Nat::from(BigUint::one() << 128)
+}
+
+/// sqrt(1.0001) in Q128
+fn sqrt_tick_base_x128() -> BigUint {
+    ((BigUint::from(10_001_u32) << 256_usize) / BigUint::from(10_000_u32)).sqrt()
+}
+
+/// sqrt price in Q96 at the given tick
+pub fn sqrt_price_at_tick(tick: i32) -> Option<Nat> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        None?
+    }
+
+    // sqrt(1.0001)^|tick| in Q128 with exponentiation by squaring
+    let mut base = sqrt_tick_base_x128();
+    let mut ratio = BigUint::one() << 128;
+    let mut exp = tick.unsigned_abs();
+    while exp > 0 {
+        if exp & 1 == 1 {
+            ratio = (ratio * &base) >> 128;
+        }
+        base = (&base * &base) >> 128;
+        exp >>= 1;
+    }
+    if tick < 0 {
+        ratio = (BigUint::one() << 256) / ratio;
+    }
+
+    // Q128 to Q96 rounding up so that tick_at_sqrt_price(sqrt_price_at_tick(tick)) == tick
+    let remainder = BigUint::one() << 32;
+    Some(Nat::from((ratio + &remainder - BigUint::one()) / remainder))
+}
+
+pub fn min_sqrt_price() -> Nat {
+    sqrt_price_at_tick(MIN_TICK).unwrap_or(nat_zero())
+}
+
+pub fn max_sqrt_price() -> Nat {
+    sqrt_price_at_tick(MAX_TICK).unwrap_or(nat_zero())
+}
+
+/// greatest tick whose sqrt price is less than or equal to sqrt_price
+pub fn tick_at_sqrt_price(sqrt_price: &Nat) -> Option<i32> {
+    if *sqrt_price < min_sqrt_price() || *sqrt_price > max_sqrt_price() {
+        None?
+    }
+
+    let mut low = MIN_TICK;
+    let mut high = MAX_TICK;
+    while low < high {
+        // round up so the range always shrinks
+        let mid = low + (high - low + 1) / 2;
+        if sqrt_price_at_tick(mid)? <= *sqrt_price {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Some(low)
+}
+
+/// sqrt price in Q96 of price = amount_1 / amount_0
+pub fn sqrt_price_from_amounts(amount_0: &Nat, amount_1: &Nat) -> Option<Nat> {
+    if nat_is_zero(amount_0) {
+        None?
+    }
+    let price_x192 = nat_divide(&Nat::from(nat_multiply(amount_1, &q96()).0 << 96), amount_0)?;
+    Some(nat_sqrt(&price_x192))
+}
+
+fn sort_sqrt_prices<'a>(sqrt_price_a: &'a Nat, sqrt_price_b: &'a Nat) -> (&'a Nat, &'a Nat) {
+    if sqrt_price_a > sqrt_price_b {
+        (sqrt_price_b, sqrt_price_a)
+    } else {
+        (sqrt_price_a, sqrt_price_b)
+    }
+}
+
+/// amount_0 between two sqrt prices for liquidity
+/// amount_0 = liquidity * (sqrt_price_b - sqrt_price_a) / (sqrt_price_a * sqrt_price_b)
+pub fn amount_0_delta(sqrt_price_a: &Nat, sqrt_price_b: &Nat, liquidity: &Nat, round_up: bool) -> Option<Nat> {
+    let (sqrt_price_a, sqrt_price_b) = sort_sqrt_prices(sqrt_price_a, sqrt_price_b);
+    if nat_is_zero(sqrt_price_a) {
+        None?
+    }
+    let numerator = nat_multiply(&nat_multiply(liquidity, &q96()), &nat_subtract(sqrt_price_b, sqrt_price_a)?);
+    if round_up {
+        nat_divide_ceil(&nat_divide_ceil(&numerator, sqrt_price_b)?, sqrt_price_a)
+    } else {
+        nat_divide(&nat_divide(&numerator, sqrt_price_b)?, sqrt_price_a)
+    }
+}
+
+/// amount_1 between two sqrt prices for liquidity
+/// amount_1 = liquidity * (sqrt_price_b - sqrt_price_a)
+pub fn amount_1_delta(sqrt_price_a: &Nat, sqrt_price_b: &Nat, liquidity: &Nat, round_up: bool) -> Option<Nat> {
+    let (sqrt_price_a, sqrt_price_b) = sort_sqrt_prices(sqrt_price_a, sqrt_price_b);
+    let numerator = nat_multiply(liquidity, &nat_subtract(sqrt_price_b, sqrt_price_a)?);
+    if round_up {
+        nat_divide_ceil(&numerator, &q96())
+    } else {
+        nat_divide(&numerator, &q96())
+    }
+}
+
+/// next sqrt price after amount_0 is added to or removed from the pool, rounded up
+fn next_sqrt_price_from_amount_0(sqrt_price: &Nat, liquidity: &Nat, amount: &Nat, add: bool) -> Option<Nat> {
+    if nat_is_zero(amount) {
+        return Some(sqrt_price.clone());
+    }
+    let numerator = nat_multiply(liquidity, &q96());
+    let product = nat_multiply(amount, sqrt_price);
+    let denominator = if add {
+        nat_add(&numerator, &product)
+    } else {
+        nat_subtract(&numerator, &product).filter(|d| !nat_is_zero(d))?
+    };
+    nat_divide_ceil(&nat_multiply(&numerator, sqrt_price), &denominator)
+}
+
+/// next sqrt price after amount_1 is added to or removed from the pool, rounded down
+fn next_sqrt_price_from_amount_1(sqrt_price: &Nat, liquidity: &Nat, amount: &Nat, add: bool) -> Option<Nat> {
+    let amount_x96 = nat_multiply(amount, &q96());
+    if add {
+        Some(nat_add(sqrt_price, &nat_divide(&amount_x96, liquidity)?))
+    } else {
+        nat_subtract(sqrt_price, &nat_divide_ceil(&amount_x96, liquidity)?)
+    }
+}
+
+/// one step of a swap within a single tick range, fees are not included
+/// zero_for_one is implied by sqrt_price_current >= sqrt_price_target
+/// amount_remaining is the pay amount if exact_input, otherwise the receive amount
+///
+/// returns (sqrt_price_next, amount_in, amount_out)
+pub fn swap_step(
+    sqrt_price_current: &Nat,
+    sqrt_price_target: &Nat,
+    liquidity: &Nat,
+    amount_remaining: &Nat,
+    exact_input: bool,
+) -> Option<(Nat, Nat, Nat)> {
+    let zero_for_one = sqrt_price_current >= sqrt_price_target;
+
+    let sqrt_price_next = if exact_input {
+        let amount_in_max = if zero_for_one {
+            amount_0_delta(sqrt_price_target, sqrt_price_current, liquidity, true)?
+        } else {
+            amount_1_delta(sqrt_price_current, sqrt_price_target, liquidity, true)?
+        };
+        if *amount_remaining >= amount_in_max {
+            sqrt_price_target.clone()
+        } else if zero_for_one {
+            next_sqrt_price_from_amount_0(sqrt_price_current, liquidity, amount_remaining, true)?
+        } else {
+            next_sqrt_price_from_amount_1(sqrt_price_current, liquidity, amount_remaining, true)?
+        }
+    } else {
+        let amount_out_max = if zero_for_one {
+            amount_1_delta(sqrt_price_target, sqrt_price_current, liquidity, false)?
+        } else {
+            amount_0_delta(sqrt_price_current, sqrt_price_target, liquidity, false)?
+        };
+        if *amount_remaining >= amount_out_max {
+            sqrt_price_target.clone()
+        } else if zero_for_one {
+            next_sqrt_price_from_amount_1(sqrt_price_current, liquidity, amount_remaining, false)?
+        } else {
+            next_sqrt_price_from_amount_0(sqrt_price_current, liquidity, amount_remaining, false)?
+        }
+    };
+
+    let (amount_in, amount_out) = if zero_for_one {
+        (
+            amount_0_delta(&sqrt_price_next, sqrt_price_current, liquidity, true)?,
+            amount_1_delta(&sqrt_price_next, sqrt_price_current, liquidity, false)?,
+        )
+    } else {
+        (
+            amount_1_delta(sqrt_price_current, &sqrt_price_next, liquidity, true)?,
+            amount_0_delta(sqrt_price_current, &sqrt_price_next, liquidity, false)?,
+        )
+    };
+
+    if exact_input && sqrt_price_next != *sqrt_price_target {
+        // target not reached so the whole remaining amount is consumed, any rounding dust stays in the pool
+        Some((sqrt_price_next, amount_remaining.clone(), amount_out))
+    } else if !exact_input {
+        Some((sqrt_price_next, amount_in, cmp::min(amount_out, amount_remaining.clone())))
+    } else {
+        Some((sqrt_price_next, amount_in, amount_out))
+    }
+}
+
+/// liquidity for amount_0 between two sqrt prices
+fn liquidity_for_amount_0(sqrt_price_a: &Nat, sqrt_price_b: &Nat, amount_0: &Nat) -> Option<Nat> {
+    let (sqrt_price_a, sqrt_price_b) = sort_sqrt_prices(sqrt_price_a, sqrt_price_b);
+    let intermediate = nat_divide(&nat_multiply(sqrt_price_a, sqrt_price_b), &q96())?;
+    nat_divide(&nat_multiply(amount_0, &intermediate), &nat_subtract(sqrt_price_b, sqrt_price_a)?)
+}
+
+/// liquidity for amount_1 between two sqrt prices
+fn liquidity_for_amount_1(sqrt_price_a: &Nat, sqrt_price_b: &Nat, amount_1: &Nat) -> Option<Nat> {
+    let (sqrt_price_a, sqrt_price_b) = sort_sqrt_prices(sqrt_price_a, sqrt_price_b);
+    nat_divide(&nat_multiply(amount_1, &q96()), &nat_subtract(sqrt_price_b, sqrt_price_a)?)
+}
+
+/// max liquidity that amount_0 and amount_1 can provide between sqrt_price_lower and sqrt_price_upper at the current sqrt_price
+pub fn liquidity_for_amounts(
+    sqrt_price: &Nat,
+    sqrt_price_lower: &Nat,
+    sqrt_price_upper: &Nat,
+    amount_0: &Nat,
+    amount_1: &Nat,
+) -> Option<Nat> {
+    if sqrt_price_lower >= sqrt_price_upper {
+        None?
+    }
+    if sqrt_price <= sqrt_price_lower {
+        liquidity_for_amount_0(sqrt_price_lower, sqrt_price_upper, amount_0)
+    } else if sqrt_price < sqrt_price_upper {
+        let liquidity_0 = liquidity_for_amount_0(sqrt_price, sqrt_price_upper, amount_0)?;
+        let liquidity_1 = liquidity_for_amount_1(sqrt_price_lower, sqrt_price, amount_1)?;
+        Some(cmp::min(liquidity_0, liquidity_1))
+    } else {
+        liquidity_for_amount_1(sqrt_price_lower, sqrt_price_upper, amount_1)
+    }
+}
+
+/// amount_0 and amount_1 of liquidity between sqrt_price_lower and sqrt_price_upper at the current sqrt_price
+/// round up when adding liquidity and round down when removing
+pub fn amounts_for_liquidity(
+    sqrt_price: &Nat,
+    sqrt_price_lower: &Nat,
+    sqrt_price_upper: &Nat,
+    liquidity: &Nat,
+    round_up: bool,
+) -> Option<(Nat, Nat)> {
+    if sqrt_price <= sqrt_price_lower {
+        Some((amount_0_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?, nat_zero()))
+    } else if sqrt_price < sqrt_price_upper {
+        Some((
+            amount_0_delta(sqrt_price, sqrt_price_upper, liquidity, round_up)?,
+            amount_1_delta(sqrt_price_lower, sqrt_price, liquidity, round_up)?,
+        ))
+    } else {
+        Some((nat_zero(), amount_1_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqrt_price_at_tick() {
+        assert_eq!(sqrt_price_at_tick(0), Some(q96()));
+        assert_eq!(sqrt_price_at_tick(MAX_TICK + 1), None);
+        assert_eq!(sqrt_price_at_tick(MIN_TICK - 1), None);
+
+        // 1.0001^(100 / 2) = 1.00501...
+        let sqrt_price = sqrt_price_at_tick(100).unwrap();
+        let expected = nat_divide(&nat_multiply(&q96(), &Nat::from(1_005_012_u32)), &Nat::from(1_000_000_u32)).unwrap();
+        assert!(sqrt_price > expected);
+        assert!(sqrt_price_at_tick(-100).unwrap() < q96());
+    }
+
+    #[test]
+    fn test_tick_at_sqrt_price() {
+        for tick in [MIN_TICK, -50_000, -1, 0, 1, 60, 50_000, MAX_TICK] {
+            let sqrt_price = sqrt_price_at_tick(tick).unwrap();
+            assert_eq!(tick_at_sqrt_price(&sqrt_price), Some(tick));
+            if tick < MAX_TICK {
+                assert_eq!(tick_at_sqrt_price(&nat_add(&sqrt_price, &Nat::from(1_u8))), Some(tick));
+            }
+        }
+    }
+
+    #[test]
+    fn test_liquidity_for_amounts() {
+        let sqrt_price = q96();
+        let sqrt_price_lower = sqrt_price_at_tick(-600).unwrap();
+        let sqrt_price_upper = sqrt_price_at_tick(600).unwrap();
+        let amount = Nat::from(1_000_000_000_u128);
+        let liquidity = liquidity_for_amounts(&sqrt_price, &sqrt_price_lower, &sqrt_price_upper, &amount, &amount).unwrap();
+        let (amount_0, amount_1) = amounts_for_liquidity(&sqrt_price, &sqrt_price_lower, &sqrt_price_upper, &liquidity, false).unwrap();
+        assert!(amount_0 <= amount);
+        assert!(amount_1 <= amount);
+
+        // below the range, only token_0 is needed
+        let sqrt_price = sqrt_price_at_tick(-1_000).unwrap();
+        let (_, amount_1) = amounts_for_liquidity(&sqrt_price, &sqrt_price_lower, &sqrt_price_upper, &liquidity, true).unwrap();
+        assert_eq!(amount_1, nat_zero());
+    }
+
+    #[test]
+    fn test_swap_step() {
+        let liquidity = Nat::from(1_000_000_000_000_u128);
+        let sqrt_price = q96();
+        let sqrt_price_target = sqrt_price_at_tick(-600).unwrap();
+
+        // exact input within the range
+        let amount_in = Nat::from(1_000_000_u128);
+        let (sqrt_price_next, step_in, step_out) = swap_step(&sqrt_price, &sqrt_price_target, &liquidity, &amount_in, true).unwrap();
+        assert!(sqrt_price_next < sqrt_price && sqrt_price_next > sqrt_price_target);
+        assert_eq!(step_in, amount_in);
+        assert!(step_out < amount_in);
+
+        // exact output within the range needs at least as much in as out at price 1
+        let (_, step_in, step_out) = swap_step(&sqrt_price, &sqrt_price_target, &liquidity, &amount_in, false).unwrap();
+        assert_eq!(step_out, amount_in);
+        assert!(step_in > step_out);
+
+        // large amount stops at the target
+        let amount_in = Nat::from(1_000_000_000_000_000_u128);
+        let (sqrt_price_next, step_in, _) = swap_step(&sqrt_price, &sqrt_price_target, &liquidity, &amount_in, true).unwrap();
+        assert_eq!(sqrt_price_next, sqrt_price_target);
+        assert!(step_in < amount_in);
+    }
+}
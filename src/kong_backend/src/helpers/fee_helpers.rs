@@ -0,0 +1,150 @@
+use candid::Nat;
+use std::collections::BTreeMap;
+
+use super::nat_helpers::{nat_add, nat_zero};
+
+use crate::stable_kong_settings::fee_tier::FeeTier;
+use crate::stable_user_volume::stable_user_volume::StableUserVolume;
+
+/// append price to the ring buffer of recent prices, dropping the oldest prices beyond window
+pub fn push_price(prices: &mut Vec<f64>, price: f64, window: usize) {
+    if !price.is_finite() || price <= 0_f64 {
+        return;
+    }
+    prices.push(price);
+    if prices.len() > window {
+        prices.drain(..prices.len() - window);
+    }
+}
+
+/// realised volatility of prices in basis points
+/// root mean square of the log returns between consecutive prices
+pub fn realised_volatility_bps(prices: &[f64]) -> f64 {
+    if prices.len() < 2 {
+        return 0_f64;
+    }
+    let sum_squares: f64 = prices.windows(2).map(|w| (w[1] / w[0]).ln().powi(2)).sum();
+    (sum_squares / (prices.len() - 1) as f64).sqrt() * 10_000_f64
+}
+
+/// LP fee in basis points of a dynamic fee pool
+/// fee = min_lp_fee_bps + realised volatility in bps, bounded by min_lp_fee_bps and max_lp_fee_bps
+pub fn dynamic_lp_fee_bps(volatility_bps: f64, min_lp_fee_bps: u8, max_lp_fee_bps: u8) -> u8 {
+    let lp_fee_bps = min_lp_fee_bps as f64 + volatility_bps.round();
+    if !lp_fee_bps.is_finite() {
+        return max_lp_fee_bps;
+    }
+    lp_fee_bps.clamp(min_lp_fee_bps as f64, max_lp_fee_bps as f64) as u8
+}
+
+/// highest fee tier reached by volume
+pub fn fee_tier_for_volume<'a>(fee_tiers: &'a [FeeTier], volume: &Nat) -> Option<&'a FeeTier> {
+    fee_tiers
+        .iter()
+        .filter(|fee_tier| *volume >= fee_tier.min_volume)
+        .max_by_key(|fee_tier| fee_tier.fee_level)
+}
+
+/// fee tier with the lowest volume above fee_level not reached by volume yet
+pub fn next_fee_tier<'a>(fee_tiers: &'a [FeeTier], volume: &Nat, fee_level: u8) -> Option<&'a FeeTier> {
+    fee_tiers
+        .iter()
+        .filter(|fee_tier| fee_tier.fee_level > fee_level && *volume < fee_tier.min_volume)
+        .min_by(|a, b| a.min_volume.cmp(&b.min_volume))
+}
+
+/// total volume by user of daily volumes
+pub fn user_volumes(volumes: &[StableUserVolume]) -> BTreeMap<u32, Nat> {
+    volumes.iter().fold(BTreeMap::new(), |mut user_volumes, daily_volume| {
+        let user_volume = user_volumes.entry(daily_volume.user_id).or_insert(nat_zero());
+        *user_volume = nat_add(user_volume, &daily_volume.volume);
+        user_volumes
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee_tiers() -> Vec<FeeTier> {
+        [("Silver", 10_000_u64, 10_u8), ("Bronze", 1_000, 5), ("Gold", 100_000, 20)]
+            .iter()
+            .map(|(name, min_volume, fee_level)| FeeTier {
+                name: name.to_string(),
+                min_volume: Nat::from(*min_volume),
+                fee_level: *fee_level,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fee_tier_for_volume() {
+        let fee_tiers = fee_tiers();
+        assert!(fee_tier_for_volume(&fee_tiers, &Nat::from(999_u64)).is_none());
+        assert_eq!(fee_tier_for_volume(&fee_tiers, &Nat::from(1_000_u64)).unwrap().fee_level, 5);
+        assert_eq!(fee_tier_for_volume(&fee_tiers, &Nat::from(50_000_u64)).unwrap().fee_level, 10);
+        assert_eq!(fee_tier_for_volume(&fee_tiers, &Nat::from(1_000_000_u64)).unwrap().fee_level, 20);
+        assert!(fee_tier_for_volume(&[], &Nat::from(1_000_000_u64)).is_none());
+    }
+
+    #[test]
+    fn test_next_fee_tier() {
+        let fee_tiers = fee_tiers();
+        assert_eq!(next_fee_tier(&fee_tiers, &Nat::from(0_u64), 0).unwrap().fee_level, 5);
+        assert_eq!(next_fee_tier(&fee_tiers, &Nat::from(5_000_u64), 5).unwrap().fee_level, 10);
+        // promotional fee level above the volume tier
+        assert_eq!(next_fee_tier(&fee_tiers, &Nat::from(5_000_u64), 10).unwrap().fee_level, 20);
+        assert!(next_fee_tier(&fee_tiers, &Nat::from(1_000_000_u64), 20).is_none());
+    }
+
+    #[test]
+    fn test_push_price() {
+        let mut prices = Vec::new();
+        (1..=5).for_each(|i| push_price(&mut prices, i as f64, 3));
+        assert_eq!(prices, vec![3.0, 4.0, 5.0]);
+        push_price(&mut prices, 0.0, 3);
+        push_price(&mut prices, f64::NAN, 3);
+        assert_eq!(prices, vec![3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn test_realised_volatility_bps() {
+        assert_eq!(realised_volatility_bps(&[]), 0.0);
+        assert_eq!(realised_volatility_bps(&[1.0]), 0.0);
+        assert_eq!(realised_volatility_bps(&[1.0, 1.0, 1.0]), 0.0);
+        // alternating moves of 1% up and down
+        let volatility_bps = realised_volatility_bps(&[1.0, 1.01, 1.0, 1.01]);
+        assert!((volatility_bps - 99.5).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_dynamic_lp_fee_bps() {
+        assert_eq!(dynamic_lp_fee_bps(0.0, 5, 100), 5);
+        assert_eq!(dynamic_lp_fee_bps(24.6, 5, 100), 30);
+        assert_eq!(dynamic_lp_fee_bps(500.0, 5, 100), 100);
+        assert_eq!(dynamic_lp_fee_bps(f64::INFINITY, 5, 100), 100);
+    }
+
+    #[test]
+    fn test_user_volumes() {
+        let volumes = [
+            (20_000_u64, 100_u32, 1_000_u64),
+            (20_000, 101, 50),
+            (20_001, 100, 2_000),
+            (20_002, 102, 7),
+        ]
+        .iter()
+        .map(|(day, user_id, volume)| StableUserVolume {
+            day: *day,
+            user_id: *user_id,
+            volume: Nat::from(*volume),
+        })
+        .collect::<Vec<StableUserVolume>>();
+        let volumes_by_user = user_volumes(&volumes);
+        assert_eq!(volumes_by_user.len(), 3);
+        assert_eq!(volumes_by_user.get(&100), Some(&Nat::from(3_000_u64)));
+        assert_eq!(volumes_by_user.get(&101), Some(&Nat::from(50_u64)));
+        assert_eq!(volumes_by_user.get(&102), Some(&Nat::from(7_u64)));
+        assert!(user_volumes(&[]).is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use candid::Nat;
+
+use super::nat_helpers::{nat_add, nat_divide, nat_divide_ceil, nat_multiply, nat_subtract, nat_zero};
+
+use crate::stable_pool::stable_pool::StablePool;
+
+/// fee of a flash loan of amount, rounded up in favour of the pool
+/// fee = amount * fee_bps / 10_000
+pub fn flash_loan_fee(amount: &Nat, fee_bps: u8) -> Option<Nat> {
+    nat_divide_ceil(&nat_multiply(amount, &Nat::from(fee_bps)), &Nat::from(10_000_u128))
+}
+
+/// amount the borrower repays: the loan, the fee and the gas fee of the transfer out
+pub fn flash_loan_repay_amount(amount: &Nat, fee: &Nat, gas_fee: &Nat) -> Nat {
+    nat_add(&nat_add(amount, fee), gas_fee)
+}
+
+/// largest flash loan of a pool balance. max_bps of the balance, rounded down
+pub fn max_flash_loan_amount(balance: &Nat, max_bps: u16) -> Nat {
+    nat_divide(&nat_multiply(balance, &Nat::from(max_bps)), &Nat::from(10_000_u128)).unwrap_or(nat_zero())
+}
+
+/// charge amount to Kong's fee of a pool together with any earlier shortfall
+/// returns (kong_fee, shortfall), shortfall being what kong_fee could not cover and is owed by the treasury
+pub fn charge_kong_fee(kong_fee: &Nat, shortfall: &Nat, amount: &Nat) -> (Nat, Nat) {
+    let owed = nat_add(shortfall, amount);
+    match nat_subtract(kong_fee, &owed) {
+        Some(kong_fee) => (kong_fee, nat_zero()),
+        None => (nat_zero(), nat_subtract(&owed, kong_fee).unwrap_or(nat_zero())),
+    }
+}
+
+/// add the fee of a repaid flash loan to the LP fees of the pool
+pub fn credit_flash_loan_fee(pool: &mut StablePool, is_token_0: bool, fee: &Nat) {
+    if is_token_0 {
+        pool.lp_fee_0 = nat_add(&pool.lp_fee_0, fee);
+    } else {
+        pool.lp_fee_1 = nat_add(&pool.lp_fee_1, fee);
+    }
+}
+
+/// charge an unrepaid flash loan to Kong's fees of the pool so the LPs' balance is not affected
+/// returns the shortfall not covered by Kong's fees
+pub fn write_off_flash_loan(pool: &mut StablePool, is_token_0: bool, amount: &Nat) -> Nat {
+    if is_token_0 {
+        (pool.kong_fee_0, pool.flash_loan_shortfall_0) = charge_kong_fee(&pool.kong_fee_0, &pool.flash_loan_shortfall_0, amount);
+        pool.flash_loan_shortfall_0.clone()
+    } else {
+        (pool.kong_fee_1, pool.flash_loan_shortfall_1) = charge_kong_fee(&pool.kong_fee_1, &pool.flash_loan_shortfall_1, amount);
+        pool.flash_loan_shortfall_1.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::stable_pool::pool_curve::PoolCurve;
+
+    fn pool() -> StablePool {
+        let mut pool = StablePool::new(1, 2, 30, 8, 3, PoolCurve::ConstantProduct);
+        pool.balance_0 = Nat::from(1_000_000_u64);
+        pool.balance_1 = Nat::from(2_000_000_u64);
+        pool
+    }
+
+    #[test]
+    fn test_flash_loan_fee() {
+        assert_eq!(flash_loan_fee(&Nat::from(10_000_u64), 9), Some(Nat::from(9_u64)));
+        // rounded up in favour of the pool
+        assert_eq!(flash_loan_fee(&Nat::from(10_001_u64), 9), Some(Nat::from(10_u64)));
+        assert_eq!(flash_loan_fee(&Nat::from(1_u64), 9), Some(Nat::from(1_u64)));
+        assert_eq!(flash_loan_fee(&Nat::from(1_000_000_u64), 0), Some(Nat::from(0_u64)));
+    }
+
+    #[test]
+    fn test_flash_loan_repay_amount() {
+        let fee = flash_loan_fee(&Nat::from(1_000_000_u64), 9).unwrap();
+        assert_eq!(
+            flash_loan_repay_amount(&Nat::from(1_000_000_u64), &fee, &Nat::from(10_000_u64)),
+            Nat::from(1_010_900_u64)
+        );
+    }
+
+    #[test]
+    fn test_max_flash_loan_amount() {
+        assert_eq!(max_flash_loan_amount(&Nat::from(1_000_000_u64), 1_000), Nat::from(100_000_u64));
+        assert_eq!(max_flash_loan_amount(&Nat::from(9_u64), 1_000), Nat::from(0_u64));
+        assert_eq!(max_flash_loan_amount(&Nat::from(1_000_000_u64), 10_000), Nat::from(1_000_000_u64));
+    }
+
+    #[test]
+    fn test_charge_kong_fee() {
+        // covered by Kong's fee
+        assert_eq!(
+            charge_kong_fee(&Nat::from(100_u64), &Nat::from(0_u64), &Nat::from(40_u64)),
+            (Nat::from(60_u64), Nat::from(0_u64))
+        );
+        // not covered, the rest is a shortfall
+        assert_eq!(
+            charge_kong_fee(&Nat::from(100_u64), &Nat::from(0_u64), &Nat::from(140_u64)),
+            (Nat::from(0_u64), Nat::from(40_u64))
+        );
+        // an earlier shortfall is settled by new Kong fees
+        assert_eq!(
+            charge_kong_fee(&Nat::from(100_u64), &Nat::from(40_u64), &Nat::from(0_u64)),
+            (Nat::from(60_u64), Nat::from(0_u64))
+        );
+        assert_eq!(
+            charge_kong_fee(&Nat::from(10_u64), &Nat::from(40_u64), &Nat::from(0_u64)),
+            (Nat::from(0_u64), Nat::from(30_u64))
+        );
+    }
+
+    #[test]
+    fn test_credit_flash_loan_fee() {
+        let mut pool = pool();
+        credit_flash_loan_fee(&mut pool, true, &Nat::from(9_u64));
+        credit_flash_loan_fee(&mut pool, false, &Nat::from(18_u64));
+        assert_eq!(pool.lp_fee_0, Nat::from(9_u64));
+        assert_eq!(pool.lp_fee_1, Nat::from(18_u64));
+        assert_eq!(pool.balance_0, Nat::from(1_000_000_u64));
+        assert_eq!(pool.balance_1, Nat::from(2_000_000_u64));
+    }
+
+    #[test]
+    fn test_write_off_flash_loan() {
+        let mut pool = pool();
+        pool.kong_fee_0 = Nat::from(500_u64);
+        // LPs' balance is never written off
+        assert_eq!(write_off_flash_loan(&mut pool, true, &Nat::from(300_u64)), Nat::from(0_u64));
+        assert_eq!(pool.kong_fee_0, Nat::from(200_u64));
+        assert_eq!(write_off_flash_loan(&mut pool, true, &Nat::from(300_u64)), Nat::from(100_u64));
+        assert_eq!(pool.kong_fee_0, Nat::from(0_u64));
+        assert_eq!(pool.flash_loan_shortfall_0, Nat::from(100_u64));
+        assert_eq!(pool.balance_0, Nat::from(1_000_000_u64));
+        assert_eq!(write_off_flash_loan(&mut pool, false, &Nat::from(50_u64)), Nat::from(50_u64));
+        assert_eq!(pool.flash_loan_shortfall_1, Nat::from(50_u64));
+        assert_eq!(pool.balance_1, Nat::from(2_000_000_u64));
+    }
+}
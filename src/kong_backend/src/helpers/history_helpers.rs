@@ -0,0 +1,42 @@
+/// take up to limit items from entries in descending id order. entries filtered out are None
+/// at most max_scan entries are scanned so large histories don't exceed the instruction limit of queries
+///
+/// returns the items and the cursor of the next entry to continue from, None when there are no more entries
+pub fn paginate<T>(entries: impl Iterator<Item = (u64, Option<T>)>, limit: usize, max_scan: usize) -> (Vec<T>, Option<u64>) {
+    let mut items = Vec::new();
+    for (scanned, (id, item)) in entries.enumerate() {
+        if items.len() == limit || scanned == max_scan {
+            return (items, Some(id));
+        }
+        if let Some(item) = item {
+            items.push(item);
+        }
+    }
+    (items, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(ids: &[u64]) -> impl Iterator<Item = (u64, Option<u64>)> + '_ {
+        // odd ids are filtered out
+        ids.iter().map(|&id| (id, if id % 2 == 0 { Some(id) } else { None }))
+    }
+
+    #[test]
+    fn test_paginate() {
+        let ids = [10, 9, 8, 7, 6, 5, 4];
+        assert_eq!(paginate(entries(&ids), 2, 100), (vec![10, 8], Some(7)));
+        assert_eq!(paginate(entries(&ids[3..]), 2, 100), (vec![6, 4], None));
+        assert_eq!(paginate(entries(&ids), 10, 100), (vec![10, 8, 6, 4], None));
+        assert_eq!(paginate(entries(&[]), 10, 100), (vec![], None));
+    }
+
+    #[test]
+    fn test_paginate_max_scan() {
+        let ids = [9, 7, 5, 3, 2];
+        assert_eq!(paginate(entries(&ids), 10, 3), (vec![], Some(3)));
+        assert_eq!(paginate(entries(&ids[3..]), 10, 3), (vec![2], None));
+    }
+}
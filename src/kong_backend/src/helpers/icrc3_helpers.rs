@@ -0,0 +1,139 @@
+use candid::{Nat, Principal};
+use icrc_ledger_types::icrc::generic_value::ICRC3Value;
+use icrc_ledger_types::icrc1::account::Account;
+
+use super::nat_helpers::nat_to_u64;
+
+/// operation of an ICRC-3 block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ICRC3Operation {
+    Mint,
+    Burn,
+    Transfer,
+    Approve,
+}
+
+/// transaction of an ICRC-3 block. icrc2_transfer_from is a Transfer with a spender
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ICRC3Transaction {
+    pub operation: ICRC3Operation,
+    pub from: Option<Account>,
+    pub to: Option<Account>,
+    pub spender: Option<Account>,
+    pub amount: Nat,
+    pub ts: u64, // timestamp of the block
+}
+
+/// decode the transaction of an ICRC-3 block
+/// the operation is the block's btype, or the op of the transaction for ledgers which predate btype
+pub fn to_icrc3_transaction(block: &ICRC3Value) -> Result<ICRC3Transaction, String> {
+    let ICRC3Value::Map(block) = block else { Err("Invalid block")? };
+    let Some(ICRC3Value::Map(tx)) = block.get("tx") else {
+        Err("Missing block transaction")?
+    };
+    let operation = match block.get("btype").or_else(|| tx.get("op")) {
+        Some(ICRC3Value::Text(btype)) => match btype.as_str() {
+            "1mint" | "mint" => ICRC3Operation::Mint,
+            "1burn" | "burn" => ICRC3Operation::Burn,
+            "1xfer" | "2xfer" | "xfer" => ICRC3Operation::Transfer,
+            "2approve" | "approve" => ICRC3Operation::Approve,
+            _ => Err(format!("Unsupported block type {}", btype))?,
+        },
+        _ => Err("Missing block type")?,
+    };
+    let amount = match tx.get("amt") {
+        Some(ICRC3Value::Nat(amount)) => amount.clone(),
+        _ => Err("Missing block amount")?,
+    };
+    let ts = match block.get("ts") {
+        Some(ICRC3Value::Nat(ts)) => nat_to_u64(ts).ok_or("Invalid block timestamp")?,
+        _ => Err("Missing block timestamp")?,
+    };
+
+    Ok(ICRC3Transaction {
+        operation,
+        from: tx.get("from").map(to_account).transpose()?,
+        to: tx.get("to").map(to_account).transpose()?,
+        spender: tx.get("spender").map(to_account).transpose()?,
+        amount,
+        ts,
+    })
+}
+
+/// ICRC-3 accounts are an array of the owner and an optional subaccount
+fn to_account(value: &ICRC3Value) -> Result<Account, String> {
+    let ICRC3Value::Array(value) = value else { Err("Invalid account")? };
+    let owner = match value.first() {
+        Some(ICRC3Value::Blob(owner)) => Principal::try_from_slice(owner).map_err(|e| e.to_string())?,
+        _ => Err("Invalid account owner")?,
+    };
+    let subaccount = match value.get(1) {
+        Some(ICRC3Value::Blob(subaccount)) => Some(<[u8; 32]>::try_from(subaccount.as_slice()).map_err(|_| "Invalid subaccount")?),
+        Some(_) => Err("Invalid subaccount")?,
+        None => None,
+    };
+    Ok(Account { owner, subaccount })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icrc_ledger_types::icrc::generic_value::ICRC3Map;
+    use serde_bytes::ByteBuf;
+
+    use crate::stable_lp_block::stable_lp_block::{LPBlockType, StableLPBlock};
+
+    #[test]
+    fn test_to_icrc3_transaction() {
+        let from = Account {
+            owner: Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap(),
+            subaccount: None,
+        };
+        let to = Account {
+            owner: Principal::from_text("2ipq2-uqaaa-aaaar-qailq-cai").unwrap(),
+            subaccount: Some([1; 32]),
+        };
+        let block = StableLPBlock::new(1, LPBlockType::Transfer, Some(from), Some(to), None, &Nat::from(100_u32), 5);
+        let transaction = to_icrc3_transaction(&block.to_icrc3_value()).unwrap();
+        assert_eq!(
+            transaction,
+            ICRC3Transaction {
+                operation: ICRC3Operation::Transfer,
+                from: Some(from),
+                to: Some(to),
+                spender: None,
+                amount: Nat::from(100_u32),
+                ts: 5,
+            }
+        );
+
+        let block = StableLPBlock::new(1, LPBlockType::Burn, Some(from), None, Some(to), &Nat::from(7_u32), 6);
+        let transaction = to_icrc3_transaction(&block.to_icrc3_value()).unwrap();
+        assert_eq!(transaction.operation, ICRC3Operation::Burn);
+        assert_eq!(transaction.spender, Some(to));
+    }
+
+    #[test]
+    fn test_to_icrc3_transaction_op() {
+        // ledgers which predate btype have the operation in the transaction
+        let mut tx = ICRC3Map::new();
+        tx.insert("op".to_string(), ICRC3Value::Text("xfer".to_string()));
+        tx.insert("amt".to_string(), ICRC3Value::Nat(Nat::from(10_u32)));
+        tx.insert(
+            "from".to_string(),
+            ICRC3Value::Array(vec![ICRC3Value::Blob(ByteBuf::from(vec![4_u8]))]),
+        );
+        let mut block = ICRC3Map::new();
+        block.insert("ts".to_string(), ICRC3Value::Nat(Nat::from(1_u32)));
+        block.insert("tx".to_string(), ICRC3Value::Map(tx.clone()));
+        let transaction = to_icrc3_transaction(&ICRC3Value::Map(block.clone())).unwrap();
+        assert_eq!(transaction.operation, ICRC3Operation::Transfer);
+        assert_eq!(transaction.from.unwrap().owner, Principal::from_slice(&[4]));
+        assert_eq!(transaction.to, None);
+
+        tx.insert("op".to_string(), ICRC3Value::Text("unknown".to_string()));
+        block.insert("tx".to_string(), ICRC3Value::Map(tx));
+        assert!(to_icrc3_transaction(&ICRC3Value::Map(block)).is_err());
+        assert!(to_icrc3_transaction(&ICRC3Value::Text("block".to_string())).is_err());
+    }
+}
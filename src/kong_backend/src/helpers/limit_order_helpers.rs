@@ -0,0 +1,94 @@
+use candid::Nat;
+
+use super::nat_helpers::{nat_divide, nat_divide_ceil, nat_is_zero, nat_multiply, nat_to_decimals_f64};
+
+/// limit price in receive token per pay token of the amounts of an order
+pub fn limit_price(pay_decimals: u8, pay_amount: &Nat, receive_decimals: u8, receive_amount: &Nat) -> Option<f64> {
+    let pay_amount = nat_to_decimals_f64(pay_decimals, pay_amount)?;
+    let receive_amount = nat_to_decimals_f64(receive_decimals, receive_amount)?;
+    if pay_amount == 0_f64 {
+        return None;
+    }
+    Some(receive_amount / pay_amount)
+}
+
+/// minimum receive amount of a fill of fill_amount at the limit price of the order, rounded up in favour of the order owner
+/// min_receive_amount = receive_amount * fill_amount / pay_amount
+pub fn fill_receive_amount(pay_amount: &Nat, receive_amount: &Nat, fill_amount: &Nat) -> Option<Nat> {
+    nat_divide_ceil(&nat_multiply(receive_amount, fill_amount), pay_amount)
+}
+
+/// amounts to try to fill, largest first. the remaining amount followed by up to max_halvings halvings of it
+pub fn fill_amounts(remaining_pay_amount: &Nat, max_halvings: u32) -> Vec<Nat> {
+    let mut fill_amounts = Vec::new();
+    let mut fill_amount = remaining_pay_amount.clone();
+    for _ in 0..=max_halvings {
+        if nat_is_zero(&fill_amount) {
+            break;
+        }
+        fill_amounts.push(fill_amount.clone());
+        fill_amount = match nat_divide(&fill_amount, &Nat::from(2_u8)) {
+            Some(fill_amount) => fill_amount,
+            None => break,
+        };
+    }
+    fill_amounts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_price() {
+        // 1 BTC (8 decimals) for 60,000 ckUSDT (6 decimals)
+        assert_eq!(
+            limit_price(8, &Nat::from(100_000_000_u64), 6, &Nat::from(60_000_000_000_u64)),
+            Some(60_000_f64)
+        );
+        assert_eq!(
+            limit_price(6, &Nat::from(60_000_000_000_u64), 8, &Nat::from(100_000_000_u64)),
+            Some(1_f64 / 60_000_f64)
+        );
+        assert_eq!(limit_price(8, &Nat::from(0_u64), 6, &Nat::from(1_u64)), None);
+    }
+
+    #[test]
+    fn test_fill_receive_amount() {
+        let pay_amount = Nat::from(1_000_u64);
+        let receive_amount = Nat::from(3_000_u64);
+        assert_eq!(
+            fill_receive_amount(&pay_amount, &receive_amount, &pay_amount),
+            Some(Nat::from(3_000_u64))
+        );
+        assert_eq!(
+            fill_receive_amount(&pay_amount, &receive_amount, &Nat::from(500_u64)),
+            Some(Nat::from(1_500_u64))
+        );
+        // rounded up so a partial fill is never below the limit price
+        let receive_amount = Nat::from(1_000_u64);
+        let pay_amount = Nat::from(3_u64);
+        assert_eq!(
+            fill_receive_amount(&pay_amount, &receive_amount, &Nat::from(1_u64)),
+            Some(Nat::from(334_u64))
+        );
+        assert_eq!(fill_receive_amount(&Nat::from(0_u64), &receive_amount, &Nat::from(1_u64)), None);
+    }
+
+    #[test]
+    fn test_fill_amounts() {
+        assert_eq!(
+            fill_amounts(&Nat::from(1_000_u64), 4),
+            [1_000_u64, 500, 250, 125, 62]
+                .iter()
+                .map(|amount| Nat::from(*amount))
+                .collect::<Vec<Nat>>()
+        );
+        assert_eq!(
+            fill_amounts(&Nat::from(3_u64), 4),
+            [3_u64, 1].iter().map(|amount| Nat::from(*amount)).collect::<Vec<Nat>>()
+        );
+        assert_eq!(fill_amounts(&Nat::from(1_000_u64), 0), vec![Nat::from(1_000_u64)]);
+        assert!(fill_amounts(&Nat::from(0_u64), 4).is_empty());
+    }
+}
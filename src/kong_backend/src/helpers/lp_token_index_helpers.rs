@@ -0,0 +1,177 @@
+use candid::Nat;
+use std::collections::BTreeMap;
+
+use super::nat_helpers::{nat_add, nat_subtract, nat_zero};
+
+use crate::stable_lp_token::stable_lp_token::StableLPToken;
+
+/// update the (user_id, token_id) and (token_id, user_id) indexes of lp_token_id with lp_token replacing old_lp_token
+/// the first entry of a user and token is kept if there are several
+pub fn index_lp_token(
+    user_index: &mut BTreeMap<(u32, u32), u64>,
+    token_index: &mut BTreeMap<(u32, u32), u64>,
+    old_lp_token: Option<&StableLPToken>,
+    lp_token: &StableLPToken,
+) {
+    if let Some(old_lp_token) = old_lp_token {
+        if (old_lp_token.user_id, old_lp_token.token_id) != (lp_token.user_id, lp_token.token_id) {
+            unindex_lp_token(user_index, token_index, old_lp_token);
+        }
+    }
+    user_index
+        .entry((lp_token.user_id, lp_token.token_id))
+        .or_insert(lp_token.lp_token_id);
+    token_index
+        .entry((lp_token.token_id, lp_token.user_id))
+        .or_insert(lp_token.lp_token_id);
+}
+
+/// remove lp_token from the indexes of lp_token_id if it is the indexed entry of its user and token
+pub fn unindex_lp_token(user_index: &mut BTreeMap<(u32, u32), u64>, token_index: &mut BTreeMap<(u32, u32), u64>, lp_token: &StableLPToken) {
+    if user_index.get(&(lp_token.user_id, lp_token.token_id)) == Some(&lp_token.lp_token_id) {
+        user_index.remove(&(lp_token.user_id, lp_token.token_id));
+    }
+    if token_index.get(&(lp_token.token_id, lp_token.user_id)) == Some(&lp_token.lp_token_id) {
+        token_index.remove(&(lp_token.token_id, lp_token.user_id));
+    }
+}
+
+/// update the total supply of the LP token with lp_token replacing old_lp_token
+pub fn update_total_supply(supply_map: &mut BTreeMap<u32, Nat>, old_lp_token: Option<&StableLPToken>, lp_token: &StableLPToken) {
+    if let Some(old_lp_token) = old_lp_token {
+        remove_total_supply(supply_map, old_lp_token);
+    }
+    let total_supply = supply_map.get(&lp_token.token_id).cloned().unwrap_or_else(nat_zero);
+    supply_map.insert(lp_token.token_id, nat_add(&total_supply, &lp_token.amount));
+}
+
+/// remove the amount of lp_token from the total supply of the LP token
+pub fn remove_total_supply(supply_map: &mut BTreeMap<u32, Nat>, lp_token: &StableLPToken) {
+    if let Some(total_supply) = supply_map.get_mut(&lp_token.token_id) {
+        *total_supply = nat_subtract(total_supply, &lp_token.amount).unwrap_or_else(nat_zero);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Index {
+        user_index: BTreeMap<(u32, u32), u64>,
+        token_index: BTreeMap<(u32, u32), u64>,
+        supply_map: BTreeMap<u32, Nat>,
+        lp_tokens: BTreeMap<u64, StableLPToken>,
+    }
+
+    impl Index {
+        fn new() -> Self {
+            Index {
+                user_index: BTreeMap::new(),
+                token_index: BTreeMap::new(),
+                supply_map: BTreeMap::new(),
+                lp_tokens: BTreeMap::new(),
+            }
+        }
+
+        // same as lp_token_map::insert() and update()
+        fn update(&mut self, lp_token_id: u64, user_id: u32, token_id: u32, amount: u64) {
+            let lp_token = StableLPToken {
+                lp_token_id,
+                ..StableLPToken::new(user_id, token_id, Nat::from(amount), 0)
+            };
+            let old_lp_token = self.lp_tokens.insert(lp_token_id, lp_token.clone());
+            index_lp_token(&mut self.user_index, &mut self.token_index, old_lp_token.as_ref(), &lp_token);
+            update_total_supply(&mut self.supply_map, old_lp_token.as_ref(), &lp_token);
+        }
+
+        // same as lp_token_map::remove()
+        fn remove(&mut self, lp_token_id: u64) {
+            if let Some(lp_token) = self.lp_tokens.remove(&lp_token_id) {
+                unindex_lp_token(&mut self.user_index, &mut self.token_index, &lp_token);
+                remove_total_supply(&mut self.supply_map, &lp_token);
+            }
+        }
+
+        fn holders(&self, token_id: u32) -> Vec<u64> {
+            self.token_index
+                .range((token_id, 0)..=(token_id, u32::MAX))
+                .map(|(_, v)| *v)
+                .collect()
+        }
+
+        fn total_supply(&self, token_id: u32) -> Nat {
+            self.supply_map.get(&token_id).cloned().unwrap_or_else(nat_zero)
+        }
+
+        // both indexes hold the same entries and the total supply is the sum of the balances
+        fn assert_consistent(&self) {
+            assert_eq!(self.user_index.len(), self.token_index.len());
+            for ((user_id, token_id), lp_token_id) in self.user_index.iter() {
+                assert_eq!(self.token_index.get(&(*token_id, *user_id)), Some(lp_token_id));
+                let lp_token = self.lp_tokens.get(lp_token_id).unwrap();
+                assert_eq!((lp_token.user_id, lp_token.token_id), (*user_id, *token_id));
+            }
+            for (token_id, total_supply) in self.supply_map.iter() {
+                let amount = self
+                    .lp_tokens
+                    .values()
+                    .filter(|lp_token| lp_token.token_id == *token_id)
+                    .fold(nat_zero(), |acc, lp_token| nat_add(&acc, &lp_token.amount));
+                assert_eq!(*total_supply, amount);
+            }
+        }
+    }
+
+    #[test]
+    fn test_update() {
+        let mut index = Index::new();
+        index.update(1, 100, 10, 1_000);
+        index.update(2, 101, 10, 500);
+        index.update(3, 100, 11, 200);
+        index.assert_consistent();
+        assert_eq!(index.holders(10), vec![1, 2]);
+        assert_eq!(index.holders(11), vec![3]);
+        assert_eq!(index.total_supply(10), Nat::from(1_500_u64));
+
+        // balance change
+        index.update(2, 101, 10, 700);
+        index.assert_consistent();
+        assert_eq!(index.holders(10), vec![1, 2]);
+        assert_eq!(index.total_supply(10), Nat::from(1_700_u64));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut index = Index::new();
+        index.update(1, 100, 10, 1_000);
+        index.update(2, 101, 10, 0);
+        index.remove(2);
+        index.assert_consistent();
+        assert_eq!(index.holders(10), vec![1]);
+        assert_eq!(index.total_supply(10), Nat::from(1_000_u64));
+        // removing an entry which is not indexed keeps the indexed one
+        index.update(3, 100, 10, 0);
+        index.remove(3);
+        assert_eq!(index.holders(10), vec![1]);
+        assert_eq!(index.user_index.get(&(100, 10)), Some(&1));
+    }
+
+    #[test]
+    fn test_transfer() {
+        let mut index = Index::new();
+        index.update(1, 100, 10, 1_000);
+        // transfer to a new holder
+        index.update(1, 100, 10, 600);
+        index.update(2, 101, 10, 400);
+        index.assert_consistent();
+        assert_eq!(index.holders(10), vec![1, 2]);
+        assert_eq!(index.total_supply(10), Nat::from(1_000_u64));
+        // entry moved to another user
+        index.update(1, 102, 10, 600);
+        index.assert_consistent();
+        assert_eq!(index.holders(10), vec![2, 1]);
+        assert_eq!(index.user_index.get(&(100, 10)), None);
+        assert_eq!(index.token_index.get(&(10, 100)), None);
+        assert_eq!(index.total_supply(10), Nat::from(1_000_u64));
+    }
+}
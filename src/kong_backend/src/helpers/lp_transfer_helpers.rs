@@ -0,0 +1,300 @@
+use candid::Nat;
+use icrc_ledger_types::icrc1::transfer::Memo;
+
+use super::nat_helpers::{nat_is_zero, nat_subtract, nat_zero};
+
+use crate::lp_ledger::lp_ledger_error::LPLedgerError;
+use crate::stable_lp_allowance::stable_lp_allowance::StableLPAllowance;
+use crate::stable_lp_block::stable_lp_block::StableLPBlock;
+
+// transactions with created_at_time are deduplicated within the window
+const TX_WINDOW_NANOSECS: u64 = 86_400_000_000_000; // 24 hours
+const PERMITTED_DRIFT_NANOSECS: u64 = 60_000_000_000; // 1 minute
+const MAX_MEMO_LENGTH: usize = 32;
+
+/// checks of fee, memo and created_at_time common to all ledger updates
+pub fn check_transaction(fee: Option<&Nat>, memo: Option<&Memo>, created_at_time: Option<u64>, ts: u64) -> Result<(), LPLedgerError> {
+    if fee.is_some_and(|fee| !nat_is_zero(fee)) {
+        Err(LPLedgerError::BadFee)?
+    }
+    if memo.is_some_and(|memo| memo.0.len() > MAX_MEMO_LENGTH) {
+        Err(LPLedgerError::GenericError(format!("Memo longer than {} bytes", MAX_MEMO_LENGTH)))?
+    }
+    if let Some(created_at_time) = created_at_time {
+        if created_at_time.saturating_add(TX_WINDOW_NANOSECS + PERMITTED_DRIFT_NANOSECS) < ts {
+            Err(LPLedgerError::TooOld)?
+        }
+        if created_at_time > ts.saturating_add(PERMITTED_DRIFT_NANOSECS) {
+            Err(LPLedgerError::CreatedInFuture { ledger_time: ts })?
+        }
+    }
+    Ok(())
+}
+
+/// transfers of zero are rejected
+pub fn check_amount(amount: &Nat) -> Result<(), LPLedgerError> {
+    if nat_is_zero(amount) {
+        Err(LPLedgerError::GenericError("Invalid zero amount".to_string()))?
+    }
+    Ok(())
+}
+
+/// earliest timestamp of blocks a transaction created at ts can be a duplicate of
+pub fn dedup_since(ts: u64) -> u64 {
+    ts.saturating_sub(TX_WINDOW_NANOSECS + PERMITTED_DRIFT_NANOSECS)
+}
+
+/// block_id of the first block matching a transaction, of blocks ordered newest first, back to since_ts
+pub fn find_duplicate_block(
+    blocks: impl Iterator<Item = StableLPBlock>,
+    since_ts: u64,
+    matches: impl Fn(&StableLPBlock) -> bool,
+) -> Option<u64> {
+    blocks.take_while(|v| v.ts >= since_ts).find(|v| matches(v)).map(|v| v.block_id)
+}
+
+/// transfer and transfer_from blocks with the same arguments
+pub fn is_duplicate_transfer(v: &StableLPBlock, block: &StableLPBlock) -> bool {
+    v.block_type == block.block_type
+        && v.from == block.from
+        && v.to == block.to
+        && v.spender == block.spender
+        && v.amount == block.amount
+        && v.memo == block.memo
+        && v.created_at_time == block.created_at_time
+}
+
+/// approve blocks with the same arguments
+pub fn is_duplicate_approve(v: &StableLPBlock, block: &StableLPBlock) -> bool {
+    v.block_type == block.block_type
+        && v.from == block.from
+        && v.spender == block.spender
+        && v.amount == block.amount
+        && v.expected_allowance == block.expected_allowance
+        && v.expires_at == block.expires_at
+        && v.memo == block.memo
+        && v.created_at_time == block.created_at_time
+}
+
+/// allowance after spending amount of it at ts. an expired allowance is no allowance
+pub fn spend_allowance(allowance: Option<&StableLPAllowance>, amount: &Nat, ts: u64) -> Result<StableLPAllowance, LPLedgerError> {
+    let allowance = allowance
+        .filter(|allowance| !allowance.is_expired(ts))
+        .ok_or(LPLedgerError::InsufficientAllowance { allowance: nat_zero() })?;
+    let remaining = nat_subtract(&allowance.amount, amount).ok_or(LPLedgerError::InsufficientAllowance {
+        allowance: allowance.amount.clone(),
+    })?;
+    Ok(StableLPAllowance {
+        amount: remaining,
+        ts,
+        ..allowance.clone()
+    })
+}
+
+/// move a balance from from_user_id to a recipient which is only created once the debit succeeded
+/// the debit is credited back to from_user_id if the recipient can not be created
+///
+/// returns the user_id of the recipient
+pub fn move_balance(
+    from_user_id: u32,
+    debit: impl FnOnce(u32) -> Result<(), String>,
+    create_recipient: impl FnOnce() -> Result<u32, String>,
+    mut credit: impl FnMut(u32) -> Result<(), String>,
+) -> Result<u32, String> {
+    debit(from_user_id)?;
+    let to_user_id = match create_recipient() {
+        Ok(to_user_id) => to_user_id,
+        Err(e) => {
+            // put the amount back
+            credit(from_user_id)?;
+            Err(e)?
+        }
+    };
+    credit(to_user_id)?;
+    Ok(to_user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use candid::Principal;
+    use icrc_ledger_types::icrc1::account::Account;
+    use serde_bytes::ByteBuf;
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+
+    use crate::stable_lp_block::stable_lp_block::LPBlockType;
+
+    const TS: u64 = 1_700_000_000_000_000_000;
+
+    fn account(id: u8) -> Account {
+        Account::from(Principal::from_slice(&[id; 29]))
+    }
+
+    fn transfer_block(block_id: u64, amount: u64, created_at_time: Option<u64>, ts: u64) -> StableLPBlock {
+        StableLPBlock {
+            block_id,
+            created_at_time,
+            ..StableLPBlock::new(
+                1,
+                LPBlockType::Transfer,
+                Some(account(1)),
+                Some(account(2)),
+                None,
+                &Nat::from(amount),
+                ts,
+            )
+        }
+    }
+
+    fn allowance(amount: u64, expires_at: Option<u64>) -> StableLPAllowance {
+        StableLPAllowance {
+            token_id: 1,
+            owner_user_id: 100,
+            spender: account(3),
+            amount: Nat::from(amount),
+            expires_at,
+            ts: TS,
+        }
+    }
+
+    #[test]
+    fn test_check_transaction() {
+        assert!(check_transaction(None, None, None, TS).is_ok());
+        assert!(check_transaction(Some(&Nat::from(0_u64)), None, None, TS).is_ok());
+        assert!(matches!(
+            check_transaction(Some(&Nat::from(1_u64)), None, None, TS),
+            Err(LPLedgerError::BadFee)
+        ));
+        // memo length
+        let memo = Memo(ByteBuf::from(vec![0_u8; MAX_MEMO_LENGTH]));
+        assert!(check_transaction(None, Some(&memo), None, TS).is_ok());
+        let memo = Memo(ByteBuf::from(vec![0_u8; MAX_MEMO_LENGTH + 1]));
+        assert!(matches!(
+            check_transaction(None, Some(&memo), None, TS),
+            Err(LPLedgerError::GenericError(_))
+        ));
+        // created_at_time within the window and drift
+        assert!(check_transaction(None, None, Some(TS - TX_WINDOW_NANOSECS), TS).is_ok());
+        assert!(check_transaction(None, None, Some(TS + PERMITTED_DRIFT_NANOSECS), TS).is_ok());
+        assert!(matches!(
+            check_transaction(None, None, Some(TS - TX_WINDOW_NANOSECS - PERMITTED_DRIFT_NANOSECS - 1), TS),
+            Err(LPLedgerError::TooOld)
+        ));
+        assert!(matches!(
+            check_transaction(None, None, Some(TS + PERMITTED_DRIFT_NANOSECS + 1), TS),
+            Err(LPLedgerError::CreatedInFuture { ledger_time: TS })
+        ));
+    }
+
+    #[test]
+    fn test_check_amount() {
+        assert!(matches!(check_amount(&Nat::from(0_u64)), Err(LPLedgerError::GenericError(_))));
+        assert!(check_amount(&Nat::from(1_u64)).is_ok());
+    }
+
+    #[test]
+    fn test_find_duplicate_block() {
+        let created_at_time = Some(TS - 1_000);
+        let block = transfer_block(0, 100, created_at_time, TS);
+        let since_ts = dedup_since(TS);
+        // newest first
+        let blocks = vec![
+            transfer_block(3, 200, created_at_time, TS - 10),
+            transfer_block(2, 100, created_at_time, TS - 20),
+            transfer_block(1, 100, created_at_time, TS - 30),
+        ];
+        assert_eq!(
+            find_duplicate_block(blocks.into_iter(), since_ts, |v| is_duplicate_transfer(v, &block)),
+            Some(2)
+        );
+        // a different created_at_time is not a duplicate
+        let blocks = vec![transfer_block(1, 100, Some(TS - 2_000), TS - 30)];
+        assert_eq!(
+            find_duplicate_block(blocks.into_iter(), since_ts, |v| is_duplicate_transfer(v, &block)),
+            None
+        );
+        // blocks before the window are not checked
+        let blocks = vec![
+            transfer_block(2, 200, created_at_time, TS - 10),
+            transfer_block(1, 100, created_at_time, since_ts - 1),
+        ];
+        assert_eq!(
+            find_duplicate_block(blocks.into_iter(), since_ts, |v| is_duplicate_transfer(v, &block)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_duplicate_approve() {
+        let approve = |amount: u64, expected_allowance: Option<u64>| StableLPBlock {
+            expected_allowance: expected_allowance.map(Nat::from),
+            created_at_time: Some(TS),
+            ..StableLPBlock::new(
+                1,
+                LPBlockType::Approve,
+                Some(account(1)),
+                None,
+                Some(account(3)),
+                &Nat::from(amount),
+                TS,
+            )
+        };
+        assert!(is_duplicate_approve(&approve(100, None), &approve(100, None)));
+        assert!(!is_duplicate_approve(&approve(100, None), &approve(100, Some(0))));
+        assert!(!is_duplicate_approve(&approve(100, None), &approve(200, None)));
+    }
+
+    #[test]
+    fn test_spend_allowance() {
+        let remaining = spend_allowance(Some(&allowance(100, None)), &Nat::from(40_u64), TS + 1).unwrap();
+        assert_eq!(remaining.amount, Nat::from(60_u64));
+        assert_eq!(remaining.ts, TS + 1);
+        let remaining = spend_allowance(Some(&remaining), &Nat::from(60_u64), TS + 2).unwrap();
+        assert_eq!(remaining.amount, Nat::from(0_u64));
+        assert!(matches!(
+            spend_allowance(Some(&allowance(100, None)), &Nat::from(101_u64), TS),
+            Err(LPLedgerError::InsufficientAllowance { allowance }) if allowance == 100_u64
+        ));
+        assert!(matches!(
+            spend_allowance(None, &Nat::from(1_u64), TS),
+            Err(LPLedgerError::InsufficientAllowance { allowance }) if allowance == 0_u64
+        ));
+        // expiry
+        assert!(spend_allowance(Some(&allowance(100, Some(TS + 1))), &Nat::from(1_u64), TS).is_ok());
+        assert!(matches!(
+            spend_allowance(Some(&allowance(100, Some(TS))), &Nat::from(1_u64), TS),
+            Err(LPLedgerError::InsufficientAllowance { allowance }) if allowance == 0_u64
+        ));
+    }
+
+    #[test]
+    fn test_move_balance() {
+        let balances = RefCell::new(BTreeMap::from([(100_u32, 1_000_u64)]));
+        let debit = |user_id: u32| {
+            let mut balances = balances.borrow_mut();
+            let balance = balances.get_mut(&user_id).ok_or("Not enough LP token")?;
+            *balance = balance.checked_sub(300).ok_or("Not enough LP token")?;
+            Ok(())
+        };
+        let credit = |user_id: u32| {
+            *balances.borrow_mut().entry(user_id).or_insert(0) += 300;
+            Ok(())
+        };
+
+        assert_eq!(move_balance(100, debit, || Ok(101), credit), Ok(101));
+        assert_eq!(*balances.borrow(), BTreeMap::from([(100, 700), (101, 300)]));
+
+        // the debit is rolled back when the recipient can't be created
+        assert_eq!(
+            move_balance(100, debit, || Err("Failed to create user".to_string()), credit),
+            Err("Failed to create user".to_string())
+        );
+        assert_eq!(*balances.borrow(), BTreeMap::from([(100, 700), (101, 300)]));
+
+        // nothing moves if the debit fails
+        assert!(move_balance(102, debit, || Ok(101), credit).is_err());
+        assert_eq!(*balances.borrow(), BTreeMap::from([(100, 700), (101, 300)]));
+    }
+}
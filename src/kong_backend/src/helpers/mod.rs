@@ -1,3 +1,20 @@
+pub mod concentrated_helpers;
+pub mod fee_helpers;
+pub mod flash_loan_helpers;
+pub mod history_helpers;
+pub mod icrc3_helpers;
 pub mod json_helpers;
+pub mod limit_order_helpers;
+pub mod lp_token_index_helpers;
+pub mod lp_transfer_helpers;
 pub mod math_helpers;
 pub mod nat_helpers;
+pub mod pool_helpers;
+pub mod protocol_fee_helpers;
+pub mod referral_helpers;
+pub mod split_swap_helpers;
+pub mod stableswap_helpers;
+pub mod stats_helpers;
+pub mod time_helpers;
+pub mod twap_helpers;
+pub mod webhook_helpers;
@@ -103,6 +103,19 @@ pub fn nat_divide(numerator: &Nat, denominator: &Nat) -> Option<Nat> {
     Some(numerator.clone() / denominator.clone())
 }
 
+// integer division rounded up
+pub fn nat_divide_ceil(numerator: &Nat, denominator: &Nat) -> Option<Nat> {
+    if nat_is_zero(denominator) {
+        None?
+    }
+    let quotient = numerator.clone() / denominator.clone();
+    if nat_multiply(&quotient, denominator) == *numerator {
+        Some(quotient)
+    } else {
+        Some(quotient + 1_u128)
+    }
+}
+
 // division with decimal precision
 pub fn nat_divide_as_f64(numerator: &Nat, denominator: &Nat) -> Option<f64> {
     if nat_is_zero(numerator) {
@@ -219,6 +232,24 @@ mod tests {
         assert_eq!(x, Some(Nat::from(5_u128)));
     }
 
+    #[test]
+    fn test_nat_divide_ceil() {
+        let n1 = Nat::from(5_000_000_000_u128);
+        let n2 = Nat::from(0_u128);
+        let x = nat_divide_ceil(&n1, &n2);
+        assert_eq!(x, None);
+
+        let n1 = Nat::from(5_000_000_000_u128);
+        let n2 = Nat::from(1_000_000_000_u128);
+        let x = nat_divide_ceil(&n1, &n2);
+        assert_eq!(x, Some(Nat::from(5_u128)));
+
+        let n1 = Nat::from(5_000_000_001_u128);
+        let n2 = Nat::from(1_000_000_000_u128);
+        let x = nat_divide_ceil(&n1, &n2);
+        assert_eq!(x, Some(Nat::from(6_u128)));
+    }
+
     #[test]
     fn test_nat_divide_f64() {
         let n1 = Nat::from(5_000_000_000_u128);
@@ -0,0 +1,32 @@
+/// actions of users on a pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolAction {
+    Swap,
+    AddLiquidity,
+    RemoveLiquidity,
+}
+
+/// check the action is allowed by the status of the pool
+/// pools suspended for low TVL only allow LPs to remove their liquidity
+pub fn check_pool_action(suspended_at: Option<u64>, action: PoolAction) -> Result<(), String> {
+    match (suspended_at, action) {
+        (None, _) | (Some(_), PoolAction::RemoveLiquidity) => Ok(()),
+        (Some(_), _) => Err("Pool is suspended for low TVL. Only removing liquidity is allowed".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_pool_action() {
+        for action in [PoolAction::Swap, PoolAction::AddLiquidity, PoolAction::RemoveLiquidity] {
+            assert!(check_pool_action(None, action).is_ok());
+        }
+        // LPs can still remove liquidity from a suspended pool
+        assert!(check_pool_action(Some(1_700_000_000_000_000_000), PoolAction::RemoveLiquidity).is_ok());
+        assert!(check_pool_action(Some(1_700_000_000_000_000_000), PoolAction::Swap).is_err());
+        assert!(check_pool_action(Some(1_700_000_000_000_000_000), PoolAction::AddLiquidity).is_err());
+    }
+}
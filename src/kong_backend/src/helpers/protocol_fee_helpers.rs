@@ -0,0 +1,132 @@
+use candid::Nat;
+use icrc_ledger_types::icrc1::transfer::TransferError;
+
+use super::nat_helpers::{nat_add, nat_zero};
+
+use crate::stable_tx::status_tx::StatusTx;
+
+/// outcome of the transfer of a protocol fee to the treasury
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolFeeTransfer {
+    // block_id of the transfer, or None if there was nothing to transfer
+    Sent(Option<Nat>),
+    // rejected by the ledger, nothing was transferred
+    Rejected,
+    // the transfer may or may not have been executed
+    Unknown,
+}
+
+/// outcome of the result of icrc1_transfer_with_ledger_error()
+/// only InsufficientFunds and BadFee errors of the ledger are taken as the transfer not being executed
+/// any other error of the ledger, or a failed call to the ledger, leaves the outcome unknown
+pub fn to_protocol_fee_transfer(result: &Result<Result<Nat, TransferError>, String>) -> ProtocolFeeTransfer {
+    match result {
+        Ok(Ok(block_id)) => ProtocolFeeTransfer::Sent(Some(block_id.clone())),
+        Ok(Err(TransferError::InsufficientFunds { .. } | TransferError::BadFee { .. })) => ProtocolFeeTransfer::Rejected,
+        Ok(Err(_)) | Err(_) => ProtocolFeeTransfer::Unknown,
+    }
+}
+
+/// settle a fee taken out of the pool once the outcome of its transfer is known
+/// returns (kong_fee, collected_kong_fee, amount) where amount is recorded in the CollectProtocolFees tx
+/// - sent: amount is added to the collected fees
+/// - rejected: amount is put back into kong_fee and none is recorded as collected
+/// - unknown: amount stays out of the pool so it can not be collected twice, until it is reconciled with the ledger
+pub fn settle_protocol_fee(transfer: &ProtocolFeeTransfer, kong_fee: &Nat, collected_kong_fee: &Nat, amount: &Nat) -> (Nat, Nat, Nat) {
+    match transfer {
+        ProtocolFeeTransfer::Sent(_) => (kong_fee.clone(), nat_add(collected_kong_fee, amount), amount.clone()),
+        ProtocolFeeTransfer::Rejected => (nat_add(kong_fee, amount), collected_kong_fee.clone(), nat_zero()),
+        ProtocolFeeTransfer::Unknown => (kong_fee.clone(), collected_kong_fee.clone(), amount.clone()),
+    }
+}
+
+/// status of the CollectProtocolFees tx of the transfers of both tokens
+pub fn protocol_fees_status(transfer_0: &ProtocolFeeTransfer, transfer_1: &ProtocolFeeTransfer) -> StatusTx {
+    match (transfer_0, transfer_1) {
+        (ProtocolFeeTransfer::Unknown, _) | (_, ProtocolFeeTransfer::Unknown) => StatusTx::PendingReconciliation,
+        (ProtocolFeeTransfer::Sent(_), ProtocolFeeTransfer::Sent(_)) => StatusTx::Success,
+        _ => StatusTx::Failed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_protocol_fee_transfer() {
+        assert_eq!(
+            to_protocol_fee_transfer(&Ok(Ok(Nat::from(7_u64)))),
+            ProtocolFeeTransfer::Sent(Some(Nat::from(7_u64)))
+        );
+        // definitive rejections of the ledger
+        let insufficient_funds = TransferError::InsufficientFunds { balance: Nat::from(0_u64) };
+        assert_eq!(
+            to_protocol_fee_transfer(&Ok(Err(insufficient_funds))),
+            ProtocolFeeTransfer::Rejected
+        );
+        let bad_fee = TransferError::BadFee {
+            expected_fee: Nat::from(10_000_u64),
+        };
+        assert_eq!(to_protocol_fee_transfer(&Ok(Err(bad_fee))), ProtocolFeeTransfer::Rejected);
+        // anything else could have been executed
+        assert_eq!(
+            to_protocol_fee_transfer(&Ok(Err(TransferError::TemporarilyUnavailable))),
+            ProtocolFeeTransfer::Unknown
+        );
+        let duplicate = TransferError::Duplicate {
+            duplicate_of: Nat::from(7_u64),
+        };
+        assert_eq!(to_protocol_fee_transfer(&Ok(Err(duplicate))), ProtocolFeeTransfer::Unknown);
+        assert_eq!(
+            to_protocol_fee_transfer(&Err("Canister is stopping".to_string())),
+            ProtocolFeeTransfer::Unknown
+        );
+    }
+
+    #[test]
+    fn test_settle_protocol_fee() {
+        let kong_fee = Nat::from(5_u64);
+        let collected_kong_fee = Nat::from(1_000_u64);
+        let amount = Nat::from(300_u64);
+        // collected
+        assert_eq!(
+            settle_protocol_fee(
+                &ProtocolFeeTransfer::Sent(Some(Nat::from(7_u64))),
+                &kong_fee,
+                &collected_kong_fee,
+                &amount
+            ),
+            (Nat::from(5_u64), Nat::from(1_300_u64), Nat::from(300_u64))
+        );
+        // restored to the pool
+        assert_eq!(
+            settle_protocol_fee(&ProtocolFeeTransfer::Rejected, &kong_fee, &collected_kong_fee, &amount),
+            (Nat::from(305_u64), Nat::from(1_000_u64), Nat::from(0_u64))
+        );
+        // neither restored nor collected until reconciled
+        assert_eq!(
+            settle_protocol_fee(&ProtocolFeeTransfer::Unknown, &kong_fee, &collected_kong_fee, &amount),
+            (Nat::from(5_u64), Nat::from(1_000_u64), Nat::from(300_u64))
+        );
+    }
+
+    #[test]
+    fn test_protocol_fees_status() {
+        let sent = ProtocolFeeTransfer::Sent(None);
+        assert_eq!(protocol_fees_status(&sent, &sent), StatusTx::Success);
+        assert_eq!(protocol_fees_status(&sent, &ProtocolFeeTransfer::Rejected), StatusTx::Failed);
+        assert_eq!(
+            protocol_fees_status(&ProtocolFeeTransfer::Rejected, &ProtocolFeeTransfer::Rejected),
+            StatusTx::Failed
+        );
+        assert_eq!(
+            protocol_fees_status(&ProtocolFeeTransfer::Rejected, &ProtocolFeeTransfer::Unknown),
+            StatusTx::PendingReconciliation
+        );
+        assert_eq!(
+            protocol_fees_status(&ProtocolFeeTransfer::Unknown, &sent),
+            StatusTx::PendingReconciliation
+        );
+    }
+}
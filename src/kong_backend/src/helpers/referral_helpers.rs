@@ -0,0 +1,150 @@
+use candid::Nat;
+use std::collections::BTreeMap;
+
+use super::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_zero};
+
+use crate::stable_pool::stable_pool::StablePool;
+use crate::stable_referral::stable_referral::StableReferral;
+
+/// update the number of referees of each referrer when a user's referred_by changes from old_referred_by to referred_by
+pub fn update_referee_count(referee_counts: &mut BTreeMap<u32, u32>, old_referred_by: Option<u32>, referred_by: Option<u32>) {
+    if old_referred_by == referred_by {
+        return;
+    }
+    if let Some(old_referred_by) = old_referred_by {
+        if let Some(count) = referee_counts.get_mut(&old_referred_by) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                referee_counts.remove(&old_referred_by);
+            }
+        }
+    }
+    if let Some(referred_by) = referred_by {
+        *referee_counts.entry(referred_by).or_insert(0) += 1;
+    }
+}
+
+/// take referral_fee_pct of kong_fee out of Kong's fee of the receive token of the pool
+/// the referral fee is capped at Kong's fee in the pool
+/// returns the token_id of the receive token and the referral fee
+pub fn take_referral_fee(pool: &mut StablePool, receive_token_0: bool, kong_fee: &Nat, referral_fee_pct: u8) -> (u32, Nat) {
+    // referral_fee = kong_fee * referral_fee_pct / 100
+    let numerator = nat_multiply(kong_fee, &Nat::from(referral_fee_pct));
+    let referral_fee = nat_divide(&numerator, &Nat::from(100_u128)).unwrap_or(nat_zero());
+    let (token_id, pool_kong_fee) = if receive_token_0 {
+        (pool.token_id_0, &mut pool.kong_fee_0)
+    } else {
+        (pool.token_id_1, &mut pool.kong_fee_1)
+    };
+    let referral_fee = std::cmp::min(referral_fee, pool_kong_fee.clone());
+    *pool_kong_fee = nat_subtract(pool_kong_fee, &referral_fee).unwrap_or(nat_zero());
+    (token_id, referral_fee)
+}
+
+/// add referral_fee and volume to the referral rewards of the referrer in token_id
+/// returns None if there is nothing to add
+pub fn add_referral_reward(
+    referral: Option<StableReferral>,
+    referrer_user_id: u32,
+    token_id: u32,
+    referral_fee: &Nat,
+    volume: &Nat,
+    ts: u64,
+) -> Option<StableReferral> {
+    if nat_is_zero(referral_fee) && nat_is_zero(volume) {
+        return None;
+    }
+    let referral = referral.unwrap_or(StableReferral {
+        user_id: referrer_user_id,
+        token_id,
+        volume: nat_zero(),
+        earned: nat_zero(),
+        claimed: nat_zero(),
+        ts,
+    });
+    Some(StableReferral {
+        volume: nat_add(&referral.volume, volume),
+        earned: nat_add(&referral.earned, referral_fee),
+        ts,
+        ..referral
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::stable_pool::pool_curve::PoolCurve;
+
+    fn pool() -> StablePool {
+        let mut pool = StablePool::new(1, 2, 30, 8, 3, PoolCurve::ConstantProduct);
+        pool.kong_fee_0 = Nat::from(1_000_u64);
+        pool.kong_fee_1 = Nat::from(50_u64);
+        pool
+    }
+
+    #[test]
+    fn test_update_referee_count() {
+        let mut referee_counts = BTreeMap::new();
+        update_referee_count(&mut referee_counts, None, Some(1));
+        update_referee_count(&mut referee_counts, None, Some(1));
+        update_referee_count(&mut referee_counts, None, Some(2));
+        update_referee_count(&mut referee_counts, None, None);
+        assert_eq!(referee_counts, BTreeMap::from([(1, 2), (2, 1)]));
+        // unchanged referrer
+        update_referee_count(&mut referee_counts, Some(1), Some(1));
+        assert_eq!(referee_counts, BTreeMap::from([(1, 2), (2, 1)]));
+        // expired referral
+        update_referee_count(&mut referee_counts, Some(2), None);
+        assert_eq!(referee_counts, BTreeMap::from([(1, 2)]));
+        update_referee_count(&mut referee_counts, Some(1), Some(3));
+        assert_eq!(referee_counts, BTreeMap::from([(1, 1), (3, 1)]));
+        // referrer not counted is left alone
+        update_referee_count(&mut referee_counts, Some(4), None);
+        assert_eq!(referee_counts, BTreeMap::from([(1, 1), (3, 1)]));
+    }
+
+    #[test]
+    fn test_take_referral_fee() {
+        let mut pool = pool();
+        assert_eq!(take_referral_fee(&mut pool, true, &Nat::from(200_u64), 25), (1, Nat::from(50_u64)));
+        assert_eq!(pool.kong_fee_0, Nat::from(950_u64));
+        assert_eq!(pool.kong_fee_1, Nat::from(50_u64));
+
+        // rounded down
+        assert_eq!(take_referral_fee(&mut pool, true, &Nat::from(3_u64), 25), (1, Nat::from(0_u64)));
+        assert_eq!(pool.kong_fee_0, Nat::from(950_u64));
+
+        // capped at Kong's fee of the pool
+        assert_eq!(
+            take_referral_fee(&mut pool, false, &Nat::from(1_000_u64), 10),
+            (2, Nat::from(50_u64))
+        );
+        assert_eq!(pool.kong_fee_1, Nat::from(0_u64));
+
+        assert_eq!(take_referral_fee(&mut pool, true, &Nat::from(200_u64), 0), (1, Nat::from(0_u64)));
+        assert_eq!(pool.kong_fee_0, Nat::from(950_u64));
+    }
+
+    #[test]
+    fn test_add_referral_reward() {
+        assert!(add_referral_reward(None, 7, 1, &nat_zero(), &nat_zero(), 100).is_none());
+
+        let referral = add_referral_reward(None, 7, 1, &Nat::from(50_u64), &Nat::from(1_000_u64), 100).unwrap();
+        assert_eq!((referral.user_id, referral.token_id), (7, 1));
+        assert_eq!(referral.earned, Nat::from(50_u64));
+        assert_eq!(referral.volume, Nat::from(1_000_u64));
+        assert_eq!(referral.ts, 100);
+
+        // volume is recorded even if the referral fee rounds to zero
+        let referral = StableReferral {
+            claimed: Nat::from(20_u64),
+            ..referral
+        };
+        let referral = add_referral_reward(Some(referral), 7, 1, &nat_zero(), &Nat::from(500_u64), 200).unwrap();
+        assert_eq!(referral.earned, Nat::from(50_u64));
+        assert_eq!(referral.claimed, Nat::from(20_u64));
+        assert_eq!(referral.volume, Nat::from(1_500_u64));
+        assert_eq!(referral.ts, 200);
+    }
+}
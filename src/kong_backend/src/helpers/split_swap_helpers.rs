@@ -0,0 +1,166 @@
+use candid::Nat;
+
+use super::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_zero};
+
+use crate::swap::swap_calc::SwapCalc;
+
+/// allocate pay_amount across routes in parts, each part to the route with the highest marginal receive amount
+/// a route can not be used once it shares a pool with a route already used, as their pool balances would affect each other
+///
+/// route_pool_ids - pool_ids of each route
+/// receive_amount - receive amount of route i for a pay amount. None if the route can not fill it
+///
+/// returns the pay amount allocated to each route. None if a part could not be filled by any route
+pub fn split_allocations(
+    route_pool_ids: &[Vec<u32>],
+    pay_amount: &Nat,
+    parts: u32,
+    receive_amount: impl Fn(usize, &Nat) -> Option<Nat>,
+) -> Option<Vec<Nat>> {
+    let part_amount = nat_divide(pay_amount, &Nat::from(parts))?;
+    let mut allocations = vec![nat_zero(); route_pool_ids.len()];
+    let mut receive_amounts = vec![nat_zero(); route_pool_ids.len()];
+    let mut used_pool_ids: Vec<u32> = Vec::new();
+    for part in 0..parts {
+        // last part takes the remainder of pay_amount
+        let amount = if part == parts - 1 {
+            nat_subtract(pay_amount, &nat_multiply(&part_amount, &Nat::from(parts - 1)))?
+        } else {
+            part_amount.clone()
+        };
+
+        let mut best_route: Option<(usize, Nat, Nat)> = None; // (route index, marginal receive amount, receive amount)
+        for (i, pool_ids) in route_pool_ids.iter().enumerate() {
+            if nat_is_zero(&allocations[i]) && pool_ids.iter().any(|pool_id| used_pool_ids.contains(pool_id)) {
+                continue;
+            }
+            let Some(route_receive_amount) = receive_amount(i, &nat_add(&allocations[i], &amount)) else {
+                continue; // not enough liquidity in the route
+            };
+            let marginal_amount = nat_subtract(&route_receive_amount, &receive_amounts[i]).unwrap_or(nat_zero());
+            if best_route.as_ref().is_none_or(|(_, best_amount, _)| marginal_amount > *best_amount) {
+                best_route = Some((i, marginal_amount, route_receive_amount));
+            }
+        }
+
+        let (i, _, route_receive_amount) = best_route?;
+        if nat_is_zero(&allocations[i]) {
+            used_pool_ids.extend(route_pool_ids[i].iter().copied());
+        }
+        allocations[i] = nat_add(&allocations[i], &amount);
+        receive_amounts[i] = route_receive_amount;
+    }
+
+    Some(allocations)
+}
+
+/// take the gas fee once on the last swap of the first route as the receive token is sent in one transfer
+pub fn take_gas_fee_once(routes: &mut [Vec<SwapCalc>], gas_fee: &Nat) {
+    for (i, route) in routes.iter_mut().enumerate() {
+        if let Some(last_swap) = route.last_mut() {
+            last_swap.gas_fee = if i == 0 { gas_fee.clone() } else { nat_zero() };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // constant product pools of (pool_id, reserve_in, reserve_out) with no fees
+    fn receive_amount(pools: &[(u32, u64, u64)], i: usize, pay_amount: &Nat) -> Option<Nat> {
+        let (_, reserve_in, reserve_out) = pools[i];
+        let amount = nat_multiply(pay_amount, &Nat::from(reserve_out));
+        nat_divide(&amount, &nat_add(&Nat::from(reserve_in), pay_amount))
+    }
+
+    fn swap(pool_id: u32, receive_amount: u64, gas_fee: u64) -> SwapCalc {
+        SwapCalc {
+            pool_id,
+            pay_token_id: 1,
+            pay_amount: Nat::from(receive_amount),
+            receive_token_id: 2,
+            receive_amount: Nat::from(receive_amount),
+            lp_fee: nat_zero(),
+            gas_fee: Nat::from(gas_fee),
+        }
+    }
+
+    #[test]
+    fn test_split_beats_best_single_route() {
+        let pools = [(1, 1_000_000, 1_000_000), (2, 100_000, 100_000)];
+        let pay_amount = Nat::from(500_000_u64);
+        let route_pool_ids = vec![vec![1], vec![2]];
+        let allocations = split_allocations(&route_pool_ids, &pay_amount, 10, |i, amount| receive_amount(&pools, i, amount)).unwrap();
+
+        // both routes are used and all of pay_amount is allocated
+        assert!(allocations.iter().all(|allocation| !nat_is_zero(allocation)));
+        assert_eq!(nat_add(&allocations[0], &allocations[1]), pay_amount);
+        // the deep pool takes more
+        assert!(allocations[0] > allocations[1]);
+
+        let split_amount = allocations.iter().enumerate().fold(nat_zero(), |total, (i, allocation)| {
+            nat_add(&total, &receive_amount(&pools, i, allocation).unwrap())
+        });
+        let single_amount = receive_amount(&pools, 0, &pay_amount).unwrap();
+        assert!(split_amount > single_amount);
+    }
+
+    #[test]
+    fn test_split_routes_do_not_share_pools() {
+        // routes 0 and 1 both go through pool 1
+        let pools = [(1, 1_000_000, 1_000_000), (1, 1_000_000, 1_000_000), (2, 100_000, 100_000)];
+        let route_pool_ids = vec![vec![1, 3], vec![1, 4], vec![2]];
+        let allocations = split_allocations(&route_pool_ids, &Nat::from(500_000_u64), 10, |i, amount| {
+            receive_amount(&pools, i, amount)
+        })
+        .unwrap();
+
+        let used_pool_ids = route_pool_ids
+            .iter()
+            .zip(allocations.iter())
+            .filter(|(_, allocation)| !nat_is_zero(allocation))
+            .flat_map(|(pool_ids, _)| pool_ids.iter().copied())
+            .collect::<Vec<_>>();
+        let mut unique_pool_ids = used_pool_ids.clone();
+        unique_pool_ids.sort();
+        unique_pool_ids.dedup();
+        assert_eq!(used_pool_ids.len(), unique_pool_ids.len());
+        assert!(nat_is_zero(&allocations[1]));
+        assert!(!nat_is_zero(&allocations[2]));
+    }
+
+    #[test]
+    fn test_split_without_liquidity() {
+        let route_pool_ids = vec![vec![1], vec![2]];
+        assert_eq!(split_allocations(&route_pool_ids, &Nat::from(1_000_u64), 10, |_, _| None), None);
+        // a part only one route can fill goes to that route
+        let allocations = split_allocations(&route_pool_ids, &Nat::from(1_000_u64), 10, |i, amount| {
+            (i == 1).then(|| amount.clone())
+        })
+        .unwrap();
+        assert_eq!(allocations, vec![nat_zero(), Nat::from(1_000_u64)]);
+    }
+
+    #[test]
+    fn test_take_gas_fee_once() {
+        let gas_fee = Nat::from(10_u64);
+        let mut routes = vec![
+            vec![swap(1, 500, 0), swap(2, 400, 10)],
+            vec![swap(3, 300, 10)],
+            vec![swap(4, 200, 0), swap(5, 100, 10)],
+        ];
+        take_gas_fee_once(&mut routes, &gas_fee);
+
+        let total_gas_fee = routes
+            .iter()
+            .flatten()
+            .fold(nat_zero(), |total, swap| nat_add(&total, &swap.gas_fee));
+        assert_eq!(total_gas_fee, gas_fee);
+        assert_eq!(routes[0][1].gas_fee, gas_fee);
+        let receive_amount = routes.iter().fold(nat_zero(), |total, route| {
+            nat_add(&total, &route.last().unwrap().receive_amount_with_fees_and_gas())
+        });
+        assert_eq!(receive_amount, Nat::from(790_u64));
+    }
+}
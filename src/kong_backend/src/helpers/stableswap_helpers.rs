@@ -0,0 +1,170 @@
+use candid::Nat;
+use num::BigRational;
+
+use super::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_to_bigint, nat_zero};
+
+// Curve-style stableswap invariant for 2 tokens
+// A * n^n * (x + y) + D = A * D * n^n + D^(n+1) / (n^n * x * y)
+// balances x and y must be in the same decimal precision
+
+const N_COINS: u32 = 2;
+// max iterations of Newton's method before giving up
+const MAX_ITERATIONS: usize = 255;
+
+fn converged(n1: &Nat, n2: &Nat) -> bool {
+    if n1 > n2 {
+        nat_subtract(n1, n2).is_some_and(|diff| diff <= 1_u8)
+    } else {
+        nat_subtract(n2, n1).is_some_and(|diff| diff <= 1_u8)
+    }
+}
+
+/// calculate the invariant D of balances x and y
+pub fn stableswap_d(amp: u32, x: &Nat, y: &Nat) -> Option<Nat> {
+    let s = nat_add(x, y);
+    if nat_is_zero(&s) {
+        return Some(nat_zero());
+    }
+    if nat_is_zero(x) || nat_is_zero(y) {
+        None?
+    }
+
+    let n = Nat::from(N_COINS);
+    let ann = Nat::from(amp as u64 * N_COINS as u64);
+    let mut d = s.clone();
+    for _ in 0..MAX_ITERATIONS {
+        // d_p = D^3 / (n^n * x * y)
+        let d_p = nat_divide(&nat_multiply(&d, &d), &nat_multiply(x, &n))?;
+        let d_p = nat_divide(&nat_multiply(&d_p, &d), &nat_multiply(y, &n))?;
+        let d_prev = d.clone();
+        // D = (ann * s + d_p * n) * D / ((ann - 1) * D + (n + 1) * d_p)
+        let numerator = nat_multiply(&nat_add(&nat_multiply(&ann, &s), &nat_multiply(&d_p, &n)), &d);
+        let denominator = nat_add(
+            &nat_multiply(&nat_subtract(&ann, &Nat::from(1_u8))?, &d),
+            &nat_multiply(&Nat::from(N_COINS + 1), &d_p),
+        );
+        d = nat_divide(&numerator, &denominator)?;
+        if converged(&d, &d_prev) {
+            return Some(d);
+        }
+    }
+
+    None
+}
+
+/// calculate the balance y for a new balance x keeping the invariant D
+pub fn stableswap_y(amp: u32, x: &Nat, d: &Nat) -> Option<Nat> {
+    if nat_is_zero(x) {
+        None?
+    }
+
+    let n = Nat::from(N_COINS);
+    let ann = Nat::from(amp as u64 * N_COINS as u64);
+    // c = D^3 / (n^n * x * ann)
+    let c = nat_divide(&nat_multiply(d, d), &nat_multiply(x, &n))?;
+    let c = nat_divide(&nat_multiply(&c, d), &nat_multiply(&ann, &n))?;
+    // b = x + D / ann
+    let b = nat_add(x, &nat_divide(d, &ann)?);
+    let mut y = d.clone();
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y.clone();
+        // y = (y^2 + c) / (2 * y + b - D)
+        let numerator = nat_add(&nat_multiply(&y, &y), &c);
+        let denominator = nat_subtract(&nat_add(&nat_multiply(&Nat::from(2_u8), &y), &b), d)?;
+        y = nat_divide(&numerator, &denominator)?;
+        if converged(&y, &y_prev) {
+            return Some(y);
+        }
+    }
+
+    None
+}
+
+/// amount of reserve_out received for amount_in of reserve_in, before fees
+pub fn stableswap_amount_out(amp: u32, reserve_in: &Nat, reserve_out: &Nat, amount_in: &Nat) -> Option<Nat> {
+    let d = stableswap_d(amp, reserve_in, reserve_out)?;
+    let new_reserve_out = stableswap_y(amp, &nat_add(reserve_in, amount_in), &d)?;
+    // subtract 1 to round in favour of the pool
+    let amount_out = nat_subtract(reserve_out, &new_reserve_out)?;
+    Some(nat_subtract(&amount_out, &Nat::from(1_u8)).unwrap_or(nat_zero()))
+}
+
+/// amount of reserve_in needed to receive amount_out of reserve_out, before fees
+pub fn stableswap_amount_in(amp: u32, reserve_in: &Nat, reserve_out: &Nat, amount_out: &Nat) -> Option<Nat> {
+    let d = stableswap_d(amp, reserve_in, reserve_out)?;
+    let new_reserve_out = nat_subtract(reserve_out, amount_out)?;
+    let new_reserve_in = stableswap_y(amp, &new_reserve_out, &d)?;
+    // add 1 to round in favour of the pool
+    let amount_in = nat_subtract(&new_reserve_in, reserve_in)?;
+    Some(nat_add(&amount_in, &Nat::from(1_u8)))
+}
+
+/// marginal price of x in terms of y (dy/dx)
+/// price = (4 * ann * x^2 * y^2 + D^3 * y) / (4 * ann * x^2 * y^2 + D^3 * x)
+pub fn stableswap_price(amp: u32, x: &Nat, y: &Nat) -> Option<BigRational> {
+    let d = stableswap_d(amp, x, y)?;
+    let ann = Nat::from(amp as u64 * N_COINS as u64);
+    let x2y2 = nat_multiply(&nat_multiply(x, x), &nat_multiply(y, y));
+    let ann_x2y2 = nat_multiply(&nat_multiply(&Nat::from(N_COINS * N_COINS), &ann), &x2y2);
+    let d3 = nat_multiply(&nat_multiply(&d, &d), &d);
+    let numerator = nat_add(&ann_x2y2, &nat_multiply(&d3, y));
+    let denominator = nat_add(&ann_x2y2, &nat_multiply(&d3, x));
+    if nat_is_zero(&denominator) {
+        None?
+    }
+    Some(BigRational::new(nat_to_bigint(&numerator), nat_to_bigint(&denominator)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num::One;
+
+    #[test]
+    fn test_stableswap_d() {
+        let x = Nat::from(1_000_000_000_u128);
+        let d = stableswap_d(100, &x, &x).unwrap();
+        assert!(converged(&d, &Nat::from(2_000_000_000_u128)));
+
+        let d = stableswap_d(100, &nat_zero(), &nat_zero());
+        assert_eq!(d, Some(nat_zero()));
+
+        let d = stableswap_d(100, &x, &nat_zero());
+        assert_eq!(d, None);
+    }
+
+    #[test]
+    fn test_stableswap_amount_out() {
+        let reserve = Nat::from(1_000_000_000_000_u128);
+        let amount_in = Nat::from(1_000_000_000_u128);
+        let amount_out = stableswap_amount_out(100, &reserve, &reserve, &amount_in).unwrap();
+        // balanced stableswap pool should give close to 1:1
+        assert!(amount_out < amount_in);
+        assert!(amount_out > 999_000_000_u128);
+
+        // constant product for the same trade
+        let cp_amount_out = nat_divide(&nat_multiply(&amount_in, &reserve), &nat_add(&reserve, &amount_in)).unwrap();
+        assert!(amount_out > cp_amount_out);
+    }
+
+    #[test]
+    fn test_stableswap_amount_in() {
+        let reserve_0 = Nat::from(1_000_000_000_000_u128);
+        let reserve_1 = Nat::from(900_000_000_000_u128);
+        let amount_out = Nat::from(1_000_000_000_u128);
+        let amount_in = stableswap_amount_in(100, &reserve_0, &reserve_1, &amount_out).unwrap();
+        let received = stableswap_amount_out(100, &reserve_0, &reserve_1, &amount_in).unwrap();
+        assert!(received >= amount_out);
+    }
+
+    #[test]
+    fn test_stableswap_price() {
+        let x = Nat::from(1_000_000_000_u128);
+        let price = stableswap_price(100, &x, &x).unwrap();
+        assert_eq!(price, BigRational::one());
+
+        // less x in the pool makes x more expensive
+        let price = stableswap_price(100, &Nat::from(500_000_000_u128), &x).unwrap();
+        assert!(price > BigRational::one());
+    }
+}
@@ -0,0 +1,58 @@
+use candid::Nat;
+
+use super::math_helpers::round_f64;
+use super::nat_helpers::nat_divide_as_f64;
+
+const HOUR_NANOSECS: u64 = 3_600_000_000_000;
+const DAY_NANOSECS: u64 = 24 * HOUR_NANOSECS;
+
+/// hours since the Unix epoch of ts in nanoseconds
+pub fn stats_hour(ts: u64) -> u64 {
+    ts / HOUR_NANOSECS
+}
+
+/// days since the Unix epoch of ts in nanoseconds
+pub fn stats_day(ts: u64) -> u64 {
+    ts / DAY_NANOSECS
+}
+
+/// first hour of a rolling window of window_hours ending with the current hour
+pub fn window_start_hour(current_hour: u64, window_hours: u64) -> u64 {
+    (current_hour + 1).saturating_sub(window_hours)
+}
+
+/// annualized percentage yield of lp_fee earned over days on tvl
+/// APY = (lp_fee / tvl) * (365 / days) * 100
+pub fn apy(lp_fee: &Nat, tvl: &Nat, days: u64) -> f64 {
+    if days == 0 {
+        return 0_f64;
+    }
+    round_f64(nat_divide_as_f64(lp_fee, tvl).unwrap_or(0_f64) * 365_f64 / days as f64 * 100_f64, 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_start_hour() {
+        assert_eq!(window_start_hour(stats_hour(0), 24), 0);
+        assert_eq!(window_start_hour(stats_hour(100 * HOUR_NANOSECS + 1), 24), 77);
+        assert_eq!(window_start_hour(100, 1), 100);
+    }
+
+    #[test]
+    fn test_stats_day() {
+        assert_eq!(stats_day(0), 0);
+        assert_eq!(stats_day(DAY_NANOSECS - 1), 0);
+        assert_eq!(stats_day(30 * DAY_NANOSECS + 1), 30);
+        assert_eq!(stats_day(48 * HOUR_NANOSECS), stats_hour(48 * HOUR_NANOSECS) / 24);
+    }
+
+    #[test]
+    fn test_apy() {
+        assert_eq!(apy(&Nat::from(1_u32), &Nat::from(365_u32), 1), 100.0);
+        assert_eq!(apy(&Nat::from(7_u32), &Nat::from(365_u32), 7), 100.0);
+        assert_eq!(apy(&Nat::from(1_u32), &Nat::from(0_u32), 1), 0.0);
+    }
+}
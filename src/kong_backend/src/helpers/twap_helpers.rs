@@ -0,0 +1,67 @@
+use candid::Nat;
+use num::{BigRational, ToPrimitive};
+use num_bigint::BigInt;
+
+use super::nat_helpers::{nat_add, nat_multiply, nat_subtract, nat_to_bigint};
+
+// Uniswap v2 style cumulative prices
+// price_cumulative += price * elapsed time, where price is stored as a Q64 fixed point number
+// the time-weighted average price between 2 points in time is (price_cumulative_end - price_cumulative_start) / elapsed time
+
+const Q64_SHIFT: usize = 64;
+
+/// price as a Q64 fixed point number, rounded down
+pub fn price_x64(price: &BigRational) -> Option<Nat> {
+    let price_x64 = (price * BigRational::from_integer(BigInt::from(1) << Q64_SHIFT))
+        .floor()
+        .to_integer();
+    Some(Nat::from(price_x64.to_biguint()?))
+}
+
+/// price_cumulative after price has held for elapsed time
+pub fn accumulate(price_cumulative: &Nat, price_x64: &Nat, elapsed: u64) -> Nat {
+    nat_add(price_cumulative, &nat_multiply(price_x64, &Nat::from(elapsed)))
+}
+
+/// time-weighted average price between 2 cumulative prices elapsed time apart
+pub fn average_price(price_cumulative_start: &Nat, price_cumulative_end: &Nat, elapsed: u64) -> Option<f64> {
+    if elapsed == 0 {
+        None?
+    }
+    let delta = nat_subtract(price_cumulative_end, price_cumulative_start)?;
+    let denominator = BigInt::from(elapsed) << Q64_SHIFT;
+    BigRational::new(nat_to_bigint(&delta), denominator).to_f64()
+}
+
+/// slot of the ring buffer for ts. observations in the same interval share a slot
+pub fn observation_slot(ts: u64, interval: u64, cardinality: u32) -> u32 {
+    ((ts / interval) % cardinality as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average_price() {
+        // price of 2 for 10s then price of 0.5 for 30s
+        let price_2 = price_x64(&BigRational::new(BigInt::from(2), BigInt::from(1))).unwrap();
+        let price_half = price_x64(&BigRational::new(BigInt::from(1), BigInt::from(2))).unwrap();
+        let start = Nat::from(0_u8);
+        let mid = accumulate(&start, &price_2, 10);
+        let end = accumulate(&mid, &price_half, 30);
+        assert_eq!(average_price(&start, &mid, 10), Some(2.0));
+        assert_eq!(average_price(&mid, &end, 30), Some(0.5));
+        assert_eq!(average_price(&start, &end, 40), Some(0.875));
+        assert_eq!(average_price(&start, &end, 0), None);
+        assert_eq!(average_price(&end, &start, 40), None);
+    }
+
+    #[test]
+    fn test_observation_slot() {
+        assert_eq!(observation_slot(0, 60, 1440), 0);
+        assert_eq!(observation_slot(59, 60, 1440), 0);
+        assert_eq!(observation_slot(60, 60, 1440), 1);
+        assert_eq!(observation_slot(60 * 1440, 60, 1440), 0);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::stable_request::reply::Reply;
+use crate::stable_request::stable_request::StableRequest;
+use crate::stable_request::status::StatusCode;
+use crate::stable_webhook::stable_webhook_delivery::{StableWebhookDelivery, WebhookDeliveryStatus};
+
+pub const MAX_ATTEMPTS: u32 = 5;
+const REPLY_WAIT_NANOSECS: u64 = 60_000_000_000; // 1 minute
+const ACK_WAIT_NANOSECS: u64 = 300_000_000_000; // 5 minutes
+const FAILED_EXPIRY_NANOSECS: u64 = 7 * 24 * 3_600_000_000_000; // 7 days
+
+/// what the webhooks timer does with a delivery
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookDeliveryAction {
+    Send,
+    Wait,
+    Fail(String),
+    Remove,
+}
+
+/// status of a new delivery of a request which reached Success or Failed
+/// the reply of a request can be recorded after its final status, so the delivery waits for it
+pub fn queued_delivery_status(reply: &Reply) -> WebhookDeliveryStatus {
+    match reply {
+        Reply::Pending => WebhookDeliveryStatus::AwaitingReply,
+        _ => WebhookDeliveryStatus::Pending,
+    }
+}
+
+/// delivery once the final reply of its request is recorded
+pub fn with_reply_recorded(delivery: &StableWebhookDelivery) -> StableWebhookDelivery {
+    match delivery.status {
+        WebhookDeliveryStatus::AwaitingReply => StableWebhookDelivery {
+            status: WebhookDeliveryStatus::Pending,
+            ..delivery.clone()
+        },
+        _ => delivery.clone(),
+    }
+}
+
+/// action on a delivery for the state of its request at ts
+/// - AwaitingReply: sent once the reply is recorded. failed requests may never record a reply and are sent after REPLY_WAIT_NANOSECS
+/// - Pending: sent
+/// - Sent: re-sent if not acknowledged within ACK_WAIT_NANOSECS, up to MAX_ATTEMPTS
+/// - Failed: removed after FAILED_EXPIRY_NANOSECS
+pub fn delivery_action(delivery: &StableWebhookDelivery, request: Option<&StableRequest>, ts: u64) -> WebhookDeliveryAction {
+    if delivery.status == WebhookDeliveryStatus::Failed {
+        if delivery.ts + FAILED_EXPIRY_NANOSECS < ts {
+            return WebhookDeliveryAction::Remove;
+        }
+        return WebhookDeliveryAction::Wait;
+    }
+    let Some(request) = request else {
+        return WebhookDeliveryAction::Fail("Request not found".to_string());
+    };
+    match delivery.status {
+        WebhookDeliveryStatus::AwaitingReply => {
+            let is_failed = request
+                .statuses
+                .iter()
+                .any(|status| matches!(status.status_code, StatusCode::Failed));
+            if !matches!(request.reply, Reply::Pending) || (is_failed && delivery.ts + REPLY_WAIT_NANOSECS < ts) {
+                WebhookDeliveryAction::Send
+            } else if delivery.ts + FAILED_EXPIRY_NANOSECS < ts {
+                WebhookDeliveryAction::Fail("Reply not recorded".to_string())
+            } else {
+                WebhookDeliveryAction::Wait
+            }
+        }
+        WebhookDeliveryStatus::Pending => WebhookDeliveryAction::Send,
+        WebhookDeliveryStatus::Sent if delivery.ts + ACK_WAIT_NANOSECS > ts => WebhookDeliveryAction::Wait,
+        WebhookDeliveryStatus::Sent if delivery.attempts >= MAX_ATTEMPTS => WebhookDeliveryAction::Fail("Not acknowledged".to_string()),
+        WebhookDeliveryStatus::Sent => WebhookDeliveryAction::Send,
+        WebhookDeliveryStatus::Failed => WebhookDeliveryAction::Wait,
+    }
+}
+
+/// delivery after an attempt at ts to notify the webhook
+/// a sent delivery waits to be acknowledged. a delivery which failed to be sent is retried up to MAX_ATTEMPTS
+pub fn with_notify_result(delivery: &StableWebhookDelivery, result: Result<(), String>, ts: u64) -> StableWebhookDelivery {
+    let attempts = delivery.attempts + 1;
+    match result {
+        Ok(()) => StableWebhookDelivery {
+            status: WebhookDeliveryStatus::Sent,
+            attempts,
+            message: None,
+            ts,
+            ..delivery.clone()
+        },
+        Err(e) => StableWebhookDelivery {
+            status: if attempts >= MAX_ATTEMPTS {
+                WebhookDeliveryStatus::Failed
+            } else {
+                WebhookDeliveryStatus::Pending
+            },
+            attempts,
+            message: Some(e),
+            ts,
+            ..delivery.clone()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::stable_request::request::Request;
+    use crate::stable_request::status::Status;
+
+    const TS: u64 = 1_700_000_000_000_000_000;
+
+    fn delivery(status: WebhookDeliveryStatus, attempts: u32) -> StableWebhookDelivery {
+        StableWebhookDelivery {
+            delivery_id: 1,
+            user_id: 100,
+            request_id: 7,
+            status,
+            attempts,
+            message: None,
+            ts: TS,
+        }
+    }
+
+    fn request(status_code: StatusCode) -> StableRequest {
+        let mut request = StableRequest::new(100, &Request::Claim(1), TS);
+        request.request_id = 7;
+        request.statuses.push(Status {
+            status_code,
+            message: None,
+        });
+        request
+    }
+
+    #[test]
+    fn test_queued_delivery_status() {
+        assert_eq!(queued_delivery_status(&Reply::Pending), WebhookDeliveryStatus::AwaitingReply);
+        let awaiting = delivery(WebhookDeliveryStatus::AwaitingReply, 0);
+        assert_eq!(with_reply_recorded(&awaiting).status, WebhookDeliveryStatus::Pending);
+        // only deliveries awaiting the reply change
+        let sent = delivery(WebhookDeliveryStatus::Sent, 1);
+        assert_eq!(with_reply_recorded(&sent).status, WebhookDeliveryStatus::Sent);
+    }
+
+    #[test]
+    fn test_delivery_action_awaiting_reply() {
+        let awaiting = delivery(WebhookDeliveryStatus::AwaitingReply, 0);
+        // never sent while the reply is pending
+        let success = request(StatusCode::Success);
+        assert_eq!(delivery_action(&awaiting, Some(&success), TS), WebhookDeliveryAction::Wait);
+        assert_eq!(
+            delivery_action(&awaiting, Some(&success), TS + 2 * REPLY_WAIT_NANOSECS),
+            WebhookDeliveryAction::Wait
+        );
+        assert_eq!(
+            delivery_action(&awaiting, Some(&success), TS + FAILED_EXPIRY_NANOSECS + 1),
+            WebhookDeliveryAction::Fail("Reply not recorded".to_string())
+        );
+        // failed requests may not record a reply
+        let failed = request(StatusCode::Failed);
+        assert_eq!(delivery_action(&awaiting, Some(&failed), TS), WebhookDeliveryAction::Wait);
+        assert_eq!(
+            delivery_action(&awaiting, Some(&failed), TS + REPLY_WAIT_NANOSECS + 1),
+            WebhookDeliveryAction::Send
+        );
+        assert_eq!(
+            delivery_action(&awaiting, None, TS),
+            WebhookDeliveryAction::Fail("Request not found".to_string())
+        );
+    }
+
+    #[test]
+    fn test_delivery_action_sent() {
+        let success = request(StatusCode::Success);
+        let pending = delivery(WebhookDeliveryStatus::Pending, 0);
+        assert_eq!(delivery_action(&pending, Some(&success), TS), WebhookDeliveryAction::Send);
+        // re-sent until acknowledged or out of attempts
+        let sent = delivery(WebhookDeliveryStatus::Sent, 1);
+        assert_eq!(delivery_action(&sent, Some(&success), TS + 1), WebhookDeliveryAction::Wait);
+        assert_eq!(
+            delivery_action(&sent, Some(&success), TS + ACK_WAIT_NANOSECS + 1),
+            WebhookDeliveryAction::Send
+        );
+        let sent = delivery(WebhookDeliveryStatus::Sent, MAX_ATTEMPTS);
+        assert_eq!(
+            delivery_action(&sent, Some(&success), TS + ACK_WAIT_NANOSECS + 1),
+            WebhookDeliveryAction::Fail("Not acknowledged".to_string())
+        );
+        // failed deliveries are kept until they expire
+        let failed = delivery(WebhookDeliveryStatus::Failed, MAX_ATTEMPTS);
+        assert_eq!(delivery_action(&failed, None, TS + 1), WebhookDeliveryAction::Wait);
+        assert_eq!(
+            delivery_action(&failed, None, TS + FAILED_EXPIRY_NANOSECS + 1),
+            WebhookDeliveryAction::Remove
+        );
+    }
+
+    #[test]
+    fn test_with_notify_result() {
+        let pending = delivery(WebhookDeliveryStatus::Pending, 0);
+        let sent = with_notify_result(&pending, Ok(()), TS + 1);
+        assert_eq!(sent.status, WebhookDeliveryStatus::Sent);
+        assert_eq!(sent.attempts, 1);
+        assert_eq!(sent.ts, TS + 1);
+        let retry = with_notify_result(&pending, Err("SysTransient".to_string()), TS + 1);
+        assert_eq!(retry.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(retry.message, Some("SysTransient".to_string()));
+        let last_attempt = delivery(WebhookDeliveryStatus::Sent, MAX_ATTEMPTS - 1);
+        let failed = with_notify_result(&last_attempt, Err("SysTransient".to_string()), TS + 1);
+        assert_eq!(failed.status, WebhookDeliveryStatus::Failed);
+        assert_eq!(failed.attempts, MAX_ATTEMPTS);
+    }
+}
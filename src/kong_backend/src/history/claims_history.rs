@@ -0,0 +1,37 @@
+use ic_cdk::query;
+
+use super::history_args::HistoryArgs;
+use super::history_filter::HistoryFilter;
+use super::history_reply::ClaimsHistoryReply;
+
+use crate::claims::claims_reply_helpers::to_claims_reply;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_claim::stable_claim::StableClaimId;
+use crate::stable_memory::CLAIM_MAP;
+
+/// claims filtered by principal_id, token, status and time range
+/// status - Unclaimed, Claiming, Success, TooManyAttempts or UnclaimedOverride
+#[query(guard = "not_in_maintenance_mode")]
+fn claims_history(args: HistoryArgs) -> Result<ClaimsHistoryReply, String> {
+    let filter = HistoryFilter::new(&args, &["token", "status"])?;
+    let (claims, next_cursor) = CLAIM_MAP.with(|m| {
+        filter.page(
+            &m.borrow(),
+            None,
+            StableClaimId,
+            |k| k.0,
+            |claim| {
+                if filter.matches_user_id(claim.user_id)
+                    && filter.matches_token_id(claim.token_id)
+                    && filter.matches_status(&claim.status.to_string())
+                    && filter.matches_ts(claim.ts)
+                {
+                    to_claims_reply(&claim)
+                } else {
+                    None
+                }
+            },
+        )
+    });
+    Ok(ClaimsHistoryReply { claims, next_cursor })
+}
@@ -0,0 +1,17 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the arguments of the history queries.
+/// all filters are optional. results are most recent first
+#[derive(CandidType, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryArgs {
+    pub principal_id: Option<String>,
+    pub token: Option<String>, // ie. ckBTC, IC.ckBTC or IC.mxzaz-hqaaa-aaaar-qaada-cai
+    pub pool: Option<String>,  // ie. ckBTC_ckUSDT
+    pub tx_type: Option<String>,
+    pub status: Option<String>,
+    pub start_ts: Option<u64>,
+    pub end_ts: Option<u64>,
+    pub cursor: Option<u64>, // next_cursor of the previous page
+    pub limit: Option<u16>,
+}
@@ -0,0 +1,124 @@
+use ic_stable_structures::{Memory, StableBTreeMap, Storable};
+use std::cmp::min;
+
+use super::history_args::HistoryArgs;
+
+use crate::helpers::history_helpers::paginate;
+use crate::stable_pool::pool_map;
+use crate::stable_token::token::Token;
+use crate::stable_token::token_map;
+use crate::stable_user::user_map;
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+const MAX_SCAN: usize = 10_000;
+
+/// HistoryArgs with the principal, token and pool resolved to their ids
+pub struct HistoryFilter {
+    pub user_id: Option<u32>,
+    pub token_id: Option<u32>,
+    pub pool_id: Option<u32>,
+    pub tx_type: Option<String>,
+    pub status: Option<String>,
+    pub start_ts: Option<u64>,
+    pub end_ts: Option<u64>,
+    pub cursor: Option<u64>,
+    pub limit: usize,
+}
+
+impl HistoryFilter {
+    /// supported - names of the filters supported by the query, besides principal_id and time range
+    pub fn new(args: &HistoryArgs, supported: &[&str]) -> Result<Self, String> {
+        for (name, is_set) in [
+            ("token", args.token.is_some()),
+            ("pool", args.pool.is_some()),
+            ("tx_type", args.tx_type.is_some()),
+            ("status", args.status.is_some()),
+        ] {
+            if is_set && !supported.contains(&name) {
+                Err(format!("Filter by {} not supported", name))?
+            }
+        }
+        let user_id = match args.principal_id {
+            Some(ref principal_id) => Some(
+                user_map::get_by_principal_id(principal_id)
+                    .ok()
+                    .flatten()
+                    .ok_or("User not found")?
+                    .user_id,
+            ),
+            None => None,
+        };
+        let token_id = match args.token {
+            Some(ref token) => Some(token_map::get_by_token(token)?.token_id()),
+            None => None,
+        };
+        let pool_id = match args.pool {
+            Some(ref pool) => Some(pool_map::get_by_token(pool)?.pool_id),
+            None => None,
+        };
+        Ok(Self {
+            user_id,
+            token_id,
+            pool_id,
+            tx_type: args.tx_type.clone(),
+            status: args.status.clone(),
+            start_ts: args.start_ts,
+            end_ts: args.end_ts,
+            cursor: args.cursor,
+            limit: args.limit.map_or(DEFAULT_LIMIT, |limit| min(limit as usize, MAX_LIMIT)),
+        })
+    }
+
+    pub fn matches_user_id(&self, user_id: u32) -> bool {
+        self.user_id.is_none_or(|filter_user_id| filter_user_id == user_id)
+    }
+
+    pub fn matches_token_id(&self, token_id: u32) -> bool {
+        self.token_id.is_none_or(|filter_token_id| filter_token_id == token_id)
+    }
+
+    pub fn matches_tx_type(&self, tx_type: &str) -> bool {
+        self.tx_type
+            .as_ref()
+            .is_none_or(|filter_tx_type| filter_tx_type.eq_ignore_ascii_case(tx_type))
+    }
+
+    pub fn matches_status(&self, status: &str) -> bool {
+        self.status
+            .as_ref()
+            .is_none_or(|filter_status| filter_status.eq_ignore_ascii_case(status))
+    }
+
+    pub fn matches_ts(&self, ts: u64) -> bool {
+        self.start_ts.is_none_or(|start_ts| ts >= start_ts) && self.end_ts.is_none_or(|end_ts| ts <= end_ts)
+    }
+
+    /// page of the entries of map and its archive up to the cursor, most recent first
+    /// map holds the most recent entries and archive a copy of older ones. map takes precedence as archived copies may be stale
+    pub fn page<K, V, M, T>(
+        &self,
+        map: &StableBTreeMap<K, V, M>,
+        archive: Option<&StableBTreeMap<K, V, M>>,
+        to_key: fn(u64) -> K,
+        from_key: fn(&K) -> u64,
+        to_item: impl Fn(V) -> Option<T>,
+    ) -> (Vec<T>, Option<u64>)
+    where
+        K: Storable + Ord + Clone,
+        V: Storable,
+        M: Memory,
+    {
+        let cursor = self.cursor.unwrap_or(u64::MAX);
+        let first_id = map.first_key_value().map_or(u64::MAX, |(k, _)| from_key(&k));
+        let entries = map.range(..=to_key(cursor)).rev();
+        let archived_entries = archive
+            .into_iter()
+            .flat_map(|archive| archive.range(..to_key(min(first_id, cursor.saturating_add(1)))).rev());
+        paginate(
+            entries.chain(archived_entries).map(|(k, v)| (from_key(&k), to_item(v))),
+            self.limit,
+            MAX_SCAN,
+        )
+    }
+}
@@ -0,0 +1,33 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use crate::claims::claims_reply::ClaimsReply;
+use crate::requests::request_reply::RequestReply;
+use crate::transfers::transfer_reply::TransferIdReply;
+use crate::txs::txs_reply::TxsReply;
+
+// next_cursor is None on the last page
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct RequestsHistoryReply {
+    pub requests: Vec<RequestReply>,
+    pub next_cursor: Option<u64>,
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct TxsHistoryReply {
+    pub txs: Vec<TxsReply>,
+    pub next_cursor: Option<u64>,
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct TransfersHistoryReply {
+    pub transfers: Vec<TransferIdReply>,
+    pub next_cursor: Option<u64>,
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimsHistoryReply {
+    pub claims: Vec<ClaimsReply>,
+    pub next_cursor: Option<u64>,
+}
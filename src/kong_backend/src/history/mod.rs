@@ -0,0 +1,7 @@
+pub mod claims_history;
+pub mod history_args;
+pub mod history_filter;
+pub mod history_reply;
+pub mod requests_history;
+pub mod transfers_history;
+pub mod txs_history;
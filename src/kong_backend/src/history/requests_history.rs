@@ -0,0 +1,70 @@
+use ic_cdk::query;
+
+use super::history_args::HistoryArgs;
+use super::history_filter::HistoryFilter;
+use super::history_reply::RequestsHistoryReply;
+
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::requests::request_reply_helpers::to_request_reply;
+use crate::stable_memory::{REQUEST_ARCHIVE_MAP, REQUEST_MAP};
+use crate::stable_request::request::Request;
+use crate::stable_request::stable_request::{StableRequest, StableRequestId};
+use crate::stable_request::status::StatusCode;
+
+/// requests filtered by principal_id, tx_type, status and time range
+/// tx_type - AddPool, AddLiquidity, RemoveLiquidity, Swap, Claim, Send, LimitOrder, AddLiquiditySingle, RemoveLiquiditySingle or FlashLoan
+/// status - Success, Failed or Pending
+#[query(guard = "not_in_maintenance_mode")]
+fn requests_history(args: HistoryArgs) -> Result<RequestsHistoryReply, String> {
+    let filter = HistoryFilter::new(&args, &["tx_type", "status"])?;
+    Ok(get_requests_history(&filter))
+}
+
+pub fn get_requests_history(filter: &HistoryFilter) -> RequestsHistoryReply {
+    let (requests, next_cursor) = REQUEST_MAP.with(|m| {
+        REQUEST_ARCHIVE_MAP.with(|a| {
+            filter.page(
+                &m.borrow(),
+                Some(&a.borrow()),
+                StableRequestId,
+                |k| k.0,
+                |request| {
+                    if filter.matches_user_id(request.user_id)
+                        && filter.matches_tx_type(request_type(&request.request))
+                        && filter.matches_status(request_status(&request))
+                        && filter.matches_ts(request.ts)
+                    {
+                        Some(to_request_reply(&request))
+                    } else {
+                        None
+                    }
+                },
+            )
+        })
+    });
+    RequestsHistoryReply { requests, next_cursor }
+}
+
+fn request_type(request: &Request) -> &str {
+    match request {
+        Request::AddPool(_) => "AddPool",
+        Request::AddLiquidity(_) => "AddLiquidity",
+        Request::RemoveLiquidity(_) => "RemoveLiquidity",
+        Request::Swap(_) => "Swap",
+        Request::Claim(_) => "Claim",
+        Request::Send(_) => "Send",
+        Request::LimitOrder(_) => "LimitOrder",
+        Request::AddLiquiditySingle(_) => "AddLiquiditySingle",
+        Request::RemoveLiquiditySingle(_) => "RemoveLiquiditySingle",
+        Request::FlashLoan(_) => "FlashLoan",
+    }
+}
+
+/// Success or Failed once the request has finished, otherwise Pending
+fn request_status(request: &StableRequest) -> &str {
+    match request.statuses.last().map(|status| &status.status_code) {
+        Some(StatusCode::Success) => "Success",
+        Some(StatusCode::Failed) => "Failed",
+        _ => "Pending",
+    }
+}
@@ -0,0 +1,48 @@
+use ic_cdk::query;
+
+use super::history_args::HistoryArgs;
+use super::history_filter::HistoryFilter;
+use super::history_reply::TransfersHistoryReply;
+
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_memory::{REQUEST_ARCHIVE_MAP, REQUEST_MAP, TRANSFER_ARCHIVE_MAP, TRANSFER_MAP};
+use crate::stable_request::stable_request::StableRequestId;
+use crate::stable_transfer::stable_transfer::StableTransferId;
+use crate::transfers::transfer_reply_helpers::to_transfer_reply;
+
+/// transfers to and from Kong filtered by principal_id, token and time range
+#[query(guard = "not_in_maintenance_mode")]
+fn transfers_history(args: HistoryArgs) -> Result<TransfersHistoryReply, String> {
+    let filter = HistoryFilter::new(&args, &["token"])?;
+    let (transfers, next_cursor) = TRANSFER_MAP.with(|m| {
+        TRANSFER_ARCHIVE_MAP.with(|a| {
+            filter.page(
+                &m.borrow(),
+                Some(&a.borrow()),
+                StableTransferId,
+                |k| k.0,
+                |transfer| {
+                    if filter.matches_token_id(transfer.token_id)
+                        && filter.matches_ts(transfer.ts)
+                        && filter
+                            .user_id
+                            .is_none_or(|_| request_user_id(transfer.request_id).is_some_and(|user_id| filter.matches_user_id(user_id)))
+                    {
+                        to_transfer_reply(&transfer)
+                    } else {
+                        None
+                    }
+                },
+            )
+        })
+    });
+    Ok(TransfersHistoryReply { transfers, next_cursor })
+}
+
+/// transfers are made for the user of their request
+fn request_user_id(request_id: u64) -> Option<u32> {
+    REQUEST_MAP
+        .with(|m| m.borrow().get(&StableRequestId(request_id)))
+        .or_else(|| REQUEST_ARCHIVE_MAP.with(|m| m.borrow().get(&StableRequestId(request_id))))
+        .map(|request| request.user_id)
+}
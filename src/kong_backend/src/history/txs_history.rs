@@ -0,0 +1,67 @@
+use ic_cdk::query;
+
+use super::history_args::HistoryArgs;
+use super::history_filter::HistoryFilter;
+use super::history_reply::TxsHistoryReply;
+
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_memory::{TX_ARCHIVE_MAP, TX_MAP};
+use crate::stable_tx::stable_tx::{StableTx, StableTxId};
+use crate::stable_tx::status_tx::StatusTx;
+use crate::stable_tx::tx::Tx;
+use crate::stable_tx::tx_map;
+use crate::txs::txs_reply_helpers::to_txs_reply;
+
+/// txs filtered by principal_id, token, pool, tx_type, status and time range
+/// tx_type - AddPool, AddLiquidity, RemoveLiquidity, Swap, Send or CollectProtocolFees
+/// status - Success or Failed
+#[query(guard = "not_in_maintenance_mode")]
+fn txs_history(args: HistoryArgs) -> Result<TxsHistoryReply, String> {
+    let filter = HistoryFilter::new(&args, &["token", "pool", "tx_type", "status"])?;
+    let (txs, next_cursor) = TX_MAP.with(|m| {
+        TX_ARCHIVE_MAP.with(|a| {
+            filter.page(
+                &m.borrow(),
+                Some(&a.borrow()),
+                StableTxId,
+                |k| k.0,
+                |tx| {
+                    if filter.matches_user_id(tx.user_id())
+                        && filter.token_id.is_none_or(|token_id| tx_map::has_token_id(&tx, token_id))
+                        && filter.pool_id.is_none_or(|pool_id| tx_map::pool_ids(&tx).contains(&pool_id))
+                        && filter.matches_tx_type(tx_type(&tx))
+                        && filter.matches_status(&tx_status(&tx).to_string())
+                        && filter.matches_ts(tx.ts())
+                    {
+                        Some(to_txs_reply(&tx))
+                    } else {
+                        None
+                    }
+                },
+            )
+        })
+    });
+    Ok(TxsHistoryReply { txs, next_cursor })
+}
+
+fn tx_type(tx: &StableTx) -> &str {
+    match tx {
+        StableTx::AddPool(_) => "AddPool",
+        StableTx::AddLiquidity(_) => "AddLiquidity",
+        StableTx::RemoveLiquidity(_) => "RemoveLiquidity",
+        StableTx::Swap(_) => "Swap",
+        StableTx::Send(_) => "Send",
+        StableTx::CollectProtocolFees(_) => "CollectProtocolFees",
+    }
+}
+
+fn tx_status(tx: &StableTx) -> &StatusTx {
+    match tx {
+        StableTx::AddPool(tx) => &tx.status,
+        StableTx::AddLiquidity(tx) => &tx.status,
+        StableTx::RemoveLiquidity(tx) => &tx.status,
+        StableTx::Swap(tx) => &tx.status,
+        StableTx::Send(tx) => &tx.status,
+        StableTx::CollectProtocolFees(tx) => &tx.status,
+    }
+}
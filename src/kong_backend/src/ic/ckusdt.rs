@@ -1,7 +1,6 @@
 use candid::Nat;
 
 use crate::helpers::nat_helpers::nat_to_decimals_f64;
-use crate::stable_kong_settings::kong_settings_map;
 use crate::stable_token::stable_token::StableToken;
 use crate::stable_token::token::Token;
 use crate::stable_token::token_map;
@@ -29,19 +28,8 @@ pub const CKUSDT_ADDRESS_WITH_CHAIN: &str = if cfg!(any(feature = "local", featu
     "IC.cngnf-vqaaa-aaaar-qag4q-cai"
 };
 
-pub fn is_ckusdt(token: &str) -> bool {
-    let kong_settings = kong_settings_map::get();
-    if token == kong_settings.ckusdt_symbol
-        || token == kong_settings.ckusdt_symbol_with_chain
-        || token == kong_settings.ckusdt_address
-        || token == kong_settings.ckusdt_address_with_chain
-    {
-        return true;
-    }
-    false
-}
-
 /// Calculate the ckusdt amount for a given token and amount
+/// uses the mid price of the best route, which can go through any of the quote tokens
 pub fn ckusdt_amount(token: &StableToken, amount: &Nat) -> Result<Nat, String> {
     let ckusdt_token = token_map::get_ckusdt()?;
     let receive_amount = swap_mid_amounts(token, amount, &ckusdt_token)?;
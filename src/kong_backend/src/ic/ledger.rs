@@ -1,7 +1,13 @@
 use candid::{CandidType, Nat, Principal};
+use ic_ledger_types::{query_blocks, GetBlocksArgs};
 use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc3::transactions::{GetTransactionsRequest, GetTransactionsResponse};
 use serde::{Deserialize, Serialize};
 
+use super::wumbo::Transaction1;
+
+use crate::stable_token::verification_method::VerificationMethod;
+
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub struct StandardRecord {
     pub url: String,
@@ -57,6 +63,34 @@ pub async fn get_supported_standards(ledger: &Principal) -> Result<Vec<StandardR
     }
 }
 
+/// detect how transfers are verified on the ledger by probing the ledger's methods
+/// ICRC-3 ledgers use icrc3_get_blocks. others fall back to get_transactions if nothing else answers
+pub async fn get_verification_method(ledger: &Principal, icrc3: bool) -> VerificationMethod {
+    if icrc3 {
+        return VerificationMethod::ICRC3GetBlocks;
+    }
+    let get_transactions_args = GetTransactionsRequest {
+        start: Nat::from(0_u32),
+        length: Nat::from(0_u32),
+    };
+    if ic_cdk::call::<(GetTransactionsRequest,), (GetTransactionsResponse,)>(*ledger, "get_transactions", (get_transactions_args,))
+        .await
+        .is_ok()
+    {
+        return VerificationMethod::GetTransactions;
+    }
+    if query_blocks(*ledger, GetBlocksArgs { start: 0, length: 0 }).await.is_ok() {
+        return VerificationMethod::QueryBlocks;
+    }
+    if ic_cdk::call::<(Nat,), (Option<Transaction1>,)>(*ledger, "get_transaction", (Nat::from(0_u32),))
+        .await
+        .is_ok()
+    {
+        return VerificationMethod::Legacy;
+    }
+    VerificationMethod::GetTransactions
+}
+
 #[allow(dead_code)]
 pub async fn get_total_supply(ledger: &Principal) -> Result<Nat, String> {
     ic_cdk::call::<(), (Nat,)>(*ledger, "icrc1_total_supply", ())
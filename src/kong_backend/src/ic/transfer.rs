@@ -59,9 +59,28 @@ pub async fn icrc1_transfer(
     token: &StableToken,
     created_at_time: Option<u64>,
 ) -> Result<Nat, String> {
+    icrc1_transfer_with_ledger_error(amount, to_principal_id, token, created_at_time)
+        .await?
+        .map_err(|e| e.to_string())
+}
+
+/// Same as icrc1_transfer() but returns the TransferError of the ledger so a rejected transfer can be told apart
+/// from a transfer with an unknown outcome.
+///
+/// # Returns
+///
+/// * `Ok(Ok(Nat))` - The block ID of the transfer if successful.
+/// * `Ok(Err(TransferError))` - The error returned by the ledger.
+/// * `Err(String)` - An error message if the call to the ledger fails. The transfer may or may not have been executed.
+pub async fn icrc1_transfer_with_ledger_error(
+    amount: &Nat,
+    to_principal_id: &Account,
+    token: &StableToken,
+    created_at_time: Option<u64>,
+) -> Result<Result<Nat, TransferError>, String> {
     if nat_is_zero(amount) {
         // if amount = 0, return Ok(block_id = 0) to return success. Don't error Err as it could be put into claims
-        return Ok(nat_zero());
+        return Ok(Ok(nat_zero()));
     }
     let id = *token.canister_id().ok_or("Invalid principal id")?;
 
@@ -74,15 +93,13 @@ pub async fn icrc1_transfer(
         created_at_time,
     };
 
-    match ic_cdk::call::<(TransferArg,), (Result<Nat, TransferError>,)>(id, "icrc1_transfer", (transfer_args,))
-        .await
-        .map_err(|e| e.1)?
-        // Access the first element of the tuple, which is the `Result<BlockIndex, TransferError>`, for further processing.
-        .0
-    {
-        Ok(block_id) => Ok(block_id),
-        Err(e) => Err(e.to_string())?,
-    }
+    // Access the first element of the tuple, which is the `Result<BlockIndex, TransferError>`, for further processing.
+    Ok(
+        ic_cdk::call::<(TransferArg,), (Result<Nat, TransferError>,)>(id, "icrc1_transfer", (transfer_args,))
+            .await
+            .map_err(|e| e.1)?
+            .0,
+    )
 }
 
 // icrc2_transfer_from using principal id's where from_principal_id has issued an icrc2_approve
@@ -1,25 +1,21 @@
 use candid::Nat;
-use ic_ledger_types::{query_blocks, AccountIdentifier, Block, GetBlocksArgs, Operation, Subaccount, Tokens};
+use ic_ledger_types::{query_archived_blocks, query_blocks, AccountIdentifier, Block, GetBlocksArgs, Operation, Subaccount, Tokens};
 use icrc_ledger_types::icrc1::account::Account;
 use icrc_ledger_types::icrc2::allowance::{Allowance, AllowanceArgs};
-use icrc_ledger_types::icrc3::transactions::{GetTransactionsRequest, GetTransactionsResponse};
+use icrc_ledger_types::icrc3::blocks::{BlockWithId, GetBlocksRequest, GetBlocksResult};
+use icrc_ledger_types::icrc3::transactions::{GetTransactionsRequest, GetTransactionsResponse, Transaction, TransactionRange};
 
 use super::wumbo::Transaction1;
 
-use crate::helpers::nat_helpers::nat_to_u64;
+use crate::helpers::icrc3_helpers::{to_icrc3_transaction, ICRC3Operation};
+use crate::helpers::nat_helpers::{nat_add, nat_to_u64};
 use crate::ic::get_time::get_time;
 use crate::ic::id::{caller_account_id, caller_id};
 use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_token::ic_token::ICToken;
 use crate::stable_token::stable_token::StableToken;
 use crate::stable_token::token::Token;
-
-#[cfg(not(feature = "prod"))]
-const ICP_CANISTER_ID: &str = "IC.ryjl3-tyaaa-aaaaa-aaaba-cai";
-#[cfg(feature = "prod")]
-const ICP_CANISTER_ID: &str = "IC.ryjl3-tyaaa-aaaaa-aaaba-cai";
-const WUMBO_CANISTER_ID: &str = "IC.wkv3f-iiaaa-aaaap-ag73a-cai";
-const DAMONIC_CANISTER_ID: &str = "IC.zzsnb-aaaaa-aaaap-ag66q-cai";
-const CLOWN_CANISTER_ID: &str = "IC.iwv6l-6iaaa-aaaal-ajjjq-cai";
+use crate::stable_token::verification_method::VerificationMethod;
 
 /// Represents the type of a transaction.
 #[derive(Debug, PartialEq, Eq)]
@@ -32,185 +28,275 @@ pub enum TransactionType {
 /// verify that the block_id is a transfer from caller, amount matches
 /// ts_start timestamp where transfer must be after this time
 pub async fn verify_transfer(token: &StableToken, block_id: &Nat, amount: &Nat) -> Result<(), String> {
-    let token_address_with_chain = token.address_with_chain();
     let ts_start = get_time() - kong_settings_map::get().transfer_expiry_nanosecs; // only accept transfers within the hour
     match token {
-        StableToken::IC(_) => {
-            if token_address_with_chain == ICP_CANISTER_ID {
-                // use query_blocks
-                let block_args = GetBlocksArgs {
-                    start: nat_to_u64(block_id).ok_or_else(|| format!("ICP ledger block id {:?} not found", block_id))?,
-                    length: 1,
-                };
-                match query_blocks(*token.canister_id().ok_or("Invalid principal id")?, block_args)
-                    .await
-                    .map_err(|e| e.1)
-                {
-                    Ok(query_response) => {
-                        let blocks: Vec<Block> = query_response.blocks;
-                        let backend_account = kong_settings_map::get().kong_backend;
-                        let backend_account_id =
-                            AccountIdentifier::new(&backend_account.owner, &Subaccount(backend_account.subaccount.unwrap_or([0; 32])));
-                        let amount = Tokens::from_e8s(nat_to_u64(amount).ok_or("Invalid ICP amount")?);
-                        for block in blocks.into_iter() {
-                            match block.transaction.operation {
-                                Some(operation) => match operation {
-                                    Operation::Transfer {
-                                        from,
-                                        to,
-                                        amount: transfer_amount,
-                                        ..
-                                    } => {
-                                        // ICP ledger seems to combine transfer and transfer_from
-                                        if from != caller_account_id() {
-                                            Err("Transfer from does not match caller")?
-                                        }
-                                        if to != backend_account_id {
-                                            Err("Transfer to does not match Kong backend")?
-                                        }
-                                        if transfer_amount != amount {
-                                            Err(format!("Invalid transfer amount: rec {:?} exp {:?}", transfer_amount, amount))?
-                                        }
-                                        if block.transaction.created_at_time.timestamp_nanos < ts_start {
-                                            Err("Expired transfer timestamp")?
-                                        }
-                                        return Ok(());
-                                    }
-                                    Operation::Mint { .. } => (),
-                                    Operation::Burn { .. } => (),
-                                    Operation::Approve { .. } => (),
-                                    Operation::TransferFrom { .. } => (), // not supported by ICP ledger
-                                },
-                                None => Err("No transactions in block")?,
-                            }
-                        }
+        StableToken::IC(ic_token) => match ic_token.verification_method() {
+            VerificationMethod::ICRC3GetBlocks => verify_icrc3_get_blocks(ic_token, block_id, amount, ts_start).await,
+            VerificationMethod::GetTransactions => verify_get_transactions(ic_token, block_id, amount, ts_start).await,
+            VerificationMethod::QueryBlocks => verify_query_blocks(ic_token, block_id, amount, ts_start).await,
+            VerificationMethod::Legacy => verify_legacy(ic_token, block_id, amount, ts_start).await,
+        },
+        _ => Err("Verify transfer not supported for this token")?,
+    }
+}
 
-                        Err(format!("Failed to verify {} transfer block id {}", token.symbol(), block_id))?
-                    }
-                    Err(e) => Err(e)?,
+/// use icrc3_get_blocks
+async fn verify_icrc3_get_blocks(token: &ICToken, block_id: &Nat, amount: &Nat, ts_start: u64) -> Result<(), String> {
+    for block in get_icrc3_blocks(token, block_id).await? {
+        let transaction = to_icrc3_transaction(&block.block)?;
+        match transaction.operation {
+            ICRC3Operation::Transfer => {
+                if transaction.from != Some(caller_id()) {
+                    Err("Transfer from does not match caller")?
                 }
-            } else if token_address_with_chain == WUMBO_CANISTER_ID
-                || token_address_with_chain == DAMONIC_CANISTER_ID
-                || token_address_with_chain == CLOWN_CANISTER_ID
-            {
-                // use get_transaction()
-                match ic_cdk::call::<(Nat,), (Option<Transaction1>,)>(
-                    *token.canister_id().ok_or("Invalid principal id")?,
-                    "get_transaction",
-                    (block_id.clone(),),
-                )
-                .await
-                {
-                    Ok(transaction_response) => {
-                        match transaction_response.0 {
-                            Some(transaction) => {
-                                if let Some(transfer) = transaction.transfer {
-                                    let from = transfer.from;
-                                    if from != caller_id() {
-                                        Err("Transfer from does not match caller")?
-                                    }
-                                    let to = transfer.to;
-                                    if to != kong_settings_map::get().kong_backend {
-                                        Err("Transfer to does not match Kong backend")?
-                                    }
-                                    let transfer_amount = transfer.amount;
-                                    if transfer_amount != *amount {
-                                        Err(format!("Invalid transfer amount: rec {:?} exp {:?}", transfer_amount, amount))?
-                                    }
-                                    let timestamp = transaction.timestamp;
-                                    if timestamp < ts_start {
-                                        Err("Expired transfer timestamp")?
-                                    }
-                                    return Ok(()); // success
-                                } else if let Some(_burn) = transaction.burn {
-                                    Err("Invalid burn transaction")?
-                                } else if let Some(_mint) = transaction.mint {
-                                    Err("Invalid mint transaction")?
-                                } else {
-                                    Err(format!("Invalid transaction kind: {}", transaction.kind))?
-                                }
-                            }
-                            None => Err("No transaction found")?,
-                        }
+                if transaction.to != Some(kong_settings_map::get().kong_backend) {
+                    Err("Transfer to does not match Kong backend")?
+                }
+                // make sure spender is None so not an icrc2_transfer_from transaction
+                if transaction.spender.is_some() {
+                    Err("Invalid transfer spender")?
+                }
+                if transaction.amount != *amount {
+                    Err(format!("Invalid transfer amount: rec {:?} exp {:?}", transaction.amount, amount))?
+                }
+                if transaction.ts < ts_start {
+                    Err("Expired transfer timestamp")?
+                }
+                return Ok(()); // success
+            }
+            ICRC3Operation::Burn => {
+                // burn for LP token with remove liquidity
+                if transaction.from != Some(caller_id()) {
+                    Err("Burn does not match caller")?
+                }
+                if transaction.spender.is_some() {
+                    Err("Invalid burn spender")?
+                }
+                if transaction.amount != *amount {
+                    Err(format!("Invalid burn amount: rec {:?} exp {:?}", transaction.amount, amount))?
+                }
+                if transaction.ts < ts_start {
+                    Err("Expired burn timestamp")?
+                }
+                return Ok(()); // success
+            }
+            ICRC3Operation::Mint => (),    // not used
+            ICRC3Operation::Approve => (), // not used
+        }
+    }
+
+    Err(format!("Failed to verify {} transfer block id {}", token.symbol, block_id))?
+}
+
+/// block block_id from the ledger or its archive
+async fn get_icrc3_blocks(token: &ICToken, block_id: &Nat) -> Result<Vec<BlockWithId>, String> {
+    let block_args = vec![GetBlocksRequest {
+        start: block_id.clone(),
+        length: Nat::from(1_u32),
+    }];
+    let (get_blocks_response,) =
+        ic_cdk::call::<(Vec<GetBlocksRequest>,), (GetBlocksResult,)>(token.canister_id, "icrc3_get_blocks", (block_args,))
+            .await
+            .map_err(|e| e.1)?;
+    let mut blocks = get_blocks_response.blocks;
+    // blocks moved to an archive canister are returned with a callback to the archive
+    for archived_blocks in get_blocks_response.archived_blocks {
+        let (archive_response,) = ic_cdk::call::<(Vec<GetBlocksRequest>,), (GetBlocksResult,)>(
+            archived_blocks.callback.canister_id,
+            &archived_blocks.callback.method,
+            (archived_blocks.args,),
+        )
+        .await
+        .map_err(|e| e.1)?;
+        blocks.extend(archive_response.blocks);
+    }
+    Ok(blocks.into_iter().filter(|block| block.id == *block_id).collect())
+}
+
+/// use get_transactions()
+async fn verify_get_transactions(token: &ICToken, block_id: &Nat, amount: &Nat, ts_start: u64) -> Result<(), String> {
+    for transaction in get_transactions(token, block_id).await? {
+        if let Some(transfer) = transaction.transfer {
+            let from = transfer.from;
+            if from != caller_id() {
+                Err("Transfer from does not match caller")?
+            }
+            let to = transfer.to;
+            if to != kong_settings_map::get().kong_backend {
+                Err("Transfer to does not match Kong backend")?
+            }
+            // make sure spender is None so not an icrc2_transfer_from transaction
+            let spender = transfer.spender;
+            if spender.is_some() {
+                Err("Invalid transfer spender")?
+            }
+            let transfer_amount = transfer.amount;
+            if transfer_amount != *amount {
+                Err(format!("Invalid transfer amount: rec {:?} exp {:?}", transfer_amount, amount))?
+            }
+            let timestamp = transaction.timestamp;
+            if timestamp < ts_start {
+                Err("Expired transfer timestamp")?
+            }
+            return Ok(()); // success
+        } else if let Some(burn) = transaction.burn {
+            // burn for LP token with remove liquidity
+            let from = burn.from;
+            if from != caller_id() {
+                Err("Burn does not match caller")?
+            }
+            // burns have to be back to the backend canister
+            // make sure spender is None so not an icrc2_transfer_from transaction
+            let spender = burn.spender;
+            if spender.is_some() {
+                Err("Invalid burn spender")?
+            }
+            let burn_amount = burn.amount;
+            if burn_amount != *amount {
+                Err(format!("Invalid burn amount: rec {:?} exp {:?}", burn_amount, amount))?
+            }
+            let timestamp = transaction.timestamp;
+            if timestamp < ts_start {
+                Err("Expired burn timestamp")?
+            }
+            return Ok(()); // success
+        } else if let Some(_mint) = transaction.mint {
+            // not used
+        } else if let Some(_approve) = transaction.approve {
+            // not used
+        } else {
+            Err(format!("Invalid transaction kind: {}", transaction.kind))?
+        }
+    }
+
+    Err(format!("Failed to verify {} transfer block id {}", token.symbol, block_id))?
+}
+
+/// transaction block_id from the ledger or its archive
+async fn get_transactions(token: &ICToken, block_id: &Nat) -> Result<Vec<Transaction>, String> {
+    let block_args = GetTransactionsRequest {
+        start: block_id.clone(),
+        length: Nat::from(1_u32),
+    };
+    let (get_transactions_response,) =
+        ic_cdk::call::<(GetTransactionsRequest,), (GetTransactionsResponse,)>(token.canister_id, "get_transactions", (block_args.clone(),))
+            .await
+            .map_err(|e| e.1)?;
+    let mut transactions = get_transactions_response.transactions;
+    // transactions moved to an archive canister are returned with a callback to the archive
+    for archived_range in get_transactions_response.archived_transactions {
+        if *block_id < archived_range.start || *block_id >= nat_add(&archived_range.start, &archived_range.length) {
+            continue;
+        }
+        let (transaction_range,) = ic_cdk::call::<(GetTransactionsRequest,), (TransactionRange,)>(
+            archived_range.callback.canister_id,
+            &archived_range.callback.method,
+            (block_args.clone(),),
+        )
+        .await
+        .map_err(|e| e.1)?;
+        transactions.extend(transaction_range.transactions);
+    }
+    Ok(transactions)
+}
+
+/// use query_blocks
+async fn verify_query_blocks(token: &ICToken, block_id: &Nat, amount: &Nat, ts_start: u64) -> Result<(), String> {
+    let backend_account = kong_settings_map::get().kong_backend;
+    let backend_account_id = AccountIdentifier::new(&backend_account.owner, &Subaccount(backend_account.subaccount.unwrap_or([0; 32])));
+    let amount = Tokens::from_e8s(nat_to_u64(amount).ok_or("Invalid ICP amount")?);
+    for block in get_query_blocks(token, block_id).await? {
+        match block.transaction.operation {
+            Some(operation) => match operation {
+                Operation::Transfer {
+                    from,
+                    to,
+                    amount: transfer_amount,
+                    ..
+                } => {
+                    // ICP ledger seems to combine transfer and transfer_from
+                    if from != caller_account_id() {
+                        Err("Transfer from does not match caller")?
                     }
-                    Err(e) => Err(e.1)?,
+                    if to != backend_account_id {
+                        Err("Transfer to does not match Kong backend")?
+                    }
+                    if transfer_amount != amount {
+                        Err(format!("Invalid transfer amount: rec {:?} exp {:?}", transfer_amount, amount))?
+                    }
+                    if block.transaction.created_at_time.timestamp_nanos < ts_start {
+                        Err("Expired transfer timestamp")?
+                    }
+                    return Ok(());
                 }
-            } else {
-                // use get_transactions()
-                let block_args = GetTransactionsRequest {
-                    start: block_id.clone(),
-                    length: Nat::from(1_u32),
-                };
-                match ic_cdk::call::<(GetTransactionsRequest,), (GetTransactionsResponse,)>(
-                    *token.canister_id().ok_or("Invalid principal id")?,
-                    "get_transactions",
-                    (block_args,),
-                )
-                .await
-                {
-                    Ok(get_transactions_response) => {
-                        let transactions = get_transactions_response.0.transactions;
-                        for transaction in transactions.into_iter() {
-                            if let Some(transfer) = transaction.transfer {
-                                let from = transfer.from;
-                                if from != caller_id() {
-                                    Err("Transfer from does not match caller")?
-                                }
-                                let to = transfer.to;
-                                if to != kong_settings_map::get().kong_backend {
-                                    Err("Transfer to does not match Kong backend")?
-                                }
-                                // make sure spender is None so not an icrc2_transfer_from transaction
-                                let spender = transfer.spender;
-                                if spender.is_some() {
-                                    Err("Invalid transfer spender")?
-                                }
-                                let transfer_amount = transfer.amount;
-                                if transfer_amount != *amount {
-                                    Err(format!("Invalid transfer amount: rec {:?} exp {:?}", transfer_amount, amount))?
-                                }
-                                let timestamp = transaction.timestamp;
-                                if timestamp < ts_start {
-                                    Err("Expired transfer timestamp")?
-                                }
-                                return Ok(()); // success
-                            } else if let Some(burn) = transaction.burn {
-                                // burn for LP token with remove liquidity
-                                let from = burn.from;
-                                if from != caller_id() {
-                                    Err("Burn does not match caller")?
-                                }
-                                // burns have to be back to the backend canister
-                                // make sure spender is None so not an icrc2_transfer_from transaction
-                                let spender = burn.spender;
-                                if spender.is_some() {
-                                    Err("Invalid burn spender")?
-                                }
-                                let burn_amount = burn.amount;
-                                if burn_amount != *amount {
-                                    Err(format!("Invalid burn amount: rec {:?} exp {:?}", burn_amount, amount))?
-                                }
-                                let timestamp = transaction.timestamp;
-                                if timestamp < ts_start {
-                                    Err("Expired burn timestamp")?
-                                }
-                                return Ok(()); // success
-                            } else if let Some(_mint) = transaction.mint {
-                                // not used
-                            } else if let Some(_approve) = transaction.approve {
-                                // not used
-                            } else {
-                                Err(format!("Invalid transaction kind: {}", transaction.kind))?
-                            }
-                        }
+                Operation::Mint { .. } => (),
+                Operation::Burn { .. } => (),
+                Operation::Approve { .. } => (),
+                Operation::TransferFrom { .. } => (), // not supported by ICP ledger
+            },
+            None => Err("No transactions in block")?,
+        }
+    }
+
+    Err(format!("Failed to verify {} transfer block id {}", token.symbol, block_id))?
+}
+
+/// block block_id from the ICP ledger or its archive
+async fn get_query_blocks(token: &ICToken, block_id: &Nat) -> Result<Vec<Block>, String> {
+    let block_args = GetBlocksArgs {
+        start: nat_to_u64(block_id).ok_or_else(|| format!("ICP ledger block id {:?} not found", block_id))?,
+        length: 1,
+    };
+    let query_response = query_blocks(token.canister_id, block_args.clone()).await.map_err(|e| e.1)?;
+    let mut blocks = query_response.blocks;
+    // blocks moved to an archive canister are returned with a callback to the archive
+    for archived_range in query_response.archived_blocks {
+        if block_args.start < archived_range.start || block_args.start >= archived_range.start + archived_range.length {
+            continue;
+        }
+        let block_range = query_archived_blocks(&archived_range.callback, block_args.clone())
+            .await
+            .map_err(|e| e.1)?
+            .map_err(|e| format!("Failed to get archived block id {}: {:?}", block_id, e))?;
+        blocks.extend(block_range.blocks);
+    }
+    Ok(blocks)
+}
 
-                        Err(format!("Failed to verify {} transfer block id {}", token.symbol(), block_id))?
+/// use get_transaction()
+async fn verify_legacy(token: &ICToken, block_id: &Nat, amount: &Nat, ts_start: u64) -> Result<(), String> {
+    match ic_cdk::call::<(Nat,), (Option<Transaction1>,)>(token.canister_id, "get_transaction", (block_id.clone(),)).await {
+        Ok(transaction_response) => match transaction_response.0 {
+            Some(transaction) => {
+                if let Some(transfer) = transaction.transfer {
+                    let from = transfer.from;
+                    if from != caller_id() {
+                        Err("Transfer from does not match caller")?
                     }
-                    Err(e) => Err(e.1)?,
+                    let to = transfer.to;
+                    if to != kong_settings_map::get().kong_backend {
+                        Err("Transfer to does not match Kong backend")?
+                    }
+                    let transfer_amount = transfer.amount;
+                    if transfer_amount != *amount {
+                        Err(format!("Invalid transfer amount: rec {:?} exp {:?}", transfer_amount, amount))?
+                    }
+                    let timestamp = transaction.timestamp;
+                    if timestamp < ts_start {
+                        Err("Expired transfer timestamp")?
+                    }
+                    Ok(()) // success
+                } else if let Some(_burn) = transaction.burn {
+                    Err("Invalid burn transaction")?
+                } else if let Some(_mint) = transaction.mint {
+                    Err("Invalid mint transaction")?
+                } else {
+                    Err(format!("Invalid transaction kind: {}", transaction.kind))?
                 }
             }
-        }
-        _ => Err("Verify transfer not supported for this token")?,
+            None => Err("No transaction found")?,
+        },
+        Err(e) => Err(e.1)?,
     }
 }
 
@@ -6,30 +6,50 @@ mod canister;
 mod chains;
 mod claims;
 mod controllers;
+mod flash_loans;
 mod helpers;
+mod history;
 mod ic;
+mod limit_orders;
+mod lp_ledger;
+mod pool_updates;
 mod pools;
+mod protocol_fees;
+mod referrals;
 mod remove_liquidity;
 mod remove_liquidity_amounts;
 mod requests;
 mod send;
 mod stable_claim;
 mod stable_kong_settings;
+mod stable_limit_order;
+mod stable_lp_allowance;
+mod stable_lp_block;
 mod stable_lp_token;
 mod stable_memory;
+mod stable_observation;
 mod stable_pool;
+mod stable_pool_stats;
+mod stable_pool_update;
+mod stable_position;
+mod stable_referral;
 mod stable_request;
+mod stable_tick;
 mod stable_token;
 mod stable_transfer;
 mod stable_tx;
 mod stable_user;
+mod stable_user_volume;
+mod stable_webhook;
 mod swap;
 mod swap_amounts;
 mod tokens;
 mod transfers;
+mod twap;
 mod txs;
 mod user;
 mod user_balances;
+mod webhooks;
 
 pub const APP_NAME: &str = "Kong Swap";
 pub const APP_VERSION: &str = "v0.0.19";
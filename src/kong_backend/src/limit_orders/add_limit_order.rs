@@ -0,0 +1,192 @@
+use candid::Nat;
+use ic_cdk::update;
+use icrc_ledger_types::icrc1::account::Account;
+
+use super::limit_order_args::LimitOrderArgs;
+use super::limit_order_reply::LimitOrderReply;
+use super::limit_order_reply_helpers::to_limit_order_reply;
+
+use crate::helpers::nat_helpers::nat_is_zero;
+use crate::ic::address::Address;
+use crate::ic::address_helpers::get_address;
+use crate::ic::ckusdt::ckusdt_amount;
+use crate::ic::{get_time::get_time, guards::not_in_maintenance_mode, id::caller_id, transfer::icrc2_transfer_from};
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_limit_order::{limit_order_map, stable_limit_order::StableLimitOrder};
+use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{stable_token::StableToken, token::Token, token_map};
+use crate::stable_transfer::{stable_transfer::StableTransfer, transfer_map, tx_id::TxId};
+use crate::stable_user::user_map;
+use crate::swap::swap_amounts::swap_mid_price;
+
+/// place a limit order to swap pay_token for receive_token once the mid price reaches receive_amount / pay_amount
+/// - before calling add_limit_order(), the user must icrc2_approve the pay_amount+gas of pay_token. pay_amount is
+///   escrowed by Kong until the order is filled, cancelled or expires
+/// - open orders are checked on a timer and filled through the pools, in parts if the full amount would exceed the limit price
+#[update(guard = "not_in_maintenance_mode")]
+pub async fn add_limit_order(args: LimitOrderArgs) -> Result<LimitOrderReply, String> {
+    let (user_id, pay_token, receive_token, to_address) = check_arguments(&args).await?;
+    let ts = get_time();
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::LimitOrder(args.clone()), ts));
+
+    let result = match process_add_limit_order(request_id, user_id, &pay_token, &receive_token, &to_address, &args, ts).await {
+        Ok(reply) => {
+            request_map::update_status(request_id, StatusCode::Success, None);
+            Ok(reply)
+        }
+        Err(e) => {
+            request_map::update_status(request_id, StatusCode::Failed, None);
+            Err(e)
+        }
+    };
+    _ = archive_to_kong_data(request_id);
+
+    result
+}
+
+async fn check_arguments(args: &LimitOrderArgs) -> Result<(u32, StableToken, StableToken, Address), String> {
+    let pay_token = token_map::get_by_token(&args.pay_token)?;
+    if pay_token.is_removed() {
+        Err("Pay token is suspended or removed".to_string())?;
+    }
+    if !pay_token.is_icrc2() {
+        Err("Pay token must support ICRC2".to_string())?;
+    }
+
+    let receive_token = token_map::get_by_token(&args.receive_token)?;
+    if receive_token.is_removed() {
+        Err("Receive token is suspended or removed".to_string())?;
+    }
+    if pay_token.token_id() == receive_token.token_id() {
+        Err("Pay token and receive token must be different".to_string())?;
+    }
+
+    if nat_is_zero(&args.pay_amount) {
+        Err("Pay amount is zero".to_string())?;
+    }
+    if nat_is_zero(&args.receive_amount) {
+        Err("Receive amount is zero".to_string())?;
+    }
+    if args.expires_at.is_some_and(|expires_at| expires_at <= get_time()) {
+        Err("Expires_at is in the past".to_string())?;
+    }
+
+    // make sure there are pools to fill the order
+    swap_mid_price(&pay_token, &receive_token)?;
+    check_min_amount(&pay_token, &args.pay_amount)?;
+
+    // use specified address or default to caller's principal id
+    let to_address = match args.receive_address {
+        Some(ref address) => get_address(&receive_token, address)?,
+        None => Address::PrincipalId(caller_id()),
+    };
+
+    // make sure user is registered, if not create a new user
+    let user_id = user_map::insert(None)?;
+
+    let max_open_limit_orders = kong_settings_map::get().max_open_limit_orders;
+    if limit_order_map::count_open_by_user_id(user_id) >= max_open_limit_orders as usize {
+        Err(format!("Maximum of {} open limit orders reached", max_open_limit_orders))?;
+    }
+
+    Ok((user_id, pay_token, receive_token, to_address))
+}
+
+/// pay_amount must be at least limit_order_min_amount in ckUSDT so orders are not too small to fill
+fn check_min_amount(pay_token: &StableToken, pay_amount: &Nat) -> Result<(), String> {
+    let kong_settings = kong_settings_map::get();
+    if nat_is_zero(&kong_settings.limit_order_min_amount) {
+        return Ok(());
+    }
+    if ckusdt_amount(pay_token, pay_amount)? < kong_settings.limit_order_min_amount {
+        Err(format!(
+            "Pay amount is below minimum of {} {}",
+            kong_settings.limit_order_min_amount, kong_settings.ckusdt_symbol
+        ))?
+    }
+    Ok(())
+}
+
+async fn process_add_limit_order(
+    request_id: u64,
+    user_id: u32,
+    pay_token: &StableToken,
+    receive_token: &StableToken,
+    to_address: &Address,
+    args: &LimitOrderArgs,
+    ts: u64,
+) -> Result<LimitOrderReply, String> {
+    let caller_id = caller_id();
+    let kong_backend = kong_settings_map::get().kong_backend;
+
+    request_map::update_status(request_id, StatusCode::Start, None);
+
+    // escrow pay_amount of pay_token
+    transfer_from_token(request_id, &caller_id, pay_token, &args.pay_amount, &kong_backend, ts)
+        .await
+        .map_err(|e| format!("Req #{} failed. Pay token transfer_from failed. {}", request_id, e))?;
+
+    let limit_order = StableLimitOrder::new(
+        user_id,
+        pay_token.token_id(),
+        &args.pay_amount,
+        receive_token.token_id(),
+        &args.receive_amount,
+        to_address,
+        args.expires_at,
+        request_id,
+        ts,
+    );
+    let order_id = limit_order_map::insert(&limit_order);
+    let reply = match limit_order_map::get_by_order_id(order_id) {
+        Some(limit_order) => to_limit_order_reply(&limit_order),
+        None => Err(format!("Req #{} failed. Limit order #{} not found", request_id, order_id))?,
+    };
+    request_map::update_reply(request_id, Reply::LimitOrder(reply.clone()));
+
+    Ok(reply)
+}
+
+async fn transfer_from_token(
+    request_id: u64,
+    from_principal_id: &Account,
+    token: &StableToken,
+    amount: &Nat,
+    to_principal_id: &Account,
+    ts: u64,
+) -> Result<(), String> {
+    let token_id = token.token_id();
+
+    request_map::update_status(request_id, StatusCode::SendPayToken, None);
+
+    match icrc2_transfer_from(token, amount, from_principal_id, to_principal_id).await {
+        Ok(tx_id) => {
+            transfer_map::insert(&StableTransfer {
+                transfer_id: 0,
+                request_id,
+                is_send: true,
+                amount: amount.clone(),
+                token_id,
+                tx_id: TxId::BlockIndex(tx_id),
+                ts,
+            });
+            request_map::update_status(request_id, StatusCode::SendPayTokenSuccess, None);
+            Ok(())
+        }
+        Err(e) => {
+            request_map::update_status(request_id, StatusCode::SendPayTokenFailed, Some(&e));
+            Err(e)
+        }
+    }
+}
+
+fn archive_to_kong_data(request_id: u64) -> Result<(), String> {
+    if !kong_settings_map::get().archive_to_kong_data {
+        return Ok(());
+    }
+
+    let request = request_map::get_by_request_id(request_id).ok_or(format!("Failed to archive. request_id #{} not found", request_id))?;
+    request_map::archive_to_kong_data(&request)?;
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use ic_cdk::update;
+
+use super::limit_order_reply::LimitOrderReply;
+use super::limit_order_reply_helpers::to_limit_order_reply;
+use super::process_limit_orders::close_limit_order;
+
+use crate::ic::{get_time::get_time, guards::not_in_maintenance_mode, id::caller_id};
+use crate::stable_limit_order::limit_order_map;
+use crate::stable_limit_order::stable_limit_order::LimitOrderStatus;
+use crate::stable_user::user_map;
+
+/// cancel an open limit order. the remaining pay token is refunded to the caller as a claim
+#[update(guard = "not_in_maintenance_mode")]
+pub fn cancel_limit_order(order_id: u64) -> Result<LimitOrderReply, String> {
+    let user_id = user_map::get_by_caller()?.ok_or("User not found")?.user_id;
+    let limit_order = limit_order_map::get_by_order_id(order_id)
+        .filter(|limit_order| limit_order.user_id == user_id)
+        .ok_or(format!("Limit order #{} not found", order_id))?;
+    if limit_order.status != LimitOrderStatus::Open {
+        Err(format!("Limit order #{} is {}", order_id, limit_order.status))?
+    }
+
+    let limit_order = close_limit_order(&limit_order, LimitOrderStatus::Cancelled, Some(caller_id()), get_time());
+
+    Ok(to_limit_order_reply(&limit_order))
+}
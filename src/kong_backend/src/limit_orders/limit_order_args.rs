@@ -0,0 +1,15 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the arguments of the `add_limit_order` function.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct LimitOrderArgs {
+    pub pay_token: String,
+    pub pay_amount: Nat,
+    pub receive_token: String,
+    // minimum amount of receive_token for the full pay_amount. limit price = receive_amount / pay_amount
+    pub receive_amount: Nat,
+    pub receive_address: Option<String>,
+    pub expires_at: Option<u64>, // timestamp in nanoseconds, no expiry if not specified
+}
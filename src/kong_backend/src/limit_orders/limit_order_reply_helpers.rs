@@ -0,0 +1,59 @@
+use super::limit_order_reply::LimitOrderReply;
+
+use crate::helpers::limit_order_helpers::limit_price;
+use crate::stable_limit_order::stable_limit_order::StableLimitOrder;
+use crate::stable_token::token::Token;
+use crate::stable_token::token_map;
+
+/// limit price of the order in receive token per pay token
+pub fn to_limit_price(limit_order: &StableLimitOrder) -> Option<f64> {
+    let pay_token = token_map::get_by_token_id(limit_order.pay_token_id)?;
+    let receive_token = token_map::get_by_token_id(limit_order.receive_token_id)?;
+    limit_price(
+        pay_token.decimals(),
+        &limit_order.pay_amount,
+        receive_token.decimals(),
+        &limit_order.receive_amount,
+    )
+}
+
+pub fn to_limit_order_reply(limit_order: &StableLimitOrder) -> LimitOrderReply {
+    let (pay_chain, pay_address, pay_symbol) = match token_map::get_by_token_id(limit_order.pay_token_id) {
+        Some(token) => (token.chain(), token.address(), token.symbol()),
+        None => (
+            "Pay chain not found".to_string(),
+            "Pay address not found".to_string(),
+            "Pay symbol not found".to_string(),
+        ),
+    };
+    let (receive_chain, receive_address, receive_symbol) = match token_map::get_by_token_id(limit_order.receive_token_id) {
+        Some(token) => (token.chain(), token.address(), token.symbol()),
+        None => (
+            "Receive chain not found".to_string(),
+            "Receive address not found".to_string(),
+            "Receive symbol not found".to_string(),
+        ),
+    };
+
+    LimitOrderReply {
+        order_id: limit_order.order_id,
+        request_id: limit_order.request_id,
+        status: limit_order.status.to_string(),
+        pay_chain,
+        pay_address,
+        pay_symbol,
+        pay_amount: limit_order.pay_amount.clone(),
+        remaining_pay_amount: limit_order.remaining_pay_amount.clone(),
+        receive_chain,
+        receive_address,
+        receive_symbol,
+        receive_amount: limit_order.receive_amount.clone(),
+        received_amount: limit_order.received_amount.clone(),
+        limit_price: to_limit_price(limit_order).unwrap_or(0_f64),
+        to_address: limit_order.to_address.to_string(),
+        expires_at: limit_order.expires_at,
+        fill_request_ids: limit_order.fill_request_ids.clone(),
+        claim_id: limit_order.claim_id,
+        ts: limit_order.ts,
+    }
+}
@@ -0,0 +1,20 @@
+use ic_cdk::query;
+
+use super::limit_order_reply::LimitOrderReply;
+use super::limit_order_reply_helpers::to_limit_order_reply;
+
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_limit_order::limit_order_map;
+use crate::stable_user::user_map;
+
+/// limit orders of a user, most recent first
+#[query(guard = "not_in_maintenance_mode")]
+pub async fn limit_orders(principal_id: String) -> Result<Vec<LimitOrderReply>, String> {
+    let user_id = user_map::get_by_principal_id(&principal_id)
+        .ok()
+        .flatten()
+        .ok_or("User not found")?
+        .user_id;
+
+    Ok(limit_order_map::get_by_user_id(user_id).iter().map(to_limit_order_reply).collect())
+}
@@ -0,0 +1,238 @@
+use candid::{Nat, Principal};
+use icrc_ledger_types::icrc1::account::Account;
+
+use super::limit_order_reply_helpers::to_limit_price;
+
+use crate::helpers::limit_order_helpers::{fill_amounts, fill_receive_amount};
+use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_subtract, nat_zero};
+use crate::ic::ckusdt::ckusdt_amount;
+use crate::ic::{address::Address, get_time::get_time, guards::not_in_maintenance_mode, logging::error_log};
+use crate::stable_claim::{claim_map, stable_claim::StableClaim};
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_limit_order::limit_order_map;
+use crate::stable_limit_order::stable_limit_order::{LimitOrderStatus, StableLimitOrder};
+use crate::stable_request::{request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{stable_token::StableToken, token::Token, token_map};
+use crate::stable_user::user_map;
+use crate::swap::archive_to_kong_data::archive_to_kong_data;
+use crate::swap::calculate_amounts::calculate_amounts;
+use crate::swap::send_receive_token::send_receive_token;
+use crate::swap::swap_amounts::{swap_mid_price, user_fee_level};
+use crate::swap::swap_args::SwapArgs;
+use crate::swap::update_liquidity_pool::update_liquidity_pool;
+
+// number of times the fill amount is halved when the remaining amount can not be filled at the limit price
+const MAX_PARTIAL_FILL_HALVINGS: u32 = 4;
+// receive_amount of the fill already enforces the limit price, so slippage is not checked
+const FILL_MAX_SLIPPAGE: f64 = 100.0;
+
+/// fill, or expire open limit orders
+pub async fn process_limit_orders() {
+    if not_in_maintenance_mode().is_err() {
+        return;
+    }
+
+    let ts = get_time();
+
+    for limit_order in limit_order_map::get_open() {
+        // refresh with the latest state as the order could have been cancelled while awaiting a previous fill
+        let limit_order = match limit_order_map::get_by_order_id(limit_order.order_id) {
+            Some(limit_order) if limit_order.status == LimitOrderStatus::Open => limit_order,
+            _ => continue,
+        };
+
+        if limit_order.expires_at.is_some_and(|expires_at| expires_at <= ts) {
+            close_limit_order(&limit_order, LimitOrderStatus::Expired, None, ts);
+            continue;
+        }
+
+        fill_limit_order(&limit_order, ts).await;
+    }
+}
+
+/// cancel or expire a limit order
+/// the remaining pay token is refunded through a claim to to_principal_id or the principal id of the user
+pub fn close_limit_order(
+    limit_order: &StableLimitOrder,
+    status: LimitOrderStatus,
+    to_principal_id: Option<Account>,
+    ts: u64,
+) -> StableLimitOrder {
+    let to_principal_id = to_principal_id.or_else(|| {
+        user_map::get_by_user_id(limit_order.user_id)
+            .and_then(|user| Principal::from_text(user.principal_id).ok())
+            .map(Account::from)
+    });
+
+    let claim_id = if nat_is_zero(&limit_order.remaining_pay_amount) {
+        None
+    } else {
+        let claim = StableClaim::new(
+            limit_order.user_id,
+            limit_order.pay_token_id,
+            &limit_order.remaining_pay_amount,
+            Some(limit_order.request_id),
+            to_principal_id.map(Address::PrincipalId),
+            ts,
+        );
+        Some(claim_map::insert(&claim))
+    };
+
+    let closed_limit_order = StableLimitOrder {
+        status,
+        claim_id,
+        ts,
+        ..limit_order.clone()
+    };
+    limit_order_map::update(&closed_limit_order);
+    closed_limit_order
+}
+
+/// fill the limit order if the mid price has reached the limit price
+/// if the remaining amount would receive less than the limit price, try to fill smaller parts of it
+async fn fill_limit_order(limit_order: &StableLimitOrder, ts: u64) {
+    let Some(pay_token) = token_map::get_by_token_id(limit_order.pay_token_id) else {
+        return;
+    };
+    let Some(receive_token) = token_map::get_by_token_id(limit_order.receive_token_id) else {
+        return;
+    };
+    if pay_token.is_removed() || receive_token.is_removed() {
+        return;
+    }
+
+    let Some(limit_price) = to_limit_price(limit_order) else {
+        return;
+    };
+    match swap_mid_price(&pay_token, &receive_token) {
+        Ok(mid_price) if mid_price >= limit_price => (),
+        _ => return,
+    }
+
+    // fills are swaps of the order owner, not of the caller of the timer
+    let user_fee_level = user_fee_level(limit_order.user_id);
+    let min_amount = kong_settings_map::get().limit_order_min_amount;
+    for fill_amount in fill_amounts(&limit_order.remaining_pay_amount, MAX_PARTIAL_FILL_HALVINGS) {
+        // partial fills below the minimum are not worth the swap. the remaining amount is always tried
+        if fill_amount != limit_order.remaining_pay_amount
+            && !nat_is_zero(&min_amount)
+            && ckusdt_amount(&pay_token, &fill_amount).map_or(true, |amount| amount < min_amount)
+        {
+            return;
+        }
+        // receive_amount for the fill at the limit price
+        let Some(min_receive_amount) = fill_receive_amount(&limit_order.pay_amount, &limit_order.receive_amount, &fill_amount) else {
+            return;
+        };
+        if calculate_amounts(
+            &pay_token,
+            &fill_amount,
+            &receive_token,
+            Some(&min_receive_amount),
+            FILL_MAX_SLIPPAGE,
+            false,
+            user_fee_level,
+        )
+        .is_ok()
+        {
+            process_fill(
+                limit_order,
+                &pay_token,
+                &fill_amount,
+                &receive_token,
+                &min_receive_amount,
+                user_fee_level,
+                ts,
+            )
+            .await;
+            return;
+        }
+    }
+}
+
+/// swap fill_amount of the escrowed pay token through the pools
+async fn process_fill(
+    limit_order: &StableLimitOrder,
+    pay_token: &StableToken,
+    fill_amount: &Nat,
+    receive_token: &StableToken,
+    min_receive_amount: &Nat,
+    user_fee_level: u8,
+    ts: u64,
+) {
+    let user_id = limit_order.user_id;
+    let args = SwapArgs {
+        pay_token: pay_token.address_with_chain(),
+        pay_amount: fill_amount.clone(),
+        pay_tx_id: None,
+        receive_token: receive_token.address_with_chain(),
+        receive_amount: Some(min_receive_amount.clone()),
+        receive_address: Some(limit_order.to_address.to_string()),
+        max_slippage: Some(FILL_MAX_SLIPPAGE),
+        referred_by: None,
+        exact_output: None,
+        deadline: None,
+    };
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::Swap(args), ts));
+
+    request_map::update_status(request_id, StatusCode::Start, None);
+
+    // pay token is already escrowed by Kong so the swap only needs to update the pools
+    let (receive_amount, mid_price, price, slippage, swaps) = match update_liquidity_pool(
+        request_id,
+        pay_token,
+        fill_amount,
+        receive_token,
+        Some(min_receive_amount),
+        FILL_MAX_SLIPPAGE,
+        false,
+        user_fee_level,
+    ) {
+        Ok(amounts) => amounts,
+        Err(e) => {
+            error_log(&format!(
+                "Req #{}. Failed to fill limit order #{}. {}",
+                request_id, limit_order.order_id, e
+            ));
+            request_map::update_status(request_id, StatusCode::Failed, None);
+            let _ = archive_to_kong_data(request_id);
+            return;
+        }
+    };
+    request_map::update_status(request_id, StatusCode::SwapSuccess, None);
+
+    // update the order before sending the receive token so the filled amount can not be used again
+    if let Some(mut limit_order) = limit_order_map::get_by_order_id(limit_order.order_id) {
+        limit_order.remaining_pay_amount = nat_subtract(&limit_order.remaining_pay_amount, fill_amount).unwrap_or(nat_zero());
+        limit_order.received_amount = nat_add(&limit_order.received_amount, &receive_amount);
+        limit_order.fill_request_ids.push(request_id);
+        if nat_is_zero(&limit_order.remaining_pay_amount) {
+            limit_order.status = LimitOrderStatus::Filled;
+        }
+        limit_order.ts = ts;
+        limit_order_map::update(&limit_order);
+    }
+
+    let mut transfer_ids = Vec::new();
+    send_receive_token(
+        request_id,
+        user_id,
+        pay_token,
+        fill_amount,
+        receive_token,
+        &receive_amount,
+        &limit_order.to_address,
+        &mut transfer_ids,
+        &mut Vec::new(),
+        mid_price,
+        price,
+        slippage,
+        &swaps,
+        None,
+        ts,
+    )
+    .await;
+
+    request_map::update_status(request_id, StatusCode::Success, None);
+    let _ = archive_to_kong_data(request_id);
+}
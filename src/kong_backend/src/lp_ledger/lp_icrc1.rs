@@ -0,0 +1,110 @@
+use candid::{CandidType, Nat};
+use ic_cdk::{query, update};
+use icrc_ledger_types::icrc::generic_metadata_value::MetadataValue;
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::{TransferArg, TransferError};
+use serde::{Deserialize, Serialize};
+
+use super::lp_ledger_helpers::{account_user_id, get_lp_token};
+use super::lp_transfer::transfer;
+
+use crate::helpers::nat_helpers::nat_zero;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::ic::id::caller_id;
+use crate::stable_lp_token::lp_token_map;
+
+// ICRC-1 interface of LP tokens
+// a multi-token ledger keyed by the token_id of the LP token. only the default subaccount is supported
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct LPStandardRecord {
+    pub name: String,
+    pub url: String,
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_name(token_id: u32) -> Result<String, String> {
+    Ok(get_lp_token(token_id)?.name())
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_symbol(token_id: u32) -> Result<String, String> {
+    Ok(get_lp_token(token_id)?.symbol)
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_decimals(token_id: u32) -> Result<u8, String> {
+    Ok(get_lp_token(token_id)?.decimals)
+}
+
+/// LP tokens have no fee
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_fee(token_id: u32) -> Result<Nat, String> {
+    get_lp_token(token_id)?;
+    Ok(nat_zero())
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_metadata(token_id: u32) -> Result<Vec<(String, MetadataValue)>, String> {
+    let lp_token = get_lp_token(token_id)?;
+    Ok(vec![
+        MetadataValue::entry("icrc1:name", lp_token.name()),
+        MetadataValue::entry("icrc1:symbol", lp_token.symbol.clone()),
+        MetadataValue::entry("icrc1:decimals", Nat::from(lp_token.decimals)),
+        MetadataValue::entry("icrc1:fee", nat_zero()),
+    ])
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_total_supply(token_id: u32) -> Result<Nat, String> {
+    get_lp_token(token_id)?;
+    Ok(lp_token_map::get_total_supply(token_id))
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_balance_of(token_id: u32, account: Account) -> Result<Nat, String> {
+    get_lp_token(token_id)?;
+    Ok(match account_user_id(&account) {
+        Ok(Some(user_id)) => lp_token_map::get_by_token_id_by_user_id(token_id, user_id).map_or_else(nat_zero, |lp_token| lp_token.amount),
+        _ => nat_zero(),
+    })
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_supported_standards() -> Vec<LPStandardRecord> {
+    vec![
+        LPStandardRecord {
+            name: "ICRC-1".to_string(),
+            url: "https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-1".to_string(),
+        },
+        LPStandardRecord {
+            name: "ICRC-2".to_string(),
+            url: "https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-2".to_string(),
+        },
+        LPStandardRecord {
+            name: "ICRC-3".to_string(),
+            url: "https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-3".to_string(),
+        },
+    ]
+}
+
+/// transfer LP tokens of the caller
+#[update(guard = "not_in_maintenance_mode")]
+fn lp_icrc1_transfer(token_id: u32, args: TransferArg) -> Result<Nat, TransferError> {
+    let from = Account {
+        owner: caller_id().owner,
+        subaccount: args.from_subaccount,
+    };
+    transfer(
+        token_id,
+        &from,
+        &args.to,
+        None,
+        &args.amount,
+        args.fee.as_ref(),
+        args.memo.as_ref(),
+        args.created_at_time,
+    )
+    .map(Nat::from)
+    .map_err(TransferError::from)
+}
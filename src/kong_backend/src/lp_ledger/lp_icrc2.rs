@@ -0,0 +1,132 @@
+use candid::Nat;
+use ic_cdk::{query, update};
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc2::allowance::{Allowance, AllowanceArgs};
+use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
+use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
+
+use super::lp_ledger_error::LPLedgerError;
+use super::lp_ledger_helpers::{account_user_id, check_default_subaccount, get_lp_token};
+use super::lp_transfer::transfer;
+
+use crate::helpers::lp_transfer_helpers::{check_transaction, dedup_since, is_duplicate_approve};
+use crate::helpers::nat_helpers::{nat_is_zero, nat_zero};
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::ic::id::caller;
+use crate::stable_lp_allowance::{lp_allowance_map, stable_lp_allowance::StableLPAllowance};
+use crate::stable_lp_block::lp_block_map;
+use crate::stable_lp_block::stable_lp_block::{LPBlockType, StableLPBlock};
+use crate::stable_user::user_map;
+
+// ICRC-2 interface of LP tokens
+
+/// approve spender to transfer up to amount of the caller's LP tokens
+#[update(guard = "not_in_maintenance_mode")]
+fn lp_icrc2_approve(token_id: u32, args: ApproveArgs) -> Result<Nat, ApproveError> {
+    approve(token_id, &args).map(Nat::from).map_err(ApproveError::from)
+}
+
+fn approve(token_id: u32, args: &ApproveArgs) -> Result<u64, LPLedgerError> {
+    let ts = get_time();
+    get_lp_token(token_id)?;
+    check_transaction(args.fee.as_ref(), args.memo.as_ref(), args.created_at_time, ts)?;
+    if args.expires_at.is_some_and(|expires_at| expires_at <= ts) {
+        Err(LPLedgerError::Expired { ledger_time: ts })?
+    }
+    let owner = Account {
+        owner: caller(),
+        subaccount: args.from_subaccount,
+    };
+    check_default_subaccount(&owner)?;
+    if args.spender.owner == owner.owner {
+        Err(LPLedgerError::GenericError("Self approval is not allowed".to_string()))?
+    }
+
+    let block = StableLPBlock {
+        expected_allowance: args.expected_allowance.clone(),
+        expires_at: args.expires_at,
+        memo: args.memo.clone(),
+        created_at_time: args.created_at_time,
+        ..StableLPBlock::new(
+            token_id,
+            LPBlockType::Approve,
+            Some(owner),
+            None,
+            Some(args.spender),
+            &args.amount,
+            ts,
+        )
+    };
+
+    // deduplicate transactions with created_at_time
+    if args.created_at_time.is_some() {
+        if let Some(duplicate_of) = lp_block_map::find_duplicate(token_id, dedup_since(ts), |v| is_duplicate_approve(v, &block)) {
+            Err(LPLedgerError::Duplicate { duplicate_of })?
+        }
+    }
+
+    // make sure user is registered, if not create a new user
+    let owner_user_id = user_map::insert(None)?;
+    let current_allowance = lp_allowance_map::get(token_id, owner_user_id, &args.spender, ts).map_or_else(nat_zero, |v| v.amount);
+    if let Some(expected_allowance) = &args.expected_allowance {
+        if *expected_allowance != current_allowance {
+            Err(LPLedgerError::AllowanceChanged { current_allowance })?
+        }
+    }
+
+    if nat_is_zero(&args.amount) {
+        lp_allowance_map::remove(token_id, owner_user_id, &args.spender);
+    } else {
+        lp_allowance_map::update(&StableLPAllowance {
+            token_id,
+            owner_user_id,
+            spender: args.spender,
+            amount: args.amount.clone(),
+            expires_at: args.expires_at,
+            ts,
+        });
+    }
+
+    Ok(lp_block_map::insert(&block))
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc2_allowance(token_id: u32, args: AllowanceArgs) -> Result<Allowance, String> {
+    get_lp_token(token_id)?;
+    let allowance = match account_user_id(&args.account) {
+        Ok(Some(owner_user_id)) => lp_allowance_map::get(token_id, owner_user_id, &args.spender, get_time()),
+        _ => None,
+    };
+    Ok(match allowance {
+        Some(allowance) => Allowance {
+            allowance: allowance.amount,
+            expires_at: allowance.expires_at,
+        },
+        None => Allowance {
+            allowance: nat_zero(),
+            expires_at: None,
+        },
+    })
+}
+
+/// transfer LP tokens of args.from using the caller's allowance
+#[update(guard = "not_in_maintenance_mode")]
+fn lp_icrc2_transfer_from(token_id: u32, args: TransferFromArgs) -> Result<Nat, TransferFromError> {
+    let spender = Account {
+        owner: caller(),
+        subaccount: args.spender_subaccount,
+    };
+    transfer(
+        token_id,
+        &args.from,
+        &args.to,
+        Some(&spender),
+        &args.amount,
+        args.fee.as_ref(),
+        args.memo.as_ref(),
+        args.created_at_time,
+    )
+    .map(Nat::from)
+    .map_err(TransferFromError::from)
+}
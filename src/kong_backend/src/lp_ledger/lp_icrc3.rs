@@ -0,0 +1,62 @@
+use candid::Nat;
+use ic_cdk::query;
+use icrc_ledger_types::icrc3::blocks::{BlockWithId, GetBlocksRequest, GetBlocksResult, SupportedBlockType};
+
+use super::lp_ledger_helpers::get_lp_token;
+
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_lp_block::lp_block_map;
+use crate::stable_lp_block::stable_lp_block::LPBlockType;
+
+// ICRC-3 interface of LP tokens. blocks are not archived
+
+const MAX_BLOCKS: u64 = 1_000;
+
+/// blocks of the LP token's log. up to 1,000 blocks are returned per call
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc3_get_blocks(token_id: u32, args: Vec<GetBlocksRequest>) -> Result<GetBlocksResult, String> {
+    get_lp_token(token_id)?;
+
+    let mut remaining = MAX_BLOCKS;
+    let mut blocks = Vec::new();
+    for request in args {
+        let (start, length) = request.as_start_and_length()?;
+        let length = length.min(remaining);
+        let range = lp_block_map::get_range(token_id, start, length);
+        remaining -= range.len() as u64;
+        blocks.extend(range.iter().map(|block| BlockWithId {
+            id: Nat::from(block.block_id),
+            block: block.to_icrc3_value(),
+        }));
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    Ok(GetBlocksResult {
+        log_length: Nat::from(lp_block_map::get_log_length(token_id)),
+        blocks,
+        archived_blocks: Vec::new(),
+    })
+}
+
+#[query(guard = "not_in_maintenance_mode")]
+fn lp_icrc3_supported_block_types() -> Vec<SupportedBlockType> {
+    [
+        LPBlockType::Mint,
+        LPBlockType::Burn,
+        LPBlockType::Transfer,
+        LPBlockType::Approve,
+        LPBlockType::TransferFrom,
+    ]
+    .iter()
+    .map(|block_type| SupportedBlockType {
+        block_type: block_type.btype().to_string(),
+        url: if block_type.btype().starts_with('1') {
+            "https://github.com/dfinity/ICRC-1/blob/main/standards/ICRC-1/README.md".to_string()
+        } else {
+            "https://github.com/dfinity/ICRC-1/blob/main/standards/ICRC-2/README.md".to_string()
+        },
+    })
+    .collect()
+}
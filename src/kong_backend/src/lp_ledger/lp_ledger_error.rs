@@ -0,0 +1,103 @@
+use candid::Nat;
+use icrc_ledger_types::icrc1::transfer::TransferError;
+use icrc_ledger_types::icrc2::approve::ApproveError;
+use icrc_ledger_types::icrc2::transfer_from::TransferFromError;
+
+use crate::helpers::nat_helpers::nat_zero;
+
+/// errors of the LP token ledger. converted into the ICRC error type of each endpoint
+#[derive(Debug)]
+pub enum LPLedgerError {
+    BadFee,
+    InsufficientFunds { balance: Nat },
+    InsufficientAllowance { allowance: Nat },
+    AllowanceChanged { current_allowance: Nat },
+    Expired { ledger_time: u64 },
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: u64 },
+    GenericError(String),
+}
+
+impl From<String> for LPLedgerError {
+    fn from(message: String) -> Self {
+        LPLedgerError::GenericError(message)
+    }
+}
+
+fn generic_error(message: String) -> (Nat, String) {
+    (nat_zero(), message)
+}
+
+impl From<LPLedgerError> for TransferError {
+    fn from(e: LPLedgerError) -> Self {
+        match e {
+            LPLedgerError::BadFee => TransferError::BadFee { expected_fee: nat_zero() },
+            LPLedgerError::InsufficientFunds { balance } => TransferError::InsufficientFunds { balance },
+            LPLedgerError::TooOld => TransferError::TooOld,
+            LPLedgerError::CreatedInFuture { ledger_time } => TransferError::CreatedInFuture { ledger_time },
+            LPLedgerError::Duplicate { duplicate_of } => TransferError::Duplicate {
+                duplicate_of: Nat::from(duplicate_of),
+            },
+            e => {
+                let (error_code, message) = generic_error(e.to_string());
+                TransferError::GenericError { error_code, message }
+            }
+        }
+    }
+}
+
+impl From<LPLedgerError> for TransferFromError {
+    fn from(e: LPLedgerError) -> Self {
+        match e {
+            LPLedgerError::BadFee => TransferFromError::BadFee { expected_fee: nat_zero() },
+            LPLedgerError::InsufficientFunds { balance } => TransferFromError::InsufficientFunds { balance },
+            LPLedgerError::InsufficientAllowance { allowance } => TransferFromError::InsufficientAllowance { allowance },
+            LPLedgerError::TooOld => TransferFromError::TooOld,
+            LPLedgerError::CreatedInFuture { ledger_time } => TransferFromError::CreatedInFuture { ledger_time },
+            LPLedgerError::Duplicate { duplicate_of } => TransferFromError::Duplicate {
+                duplicate_of: Nat::from(duplicate_of),
+            },
+            e => {
+                let (error_code, message) = generic_error(e.to_string());
+                TransferFromError::GenericError { error_code, message }
+            }
+        }
+    }
+}
+
+impl From<LPLedgerError> for ApproveError {
+    fn from(e: LPLedgerError) -> Self {
+        match e {
+            LPLedgerError::BadFee => ApproveError::BadFee { expected_fee: nat_zero() },
+            LPLedgerError::InsufficientFunds { balance } => ApproveError::InsufficientFunds { balance },
+            LPLedgerError::AllowanceChanged { current_allowance } => ApproveError::AllowanceChanged { current_allowance },
+            LPLedgerError::Expired { ledger_time } => ApproveError::Expired { ledger_time },
+            LPLedgerError::TooOld => ApproveError::TooOld,
+            LPLedgerError::CreatedInFuture { ledger_time } => ApproveError::CreatedInFuture { ledger_time },
+            LPLedgerError::Duplicate { duplicate_of } => ApproveError::Duplicate {
+                duplicate_of: Nat::from(duplicate_of),
+            },
+            e => {
+                let (error_code, message) = generic_error(e.to_string());
+                ApproveError::GenericError { error_code, message }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for LPLedgerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LPLedgerError::BadFee => write!(f, "LP tokens have no fee"),
+            LPLedgerError::InsufficientFunds { balance } => write!(f, "Insufficient LP token balance {}", balance),
+            LPLedgerError::InsufficientAllowance { allowance } => write!(f, "Insufficient allowance {}", allowance),
+            LPLedgerError::AllowanceChanged { current_allowance } => write!(f, "Allowance changed to {}", current_allowance),
+            LPLedgerError::Expired { ledger_time } => write!(f, "Approval expired. Ledger time {}", ledger_time),
+            LPLedgerError::TooOld => write!(f, "Transaction too old"),
+            LPLedgerError::CreatedInFuture { ledger_time } => write!(f, "Transaction created in the future. Ledger time {}", ledger_time),
+            LPLedgerError::Duplicate { duplicate_of } => write!(f, "Duplicate of block {}", duplicate_of),
+            LPLedgerError::GenericError(message) => write!(f, "{}", message),
+        }
+    }
+}
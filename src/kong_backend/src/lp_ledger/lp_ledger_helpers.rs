@@ -0,0 +1,27 @@
+use icrc_ledger_types::icrc1::account::Account;
+
+use crate::stable_token::lp_token::LPToken;
+use crate::stable_token::stable_token::StableToken;
+use crate::stable_token::token_map;
+use crate::stable_user::principal_id_map;
+
+pub fn get_lp_token(token_id: u32) -> Result<LPToken, String> {
+    match token_map::get_by_token_id(token_id) {
+        Some(StableToken::LP(lp_token)) => Ok(lp_token),
+        _ => Err(format!("LP token #{} not found", token_id)),
+    }
+}
+
+/// LP tokens are held by principal so only the default subaccount is supported
+pub fn check_default_subaccount(account: &Account) -> Result<(), String> {
+    if account.subaccount.is_some_and(|subaccount| subaccount != [0; 32]) {
+        Err("Only the default subaccount is supported for LP tokens".to_string())?
+    }
+    Ok(())
+}
+
+/// user_id of the holder of account. None if the principal is not a user
+pub fn account_user_id(account: &Account) -> Result<Option<u32>, String> {
+    check_default_subaccount(account)?;
+    Ok(principal_id_map::get_user_id(&account.owner.to_text()))
+}
@@ -0,0 +1,86 @@
+use candid::Nat;
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::Memo;
+
+use super::lp_ledger_error::LPLedgerError;
+use super::lp_ledger_helpers::{account_user_id, check_default_subaccount, get_lp_token};
+
+use crate::helpers::lp_transfer_helpers::{
+    check_amount, check_transaction, dedup_since, is_duplicate_transfer, move_balance, spend_allowance,
+};
+use crate::helpers::nat_helpers::nat_zero;
+use crate::ic::get_time::get_time;
+use crate::stable_lp_allowance::lp_allowance_map;
+use crate::stable_lp_block::lp_block_map;
+use crate::stable_lp_block::stable_lp_block::{LPBlockType, StableLPBlock};
+use crate::stable_lp_token::lp_token_map;
+use crate::stable_lp_token::transfer::{credit_by_user_id, debit_by_user_id};
+use crate::stable_user::user_map;
+
+/// transfer amount of LP token from one account to another
+/// spender transfers on behalf of from using its allowance. None if from is the caller
+///
+/// returns the block_id of the transfer
+#[allow(clippy::too_many_arguments)]
+pub fn transfer(
+    token_id: u32,
+    from: &Account,
+    to: &Account,
+    spender: Option<&Account>,
+    amount: &Nat,
+    fee: Option<&Nat>,
+    memo: Option<&Memo>,
+    created_at_time: Option<u64>,
+) -> Result<u64, LPLedgerError> {
+    let ts = get_time();
+    get_lp_token(token_id)?;
+    check_transaction(fee, memo, created_at_time, ts)?;
+    check_default_subaccount(to)?;
+    check_amount(amount)?;
+
+    let block_type = if spender.is_some() {
+        LPBlockType::TransferFrom
+    } else {
+        LPBlockType::Transfer
+    };
+    let block = StableLPBlock {
+        memo: memo.cloned(),
+        created_at_time,
+        ..StableLPBlock::new(token_id, block_type, Some(*from), Some(*to), spender.copied(), amount, ts)
+    };
+
+    // deduplicate transactions with created_at_time
+    if created_at_time.is_some() {
+        if let Some(duplicate_of) = lp_block_map::find_duplicate(token_id, dedup_since(ts), |v| is_duplicate_transfer(v, &block)) {
+            Err(LPLedgerError::Duplicate { duplicate_of })?
+        }
+    }
+
+    let from_user_id = account_user_id(from)?.ok_or(LPLedgerError::InsufficientFunds { balance: nat_zero() })?;
+    let balance = lp_token_map::get_by_token_id_by_user_id(token_id, from_user_id).map_or_else(nat_zero, |lp_token| lp_token.amount);
+    if balance < *amount {
+        Err(LPLedgerError::InsufficientFunds { balance })?
+    }
+
+    // spend the allowance, unless the spender is the owner
+    let allowance = match spender {
+        Some(spender) if spender != from => {
+            let allowance = lp_allowance_map::get(token_id, from_user_id, spender, ts);
+            Some(spend_allowance(allowance.as_ref(), amount, ts)?)
+        }
+        _ => None,
+    };
+
+    // the receiver does not need to have interacted with Kong before. only created once the debit succeeded
+    move_balance(
+        from_user_id,
+        |user_id| debit_by_user_id(token_id, user_id, amount, ts).map(|_| ()),
+        || user_map::insert_by_principal_id(&to.owner.to_text()),
+        |user_id| credit_by_user_id(token_id, user_id, amount, ts),
+    )?;
+    if let Some(allowance) = allowance {
+        lp_allowance_map::update(&allowance);
+    }
+
+    Ok(lp_block_map::insert(&block))
+}
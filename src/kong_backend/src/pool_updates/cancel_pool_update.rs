@@ -0,0 +1,31 @@
+use ic_cdk::update;
+
+use super::pool_update_reply::PoolUpdateReply;
+use super::pool_update_reply_helpers::to_pool_update_reply;
+
+use crate::ic::get_time::get_time;
+use crate::ic::guards::caller_is_kingkong;
+use crate::ic::id::caller_principal_id;
+use crate::ic::logging::info_log;
+use crate::stable_pool_update::pool_update_map;
+use crate::stable_pool_update::stable_pool_update::{PoolUpdateStatus, StablePoolUpdate};
+
+/// cancel a scheduled pool update
+#[update(hidden = true, guard = "caller_is_kingkong")]
+fn cancel_pool_update(pool_update_id: u64) -> Result<PoolUpdateReply, String> {
+    let pool_update = pool_update_map::get_by_pool_update_id(pool_update_id).ok_or(format!("Pool update #{} not found", pool_update_id))?;
+    if pool_update.status != PoolUpdateStatus::Scheduled {
+        Err(format!("Pool update #{} is {}", pool_update_id, pool_update.status))?
+    }
+
+    let pool_update = StablePoolUpdate {
+        status: PoolUpdateStatus::Cancelled,
+        status_ts: Some(get_time()),
+        message: Some(format!("Cancelled by {}", caller_principal_id())),
+        ..pool_update
+    };
+    pool_update_map::update(&pool_update);
+    info_log(&format!("Pool update #{} cancelled", pool_update_id));
+
+    Ok(to_pool_update_reply(&pool_update))
+}
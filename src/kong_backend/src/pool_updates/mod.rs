@@ -0,0 +1,8 @@
+pub mod cancel_pool_update;
+pub mod pool_update_reply;
+pub mod pool_update_reply_helpers;
+#[allow(clippy::module_inception)]
+pub mod pool_updates;
+pub mod process_pool_updates;
+pub mod update_pool_parameters;
+pub mod update_pool_parameters_args;
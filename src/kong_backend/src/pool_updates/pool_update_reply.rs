@@ -0,0 +1,22 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use crate::stable_pool::pool_curve::PoolCurve;
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct PoolUpdateReply {
+    pub pool_update_id: u64,
+    pub symbol: String,
+    pub status: String,
+    pub lp_fee_bps: u8,
+    pub kong_fee_bps: u8,
+    pub curve: PoolCurve,
+    pub prev_lp_fee_bps: u8,
+    pub prev_kong_fee_bps: u8,
+    pub prev_curve: PoolCurve,
+    pub effective_ts: u64,
+    pub status_ts: Option<u64>,
+    pub message: Option<String>,
+    pub caller: String,
+    pub ts: u64,
+}
@@ -0,0 +1,24 @@
+use super::pool_update_reply::PoolUpdateReply;
+
+use crate::stable_pool::pool_map;
+use crate::stable_pool_update::stable_pool_update::StablePoolUpdate;
+
+pub fn to_pool_update_reply(pool_update: &StablePoolUpdate) -> PoolUpdateReply {
+    let symbol = pool_map::get_by_pool_id(pool_update.pool_id).map_or("Pool symbol not found".to_string(), |pool| pool.symbol());
+    PoolUpdateReply {
+        pool_update_id: pool_update.pool_update_id,
+        symbol,
+        status: pool_update.status.to_string(),
+        lp_fee_bps: pool_update.lp_fee_bps,
+        kong_fee_bps: pool_update.kong_fee_bps,
+        curve: pool_update.curve,
+        prev_lp_fee_bps: pool_update.prev_lp_fee_bps,
+        prev_kong_fee_bps: pool_update.prev_kong_fee_bps,
+        prev_curve: pool_update.prev_curve,
+        effective_ts: pool_update.effective_ts,
+        status_ts: pool_update.status_ts,
+        message: pool_update.message.clone(),
+        caller: pool_update.caller.clone(),
+        ts: pool_update.ts,
+    }
+}
@@ -0,0 +1,19 @@
+use ic_cdk::query;
+
+use super::pool_update_reply::PoolUpdateReply;
+use super::pool_update_reply_helpers::to_pool_update_reply;
+
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_pool::pool_map;
+use crate::stable_pool_update::pool_update_map;
+
+/// scheduled and applied updates of the fees and curve of pools, most recent first
+#[query(guard = "not_in_maintenance_mode")]
+pub fn pool_updates(pool: Option<String>) -> Result<Vec<PoolUpdateReply>, String> {
+    let pool_id = match pool {
+        Some(pool) => Some(pool_map::get_by_token(&pool)?.pool_id),
+        None => None,
+    };
+
+    Ok(pool_update_map::get(pool_id).iter().map(to_pool_update_reply).collect())
+}
@@ -0,0 +1,60 @@
+use super::update_pool_parameters::check_pool_parameters;
+
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::ic::logging::{error_log, info_log};
+use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::StablePool;
+use crate::stable_pool_update::pool_update_map;
+use crate::stable_pool_update::stable_pool_update::{PoolUpdateStatus, StablePoolUpdate};
+
+/// apply the scheduled pool updates which reached their effective time
+pub fn process_pool_updates() {
+    if not_in_maintenance_mode().is_err() {
+        return;
+    }
+
+    let ts = get_time();
+    for pool_update in pool_update_map::get_scheduled()
+        .into_iter()
+        .filter(|pool_update| pool_update.effective_ts <= ts)
+    {
+        let pool = pool_map::get_by_pool_id(pool_update.pool_id).ok_or("Pool not found".to_string());
+        match pool.and_then(|pool| {
+            check_pool_parameters(&pool, pool_update.lp_fee_bps, pool_update.kong_fee_bps, &pool_update.curve)?;
+            Ok(pool)
+        }) {
+            Ok(pool) => {
+                pool_map::update(&StablePool {
+                    lp_fee_bps: pool_update.lp_fee_bps,
+                    kong_fee_bps: pool_update.kong_fee_bps,
+                    curve: pool_update.curve,
+                    ..pool.clone()
+                });
+                // record the parameters replaced as the pool may have changed since the update was scheduled
+                pool_update_map::update(&StablePoolUpdate {
+                    status: PoolUpdateStatus::Applied,
+                    prev_lp_fee_bps: pool.lp_fee_bps,
+                    prev_kong_fee_bps: pool.kong_fee_bps,
+                    prev_curve: pool.curve,
+                    status_ts: Some(ts),
+                    ..pool_update.clone()
+                });
+                info_log(&format!(
+                    "Pool update #{} applied to pool {}",
+                    pool_update.pool_update_id,
+                    pool.symbol()
+                ));
+            }
+            Err(e) => {
+                pool_update_map::update(&StablePoolUpdate {
+                    status: PoolUpdateStatus::Failed,
+                    status_ts: Some(ts),
+                    message: Some(e.clone()),
+                    ..pool_update.clone()
+                });
+                error_log(&format!("Pool update #{} failed. {}", pool_update.pool_update_id, e));
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use ic_cdk::update;
+
+use super::pool_update_reply::PoolUpdateReply;
+use super::pool_update_reply_helpers::to_pool_update_reply;
+use super::update_pool_parameters_args::UpdatePoolParametersArgs;
+
+use crate::helpers::time_helpers::format_timestamp;
+use crate::ic::get_time::get_time;
+use crate::ic::guards::caller_is_kingkong;
+use crate::ic::id::caller_principal_id;
+use crate::ic::logging::info_log;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_pool::pool_curve::{PoolCurve, MAX_AMP, MIN_AMP};
+use crate::stable_pool::pool_map;
+use crate::stable_pool::stable_pool::StablePool;
+use crate::stable_pool_update::pool_update_map;
+use crate::stable_pool_update::stable_pool_update::{PoolUpdateStatus, StablePoolUpdate};
+
+pub const MAX_LP_FEE_BPS: u8 = 100; // 1%
+
+/// schedule a change of the LP fee, Kong fee and curve of a pool
+/// the update is applied at effective_ts by process_pool_updates() so LPs get notice
+#[update(guard = "caller_is_kingkong")]
+fn update_pool_parameters(args: UpdatePoolParametersArgs) -> Result<PoolUpdateReply, String> {
+    let (pool, lp_fee_bps, kong_fee_bps, curve) = check_arguments(&args)?;
+
+    let pool_update_id = pool_update_map::insert(&StablePoolUpdate {
+        pool_update_id: 0,
+        pool_id: pool.pool_id,
+        caller: caller_principal_id(),
+        status: PoolUpdateStatus::Scheduled,
+        lp_fee_bps,
+        kong_fee_bps,
+        curve,
+        prev_lp_fee_bps: pool.lp_fee_bps,
+        prev_kong_fee_bps: pool.kong_fee_bps,
+        prev_curve: pool.curve,
+        effective_ts: args.effective_ts,
+        status_ts: None,
+        message: None,
+        ts: get_time(),
+    });
+    info_log(&format!(
+        "Pool update #{} scheduled. {}",
+        pool_update_id,
+        to_description(&pool, lp_fee_bps, kong_fee_bps, &curve, args.effective_ts)
+    ));
+
+    let pool_update = pool_update_map::get_by_pool_update_id(pool_update_id).ok_or("Failed to schedule pool update")?;
+    Ok(to_pool_update_reply(&pool_update))
+}
+
+/// api to validate update_pool_parameters for SNS proposals
+#[update]
+fn validate_update_pool_parameters(args: UpdatePoolParametersArgs) -> Result<String, String> {
+    let (pool, lp_fee_bps, kong_fee_bps, curve) = check_arguments(&args)?;
+    Ok(to_description(&pool, lp_fee_bps, kong_fee_bps, &curve, args.effective_ts))
+}
+
+/// returns the pool and its new lp_fee_bps, kong_fee_bps and curve
+fn check_arguments(args: &UpdatePoolParametersArgs) -> Result<(StablePool, u8, u8, PoolCurve), String> {
+    let pool = pool_map::get_by_token(&args.pool)?;
+    let lp_fee_bps = args.lp_fee_bps.unwrap_or(pool.lp_fee_bps);
+    let kong_fee_bps = args.kong_fee_bps.unwrap_or(pool.kong_fee_bps);
+    let curve = args.curve.unwrap_or(pool.curve);
+    check_pool_parameters(&pool, lp_fee_bps, kong_fee_bps, &curve)?;
+    if lp_fee_bps == pool.lp_fee_bps && kong_fee_bps == pool.kong_fee_bps && curve == pool.curve {
+        Err(format!("No changes to parameters of pool {}", pool.symbol()))?
+    }
+
+    // LPs must get notice of the update
+    let pool_update_notice_secs = kong_settings_map::get().pool_update_notice_secs;
+    if args.effective_ts < get_time().saturating_add(pool_update_notice_secs.saturating_mul(1_000_000_000)) {
+        Err(format!(
+            "Effective time must be at least {} seconds from now",
+            pool_update_notice_secs
+        ))?
+    }
+
+    // one update at a time so LPs know the parameters that will apply
+    if pool_update_map::get_scheduled()
+        .iter()
+        .any(|pool_update| pool_update.pool_id == pool.pool_id)
+    {
+        Err(format!("Pool {} already has a scheduled update", pool.symbol()))?
+    }
+
+    Ok((pool, lp_fee_bps, kong_fee_bps, curve))
+}
+
+/// check the new parameters of a pool are valid. also checked when the update is applied as the pool may have changed
+pub fn check_pool_parameters(pool: &StablePool, lp_fee_bps: u8, kong_fee_bps: u8, curve: &PoolCurve) -> Result<(), String> {
+    if lp_fee_bps > MAX_LP_FEE_BPS {
+        Err(format!("LP fee cannot be more than {} bps", MAX_LP_FEE_BPS))?
+    }
+    if lp_fee_bps < kong_fee_bps {
+        Err(format!("LP fee cannot be less than Kong fee of {}", kong_fee_bps))?
+    }
+    if let PoolCurve::StableSwap { amp } = curve {
+        if !(MIN_AMP..=MAX_AMP).contains(amp) {
+            Err(format!("Amplification coefficient must be between {} and {}", MIN_AMP, MAX_AMP))?
+        }
+    }
+    // liquidity of Concentrated pools is held in positions of the tick spacing
+    if (pool.curve.is_concentrated() || curve.is_concentrated()) && pool.curve != *curve {
+        Err("Curve of Concentrated pools cannot be changed".to_string())?
+    }
+    Ok(())
+}
+
+fn to_description(pool: &StablePool, lp_fee_bps: u8, kong_fee_bps: u8, curve: &PoolCurve, effective_ts: u64) -> String {
+    format!(
+        "Update pool {} at {}. LP fee {} -> {} bps, Kong fee {} -> {} bps, curve {} -> {}",
+        pool.symbol(),
+        format_timestamp(effective_ts),
+        pool.lp_fee_bps,
+        lp_fee_bps,
+        pool.kong_fee_bps,
+        kong_fee_bps,
+        pool.curve,
+        curve
+    )
+}
@@ -0,0 +1,14 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use crate::stable_pool::pool_curve::PoolCurve;
+
+/// Data structure for the arguments of the `update_pool_parameters` function.
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatePoolParametersArgs {
+    pub pool: String,           // symbol of the pool, ie. ckBTC_ckUSDT
+    pub lp_fee_bps: Option<u8>, // None keeps the current value
+    pub kong_fee_bps: Option<u8>,
+    pub curve: Option<PoolCurve>,
+    pub effective_ts: u64, // time the update is applied, at least pool_update_notice_secs from now
+}
@@ -1,6 +1,8 @@
 use candid::{CandidType, Nat};
 use serde::{Deserialize, Serialize};
 
+use crate::stable_pool::pool_curve::PoolCurve;
+
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub struct PoolsReply {
     pub pools: Vec<PoolReply>,
@@ -27,11 +29,24 @@ pub struct PoolReply {
     pub lp_fee_1: Nat,
     pub price: f64,
     pub lp_fee_bps: u8,
+    pub effective_lp_fee_bps: u8,
+    pub min_lp_fee_bps: Option<u8>,
+    pub max_lp_fee_bps: Option<u8>,
+    pub curve: PoolCurve,
     pub tvl: Nat,
     pub rolling_24h_volume: Nat,
     pub rolling_24h_lp_fee: Nat,
     pub rolling_24h_num_swaps: Nat,
     pub rolling_24h_apy: f64,
+    pub rolling_7d_volume: Nat,
+    pub rolling_7d_lp_fee: Nat,
+    pub rolling_7d_num_swaps: Nat,
+    pub rolling_7d_apy: f64,
+    pub rolling_30d_volume: Nat,
+    pub rolling_30d_lp_fee: Nat,
+    pub rolling_30d_num_swaps: Nat,
+    pub rolling_30d_apy: f64,
     pub lp_token_symbol: String,
     pub is_removed: bool,
+    pub suspended_at: Option<u64>, // suspended for low TVL, only removing liquidity is allowed
 }
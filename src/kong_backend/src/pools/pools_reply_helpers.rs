@@ -47,13 +47,26 @@ pub fn to_pool_reply(pool: &StablePool) -> PoolReply {
         lp_fee_1: pool.lp_fee_1.clone(),
         price: pool.get_price_as_f64().unwrap_or(0_f64),
         lp_fee_bps: pool.lp_fee_bps,
+        effective_lp_fee_bps: pool.effective_lp_fee_bps(),
+        min_lp_fee_bps: pool.dynamic_fee.as_ref().map(|dynamic_fee| dynamic_fee.min_lp_fee_bps),
+        max_lp_fee_bps: pool.dynamic_fee.as_ref().map(|dynamic_fee| dynamic_fee.max_lp_fee_bps),
+        curve: pool.curve,
         tvl: pool.tvl.clone(),
         rolling_24h_volume: pool.rolling_24h_volume.clone(),
         rolling_24h_lp_fee: pool.rolling_24h_lp_fee.clone(),
         rolling_24h_num_swaps: pool.rolling_24h_num_swaps.clone(),
         rolling_24h_apy: pool.rolling_24h_apy,
+        rolling_7d_volume: pool.rolling_7d_volume.clone(),
+        rolling_7d_lp_fee: pool.rolling_7d_lp_fee.clone(),
+        rolling_7d_num_swaps: pool.rolling_7d_num_swaps.clone(),
+        rolling_7d_apy: pool.rolling_7d_apy,
+        rolling_30d_volume: pool.rolling_30d_volume.clone(),
+        rolling_30d_lp_fee: pool.rolling_30d_lp_fee.clone(),
+        rolling_30d_num_swaps: pool.rolling_30d_num_swaps.clone(),
+        rolling_30d_apy: pool.rolling_30d_apy,
         lp_token_symbol,
         is_removed: pool.is_removed,
+        suspended_at: pool.suspended_at,
     }
 }
 
@@ -0,0 +1,144 @@
+use candid::Nat;
+use ic_cdk::update;
+use icrc_ledger_types::icrc1::account::Account;
+
+use super::collect_protocol_fees_reply::CollectProtocolFeesReply;
+use super::collect_protocol_fees_reply_helpers::to_collect_protocol_fees_reply;
+
+use crate::helpers::flash_loan_helpers::charge_kong_fee;
+use crate::helpers::nat_helpers::{nat_is_zero, nat_subtract, nat_zero};
+use crate::helpers::protocol_fee_helpers::{protocol_fees_status, settle_protocol_fee, to_protocol_fee_transfer, ProtocolFeeTransfer};
+use crate::ic::get_time::get_time;
+use crate::ic::guards::caller_is_kingkong;
+use crate::ic::logging::error_log;
+use crate::ic::transfer::icrc1_transfer_with_ledger_error;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_pool::{pool_map, stable_pool::StablePool};
+use crate::stable_token::{stable_token::StableToken, token::Token};
+use crate::stable_tx::{collect_protocol_fees_tx::CollectProtocolFeesTx, stable_tx::StableTx, tx_map};
+use crate::stable_user::user_map;
+
+/// collect Kong's share of the LP fees (kong_fee_0, kong_fee_1) of a pool, or all pools if pool is None, to the treasury
+///
+/// - the gas fee of each transfer is paid out of the collected fees. fees not more than the gas fee are left to accrue
+/// - any shortfall of unrepaid flash loans is settled out of the fees first
+/// - the fees are taken out of the pool before the transfers and put back if the ledger rejects a transfer
+/// - if the outcome of a transfer is unknown, its fees are kept out of the pool and the tx is marked PendingReconciliation
+///   to be checked against the ledger manually
+/// - each pool with fees collected is recorded as a CollectProtocolFees tx
+#[update(hidden = true, guard = "caller_is_kingkong")]
+async fn collect_protocol_fees(pool: Option<String>) -> Result<Vec<CollectProtocolFeesReply>, String> {
+    let treasury = kong_settings_map::get().treasury.ok_or("Treasury not set")?;
+    let user_id = user_map::insert(None)?;
+    let pools = match pool {
+        Some(pool) => vec![pool_map::get_by_token(&pool)?],
+        None => pool_map::get(),
+    };
+
+    let mut replies = Vec::new();
+    for pool in pools {
+        if let Some(tx_id) = collect_pool_protocol_fees(pool.pool_id, user_id, &treasury).await {
+            _ = tx_map::archive_to_kong_data(tx_id);
+            if let Some(StableTx::CollectProtocolFees(collect_protocol_fees_tx)) =
+                tx_map::get_by_user_and_token_id(Some(tx_id), None, None, None).first()
+            {
+                replies.push(to_collect_protocol_fees_reply(collect_protocol_fees_tx));
+            }
+        }
+    }
+
+    Ok(replies)
+}
+
+/// collect the fees of a pool to the treasury
+/// returns the tx_id of the CollectProtocolFees tx, or None if there were no fees to collect
+async fn collect_pool_protocol_fees(pool_id: u32, user_id: u32, treasury: &Account) -> Option<u64> {
+    // take the fees out of the pool before any await so they can not be collected twice
+    let mut pool = pool_map::get_by_pool_id(pool_id)?;
+    let token_0 = pool.token_0();
+    let token_1 = pool.token_1();
+    // settle any shortfall of unrepaid flash loans before collecting
+    (pool.kong_fee_0, pool.flash_loan_shortfall_0) = charge_kong_fee(&pool.kong_fee_0, &pool.flash_loan_shortfall_0, &nat_zero());
+    (pool.kong_fee_1, pool.flash_loan_shortfall_1) = charge_kong_fee(&pool.kong_fee_1, &pool.flash_loan_shortfall_1, &nat_zero());
+    let amount_0 = if pool.kong_fee_0 > token_0.fee() {
+        std::mem::replace(&mut pool.kong_fee_0, nat_zero())
+    } else {
+        nat_zero()
+    };
+    let amount_1 = if pool.kong_fee_1 > token_1.fee() {
+        std::mem::replace(&mut pool.kong_fee_1, nat_zero())
+    } else {
+        nat_zero()
+    };
+    pool_map::update(&pool);
+    if nat_is_zero(&amount_0) && nat_is_zero(&amount_1) {
+        return None;
+    }
+
+    let ts = get_time();
+    let transfer_0 = transfer_protocol_fee(&pool, &token_0, &amount_0, treasury).await;
+    let transfer_1 = transfer_protocol_fee(&pool, &token_1, &amount_1, treasury).await;
+
+    // refresh pool with the latest state. put back the fees of transfers rejected by the ledger
+    // fees of transfers with an unknown outcome stay out of the pool so they can not be collected twice
+    let mut pool = pool_map::get_by_pool_id(pool_id)?;
+    let (kong_fee_0, collected_kong_fee_0, amount_0) =
+        settle_protocol_fee(&transfer_0, &pool.kong_fee_0, &pool.collected_kong_fee_0, &amount_0);
+    pool.kong_fee_0 = kong_fee_0;
+    pool.collected_kong_fee_0 = collected_kong_fee_0;
+    let (kong_fee_1, collected_kong_fee_1, amount_1) =
+        settle_protocol_fee(&transfer_1, &pool.kong_fee_1, &pool.collected_kong_fee_1, &amount_1);
+    pool.kong_fee_1 = kong_fee_1;
+    pool.collected_kong_fee_1 = collected_kong_fee_1;
+    pool.set_tvl();
+    pool_map::update(&pool);
+
+    let status = protocol_fees_status(&transfer_0, &transfer_1);
+    let block_id_0 = match transfer_0 {
+        ProtocolFeeTransfer::Sent(block_id) => block_id,
+        _ => None,
+    };
+    let block_id_1 = match transfer_1 {
+        ProtocolFeeTransfer::Sent(block_id) => block_id,
+        _ => None,
+    };
+    let collect_protocol_fees_tx =
+        CollectProtocolFeesTx::new(pool_id, user_id, status, treasury, &amount_0, block_id_0, &amount_1, block_id_1, ts);
+    Some(tx_map::insert(&StableTx::CollectProtocolFees(collect_protocol_fees_tx)))
+}
+
+/// transfer amount less the gas fee to the treasury
+async fn transfer_protocol_fee(pool: &StablePool, token: &StableToken, amount: &Nat, treasury: &Account) -> ProtocolFeeTransfer {
+    if nat_is_zero(amount) {
+        return ProtocolFeeTransfer::Sent(None);
+    }
+    let Some(transfer_amount) = nat_subtract(amount, &token.fee()) else {
+        // should not get here as fees not more than the gas fee are not collected
+        return ProtocolFeeTransfer::Rejected;
+    };
+    let result = icrc1_transfer_with_ledger_error(&transfer_amount, treasury, token, None).await;
+    let transfer = to_protocol_fee_transfer(&result);
+    let e = match result {
+        Ok(Ok(_)) => return transfer,
+        Ok(Err(e)) => e.to_string(),
+        Err(e) => e,
+    };
+    if transfer == ProtocolFeeTransfer::Rejected {
+        error_log(&format!(
+            "Failed to collect protocol fee of {} {} from pool {}. {}",
+            amount,
+            token.symbol(),
+            pool.symbol(),
+            e
+        ));
+    } else {
+        error_log(&format!(
+            "Unknown outcome collecting protocol fee of {} {} from pool {}. Requires manual reconciliation. {}",
+            amount,
+            token.symbol(),
+            pool.symbol(),
+            e
+        ));
+    }
+    transfer
+}
@@ -0,0 +1,6 @@
+pub mod collect_protocol_fees;
+pub mod collect_protocol_fees_reply;
+pub mod collect_protocol_fees_reply_helpers;
+#[allow(clippy::module_inception)]
+pub mod protocol_fees;
+pub mod protocol_fees_reply;
@@ -0,0 +1,53 @@
+use candid::Nat;
+use ic_cdk::query;
+
+use super::protocol_fees_reply::{PoolProtocolFeesReply, ProtocolFeesReply};
+
+use crate::helpers::nat_helpers::{nat_add, nat_zero};
+use crate::ic::ckusdt::ckusdt_amount;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_pool::{pool_map, stable_pool::StablePool};
+use crate::stable_token::stable_token::StableToken;
+
+/// Kong's share of the LP fees accrued in each pool and collected to the treasury
+#[query(guard = "not_in_maintenance_mode")]
+fn protocol_fees() -> Result<ProtocolFeesReply, String> {
+    let pools: Vec<PoolProtocolFeesReply> = pool_map::get().iter().map(to_pool_protocol_fees_reply).collect();
+    let (total_accrued_usd, total_collected_usd) = pools.iter().fold((nat_zero(), nat_zero()), |acc, pool| {
+        (nat_add(&acc.0, &pool.accrued_usd), nat_add(&acc.1, &pool.collected_usd))
+    });
+
+    Ok(ProtocolFeesReply {
+        treasury: kong_settings_map::get().treasury.map(|treasury| treasury.to_string()),
+        pools,
+        total_accrued_usd,
+        total_collected_usd,
+    })
+}
+
+fn to_pool_protocol_fees_reply(pool: &StablePool) -> PoolProtocolFeesReply {
+    let token_0 = pool.token_0();
+    let token_1 = pool.token_1();
+    PoolProtocolFeesReply {
+        pool_symbol: pool.symbol(),
+        chain_0: pool.chain_0(),
+        symbol_0: pool.symbol_0(),
+        address_0: pool.address_0(),
+        accrued_0: pool.kong_fee_0.clone(),
+        collected_0: pool.collected_kong_fee_0.clone(),
+        chain_1: pool.chain_1(),
+        symbol_1: pool.symbol_1(),
+        address_1: pool.address_1(),
+        accrued_1: pool.kong_fee_1.clone(),
+        collected_1: pool.collected_kong_fee_1.clone(),
+        accrued_usd: usd_amount(&token_0, &pool.kong_fee_0, &token_1, &pool.kong_fee_1),
+        collected_usd: usd_amount(&token_0, &pool.collected_kong_fee_0, &token_1, &pool.collected_kong_fee_1),
+    }
+}
+
+fn usd_amount(token_0: &StableToken, amount_0: &Nat, token_1: &StableToken, amount_1: &Nat) -> Nat {
+    let usd_amount_0 = ckusdt_amount(token_0, amount_0).unwrap_or(nat_zero());
+    let usd_amount_1 = ckusdt_amount(token_1, amount_1).unwrap_or(nat_zero());
+    nat_add(&usd_amount_0, &usd_amount_1)
+}
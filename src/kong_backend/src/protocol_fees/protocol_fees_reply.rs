@@ -0,0 +1,27 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolFeesReply {
+    pub treasury: Option<String>,
+    pub pools: Vec<PoolProtocolFeesReply>,
+    pub total_accrued_usd: Nat,
+    pub total_collected_usd: Nat,
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct PoolProtocolFeesReply {
+    pub pool_symbol: String,
+    pub chain_0: String,
+    pub symbol_0: String,
+    pub address_0: String,
+    pub accrued_0: Nat,
+    pub collected_0: Nat,
+    pub chain_1: String,
+    pub symbol_1: String,
+    pub address_1: String,
+    pub accrued_1: Nat,
+    pub collected_1: Nat,
+    pub accrued_usd: Nat,   // USD value of accrued fees
+    pub collected_usd: Nat, // USD value of collected fees at current prices
+}
@@ -0,0 +1,42 @@
+use ic_cdk::update;
+
+use crate::helpers::nat_helpers::{nat_subtract, nat_zero};
+use crate::ic::{address::Address, get_time::get_time, guards::not_in_maintenance_mode, id::caller_id};
+use crate::stable_claim::{claim_map, stable_claim::StableClaim};
+use crate::stable_referral::{referral_map, stable_referral::StableReferral};
+use crate::stable_token::{token::Token, token_map};
+use crate::stable_user::user_map;
+
+/// move the caller's unclaimed referral rewards to claims, which are then sent out by the claims process
+/// rewards not more than the gas fee of the token are left to accrue
+///
+/// returns the claim_ids of the new claims
+#[update(guard = "not_in_maintenance_mode")]
+fn claim_referral_rewards() -> Result<Vec<u64>, String> {
+    let user_id = user_map::get_by_caller()?.ok_or("User not found")?.user_id;
+    let to_address = Address::PrincipalId(caller_id());
+    let ts = get_time();
+
+    let mut claim_ids = Vec::new();
+    for referral in referral_map::get_by_user_id(user_id) {
+        let Some(token) = token_map::get_by_token_id(referral.token_id) else {
+            continue;
+        };
+        let unclaimed = nat_subtract(&referral.earned, &referral.claimed).unwrap_or(nat_zero());
+        if unclaimed <= token.fee() {
+            continue;
+        }
+        let claim = StableClaim::new(user_id, referral.token_id, &unclaimed, None, Some(to_address.clone()), ts);
+        claim_ids.push(claim_map::insert(&claim));
+        referral_map::update(&StableReferral {
+            claimed: referral.earned.clone(),
+            ..referral
+        });
+    }
+
+    if claim_ids.is_empty() {
+        Err("No referral rewards to claim".to_string())?
+    }
+
+    Ok(claim_ids)
+}
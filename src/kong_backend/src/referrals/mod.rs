@@ -0,0 +1,4 @@
+pub mod claim_referral_rewards;
+pub mod referral_rewards;
+pub mod referral_stats;
+pub mod referral_stats_reply;
@@ -0,0 +1,41 @@
+use candid::Nat;
+
+use crate::helpers::referral_helpers::{add_referral_reward, take_referral_fee};
+use crate::ic::get_time::get_time;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_pool::stable_pool::StablePool;
+use crate::stable_referral::referral_map;
+use crate::stable_request::request_map;
+use crate::stable_user::user_map;
+
+/// user_id of the referrer of the user who made the request, if the referral has not expired
+pub fn get_referrer(request_id: u64) -> Option<u32> {
+    let user_id = request_map::get_by_request_id(request_id)?.user_id;
+    let user = user_map::get_by_user_id(user_id)?;
+    if user.referred_by_expires_at.is_some_and(|expires_at| get_time() > expires_at) {
+        return None;
+    }
+    user.referred_by
+}
+
+/// pay referral_fee_pct of Kong's fee of a swap to the referrer
+/// kong_fee and volume are in the receive token of the swap
+/// make sure to call pool_map::update after calling this function
+pub fn pay_referral_fee(pool: &mut StablePool, referrer_user_id: u32, receive_token_0: bool, kong_fee: &Nat, volume: &Nat, ts: u64) {
+    let referral_fee_pct = kong_settings_map::get().referral_fee_pct;
+    if referral_fee_pct == 0 {
+        return;
+    }
+
+    let (token_id, referral_fee) = take_referral_fee(pool, receive_token_0, kong_fee, referral_fee_pct);
+    if let Some(referral) = add_referral_reward(
+        referral_map::get(referrer_user_id, token_id),
+        referrer_user_id,
+        token_id,
+        &referral_fee,
+        volume,
+        ts,
+    ) {
+        referral_map::update(&referral);
+    }
+}
@@ -0,0 +1,49 @@
+use ic_cdk::query;
+
+use super::referral_stats_reply::{ReferralRewardReply, ReferralStatsReply};
+
+use crate::helpers::nat_helpers::{nat_add, nat_subtract, nat_zero};
+use crate::ic::ckusdt::ckusdt_amount;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_referral::referral_map;
+use crate::stable_token::{token::Token, token_map};
+use crate::stable_user::{referee_count_map, user_map};
+
+/// referral stats of the caller: referees, volume of their swaps and rewards earned and claimed
+#[query(guard = "not_in_maintenance_mode")]
+fn referral_stats() -> Result<ReferralStatsReply, String> {
+    let user = user_map::get_by_caller()?.ok_or("User not found")?;
+    let user_id = user.user_id;
+
+    let num_referees = referee_count_map::get_num_referees(user_id);
+
+    let mut volume = nat_zero();
+    let mut earned_usd = nat_zero();
+    let mut claimed_usd = nat_zero();
+    let mut rewards = Vec::new();
+    for referral in referral_map::get_by_user_id(user_id) {
+        volume = nat_add(&volume, &referral.volume);
+        let Some(token) = token_map::get_by_token_id(referral.token_id) else {
+            continue;
+        };
+        earned_usd = nat_add(&earned_usd, &ckusdt_amount(&token, &referral.earned).unwrap_or(nat_zero()));
+        claimed_usd = nat_add(&claimed_usd, &ckusdt_amount(&token, &referral.claimed).unwrap_or(nat_zero()));
+        rewards.push(ReferralRewardReply {
+            chain: token.chain(),
+            symbol: token.symbol(),
+            address: token.address(),
+            unclaimed: nat_subtract(&referral.earned, &referral.claimed).unwrap_or(nat_zero()),
+            earned: referral.earned,
+            claimed: referral.claimed,
+        });
+    }
+
+    Ok(ReferralStatsReply {
+        my_referral_code: user.my_referral_code,
+        num_referees,
+        volume,
+        earned_usd,
+        claimed_usd,
+        rewards,
+    })
+}
@@ -0,0 +1,22 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct ReferralStatsReply {
+    pub my_referral_code: String,
+    pub num_referees: u32,
+    pub volume: Nat, // USD volume of the referees' swaps
+    pub earned_usd: Nat,
+    pub claimed_usd: Nat,
+    pub rewards: Vec<ReferralRewardReply>,
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct ReferralRewardReply {
+    pub chain: String,
+    pub symbol: String,
+    pub address: String,
+    pub earned: Nat,
+    pub claimed: Nat,
+    pub unclaimed: Nat,
+}
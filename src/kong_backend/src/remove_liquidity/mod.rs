@@ -3,3 +3,5 @@ pub mod remove_liquidity;
 pub mod remove_liquidity_args;
 pub mod remove_liquidity_reply;
 pub mod remove_liquidity_reply_helpers;
+pub mod remove_liquidity_single;
+pub mod remove_liquidity_single_args;
@@ -6,19 +6,24 @@ use super::remove_liquidity_args::RemoveLiquidityArgs;
 use super::remove_liquidity_reply::RemoveLiquidityReply;
 use super::remove_liquidity_reply_helpers::{to_remove_liquidity_reply, to_remove_liquidity_reply_failed};
 
+use crate::flash_loans::flash_loan_lock::check_flash_loan_lock;
 use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_zero};
+use crate::helpers::pool_helpers::{check_pool_action, PoolAction};
 use crate::ic::{address::Address, get_time::get_time, guards::not_in_maintenance_mode, id::caller_id, transfer::icrc1_transfer};
 use crate::stable_claim::{claim_map, stable_claim::StableClaim};
 use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_lp_block::lp_block_map;
 use crate::stable_lp_token::{lp_token_map, stable_lp_token::StableLPToken};
+use crate::stable_pool::concentrated_pool::{liquidity_delta, position_amounts, position_fees, update_position};
 use crate::stable_pool::{pool_map, stable_pool::StablePool};
+use crate::stable_position::{position_map, stable_position::StablePosition};
 use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
 use crate::stable_token::{stable_token::StableToken, token::Token};
 use crate::stable_transfer::{stable_transfer::StableTransfer, transfer_map, tx_id::TxId};
 use crate::stable_tx::{remove_liquidity_tx::RemoveLiquidityTx, stable_tx::StableTx, tx_map};
 use crate::stable_user::user_map;
 
-enum TokenIndex {
+pub enum TokenIndex {
     Token0,
     Token1,
 }
@@ -36,7 +41,7 @@ pub async fn remove_liquidity(args: RemoveLiquidityArgs) -> Result<RemoveLiquidi
     let (user_id, pool, remove_lp_token_amount, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1) =
         check_arguments(&args).await?;
     let ts = get_time();
-    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemoveLiquidity(args), ts));
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemoveLiquidity(args.clone()), ts));
     let caller_id = caller_id();
 
     let result = match process_remove_liquidity(
@@ -44,6 +49,7 @@ pub async fn remove_liquidity(args: RemoveLiquidityArgs) -> Result<RemoveLiquidi
         user_id,
         &caller_id,
         &pool,
+        &args,
         &remove_lp_token_amount,
         &payout_amount_0,
         &payout_lp_fee_0,
@@ -76,7 +82,7 @@ pub async fn remove_liquidity_from_pool(
     let (pool, remove_lp_token_amount, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1) =
         check_arguments_with_user(&args, user_id).await?;
     let ts = get_time();
-    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemoveLiquidity(args), ts));
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemoveLiquidity(args.clone()), ts));
     request_map::update_status(request_id, StatusCode::RemoveLiquidityFromPool, None);
 
     let result = match process_remove_liquidity(
@@ -84,6 +90,7 @@ pub async fn remove_liquidity_from_pool(
         user_id,
         to_principal_id,
         &pool,
+        &args,
         &remove_lp_token_amount,
         &payout_amount_0,
         &payout_lp_fee_0,
@@ -112,7 +119,7 @@ pub async fn remove_liquidity_async(args: RemoveLiquidityArgs) -> Result<u64, St
     let (user_id, pool, remove_lp_token_amount, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1) =
         check_arguments(&args).await?;
     let ts = get_time();
-    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemoveLiquidity(args), ts));
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemoveLiquidity(args.clone()), ts));
     let caller_id = caller_id();
 
     ic_cdk::spawn(async move {
@@ -121,6 +128,7 @@ pub async fn remove_liquidity_async(args: RemoveLiquidityArgs) -> Result<u64, St
             user_id,
             &caller_id,
             &pool,
+            &args,
             &remove_lp_token_amount,
             &payout_amount_0,
             &payout_lp_fee_0,
@@ -162,6 +170,9 @@ async fn check_arguments(args: &RemoveLiquidityArgs) -> Result<(u32, StablePool,
 async fn check_arguments_with_user(args: &RemoveLiquidityArgs, user_id: u32) -> Result<(StablePool, Nat, Nat, Nat, Nat, Nat), String> {
     // Pool
     let pool = pool_map::get_by_tokens(&args.token_0, &args.token_1)?;
+    if pool.curve.is_concentrated() {
+        return check_position_arguments(pool, args, user_id);
+    }
     // Token0
     let balance_0 = &pool.balance_0;
     // Token1
@@ -196,7 +207,50 @@ async fn check_arguments_with_user(args: &RemoveLiquidityArgs, user_id: u32) ->
     ))
 }
 
+/// Concentrated pools remove liquidity from a position of the user instead of burning LP tokens
+/// all uncollected fees of the position are paid out with the removed liquidity
+#[allow(clippy::type_complexity)]
+fn check_position_arguments(
+    pool: StablePool,
+    args: &RemoveLiquidityArgs,
+    user_id: u32,
+) -> Result<(StablePool, Nat, Nat, Nat, Nat, Nat), String> {
+    let position_id = args.position_id.ok_or("Position_id is required for concentrated liquidity pools")?;
+    let position = position_map::get_by_position_id(position_id)
+        .filter(|position| position.user_id == user_id && position.pool_id == pool.pool_id)
+        .ok_or(format!("Position #{} not found", position_id))?;
+    if args.remove_lp_token_amount > position.liquidity {
+        Err("Position has insufficient liquidity".to_string())?
+    }
+
+    // principal of the removed liquidity is rounded down in favour of the pool
+    let removed_position = StablePosition {
+        liquidity: args.remove_lp_token_amount.clone(),
+        ..position.clone()
+    };
+    let (payout_amount_0, payout_amount_1) = position_amounts(&pool, &removed_position).ok_or("Invalid position amounts")?;
+    let (payout_lp_fee_0, payout_lp_fee_1) = position_fees(&pool, &position).ok_or("Invalid position fees")?;
+    if nat_is_zero(&payout_amount_0) && nat_is_zero(&payout_amount_1) && nat_is_zero(&payout_lp_fee_0) && nat_is_zero(&payout_lp_fee_1) {
+        Err("Nothing to remove from position".to_string())?
+    }
+
+    Ok((
+        pool,
+        args.remove_lp_token_amount.clone(),
+        payout_amount_0,
+        payout_lp_fee_0,
+        payout_amount_1,
+        payout_lp_fee_1,
+    ))
+}
+
 pub fn calculate_amounts(pool: &StablePool, remove_lp_token_amount: &Nat) -> Result<(Nat, Nat, Nat, Nat), String> {
+    check_flash_loan_lock(pool)?;
+    check_pool_action(pool.suspended_at, PoolAction::RemoveLiquidity)?;
+
+    if pool.curve.is_concentrated() {
+        Err("Concentrated liquidity pools require a position".to_string())?
+    }
     // Token0
     let balance_0 = &pool.balance_0;
     let lp_fee_0 = &pool.lp_fee_0;
@@ -234,6 +288,7 @@ async fn process_remove_liquidity(
     user_id: u32,
     to_principal_id: &Account,
     pool: &StablePool,
+    args: &RemoveLiquidityArgs,
     remove_lp_token_amount: &Nat,
     payout_amount_0: &Nat,
     payout_lp_fee_0: &Nat,
@@ -246,8 +301,36 @@ async fn process_remove_liquidity(
 
     request_map::update_status(request_id, StatusCode::Start, None);
 
-    // remove LP tokens from user's ledger
-    let transfer_lp_token = remove_lp_token(request_id, user_id, &lp_token, remove_lp_token_amount, ts);
+    if args.deadline.is_some_and(|deadline| get_time() > deadline) {
+        request_map::update_status(request_id, StatusCode::DeadlineExceeded, None);
+        Err(format!("Req #{} failed. Deadline exceeded", request_id))?
+    }
+
+    // make sure the payout amounts are not below the minimum amounts of the user. nothing has been removed yet
+    let receive_amount_0 = nat_add(payout_amount_0, payout_lp_fee_0);
+    let receive_amount_1 = nat_add(payout_amount_1, payout_lp_fee_1);
+    if args
+        .min_amount_0
+        .as_ref()
+        .is_some_and(|min_amount_0| receive_amount_0 < *min_amount_0)
+        || args
+            .min_amount_1
+            .as_ref()
+            .is_some_and(|min_amount_1| receive_amount_1 < *min_amount_1)
+    {
+        let e = format!("Receive amount_0 {} and amount_1 {}", receive_amount_0, receive_amount_1);
+        request_map::update_status(request_id, StatusCode::RemoveAmountsBelowMinimum, Some(&e));
+        Err(format!("Req #{} failed. Remove amounts below minimum. {}", request_id, e))?
+    }
+
+    // remove LP tokens from user's ledger or liquidity from the user's position for Concentrated pools
+    let position_id = args.position_id;
+    let transfer_lp_token = match position_id {
+        Some(position_id) if pool.curve.is_concentrated() => {
+            remove_position_liquidity(request_id, user_id, pool, position_id, remove_lp_token_amount, ts)
+        }
+        _ => remove_lp_token(request_id, user_id, &lp_token, remove_lp_token_amount, ts),
+    };
     if transfer_lp_token.is_err() {
         return_tokens(request_id, user_id, pool, &transfer_lp_token, remove_lp_token_amount, ts);
         Err(format!("Req #{} failed. {}", request_id, transfer_lp_token.unwrap_err()))?
@@ -267,12 +350,13 @@ async fn process_remove_liquidity(
         payout_amount_1,
         payout_lp_fee_1,
         remove_lp_token_amount,
+        position_id,
         ts,
     )
     .await
 }
 
-fn remove_lp_token(request_id: u64, user_id: u32, lp_token: &StableToken, remove_lp_token_amount: &Nat, ts: u64) -> Result<(), String> {
+pub fn remove_lp_token(request_id: u64, user_id: u32, lp_token: &StableToken, remove_lp_token_amount: &Nat, ts: u64) -> Result<(), String> {
     // LP token
     let lp_token_id = lp_token.token_id();
 
@@ -298,6 +382,7 @@ fn remove_lp_token(request_id: u64, user_id: u32, lp_token: &StableToken, remove
                 ..lp_token.clone()
             };
             lp_token_map::update(&new_user_lp_token);
+            lp_block_map::insert_burn(lp_token_id, user_id, remove_lp_token_amount, ts);
             request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None);
             Ok(())
         }
@@ -309,6 +394,44 @@ fn remove_lp_token(request_id: u64, user_id: u32, lp_token: &StableToken, remove
     }
 }
 
+/// remove liquidity from the position and collect all its uncollected fees
+fn remove_position_liquidity(
+    request_id: u64,
+    user_id: u32,
+    pool: &StablePool,
+    position_id: u64,
+    remove_liquidity: &Nat,
+    ts: u64,
+) -> Result<(), String> {
+    request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmount, None);
+
+    match burn_position_liquidity(user_id, pool.pool_id, position_id, remove_liquidity, ts) {
+        Ok(()) => {
+            request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountSuccess, None);
+            Ok(())
+        }
+        Err(e) => {
+            request_map::update_status(request_id, StatusCode::UpdateUserLPTokenAmountFailed, Some(&e));
+            Err(e)
+        }
+    }
+}
+
+/// ensure we have the latest state of the pool and position before removing the liquidity
+fn burn_position_liquidity(user_id: u32, pool_id: u32, position_id: u64, remove_liquidity: &Nat, ts: u64) -> Result<(), String> {
+    let mut pool = pool_map::get_by_pool_id(pool_id).ok_or("Pool not found")?;
+    let mut position = position_map::get_by_position_id(position_id)
+        .filter(|position| position.user_id == user_id && position.pool_id == pool_id)
+        .ok_or(format!("Position #{} not found", position_id))?;
+    update_position(&mut pool, &mut position, &liquidity_delta(remove_liquidity, false), ts)?;
+    // fees are paid out together with the removed liquidity
+    position.tokens_owed_0 = nat_zero();
+    position.tokens_owed_1 = nat_zero();
+    position_map::update(&position);
+    pool_map::update(&pool);
+    Ok(())
+}
+
 fn return_lp_token(user_id: u32, lp_token: &StableToken, remove_lp_token_amount: &Nat, ts: u64) -> Result<(), String> {
     // LP token
     let lp_token_id = lp_token.token_id();
@@ -321,15 +444,19 @@ fn return_lp_token(user_id: u32, lp_token: &StableToken, remove_lp_token_amount:
                 ..lp_token.clone()
             };
             lp_token_map::update(&new_user_lp_token);
+            lp_block_map::insert_mint(lp_token_id, user_id, remove_lp_token_amount, ts);
             Ok(())
         }
         None => Err("Unable to find LP tokens balance".to_string())?,
     }
 }
 
-fn update_liquidity_pool(request_id: u64, pool: &StablePool, amount_0: &Nat, lp_fee_0: &Nat, amount_1: &Nat, lp_fee_1: &Nat) {
+pub fn update_liquidity_pool(request_id: u64, pool: &StablePool, amount_0: &Nat, lp_fee_0: &Nat, amount_1: &Nat, lp_fee_1: &Nat) {
     request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
 
+    // refresh with the latest state of the pool. Concentrated pools have updated their ticks and liquidity
+    let pool = &pool_map::get_by_pool_id(pool.pool_id).unwrap_or(pool.clone());
+
     let mut update_pool = StablePool {
         balance_0: nat_subtract(&pool.balance_0, amount_0).unwrap_or(nat_zero()),
         lp_fee_0: nat_subtract(&pool.lp_fee_0, lp_fee_0).unwrap_or(nat_zero()),
@@ -358,6 +485,7 @@ async fn send_payout_tokens(
     payout_amount_1: &Nat,
     payout_lp_fee_1: &Nat,
     remove_lp_token_amount: &Nat,
+    position_id: Option<u64>,
     ts: u64,
 ) -> Result<RemoveLiquidityReply, String> {
     // Token0
@@ -407,6 +535,7 @@ async fn send_payout_tokens(
         payout_amount_1,
         payout_lp_fee_1,
         remove_lp_token_amount,
+        position_id,
         &transfer_ids,
         &claim_ids,
         ts,
@@ -422,7 +551,7 @@ async fn send_payout_tokens(
 }
 
 #[allow(clippy::too_many_arguments)]
-async fn transfer_token(
+pub async fn transfer_token(
     request_id: u64,
     user_id: u32,
     to_principal_id: &Account,
@@ -482,7 +611,7 @@ async fn transfer_token(
     }
 }
 
-fn return_tokens(
+pub fn return_tokens(
     request_id: u64,
     user_id: u32,
     pool: &StablePool,
@@ -510,7 +639,7 @@ fn return_tokens(
     request_map::update_reply(request_id, Reply::RemoveLiquidity(reply));
 }
 
-fn archive_to_kong_data(request_id: u64) -> Result<(), String> {
+pub fn archive_to_kong_data(request_id: u64) -> Result<(), String> {
     if !kong_settings_map::get().archive_to_kong_data {
         return Ok(());
     }
@@ -7,5 +7,9 @@ use serde::{Deserialize, Serialize};
 pub struct RemoveLiquidityArgs {
     pub token_0: String,
     pub token_1: String,
-    pub remove_lp_token_amount: Nat,
+    pub remove_lp_token_amount: Nat, // Concentrated pools - liquidity to remove from the position. 0 to only collect fees
+    pub position_id: Option<u64>,    // Concentrated pools - position to remove liquidity from
+    pub min_amount_0: Option<Nat>,   // minimum amount of token_0 to receive including LP fees. fails if below
+    pub min_amount_1: Option<Nat>,   // minimum amount of token_1 to receive including LP fees. fails if below
+    pub deadline: Option<u64>,       // timestamp in nanoseconds. fails if executed after the deadline
 }
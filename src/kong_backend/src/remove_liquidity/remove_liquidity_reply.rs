@@ -22,6 +22,7 @@ pub struct RemoveLiquidityReply {
     pub amount_1: Nat,
     pub lp_fee_1: Nat,
     pub remove_lp_token_amount: Nat,
+    pub position_id: Option<u64>,
     pub transfer_ids: Vec<TransferIdReply>,
     pub claim_ids: Vec<u64>,
     pub ts: u64,
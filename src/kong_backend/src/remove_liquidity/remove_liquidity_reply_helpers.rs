@@ -51,6 +51,7 @@ pub fn to_remove_liquidity_reply(remove_liquidity_tx: &RemoveLiquidityTx) -> Rem
         amount_1: remove_liquidity_tx.amount_1.clone(),
         lp_fee_1: remove_liquidity_tx.lp_fee_1.clone(),
         remove_lp_token_amount: remove_liquidity_tx.remove_lp_token_amount.clone(),
+        position_id: remove_liquidity_tx.position_id,
         transfer_ids: to_transfer_ids(&remove_liquidity_tx.transfer_ids),
         claim_ids: remove_liquidity_tx.claim_ids.clone(),
         ts: remove_liquidity_tx.ts,
@@ -75,6 +76,7 @@ pub fn to_remove_liquidity_reply_failed(pool_id: u32, request_id: u64, ts: u64)
         amount_1: nat_zero(),
         lp_fee_1: nat_zero(),
         remove_lp_token_amount: nat_zero(),
+        position_id: None,
         transfer_ids: Vec::new(), // if failed, transfer_ids is empty as no tokens are returned
         claim_ids: Vec::new(),    // if failed, claims_ids is empty as no LP tokens are returned
         ts,
@@ -0,0 +1,266 @@
+use candid::Nat;
+use ic_cdk::update;
+
+use super::remove_liquidity::{
+    archive_to_kong_data, calculate_amounts, remove_lp_token, return_tokens, transfer_token, update_liquidity_pool, TokenIndex,
+};
+use super::remove_liquidity_reply::RemoveLiquidityReply;
+use super::remove_liquidity_reply_helpers::{to_remove_liquidity_reply, to_remove_liquidity_reply_failed};
+use super::remove_liquidity_single_args::RemoveLiquiditySingleArgs;
+
+use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_subtract, nat_zero};
+use crate::ic::{get_time::get_time, guards::not_in_maintenance_mode, id::caller_id};
+use crate::stable_lp_token::lp_token_map;
+use crate::stable_pool::{pool_map, stable_pool::StablePool};
+use crate::stable_request::{reply::Reply, request::Request, request_map, stable_request::StableRequest, status::StatusCode};
+use crate::stable_token::{token::Token, token_map};
+use crate::stable_tx::{remove_liquidity_tx::RemoveLiquidityTx, stable_tx::StableTx, tx_map};
+use crate::stable_user::user_map;
+use crate::swap::swap_amounts::pool_swap_amounts;
+use crate::swap::swap_calc::SwapCalc;
+use crate::swap::update_liquidity_pool::apply_swaps;
+
+/// remove liquidity from a pool into a single token
+///
+/// - removes remove_lp_token_amount from the pool like remove_liquidity() and swaps the payout of the other
+///   token through the pool for receive_token in the same request
+///
+/// Notes regarding gas:
+///   - the amount and LP fee of receive_token in the reply do not include gas fees. amount and LP fee of the
+///     other token are zero as they are swapped into receive_token
+#[update(guard = "not_in_maintenance_mode")]
+pub async fn remove_liquidity_single(args: RemoveLiquiditySingleArgs) -> Result<RemoveLiquidityReply, String> {
+    let (user_id, pool, token_index) = check_arguments(&args)?;
+    let ts = get_time();
+    let request_id = request_map::insert(&StableRequest::new(user_id, &Request::RemoveLiquiditySingle(args.clone()), ts));
+
+    let result = match process_remove_liquidity_single(request_id, user_id, &pool, token_index, &args, ts).await {
+        Ok(reply) => {
+            request_map::update_status(request_id, StatusCode::Success, None);
+            Ok(reply)
+        }
+        Err(e) => {
+            request_map::update_status(request_id, StatusCode::Failed, None);
+            Err(e)
+        }
+    };
+    _ = archive_to_kong_data(request_id);
+
+    result
+}
+
+fn check_arguments(args: &RemoveLiquiditySingleArgs) -> Result<(u32, StablePool, TokenIndex), String> {
+    // make sure user is not anonymous and exists
+    let user_id = user_map::get_by_caller()?.ok_or("Insufficient LP balance")?.user_id;
+
+    let pool = pool_map::get_by_tokens(&args.token_0, &args.token_1)?;
+    if pool.curve.is_concentrated() {
+        Err("Concentrated liquidity pools require a position".to_string())?
+    }
+    if nat_is_zero(&pool.balance_0) && nat_is_zero(&pool.balance_1) {
+        Err("Zero balances in pool".to_string())?
+    }
+
+    let receive_token = token_map::get_by_token(&args.receive_token)?;
+    let token_index = if receive_token.token_id() == pool.token_id_0 {
+        TokenIndex::Token0
+    } else if receive_token.token_id() == pool.token_id_1 {
+        TokenIndex::Token1
+    } else {
+        Err(format!("Receive token {} is not in the pool", args.receive_token))?
+    };
+
+    // Check the user has enough LP tokens
+    let user_lp_token_amount =
+        lp_token_map::get_by_token_id_by_user_id(pool.lp_token_id, user_id).map_or_else(nat_zero, |lp_token| lp_token.amount);
+    if nat_is_zero(&user_lp_token_amount) || args.remove_lp_token_amount > user_lp_token_amount {
+        Err("User has insufficient LP balance".to_string())?
+    }
+
+    // calculate the receive amount with the current state of the pool
+    let (receive_amount, receive_lp_fee, _, _, _, _, _) = calculate_single_amounts(&pool, &token_index, &args.remove_lp_token_amount)?;
+    check_min_receive_amount(args, &nat_add(&receive_amount, &receive_lp_fee))?;
+
+    Ok((user_id, pool, token_index))
+}
+
+/// calculate the payout amounts of remove_lp_token_amount and the swap of the other token into the receive token
+/// with the state of the pool after the liquidity is removed
+///
+/// returns (receive_amount, receive_lp_fee, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1, swap)
+/// receive_amount includes the amount received from the swap
+#[allow(clippy::type_complexity)]
+fn calculate_single_amounts(
+    pool: &StablePool,
+    token_index: &TokenIndex,
+    remove_lp_token_amount: &Nat,
+) -> Result<(Nat, Nat, Nat, Nat, Nat, Nat, Option<SwapCalc>), String> {
+    let (payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1) = calculate_amounts(pool, remove_lp_token_amount)?;
+
+    // state of the pool after the liquidity is removed
+    let remove_pool = StablePool {
+        balance_0: nat_subtract(&pool.balance_0, &payout_amount_0).unwrap_or(nat_zero()),
+        lp_fee_0: nat_subtract(&pool.lp_fee_0, &payout_lp_fee_0).unwrap_or(nat_zero()),
+        balance_1: nat_subtract(&pool.balance_1, &payout_amount_1).unwrap_or(nat_zero()),
+        lp_fee_1: nat_subtract(&pool.lp_fee_1, &payout_lp_fee_1).unwrap_or(nat_zero()),
+        ..pool.clone()
+    };
+
+    // swap the payout of the other token including its LP fees
+    let (swap_amount, receive_amount, receive_lp_fee) = match token_index {
+        TokenIndex::Token0 => (nat_add(&payout_amount_1, &payout_lp_fee_1), &payout_amount_0, &payout_lp_fee_0),
+        TokenIndex::Token1 => (nat_add(&payout_amount_0, &payout_lp_fee_0), &payout_amount_1, &payout_lp_fee_1),
+    };
+    let swap = if nat_is_zero(&swap_amount) {
+        None
+    } else {
+        Some(pool_swap_amounts(
+            &remove_pool,
+            matches!(token_index, TokenIndex::Token1),
+            &swap_amount,
+        )?)
+    };
+    let receive_amount = match &swap {
+        Some(swap) => nat_add(receive_amount, &swap.receive_amount_with_fees_and_gas()),
+        None => receive_amount.clone(),
+    };
+
+    Ok((
+        receive_amount,
+        receive_lp_fee.clone(),
+        payout_amount_0,
+        payout_lp_fee_0,
+        payout_amount_1,
+        payout_lp_fee_1,
+        swap,
+    ))
+}
+
+fn check_min_receive_amount(args: &RemoveLiquiditySingleArgs, receive_amount: &Nat) -> Result<(), String> {
+    if let Some(min_receive_amount) = &args.min_receive_amount {
+        if receive_amount < min_receive_amount {
+            Err(format!("Receive amount {} below minimum {}", receive_amount, min_receive_amount))?
+        }
+    }
+    Ok(())
+}
+
+async fn process_remove_liquidity_single(
+    request_id: u64,
+    user_id: u32,
+    pool: &StablePool,
+    token_index: TokenIndex,
+    args: &RemoveLiquiditySingleArgs,
+    ts: u64,
+) -> Result<RemoveLiquidityReply, String> {
+    // LP token
+    let lp_token = pool.lp_token();
+    let caller_id = caller_id();
+
+    request_map::update_status(request_id, StatusCode::Start, None);
+
+    if args.deadline.is_some_and(|deadline| get_time() > deadline) {
+        request_map::update_status(request_id, StatusCode::DeadlineExceeded, None);
+        Err(format!("Req #{} failed. Deadline exceeded", request_id))?
+    }
+
+    // calculate the amounts with the latest state of the pool. nothing has been removed yet
+    request_map::update_status(request_id, StatusCode::CalculatePoolAmounts, None);
+    let pool = &pool_map::get_by_pool_id(pool.pool_id).unwrap_or(pool.clone());
+    let (receive_amount, receive_lp_fee, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1, swap) =
+        match calculate_single_amounts(pool, &token_index, &args.remove_lp_token_amount) {
+            Ok(amounts) => {
+                request_map::update_status(request_id, StatusCode::CalculatePoolAmountsSuccess, None);
+                amounts
+            }
+            Err(e) => {
+                request_map::update_status(request_id, StatusCode::CalculatePoolAmountsFailed, Some(&e));
+                Err(format!("Req #{} failed. {}", request_id, e))?
+            }
+        };
+    if let Err(e) = check_min_receive_amount(args, &nat_add(&receive_amount, &receive_lp_fee)) {
+        request_map::update_status(request_id, StatusCode::RemoveAmountsBelowMinimum, Some(&e));
+        Err(format!("Req #{} failed. {}", request_id, e))?
+    }
+
+    // remove LP tokens from user's ledger
+    let transfer_lp_token = remove_lp_token(request_id, user_id, &lp_token, &args.remove_lp_token_amount, ts);
+    if let Err(e) = &transfer_lp_token {
+        return_tokens(request_id, user_id, pool, &transfer_lp_token, &args.remove_lp_token_amount, ts);
+        Err(format!("Req #{} failed. {}", request_id, e))?
+    }
+
+    // update liquidity pool with new removed amounts
+    update_liquidity_pool(
+        request_id,
+        pool,
+        &payout_amount_0,
+        &payout_lp_fee_0,
+        &payout_amount_1,
+        &payout_lp_fee_1,
+    );
+
+    // swap the payout of the other token through the pool into the receive token
+    if let Some(swap) = swap {
+        apply_swaps(request_id, &[swap]);
+        request_map::update_status(request_id, StatusCode::SwapSuccess, None);
+    }
+
+    let (token, amount_0, lp_fee_0, amount_1, lp_fee_1) = match token_index {
+        TokenIndex::Token0 => (
+            pool.token_0(),
+            receive_amount.clone(),
+            receive_lp_fee.clone(),
+            nat_zero(),
+            nat_zero(),
+        ),
+        TokenIndex::Token1 => (
+            pool.token_1(),
+            nat_zero(),
+            nat_zero(),
+            receive_amount.clone(),
+            receive_lp_fee.clone(),
+        ),
+    };
+    let mut transfer_ids = Vec::new();
+    let mut claim_ids = Vec::new();
+
+    // send the receive token to the user
+    transfer_token(
+        request_id,
+        user_id,
+        &caller_id,
+        token_index,
+        &token,
+        &receive_amount,
+        &receive_lp_fee,
+        &mut transfer_ids,
+        &mut claim_ids,
+        ts,
+    )
+    .await;
+
+    // successful, add tx and update request with reply
+    let remove_liquidity_tx = RemoveLiquidityTx::new_success(
+        pool.pool_id,
+        user_id,
+        request_id,
+        &amount_0,
+        &lp_fee_0,
+        &amount_1,
+        &lp_fee_1,
+        &args.remove_lp_token_amount,
+        None,
+        &transfer_ids,
+        &claim_ids,
+        ts,
+    );
+    let tx_id = tx_map::insert(&StableTx::RemoveLiquidity(remove_liquidity_tx.clone()));
+    let reply = match tx_map::get_by_user_and_token_id(Some(tx_id), None, None, None).first() {
+        Some(StableTx::RemoveLiquidity(remove_liquidity_tx)) => to_remove_liquidity_reply(remove_liquidity_tx),
+        _ => to_remove_liquidity_reply_failed(pool.pool_id, request_id, ts),
+    };
+    request_map::update_reply(request_id, Reply::RemoveLiquidity(reply.clone()));
+
+    Ok(reply)
+}
@@ -0,0 +1,14 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// Data structure for the arguments of the `remove_liquidity_single` function.
+/// Used in StableRequest
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct RemoveLiquiditySingleArgs {
+    pub token_0: String,
+    pub token_1: String,
+    pub remove_lp_token_amount: Nat,
+    pub receive_token: String, // token to receive. must be token_0 or token_1. the other token is swapped into it
+    pub min_receive_amount: Option<Nat>, // minimum amount of receive_token to receive including LP fees. fails if below
+    pub deadline: Option<u64>, // timestamp in nanoseconds. fails if executed after the deadline
+}
@@ -3,17 +3,26 @@ use ic_cdk::query;
 use super::request_reply::RequestReply;
 use super::request_reply_helpers::to_request_reply;
 
+use crate::history::history_args::HistoryArgs;
+use crate::history::history_filter::HistoryFilter;
+use crate::history::requests_history::get_requests_history;
 use crate::ic::guards::not_in_maintenance_mode;
+use crate::ic::id::caller_principal_id;
 use crate::stable_request::request_map;
 
+/// request of request_id, or the caller's most recent requests. see requests_history() for more
 #[query(guard = "not_in_maintenance_mode")]
 async fn requests(request_id: Option<u64>) -> Result<Vec<RequestReply>, String> {
-    let request_id = match request_id {
-        Some(request_id) => request_id,
-        None => Err("request_id is required".to_string())?,
+    let requests = match request_id {
+        Some(request_id) => request_map::get_by_request_id(request_id).iter().map(to_request_reply).collect(),
+        None => {
+            let args = HistoryArgs {
+                principal_id: Some(caller_principal_id()),
+                ..Default::default()
+            };
+            get_requests_history(&HistoryFilter::new(&args, &[])?).requests
+        }
     };
 
-    let requests = request_map::get_by_request_id(request_id).iter().map(to_request_reply).collect();
-
     Ok(requests)
 }
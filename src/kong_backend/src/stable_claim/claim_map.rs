@@ -1,5 +1,6 @@
 use super::stable_claim::{ClaimStatus, StableClaim, StableClaimId};
 
+use crate::ic::address::Address;
 use crate::ic::logging::error_log;
 use crate::stable_kong_settings::kong_settings_map;
 use crate::stable_memory::CLAIM_MAP;
@@ -10,6 +11,17 @@ pub fn get_by_claim_id(claim_id: u64) -> Option<StableClaim> {
     CLAIM_MAP.with(|m| m.borrow().get(&StableClaimId(claim_id)))
 }
 
+/// get all claims of a user, most recent first
+pub fn get_by_user_id(user_id: u32) -> Vec<StableClaim> {
+    CLAIM_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .rev()
+            .filter_map(|(_, v)| if v.user_id == user_id { Some(v) } else { None })
+            .collect()
+    })
+}
+
 pub fn get_token(claim: &StableClaim) -> StableToken {
     token_map::get_by_token_id(claim.token_id).unwrap()
 }
@@ -52,9 +64,36 @@ pub fn update_status(claim_id: u64, status: ClaimStatus) -> Option<StableClaim>
     })
 }
 
+// used when the user gives a new address to send the claim to
+pub fn update_to_address(claim_id: u64, to_address: &Address) -> Option<StableClaim> {
+    CLAIM_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        match map.get(&StableClaimId(claim_id)) {
+            Some(mut v) => {
+                v.to_address = Some(to_address.clone());
+                map.insert(StableClaimId(claim_id), v.clone());
+                Some(v)
+            }
+            None => None,
+        }
+    })
+}
+
 // used for setting the status of a claim to claiming to prevent reentrancy
-pub fn update_claiming_status(claim_id: u64) -> Option<StableClaim> {
-    update_status(claim_id, ClaimStatus::Claiming)
+// the status is checked and set in the same step so only one caller can send the claim
+// returns None if the claim is not found or its status is not one of from_status
+pub fn update_claiming_status(claim_id: u64, from_status: &[ClaimStatus]) -> Option<StableClaim> {
+    CLAIM_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        match map.get(&StableClaimId(claim_id)) {
+            Some(mut v) if from_status.contains(&v.status) => {
+                v.status = ClaimStatus::Claiming;
+                map.insert(StableClaimId(claim_id), v.clone());
+                Some(v)
+            }
+            _ => None,
+        }
+    })
 }
 
 pub fn update_too_many_attempts_status(claim_id: u64) -> Option<StableClaim> {
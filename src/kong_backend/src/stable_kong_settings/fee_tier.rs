@@ -0,0 +1,10 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// fee tier of users by their swap volume over the trailing 30 days
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct FeeTier {
+    pub name: String,
+    pub min_volume: Nat, // ckUSDT swap volume over the trailing 30 days
+    pub fee_level: u8,   // discount of the LP fee in percent, see StableUser.fee_level
+}
@@ -121,3 +121,59 @@ pub fn inc_lp_token_map_idx() -> u64 {
         lp_token_map_idx
     })
 }
+
+pub fn inc_position_map_idx() -> u64 {
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let kong_settings = map.get();
+        let position_map_idx = kong_settings.position_map_idx + 1;
+        let new_kong_settings = StableKongSettings {
+            position_map_idx,
+            ..kong_settings.clone()
+        };
+        _ = map.set(new_kong_settings);
+        position_map_idx
+    })
+}
+
+pub fn inc_pool_update_map_idx() -> u64 {
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let kong_settings = map.get();
+        let pool_update_map_idx = kong_settings.pool_update_map_idx + 1;
+        let new_kong_settings = StableKongSettings {
+            pool_update_map_idx,
+            ..kong_settings.clone()
+        };
+        _ = map.set(new_kong_settings);
+        pool_update_map_idx
+    })
+}
+
+pub fn inc_webhook_delivery_map_idx() -> u64 {
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let kong_settings = map.get();
+        let webhook_delivery_map_idx = kong_settings.webhook_delivery_map_idx + 1;
+        let new_kong_settings = StableKongSettings {
+            webhook_delivery_map_idx,
+            ..kong_settings.clone()
+        };
+        _ = map.set(new_kong_settings);
+        webhook_delivery_map_idx
+    })
+}
+
+pub fn inc_limit_order_map_idx() -> u64 {
+    KONG_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let kong_settings = map.get();
+        let limit_order_map_idx = kong_settings.limit_order_map_idx + 1;
+        let new_kong_settings = StableKongSettings {
+            limit_order_map_idx,
+            ..kong_settings.clone()
+        };
+        _ = map.set(new_kong_settings);
+        limit_order_map_idx
+    })
+}
@@ -1,3 +1,5 @@
+pub mod fee_tier;
 pub mod kong_settings_map;
+pub mod pool_creation_fee;
 #[allow(clippy::module_inception)]
 pub mod stable_kong_settings;
@@ -0,0 +1,9 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+/// fee charged to create a pool, sent to the treasury
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct PoolCreationFee {
+    pub token_id: u32, // ICP or KONG
+    pub amount: Nat,
+}
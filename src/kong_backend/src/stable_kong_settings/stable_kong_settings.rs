@@ -1,17 +1,22 @@
-use candid::{CandidType, Principal};
+use candid::{CandidType, Nat, Principal};
 use ic_stable_structures::{storable::Bound, Storable};
 use icrc_ledger_types::icrc1::account::Account;
 use serde::{Deserialize, Serialize};
 use std::cmp;
 
+use super::fee_tier::FeeTier;
+use super::pool_creation_fee::PoolCreationFee;
+
+use crate::helpers::nat_helpers::nat_zero;
+
 use crate::ic::{
     canister_address::{EVENT_STORE, KONG_BACKEND, KONG_DATA},
     ckusdt::{CKUSDT_ADDRESS, CKUSDT_ADDRESS_WITH_CHAIN, CKUSDT_SYMBOL, CKUSDT_SYMBOL_WITH_CHAIN, CKUSDT_TOKEN_ID},
     icp::{ICP_ADDRESS, ICP_ADDRESS_WITH_CHAIN, ICP_SYMBOL, ICP_SYMBOL_WITH_CHAIN, ICP_TOKEN_ID},
 };
 use crate::stable_memory::{
-    CLAIM_MAP, LP_TOKEN_MAP, POOL_MAP, REQUEST_ARCHIVE_MAP, REQUEST_MAP, TOKEN_MAP, TRANSFER_ARCHIVE_MAP, TRANSFER_MAP, TX_ARCHIVE_MAP,
-    TX_MAP, USER_MAP,
+    CLAIM_MAP, LIMIT_ORDER_MAP, LP_TOKEN_MAP, POOL_MAP, POOL_UPDATE_MAP, POSITION_MAP, REQUEST_ARCHIVE_MAP, REQUEST_MAP, TOKEN_MAP,
+    TRANSFER_ARCHIVE_MAP, TRANSFER_MAP, TX_ARCHIVE_MAP, TX_MAP, USER_MAP, WEBHOOK_DELIVERY_MAP,
 };
 
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
@@ -42,7 +47,17 @@ pub struct StableKongSettings {
     pub transfer_map_idx: u64, // counter for TRANSFER_MAP
     pub claim_map_idx: u64,    // counter for CLAIM_MAP
     pub lp_token_map_idx: u64, // counter for LP_TOKEN_MAP
+    #[serde(default = "position_map_idx")]
+    pub position_map_idx: u64, // counter for POSITION_MAP
+    #[serde(default = "limit_order_map_idx")]
+    pub limit_order_map_idx: u64, // counter for LIMIT_ORDER_MAP
+    #[serde(default = "pool_update_map_idx")]
+    pub pool_update_map_idx: u64, // counter for POOL_UPDATE_MAP
+    #[serde(default = "webhook_delivery_map_idx")]
+    pub webhook_delivery_map_idx: u64, // counter for WEBHOOK_DELIVERY_MAP
     pub claims_interval_secs: u64,
+    #[serde(default = "limit_orders_interval_secs")]
+    pub limit_orders_interval_secs: u64,
     pub transfer_expiry_nanosecs: u64,
     pub stats_interval_secs: u64,
     pub requests_archive_interval_secs: u64,
@@ -51,6 +66,44 @@ pub struct StableKongSettings {
     pub lp_tokens_archive_interval_secs: u64,
     pub archive_to_kong_data: bool,
     pub send_to_event_store: bool,
+    #[serde(default = "flash_loan_fee_bps")]
+    pub flash_loan_fee_bps: u8, // fee of flash loans in basis points, credited to the LP fees of the pool
+    #[serde(default = "flash_loan_max_bps")]
+    pub flash_loan_max_bps: u16, // largest flash loan as a share of the pool's balance in basis points
+    #[serde(default = "flash_loan_canisters")]
+    pub flash_loan_canisters: Vec<Principal>, // canisters allowed to take flash loans
+    #[serde(default = "treasury")]
+    pub treasury: Option<Account>, // account protocol fees are collected to
+    #[serde(default = "referral_fee_pct")]
+    pub referral_fee_pct: u8, // share of Kong's fee of referred users' swaps paid to the referrer. 0 disables referral rewards
+    #[serde(default = "fee_tiers")]
+    pub fee_tiers: Vec<FeeTier>, // fee levels of users by their 30 day swap volume. empty disables volume-based fee levels
+    #[serde(default = "fee_tiers_interval_secs")]
+    pub fee_tiers_interval_secs: u64,
+    #[serde(default = "nat_zero")]
+    pub min_pool_tvl: Nat, // minimum initial TVL in ckUSDT of new pools. 0 disables
+    #[serde(default = "pool_creation_fee")]
+    pub pool_creation_fee: Option<PoolCreationFee>, // fee to create a pool, sent to the treasury
+    #[serde(default = "token_allow_list")]
+    pub token_allow_list: Vec<String>, // Chain.Address of tokens allowed in new pools. empty allows all tokens
+    #[serde(default = "token_deny_list")]
+    pub token_deny_list: Vec<String>, // Chain.Address of tokens not allowed in new pools
+    #[serde(default = "nat_zero")]
+    pub low_tvl_threshold: Nat, // pools with TVL in ckUSDT below the threshold for low_tvl_suspend_secs are suspended. 0 disables
+    #[serde(default = "low_tvl_suspend_secs")]
+    pub low_tvl_suspend_secs: u64,
+    #[serde(default = "quote_token_ids")]
+    pub quote_token_ids: Vec<u32>, // tokens new pools are quoted in (token_1) and multi-step swaps route through, in order of priority
+    #[serde(default = "pool_update_notice_secs")]
+    pub pool_update_notice_secs: u64, // minimum notice to LPs of changes to the fees and curve of a pool
+    #[serde(default = "pool_updates_interval_secs")]
+    pub pool_updates_interval_secs: u64,
+    #[serde(default = "webhooks_interval_secs")]
+    pub webhooks_interval_secs: u64,
+    #[serde(default = "limit_order_min_amount")]
+    pub limit_order_min_amount: Nat, // minimum amount in ckUSDT of limit orders and their partial fills. 0 disables
+    #[serde(default = "max_open_limit_orders")]
+    pub max_open_limit_orders: u32, // maximum number of open limit orders of a user
 }
 
 impl Default for StableKongSettings {
@@ -60,6 +113,10 @@ impl Default for StableKongSettings {
         let pool_map_idx = POOL_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
         let claim_map_idx = CLAIM_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
         let lp_token_map_idx = LP_TOKEN_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
+        let position_map_idx = position_map_idx();
+        let limit_order_map_idx = limit_order_map_idx();
+        let pool_update_map_idx = pool_update_map_idx();
+        let webhook_delivery_map_idx = webhook_delivery_map_idx();
         let request_map_idx = cmp::max(
             REQUEST_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0)),
             REQUEST_ARCHIVE_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0)),
@@ -99,7 +156,12 @@ impl Default for StableKongSettings {
             transfer_map_idx,
             claim_map_idx,
             lp_token_map_idx,
-            claims_interval_secs: 300,                   // claims every 5 minutes
+            position_map_idx,
+            limit_order_map_idx,
+            pool_update_map_idx,
+            webhook_delivery_map_idx,
+            claims_interval_secs: 300, // claims every 5 minutes
+            limit_orders_interval_secs: limit_orders_interval_secs(),
             transfer_expiry_nanosecs: 3_600_000_000_000, // 1 hour (nano seconds)
             stats_interval_secs: 3600,                   // stats every hour
             requests_archive_interval_secs: 3600,        // archive requests every hour
@@ -108,10 +170,117 @@ impl Default for StableKongSettings {
             lp_tokens_archive_interval_secs: 3600,       // archive lp_positions every hour
             archive_to_kong_data: false,                 // replicate to kong_data
             send_to_event_store: false,                  // replicate to event_store (Token Terminal)
+            flash_loan_fee_bps: flash_loan_fee_bps(),
+            flash_loan_max_bps: flash_loan_max_bps(),
+            flash_loan_canisters: flash_loan_canisters(),
+            treasury: treasury(),
+            referral_fee_pct: referral_fee_pct(),
+            fee_tiers: fee_tiers(),
+            fee_tiers_interval_secs: fee_tiers_interval_secs(),
+            min_pool_tvl: nat_zero(),
+            pool_creation_fee: pool_creation_fee(),
+            token_allow_list: token_allow_list(),
+            token_deny_list: token_deny_list(),
+            low_tvl_threshold: nat_zero(),
+            low_tvl_suspend_secs: low_tvl_suspend_secs(),
+            quote_token_ids: quote_token_ids(),
+            pool_update_notice_secs: pool_update_notice_secs(),
+            pool_updates_interval_secs: pool_updates_interval_secs(),
+            webhooks_interval_secs: webhooks_interval_secs(),
+            limit_order_min_amount: limit_order_min_amount(),
+            max_open_limit_orders: max_open_limit_orders(),
         }
     }
 }
 
+fn position_map_idx() -> u64 {
+    POSITION_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0))
+}
+
+fn limit_order_map_idx() -> u64 {
+    LIMIT_ORDER_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0))
+}
+
+fn pool_update_map_idx() -> u64 {
+    POOL_UPDATE_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0))
+}
+
+fn webhook_delivery_map_idx() -> u64 {
+    WEBHOOK_DELIVERY_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0))
+}
+
+fn limit_orders_interval_secs() -> u64 {
+    60 // check limit orders every minute
+}
+
+fn flash_loan_fee_bps() -> u8 {
+    9 // 0.09%
+}
+
+fn flash_loan_max_bps() -> u16 {
+    1_000 // 10%
+}
+
+fn flash_loan_canisters() -> Vec<Principal> {
+    Vec::new()
+}
+
+fn treasury() -> Option<Account> {
+    None
+}
+
+fn referral_fee_pct() -> u8 {
+    0
+}
+
+fn fee_tiers() -> Vec<FeeTier> {
+    Vec::new()
+}
+
+fn fee_tiers_interval_secs() -> u64 {
+    3600 // update users' fee tiers every hour
+}
+
+fn pool_creation_fee() -> Option<PoolCreationFee> {
+    None
+}
+
+fn token_allow_list() -> Vec<String> {
+    Vec::new()
+}
+
+fn token_deny_list() -> Vec<String> {
+    Vec::new()
+}
+
+fn quote_token_ids() -> Vec<u32> {
+    vec![CKUSDT_TOKEN_ID, ICP_TOKEN_ID]
+}
+
+fn pool_update_notice_secs() -> u64 {
+    3 * 24 * 3600 // 3 days notice of pool updates
+}
+
+fn pool_updates_interval_secs() -> u64 {
+    60 // apply scheduled pool updates every minute
+}
+
+fn webhooks_interval_secs() -> u64 {
+    10 // deliver webhooks every 10 seconds
+}
+
+fn limit_order_min_amount() -> Nat {
+    Nat::from(10_000_000_u64) // 10 ckUSDT
+}
+
+fn max_open_limit_orders() -> u32 {
+    20
+}
+
+fn low_tvl_suspend_secs() -> u64 {
+    30 * 24 * 3600 // suspend pools with low TVL after 30 days
+}
+
 impl Storable for StableKongSettings {
     fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
         serde_cbor::to_vec(self).unwrap().into()
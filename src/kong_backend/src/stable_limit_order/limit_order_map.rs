@@ -0,0 +1,56 @@
+use super::stable_limit_order::{LimitOrderStatus, StableLimitOrder, StableLimitOrderId};
+
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_memory::LIMIT_ORDER_MAP;
+
+pub fn get_by_order_id(order_id: u64) -> Option<StableLimitOrder> {
+    LIMIT_ORDER_MAP.with(|m| m.borrow().get(&StableLimitOrderId(order_id)))
+}
+
+/// get all limit orders of a user, most recent first
+pub fn get_by_user_id(user_id: u32) -> Vec<StableLimitOrder> {
+    LIMIT_ORDER_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .rev()
+            .filter_map(|(_, v)| if v.user_id == user_id { Some(v) } else { None })
+            .collect()
+    })
+}
+
+/// number of open limit orders of a user
+pub fn count_open_by_user_id(user_id: u32) -> usize {
+    LIMIT_ORDER_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .filter(|(_, v)| v.user_id == user_id && v.status == LimitOrderStatus::Open)
+            .count()
+    })
+}
+
+/// get all open limit orders, oldest first
+pub fn get_open() -> Vec<StableLimitOrder> {
+    LIMIT_ORDER_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .filter_map(|(_, v)| if v.status == LimitOrderStatus::Open { Some(v) } else { None })
+            .collect()
+    })
+}
+
+pub fn insert(limit_order: &StableLimitOrder) -> u64 {
+    LIMIT_ORDER_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let order_id = kong_settings_map::inc_limit_order_map_idx();
+        let insert_limit_order = StableLimitOrder {
+            order_id,
+            ..limit_order.clone()
+        };
+        map.insert(StableLimitOrderId(order_id), insert_limit_order);
+        order_id
+    })
+}
+
+pub fn update(limit_order: &StableLimitOrder) {
+    LIMIT_ORDER_MAP.with(|m| m.borrow_mut().insert(StableLimitOrderId(limit_order.order_id), limit_order.clone()));
+}
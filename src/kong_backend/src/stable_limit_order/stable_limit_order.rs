@@ -0,0 +1,107 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::nat_helpers::nat_zero;
+use crate::ic::address::Address;
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableLimitOrderId(pub u64);
+
+impl Storable for StableLimitOrderId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LimitOrderStatus {
+    Open,
+    Filled,
+    Cancelled,
+    Expired,
+}
+
+impl std::fmt::Display for LimitOrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitOrderStatus::Open => write!(f, "Open"),
+            LimitOrderStatus::Filled => write!(f, "Filled"),
+            LimitOrderStatus::Cancelled => write!(f, "Cancelled"),
+            LimitOrderStatus::Expired => write!(f, "Expired"),
+        }
+    }
+}
+
+/// limit order resting against the pools
+/// pay_amount is escrowed by Kong when the order is placed and is swapped once the mid price reaches
+/// receive_amount / pay_amount. orders can be filled in parts, each fill is a swap with its own request
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableLimitOrder {
+    pub order_id: u64,
+    pub user_id: u32,
+    pub status: LimitOrderStatus,
+    pub pay_token_id: u32,
+    pub pay_amount: Nat,           // escrowed amount of pay token
+    pub remaining_pay_amount: Nat, // escrowed amount not filled yet
+    pub receive_token_id: u32,
+    pub receive_amount: Nat,  // minimum amount of receive token for the full pay_amount
+    pub received_amount: Nat, // amount of receive token received from fills
+    pub to_address: Address,  // address to send the receive token to
+    pub expires_at: Option<u64>,
+    pub request_id: u64,            // request of the order placement
+    pub fill_request_ids: Vec<u64>, // requests of the swaps filling the order
+    pub claim_id: Option<u64>,      // claim refunding the remaining pay token of cancelled or expired orders
+    pub ts: u64,
+}
+
+impl StableLimitOrder {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        user_id: u32,
+        pay_token_id: u32,
+        pay_amount: &Nat,
+        receive_token_id: u32,
+        receive_amount: &Nat,
+        to_address: &Address,
+        expires_at: Option<u64>,
+        request_id: u64,
+        ts: u64,
+    ) -> Self {
+        Self {
+            order_id: 0, // will be set with insert into LIMIT_ORDER_MAP
+            user_id,
+            status: LimitOrderStatus::Open,
+            pay_token_id,
+            pay_amount: pay_amount.clone(),
+            remaining_pay_amount: pay_amount.clone(),
+            receive_token_id,
+            receive_amount: receive_amount.clone(),
+            received_amount: nat_zero(),
+            to_address: to_address.clone(),
+            expires_at,
+            request_id,
+            fill_request_ids: Vec::new(),
+            claim_id: None,
+            ts,
+        }
+    }
+}
+
+impl Storable for StableLimitOrder {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
@@ -0,0 +1,41 @@
+use icrc_ledger_types::icrc1::account::Account;
+
+use super::stable_lp_allowance::{StableLPAllowance, StableLPAllowanceId};
+
+use crate::stable_memory::LP_ALLOWANCE_MAP;
+
+/// allowance of spender on the owner's LP token. expired allowances are not returned
+pub fn get(token_id: u32, owner_user_id: u32, spender: &Account, ts: u64) -> Option<StableLPAllowance> {
+    LP_ALLOWANCE_MAP.with(|m| {
+        m.borrow()
+            .get(&StableLPAllowanceId {
+                token_id,
+                owner_user_id,
+                spender: *spender,
+            })
+            .filter(|allowance| !allowance.is_expired(ts))
+    })
+}
+
+pub fn update(allowance: &StableLPAllowance) {
+    LP_ALLOWANCE_MAP.with(|m| {
+        m.borrow_mut().insert(
+            StableLPAllowanceId {
+                token_id: allowance.token_id,
+                owner_user_id: allowance.owner_user_id,
+                spender: allowance.spender,
+            },
+            allowance.clone(),
+        )
+    });
+}
+
+pub fn remove(token_id: u32, owner_user_id: u32, spender: &Account) {
+    LP_ALLOWANCE_MAP.with(|m| {
+        m.borrow_mut().remove(&StableLPAllowanceId {
+            token_id,
+            owner_user_id,
+            spender: *spender,
+        })
+    });
+}
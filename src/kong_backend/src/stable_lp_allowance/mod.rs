@@ -0,0 +1,3 @@
+pub mod lp_allowance_map;
+#[allow(clippy::module_inception)]
+pub mod stable_lp_allowance;
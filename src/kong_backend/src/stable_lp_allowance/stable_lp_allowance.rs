@@ -0,0 +1,53 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::{Deserialize, Serialize};
+
+/// allowances are ordered by token_id of the LP token, owner and then spender
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableLPAllowanceId {
+    pub token_id: u32,
+    pub owner_user_id: u32,
+    pub spender: Account,
+}
+
+impl Storable for StableLPAllowanceId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// ICRC-2 allowance of spender to transfer the owner's LP tokens
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableLPAllowance {
+    pub token_id: u32,
+    pub owner_user_id: u32,
+    pub spender: Account,
+    pub amount: Nat,
+    pub expires_at: Option<u64>,
+    pub ts: u64, // timestamp of the last allowance update
+}
+
+impl StableLPAllowance {
+    pub fn is_expired(&self, ts: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= ts)
+    }
+}
+
+impl Storable for StableLPAllowance {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
@@ -0,0 +1,121 @@
+use candid::{Nat, Principal};
+use icrc_ledger_types::icrc1::account::Account;
+use serde_bytes::ByteBuf;
+
+use super::stable_lp_block::{LPBlockType, StableLPBlock, StableLPBlockId};
+
+use crate::helpers::lp_transfer_helpers::find_duplicate_block;
+use crate::stable_memory::LP_BLOCK_MAP;
+use crate::stable_user::user_map;
+
+/// number of blocks in the log of the LP token
+pub fn get_log_length(token_id: u32) -> u64 {
+    LP_BLOCK_MAP.with(|m| {
+        m.borrow()
+            .range(
+                StableLPBlockId { token_id, block_id: 0 }..=StableLPBlockId {
+                    token_id,
+                    block_id: u64::MAX,
+                },
+            )
+            .next_back()
+            .map_or(0, |(k, _)| k.block_id + 1)
+    })
+}
+
+/// blocks of the LP token from start up to length blocks
+pub fn get_range(token_id: u32, start: u64, length: u64) -> Vec<StableLPBlock> {
+    LP_BLOCK_MAP.with(|m| {
+        m.borrow()
+            .range(
+                StableLPBlockId { token_id, block_id: start }..=StableLPBlockId {
+                    token_id,
+                    block_id: u64::MAX,
+                },
+            )
+            .take(length as usize)
+            .map(|(_, v)| v)
+            .collect()
+    })
+}
+
+/// block_id of the most recent block of the LP token at or after since_ts that matches
+pub fn find_duplicate(token_id: u32, since_ts: u64, matches: impl Fn(&StableLPBlock) -> bool) -> Option<u64> {
+    LP_BLOCK_MAP.with(|m| {
+        let map = m.borrow();
+        let blocks = map
+            .range(
+                StableLPBlockId { token_id, block_id: 0 }..=StableLPBlockId {
+                    token_id,
+                    block_id: u64::MAX,
+                },
+            )
+            .rev()
+            .map(|(_, v)| v);
+        find_duplicate_block(blocks, since_ts, matches)
+    })
+}
+
+/// append the block to the log of its LP token, chained to the hash of the previous block
+/// returns the block_id
+pub fn insert(block: &StableLPBlock) -> u64 {
+    LP_BLOCK_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let token_id = block.token_id;
+        let (block_id, phash) = match map
+            .range(
+                StableLPBlockId { token_id, block_id: 0 }..=StableLPBlockId {
+                    token_id,
+                    block_id: u64::MAX,
+                },
+            )
+            .next_back()
+        {
+            Some((_, last_block)) => (
+                last_block.block_id + 1,
+                Some(ByteBuf::from(last_block.to_icrc3_value().hash().to_vec())),
+            ),
+            None => (0, None),
+        };
+        let insert_block = StableLPBlock {
+            block_id,
+            phash,
+            ..block.clone()
+        };
+        map.insert(StableLPBlockId { token_id, block_id }, insert_block);
+        block_id
+    })
+}
+
+/// default account of the user
+pub fn user_account(user_id: u32) -> Option<Account> {
+    let user = user_map::get_by_user_id(user_id)?;
+    let owner = Principal::from_text(&user.principal_id).ok()?;
+    Some(Account::from(owner))
+}
+
+/// record LP tokens minted to a user when adding liquidity
+pub fn insert_mint(token_id: u32, to_user_id: u32, amount: &Nat, ts: u64) -> u64 {
+    insert(&StableLPBlock::new(
+        token_id,
+        LPBlockType::Mint,
+        None,
+        user_account(to_user_id),
+        None,
+        amount,
+        ts,
+    ))
+}
+
+/// record LP tokens burned from a user when removing liquidity
+pub fn insert_burn(token_id: u32, from_user_id: u32, amount: &Nat, ts: u64) -> u64 {
+    insert(&StableLPBlock::new(
+        token_id,
+        LPBlockType::Burn,
+        user_account(from_user_id),
+        None,
+        None,
+        amount,
+        ts,
+    ))
+}
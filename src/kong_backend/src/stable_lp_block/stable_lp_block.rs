@@ -0,0 +1,151 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use icrc_ledger_types::icrc::generic_value::{ICRC3Map, ICRC3Value};
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::Memo;
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+
+/// blocks are ordered by token_id of the LP token and then block_id. each LP token has its own block log
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableLPBlockId {
+    pub token_id: u32,
+    pub block_id: u64,
+}
+
+impl Storable for StableLPBlockId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LPBlockType {
+    Mint,
+    Burn,
+    Transfer,
+    Approve,
+    TransferFrom,
+}
+
+impl LPBlockType {
+    /// ICRC-3 block type
+    pub fn btype(&self) -> &str {
+        match self {
+            LPBlockType::Mint => "1mint",
+            LPBlockType::Burn => "1burn",
+            LPBlockType::Transfer => "1xfer",
+            LPBlockType::Approve => "2approve",
+            LPBlockType::TransferFrom => "2xfer",
+        }
+    }
+}
+
+/// a block of the ICRC-3 log of an LP token. LP tokens have no fees
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableLPBlock {
+    pub token_id: u32,
+    pub block_id: u64,
+    pub block_type: LPBlockType,
+    pub from: Option<Account>,
+    pub to: Option<Account>,
+    pub spender: Option<Account>,
+    pub amount: Nat,
+    pub expected_allowance: Option<Nat>, // Approve only
+    pub expires_at: Option<u64>,         // Approve only
+    pub memo: Option<Memo>,
+    pub created_at_time: Option<u64>,
+    pub phash: Option<ByteBuf>, // hash of the previous block of the LP token
+    pub ts: u64,
+}
+
+impl StableLPBlock {
+    pub fn new(
+        token_id: u32,
+        block_type: LPBlockType,
+        from: Option<Account>,
+        to: Option<Account>,
+        spender: Option<Account>,
+        amount: &Nat,
+        ts: u64,
+    ) -> Self {
+        Self {
+            token_id,
+            block_id: 0,
+            block_type,
+            from,
+            to,
+            spender,
+            amount: amount.clone(),
+            expected_allowance: None,
+            expires_at: None,
+            memo: None,
+            created_at_time: None,
+            phash: None,
+            ts,
+        }
+    }
+
+    /// ICRC-3 representation of the block
+    pub fn to_icrc3_value(&self) -> ICRC3Value {
+        let mut tx = ICRC3Map::new();
+        tx.insert("amt".to_string(), ICRC3Value::Nat(self.amount.clone()));
+        if let Some(from) = &self.from {
+            tx.insert("from".to_string(), account_value(from));
+        }
+        if let Some(to) = &self.to {
+            tx.insert("to".to_string(), account_value(to));
+        }
+        if let Some(spender) = &self.spender {
+            tx.insert("spender".to_string(), account_value(spender));
+        }
+        if let Some(expected_allowance) = &self.expected_allowance {
+            tx.insert("expected_allowance".to_string(), ICRC3Value::Nat(expected_allowance.clone()));
+        }
+        if let Some(expires_at) = self.expires_at {
+            tx.insert("expires_at".to_string(), ICRC3Value::Nat(Nat::from(expires_at)));
+        }
+        if let Some(memo) = &self.memo {
+            tx.insert("memo".to_string(), ICRC3Value::Blob(memo.0.clone()));
+        }
+        if let Some(created_at_time) = self.created_at_time {
+            tx.insert("ts".to_string(), ICRC3Value::Nat(Nat::from(created_at_time)));
+        }
+
+        let mut block = ICRC3Map::new();
+        block.insert("btype".to_string(), ICRC3Value::Text(self.block_type.btype().to_string()));
+        block.insert("ts".to_string(), ICRC3Value::Nat(Nat::from(self.ts)));
+        if let Some(phash) = &self.phash {
+            block.insert("phash".to_string(), ICRC3Value::Blob(phash.clone()));
+        }
+        block.insert("tx".to_string(), ICRC3Value::Map(tx));
+        ICRC3Value::Map(block)
+    }
+}
+
+/// ICRC-3 representation of an account. the subaccount is omitted if not set
+fn account_value(account: &Account) -> ICRC3Value {
+    let mut value = vec![ICRC3Value::Blob(ByteBuf::from(account.owner.as_slice().to_vec()))];
+    if let Some(subaccount) = account.subaccount {
+        value.push(ICRC3Value::Blob(ByteBuf::from(subaccount.to_vec())));
+    }
+    ICRC3Value::Array(value)
+}
+
+impl Storable for StableLPBlock {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
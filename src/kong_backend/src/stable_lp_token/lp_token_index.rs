@@ -0,0 +1,53 @@
+use candid::Nat;
+
+use super::stable_lp_token::StableLPToken;
+
+use crate::helpers::lp_token_index_helpers::{index_lp_token, remove_total_supply, unindex_lp_token, update_total_supply};
+use crate::helpers::nat_helpers::nat_zero;
+use crate::stable_memory::{LP_TOKEN_MAP, LP_TOKEN_SUPPLY_MAP, LP_TOKEN_TOKEN_MAP, LP_TOKEN_USER_MAP};
+
+/// rebuild the indexes of LP_TOKEN_MAP. called on init and post_upgrade
+pub fn create_lp_token_index() {
+    LP_TOKEN_USER_MAP.with(|m| m.borrow_mut().clear());
+    LP_TOKEN_TOKEN_MAP.with(|m| m.borrow_mut().clear());
+    LP_TOKEN_SUPPLY_MAP.with(|m| m.borrow_mut().clear());
+    LP_TOKEN_MAP.with(|m| {
+        let lp_token_map = m.borrow();
+        lp_token_map.iter().for_each(|(_, lp_token)| update(None, &lp_token));
+    });
+}
+
+/// lp_token_id of the user's LP token
+pub fn get_lp_token_id(user_id: u32, token_id: u32) -> Option<u64> {
+    LP_TOKEN_USER_MAP.with(|m| m.borrow().get(&(user_id, token_id)).copied())
+}
+
+/// lp_token_ids of all LP tokens of the user
+pub fn get_lp_token_ids_by_user_id(user_id: u32) -> Vec<u64> {
+    LP_TOKEN_USER_MAP.with(|m| m.borrow().range((user_id, 0)..=(user_id, u32::MAX)).map(|(_, v)| *v).collect())
+}
+
+/// lp_token_ids of all holders of the LP token
+pub fn get_lp_token_ids_by_token_id(token_id: u32) -> Vec<u64> {
+    LP_TOKEN_TOKEN_MAP.with(|m| m.borrow().range((token_id, 0)..=(token_id, u32::MAX)).map(|(_, v)| *v).collect())
+}
+
+pub fn get_total_supply(token_id: u32) -> Nat {
+    LP_TOKEN_SUPPLY_MAP.with(|m| m.borrow().get(&token_id).cloned().unwrap_or_else(nat_zero))
+}
+
+/// update the indexes with lp_token replacing old_lp_token
+pub fn update(old_lp_token: Option<&StableLPToken>, lp_token: &StableLPToken) {
+    LP_TOKEN_USER_MAP.with(|user_map| {
+        LP_TOKEN_TOKEN_MAP.with(|token_map| index_lp_token(&mut user_map.borrow_mut(), &mut token_map.borrow_mut(), old_lp_token, lp_token))
+    });
+    LP_TOKEN_SUPPLY_MAP.with(|m| update_total_supply(&mut m.borrow_mut(), old_lp_token, lp_token));
+}
+
+/// remove lp_token from the indexes
+pub fn remove(lp_token: &StableLPToken) {
+    LP_TOKEN_USER_MAP.with(|user_map| {
+        LP_TOKEN_TOKEN_MAP.with(|token_map| unindex_lp_token(&mut user_map.borrow_mut(), &mut token_map.borrow_mut(), lp_token))
+    });
+    LP_TOKEN_SUPPLY_MAP.with(|m| remove_total_supply(&mut m.borrow_mut(), lp_token));
+}
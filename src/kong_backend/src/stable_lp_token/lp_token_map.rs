@@ -1,8 +1,8 @@
 use candid::Nat;
 
+use super::lp_token_index;
 use super::stable_lp_token::{StableLPToken, StableLPTokenId};
 
-use crate::helpers::nat_helpers::{nat_add, nat_zero};
 use crate::ic::logging::error_log;
 use crate::stable_kong_settings::kong_settings_map;
 use crate::stable_memory::LP_TOKEN_MAP;
@@ -16,25 +16,36 @@ pub fn get_by_token_id(token_id: u32) -> Option<StableLPToken> {
 
 /// get lp_token for specific user
 pub fn get_by_token_id_by_user_id(token_id: u32, user_id: u32) -> Option<StableLPToken> {
+    let lp_token_id = lp_token_index::get_lp_token_id(user_id, token_id)?;
+    LP_TOKEN_MAP.with(|m| m.borrow().get(&StableLPTokenId(lp_token_id)))
+}
+
+/// get all lp_tokens of a user
+pub fn get_by_user_id(user_id: u32) -> Vec<StableLPToken> {
     LP_TOKEN_MAP.with(|m| {
-        m.borrow().iter().find_map(|(_, v)| {
-            if v.user_id == user_id && v.token_id == token_id {
-                return Some(v);
-            }
-            None
-        })
+        let map = m.borrow();
+        lp_token_index::get_lp_token_ids_by_user_id(user_id)
+            .into_iter()
+            .filter_map(|lp_token_id| map.get(&StableLPTokenId(lp_token_id)))
+            .collect()
     })
 }
 
-pub fn get_total_supply(token_id: u32) -> Nat {
+/// get lp_tokens of all holders of the LP token
+pub fn get_by_token_id_holders(token_id: u32) -> Vec<StableLPToken> {
     LP_TOKEN_MAP.with(|m| {
-        m.borrow()
-            .iter()
-            .filter_map(|(_, v)| if v.token_id == token_id { Some(v.amount) } else { None })
-            .fold(nat_zero(), |acc, x| nat_add(&acc, &x))
+        let map = m.borrow();
+        lp_token_index::get_lp_token_ids_by_token_id(token_id)
+            .into_iter()
+            .filter_map(|lp_token_id| map.get(&StableLPTokenId(lp_token_id)))
+            .collect()
     })
 }
 
+pub fn get_total_supply(token_id: u32) -> Nat {
+    lp_token_index::get_total_supply(token_id)
+}
+
 pub fn insert(lp_token: &StableLPToken) -> Result<u64, String> {
     let insert_lp_token = LP_TOKEN_MAP.with(|m| {
         let mut map = m.borrow_mut();
@@ -43,7 +54,8 @@ pub fn insert(lp_token: &StableLPToken) -> Result<u64, String> {
             lp_token_id,
             ..lp_token.clone()
         };
-        map.insert(StableLPTokenId(lp_token_id), insert_lp_token.clone());
+        let old_lp_token = map.insert(StableLPTokenId(lp_token_id), insert_lp_token.clone());
+        lp_token_index::update(old_lp_token.as_ref(), &insert_lp_token);
         insert_lp_token
     });
 
@@ -52,10 +64,20 @@ pub fn insert(lp_token: &StableLPToken) -> Result<u64, String> {
 }
 
 pub fn update(lp_token: &StableLPToken) {
-    LP_TOKEN_MAP.with(|m| m.borrow_mut().insert(StableLPTokenId(lp_token.lp_token_id), lp_token.clone()));
+    LP_TOKEN_MAP.with(|m| {
+        let old_lp_token = m.borrow_mut().insert(StableLPTokenId(lp_token.lp_token_id), lp_token.clone());
+        lp_token_index::update(old_lp_token.as_ref(), lp_token);
+    });
     _ = archive_to_kong_data(lp_token);
 }
 
+/// remove lp_token. only used to clean up LP tokens with zero balance
+pub fn remove(lp_token_id: u64) -> Option<StableLPToken> {
+    let lp_token = LP_TOKEN_MAP.with(|m| m.borrow_mut().remove(&StableLPTokenId(lp_token_id)))?;
+    lp_token_index::remove(&lp_token);
+    Some(lp_token)
+}
+
 pub fn archive_to_kong_data(lp_token: &StableLPToken) -> Result<(), String> {
     if !kong_settings_map::get().archive_to_kong_data {
         return Ok(());
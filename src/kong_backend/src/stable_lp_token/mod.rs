@@ -1,3 +1,4 @@
+pub mod lp_token_index;
 pub mod lp_token_map;
 #[allow(clippy::module_inception)]
 pub mod stable_lp_token;
@@ -6,6 +6,8 @@ use super::stable_lp_token::StableLPToken;
 
 use crate::helpers::nat_helpers::{nat_add, nat_subtract};
 use crate::ic::get_time::get_time;
+use crate::stable_lp_block::lp_block_map;
+use crate::stable_lp_block::stable_lp_block::{LPBlockType, StableLPBlock};
 
 /// transfer LP token from caller to another user
 ///
@@ -20,32 +22,57 @@ use crate::ic::get_time::get_time;
 pub fn transfer(token_id: u32, to_user_id: u32, amount: &Nat) -> Result<StableLPToken, String> {
     let ts = get_time();
 
-    let from_user = match lp_token_map::get_by_token_id(token_id) {
-        Some(from_user_lp_token) => {
-            if from_user_lp_token.amount < *amount {
-                return Err("Not enough LP token".to_string());
-            }
-            let amount = nat_subtract(&from_user_lp_token.amount, amount).ok_or("Error calculating new user balance")?;
-            StableLPToken {
-                amount,
-                ts,
-                ..from_user_lp_token
-            }
-        }
-        None => return Err("Not enough LP token".to_string()),
-    };
-    update(&from_user);
+    let from_user_id = lp_token_map::get_by_token_id(token_id).ok_or("Not enough LP token")?.user_id;
+    let from_user = transfer_by_user_id(token_id, from_user_id, to_user_id, amount, ts)?;
 
+    lp_block_map::insert(&StableLPBlock::new(
+        token_id,
+        LPBlockType::Transfer,
+        lp_block_map::user_account(from_user_id),
+        lp_block_map::user_account(to_user_id),
+        None,
+        amount,
+        ts,
+    ));
+
+    Ok(from_user)
+}
+
+/// transfer LP token between 2 users. does not record a block
+///
+/// # Returns
+/// StableLPToken - updated LP token of from_user_id
+/// Err - if LP token not found or not enough LP token
+pub fn transfer_by_user_id(token_id: u32, from_user_id: u32, to_user_id: u32, amount: &Nat, ts: u64) -> Result<StableLPToken, String> {
+    let from_user = debit_by_user_id(token_id, from_user_id, amount, ts)?;
+    credit_by_user_id(token_id, to_user_id, amount, ts)?;
+    Ok(from_user)
+}
+
+/// take amount of LP token from user_id's balance. does not record a block
+///
+/// # Returns
+/// StableLPToken - updated LP token of user_id
+/// Err - if LP token not found or not enough LP token
+pub fn debit_by_user_id(token_id: u32, user_id: u32, amount: &Nat, ts: u64) -> Result<StableLPToken, String> {
+    let lp_token = get_by_token_id_by_user_id(token_id, user_id).ok_or("Not enough LP token")?;
+    let amount = nat_subtract(&lp_token.amount, amount).ok_or("Not enough LP token")?;
+    let lp_token = StableLPToken { amount, ts, ..lp_token };
+    update(&lp_token);
+    Ok(lp_token)
+}
+
+/// add amount of LP token to user_id's balance, creating it if user_id has none. does not record a block
+pub fn credit_by_user_id(token_id: u32, user_id: u32, amount: &Nat, ts: u64) -> Result<(), String> {
     // get user's LP token balance if already exists or create new
-    if let Some(to_user_lp_token) = get_by_token_id_by_user_id(token_id, to_user_id) {
+    if let Some(lp_token) = get_by_token_id_by_user_id(token_id, user_id) {
         update(&StableLPToken {
-            amount: nat_add(&to_user_lp_token.amount, amount),
+            amount: nat_add(&lp_token.amount, amount),
             ts,
-            ..to_user_lp_token
+            ..lp_token
         });
     } else {
-        insert(&StableLPToken::new(to_user_id, token_id, amount.clone(), ts))?;
+        insert(&StableLPToken::new(user_id, token_id, amount.clone(), ts))?;
     }
-
-    Ok(from_user)
+    Ok(())
 }
@@ -1,3 +1,4 @@
+use candid::Nat;
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
 use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap, StableCell};
 use std::cell::RefCell;
@@ -5,14 +6,26 @@ use std::collections::BTreeMap;
 
 use crate::stable_claim::stable_claim::{StableClaim, StableClaimId};
 use crate::stable_kong_settings::stable_kong_settings::StableKongSettings;
+use crate::stable_limit_order::stable_limit_order::{StableLimitOrder, StableLimitOrderId};
+use crate::stable_lp_allowance::stable_lp_allowance::{StableLPAllowance, StableLPAllowanceId};
+use crate::stable_lp_block::stable_lp_block::{StableLPBlock, StableLPBlockId};
 use crate::stable_lp_token::stable_lp_token::{StableLPToken, StableLPTokenId};
+use crate::stable_observation::stable_observation::{StableObservation, StableObservationId};
 use crate::stable_pool::stable_pool::{StablePool, StablePoolId};
+use crate::stable_pool_stats::stable_pool_stats::{StablePoolStats, StablePoolStatsId};
+use crate::stable_pool_update::stable_pool_update::{StablePoolUpdate, StablePoolUpdateId};
+use crate::stable_position::stable_position::{StablePosition, StablePositionId};
+use crate::stable_referral::stable_referral::{StableReferral, StableReferralId};
 use crate::stable_request::stable_request::{StableRequest, StableRequestId};
+use crate::stable_tick::stable_tick::{StableTick, StableTickId};
 use crate::stable_token::stable_token::{StableToken, StableTokenId};
 use crate::stable_transfer::stable_transfer::{StableTransfer, StableTransferId};
 use crate::stable_tx::stable_tx::{StableTx, StableTxId};
 use crate::stable_user::banned_user_map::BannedUser;
 use crate::stable_user::stable_user::{StableUser, StableUserId};
+use crate::stable_user_volume::stable_user_volume::{StableUserVolume, StableUserVolumeId};
+use crate::stable_webhook::stable_webhook::{StableWebhook, StableWebhookId};
+use crate::stable_webhook::stable_webhook_delivery::{StableWebhookDelivery, StableWebhookDeliveryId};
 
 type Memory = VirtualMemory<DefaultMemoryImpl>;
 
@@ -26,6 +39,18 @@ pub const REQUEST_MEMORY_ID: MemoryId = MemoryId::new(26);
 pub const TRANSFER_MEMORY_ID: MemoryId = MemoryId::new(27);
 pub const CLAIM_MEMORY_ID: MemoryId = MemoryId::new(28);
 pub const LP_TOKEN_MEMORY_ID: MemoryId = MemoryId::new(29);
+pub const POSITION_MEMORY_ID: MemoryId = MemoryId::new(30);
+pub const TICK_MEMORY_ID: MemoryId = MemoryId::new(31);
+pub const LIMIT_ORDER_MEMORY_ID: MemoryId = MemoryId::new(32);
+pub const OBSERVATION_MEMORY_ID: MemoryId = MemoryId::new(33);
+pub const POOL_STATS_MEMORY_ID: MemoryId = MemoryId::new(34);
+pub const LP_ALLOWANCE_MEMORY_ID: MemoryId = MemoryId::new(35);
+pub const LP_BLOCK_MEMORY_ID: MemoryId = MemoryId::new(36);
+pub const REFERRAL_MEMORY_ID: MemoryId = MemoryId::new(37);
+pub const POOL_UPDATE_MEMORY_ID: MemoryId = MemoryId::new(38);
+pub const WEBHOOK_MEMORY_ID: MemoryId = MemoryId::new(39);
+pub const WEBHOOK_DELIVERY_MEMORY_ID: MemoryId = MemoryId::new(40);
+pub const USER_VOLUME_MEMORY_ID: MemoryId = MemoryId::new(41);
 // archives
 pub const TX_ARCHIVE_MEMORY_ID: MemoryId = MemoryId::new(204);
 pub const REQUEST_ARCHIVE_MEMORY_ID: MemoryId = MemoryId::new(205);
@@ -35,6 +60,18 @@ thread_local! {
     // static variable to store the map of principal_id to user_id
     pub static PRINCIPAL_ID_MAP: RefCell<BTreeMap<String, u32>> = RefCell::default();
 
+    // static variable to store the map of (user_id, token_id) to lp_token_id of LP_TOKEN_MAP
+    pub static LP_TOKEN_USER_MAP: RefCell<BTreeMap<(u32, u32), u64>> = RefCell::default();
+
+    // static variable to store the map of (token_id, user_id) to lp_token_id of LP_TOKEN_MAP
+    pub static LP_TOKEN_TOKEN_MAP: RefCell<BTreeMap<(u32, u32), u64>> = RefCell::default();
+
+    // static variable to store the total supply of each LP token by token_id
+    pub static LP_TOKEN_SUPPLY_MAP: RefCell<BTreeMap<u32, Nat>> = RefCell::default();
+
+    // static variable to store the number of referees of each referrer by user_id
+    pub static REFEREE_COUNT_MAP: RefCell<BTreeMap<u32, u32>> = RefCell::default();
+
     // static variable to list of temporary banned users
     pub static BANNED_USERS: RefCell<BTreeMap<u32, BannedUser>> = RefCell::default();
 
@@ -88,6 +125,66 @@ thread_local! {
         RefCell::new(StableBTreeMap::init(memory_manager.get(LP_TOKEN_MEMORY_ID)))
     });
 
+    // stable memory for storing concentrated liquidity positions for users
+    pub static POSITION_MAP: RefCell<StableBTreeMap<StablePositionId, StablePosition, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(POSITION_MEMORY_ID)))
+    });
+
+    // stable memory for storing initialized ticks of concentrated liquidity pools
+    pub static TICK_MAP: RefCell<StableBTreeMap<StableTickId, StableTick, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(TICK_MEMORY_ID)))
+    });
+
+    // stable memory for storing limit orders for users
+    pub static LIMIT_ORDER_MAP: RefCell<StableBTreeMap<StableLimitOrderId, StableLimitOrder, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(LIMIT_ORDER_MEMORY_ID)))
+    });
+
+    // stable memory for storing ring buffers of cumulative price observations of pools
+    pub static OBSERVATION_MAP: RefCell<StableBTreeMap<StableObservationId, StableObservation, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(OBSERVATION_MEMORY_ID)))
+    });
+
+    // stable memory for storing hourly swap stats of pools
+    pub static POOL_STATS_MAP: RefCell<StableBTreeMap<StablePoolStatsId, StablePoolStats, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(POOL_STATS_MEMORY_ID)))
+    });
+
+    // stable memory for storing ICRC-2 allowances of LP tokens
+    pub static LP_ALLOWANCE_MAP: RefCell<StableBTreeMap<StableLPAllowanceId, StableLPAllowance, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(LP_ALLOWANCE_MEMORY_ID)))
+    });
+
+    // stable memory for storing ICRC-3 block logs of LP tokens
+    pub static LP_BLOCK_MAP: RefCell<StableBTreeMap<StableLPBlockId, StableLPBlock, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(LP_BLOCK_MEMORY_ID)))
+    });
+
+    // stable memory for storing referral rewards of referrers
+    pub static REFERRAL_MAP: RefCell<StableBTreeMap<StableReferralId, StableReferral, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(REFERRAL_MEMORY_ID)))
+    });
+
+    // stable memory for storing scheduled and applied updates of pool parameters
+    pub static POOL_UPDATE_MAP: RefCell<StableBTreeMap<StablePoolUpdateId, StablePoolUpdate, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(POOL_UPDATE_MEMORY_ID)))
+    });
+
+    // stable memory for storing users' webhooks notified of their requests' results
+    pub static WEBHOOK_MAP: RefCell<StableBTreeMap<StableWebhookId, StableWebhook, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(WEBHOOK_MEMORY_ID)))
+    });
+
+    // stable memory for storing the outbox of webhook deliveries
+    pub static WEBHOOK_DELIVERY_MAP: RefCell<StableBTreeMap<StableWebhookDeliveryId, StableWebhookDelivery, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(WEBHOOK_DELIVERY_MEMORY_ID)))
+    });
+
+    // stable memory for storing daily swap volumes of users for their fee tiers
+    pub static USER_VOLUME_MAP: RefCell<StableBTreeMap<StableUserVolumeId, StableUserVolume, Memory>> = with_memory_manager(|memory_manager| {
+        RefCell::new(StableBTreeMap::init(memory_manager.get(USER_VOLUME_MEMORY_ID)))
+    });
+
     //
     // Archive Stable Memory
     //
@@ -0,0 +1,3 @@
+pub mod observation_map;
+#[allow(clippy::module_inception)]
+pub mod stable_observation;
@@ -0,0 +1,30 @@
+use super::stable_observation::{StableObservation, StableObservationId};
+
+use crate::stable_memory::OBSERVATION_MAP;
+
+pub fn get(pool_id: u32, slot: u32) -> Option<StableObservation> {
+    OBSERVATION_MAP.with(|m| m.borrow().get(&StableObservationId { pool_id, slot }))
+}
+
+/// most recent observation of the pool at or before ts
+pub fn get_at_or_before(pool_id: u32, ts: u64) -> Option<StableObservation> {
+    OBSERVATION_MAP.with(|m| {
+        m.borrow()
+            .range(StableObservationId { pool_id, slot: 0 }..=StableObservationId { pool_id, slot: u32::MAX })
+            .map(|(_, v)| v)
+            .filter(|v| v.ts <= ts)
+            .max_by_key(|v| v.ts)
+    })
+}
+
+pub fn update(slot: u32, observation: &StableObservation) {
+    OBSERVATION_MAP.with(|m| {
+        m.borrow_mut().insert(
+            StableObservationId {
+                pool_id: observation.pool_id,
+                slot,
+            },
+            observation.clone(),
+        )
+    });
+}
@@ -0,0 +1,43 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+/// observations are ordered by pool_id and then slot of the pool's ring buffer
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableObservationId {
+    pub pool_id: u32,
+    pub slot: u32,
+}
+
+impl Storable for StableObservationId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// cumulative prices of a pool at ts. used to calculate the time-weighted average price between 2 observations
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableObservation {
+    pub pool_id: u32,
+    pub price_0_cumulative_x64: Nat,
+    pub price_1_cumulative_x64: Nat,
+    pub ts: u64,
+}
+
+impl Storable for StableObservation {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
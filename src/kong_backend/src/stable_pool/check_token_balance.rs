@@ -2,6 +2,7 @@ use candid::{CandidType, Int, Nat};
 use serde::{Deserialize, Serialize};
 
 use crate::helpers::nat_helpers::nat_add;
+use crate::helpers::nat_helpers::nat_subtract;
 use crate::helpers::nat_helpers::nat_zero;
 use crate::ic::ledger::get_balance;
 use crate::stable_claim::claim_map;
@@ -9,6 +10,7 @@ use crate::stable_claim::stable_claim::ClaimStatus;
 use crate::stable_kong_settings::kong_settings_map;
 use crate::stable_memory::CLAIM_MAP;
 use crate::stable_memory::POOL_MAP;
+use crate::stable_memory::REFERRAL_MAP;
 use crate::stable_token::stable_token::StableToken;
 use crate::stable_token::token::Token;
 
@@ -25,6 +27,7 @@ pub struct ExpectedBalance {
     pub balance: Nat,
     pub pool_balances: Vec<PoolExpectedBalance>,
     pub unclaimed_claims: Nat,
+    pub unclaimed_referral_rewards: Nat,
 }
 
 /// token balance check
@@ -41,14 +44,19 @@ pub async fn check_token_balance(token: &StableToken) -> Result<(StableToken, Na
         balance: nat_zero(),
         pool_balances: Vec::new(),
         unclaimed_claims: nat_zero(),
+        unclaimed_referral_rewards: nat_zero(),
     };
     // iterate over all pools and sum up the balances
     POOL_MAP.with(|m| {
         let map = m.borrow();
         for (_, v) in map.iter() {
             if v.token_0().token_id() == token_id {
-                // expected_balance += v.balance_0 + v.lp_fee_0 + v.kong_fee_0;
-                expected_balance.balance += nat_add(&nat_add(&v.balance_0, &v.lp_fee_0), &v.kong_fee_0);
+                // expected_balance += v.balance_0 + v.lp_fee_0 + v.kong_fee_0 - v.flash_loan_shortfall_0;
+                expected_balance.balance += nat_subtract(
+                    &nat_add(&nat_add(&v.balance_0, &v.lp_fee_0), &v.kong_fee_0),
+                    &v.flash_loan_shortfall_0,
+                )
+                .unwrap_or(nat_zero());
                 expected_balance.pool_balances.push(PoolExpectedBalance {
                     pool_symbol: v.symbol(),
                     balance: v.balance_0,
@@ -56,8 +64,12 @@ pub async fn check_token_balance(token: &StableToken) -> Result<(StableToken, Na
                     kong_fee: v.kong_fee_0,
                 })
             } else if v.token_1().token_id() == token_id {
-                // expected_balance += v.balance_1 + v.lp_fee_1 + v.kong_fee_1;
-                expected_balance.balance += nat_add(&nat_add(&v.balance_1, &v.lp_fee_1), &v.kong_fee_1);
+                // expected_balance += v.balance_1 + v.lp_fee_1 + v.kong_fee_1 - v.flash_loan_shortfall_1;
+                expected_balance.balance += nat_subtract(
+                    &nat_add(&nat_add(&v.balance_1, &v.lp_fee_1), &v.kong_fee_1),
+                    &v.flash_loan_shortfall_1,
+                )
+                .unwrap_or(nat_zero());
                 expected_balance.pool_balances.push(PoolExpectedBalance {
                     pool_symbol: v.symbol(),
                     balance: v.balance_1,
@@ -77,6 +89,16 @@ pub async fn check_token_balance(token: &StableToken) -> Result<(StableToken, Na
         }
     });
 
+    // add referral rewards not yet moved to claims
+    REFERRAL_MAP.with(|m| {
+        let map = m.borrow();
+        for (_, v) in map.iter() {
+            if v.token_id == token_id {
+                expected_balance.unclaimed_referral_rewards += nat_subtract(&v.earned, &v.claimed).unwrap_or(nat_zero());
+            }
+        }
+    });
+
     let actual_balance_int = Int::from(actual_balance.clone());
     let expected_balance_int = Int::from(expected_balance.balance.clone());
     let difference = actual_balance_int - expected_balance_int;
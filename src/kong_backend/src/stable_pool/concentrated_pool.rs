@@ -0,0 +1,332 @@
+use candid::{CandidType, Int, Nat};
+use num_bigint::{BigInt, Sign};
+use num_traits::{Signed, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::stable_pool::StablePool;
+
+use crate::helpers::concentrated_helpers::{
+    amounts_for_liquidity, q128, sqrt_price_at_tick, swap_step, tick_at_sqrt_price, MAX_TICK, MIN_TICK,
+};
+use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_to_bigint, nat_zero};
+use crate::stable_position::stable_position::StablePosition;
+use crate::stable_tick::{stable_tick::StableTick, tick_map};
+
+// max number of swap steps (ticks crossed) in a single swap
+const MAX_SWAP_STEPS: usize = 500;
+
+/// price and in-range liquidity of a concentrated liquidity pool
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct ConcentratedState {
+    pub sqrt_price_x96: Nat,
+    pub tick: i32,
+    pub liquidity: Nat, // liquidity of the positions in range of the current tick
+    pub fee_growth_global_0_x128: Nat,
+    pub fee_growth_global_1_x128: Nat,
+}
+
+impl ConcentratedState {
+    pub fn new(sqrt_price_x96: &Nat) -> Result<Self, String> {
+        let tick = tick_at_sqrt_price(sqrt_price_x96).ok_or("Invalid initial price")?;
+        Ok(Self {
+            sqrt_price_x96: sqrt_price_x96.clone(),
+            tick,
+            liquidity: nat_zero(),
+            fee_growth_global_0_x128: nat_zero(),
+            fee_growth_global_1_x128: nat_zero(),
+        })
+    }
+}
+
+fn bigint_to_nat(n: &BigInt) -> Option<Nat> {
+    if n.is_negative() {
+        None?
+    }
+    Some(Nat::from(n.to_biguint()?))
+}
+
+fn add_liquidity_delta(liquidity: &Nat, liquidity_delta: &BigInt) -> Result<Nat, String> {
+    bigint_to_nat(&(nat_to_bigint(liquidity) + liquidity_delta)).ok_or("Insufficient liquidity".to_string())
+}
+
+/// check tick_lower and tick_upper are a valid range for the pool
+pub fn check_ticks(pool: &StablePool, tick_lower: i32, tick_upper: i32) -> Result<(), String> {
+    let tick_spacing = pool.tick_spacing().ok_or("Pool is not a concentrated liquidity pool")? as i32;
+    if tick_lower >= tick_upper {
+        Err("Tick_lower must be less than tick_upper".to_string())?
+    }
+    if tick_lower < MIN_TICK || tick_upper > MAX_TICK {
+        Err(format!("Ticks must be between {} and {}", MIN_TICK, MAX_TICK))?
+    }
+    if tick_lower % tick_spacing != 0 || tick_upper % tick_spacing != 0 {
+        Err(format!("Ticks must be multiples of tick spacing {}", tick_spacing))?
+    }
+    Ok(())
+}
+
+/// widest range allowed by the tick spacing of the pool
+pub fn full_range_ticks(tick_spacing: u32) -> (i32, i32) {
+    let tick_spacing = tick_spacing as i32;
+    ((MIN_TICK / tick_spacing) * tick_spacing, (MAX_TICK / tick_spacing) * tick_spacing)
+}
+
+/// fee growth per unit of liquidity inside tick_lower and tick_upper
+/// values can be negative as the fee growth outside of a tick is only relative to when the tick was initialized
+fn fee_growth_inside(pool_id: u32, state: &ConcentratedState, tick_lower: i32, tick_upper: i32) -> (BigInt, BigInt) {
+    let global_0 = nat_to_bigint(&state.fee_growth_global_0_x128);
+    let global_1 = nat_to_bigint(&state.fee_growth_global_1_x128);
+    let lower = tick_map::get(pool_id, tick_lower).unwrap_or(StableTick::new(pool_id, tick_lower));
+    let upper = tick_map::get(pool_id, tick_upper).unwrap_or(StableTick::new(pool_id, tick_upper));
+    let lower_0 = nat_to_bigint(&lower.fee_growth_outside_0_x128);
+    let lower_1 = nat_to_bigint(&lower.fee_growth_outside_1_x128);
+    let upper_0 = nat_to_bigint(&upper.fee_growth_outside_0_x128);
+    let upper_1 = nat_to_bigint(&upper.fee_growth_outside_1_x128);
+
+    let (below_0, below_1) = if state.tick >= tick_lower {
+        (lower_0, lower_1)
+    } else {
+        (&global_0 - lower_0, &global_1 - lower_1)
+    };
+    let (above_0, above_1) = if state.tick < tick_upper {
+        (upper_0, upper_1)
+    } else {
+        (&global_0 - upper_0, &global_1 - upper_1)
+    };
+
+    (global_0 - below_0 - above_0, global_1 - below_1 - above_1)
+}
+
+/// fees earned by liquidity since fee_growth_inside_last
+fn fees_owed(liquidity: &Nat, fee_growth_inside: &BigInt, fee_growth_inside_last: &Int) -> Nat {
+    let fee_growth = fee_growth_inside - &fee_growth_inside_last.0;
+    if fee_growth.is_negative() || fee_growth.is_zero() {
+        return nat_zero();
+    }
+    let fee_growth = bigint_to_nat(&fee_growth).unwrap_or(nat_zero());
+    nat_divide(&nat_multiply(liquidity, &fee_growth), &q128()).unwrap_or(nat_zero())
+}
+
+/// principal amount_0 and amount_1 of a position at the current price, rounded down
+pub fn position_amounts(pool: &StablePool, position: &StablePosition) -> Option<(Nat, Nat)> {
+    let state = pool.concentrated.as_ref()?;
+    let sqrt_price_lower = sqrt_price_at_tick(position.tick_lower)?;
+    let sqrt_price_upper = sqrt_price_at_tick(position.tick_upper)?;
+    amounts_for_liquidity(
+        &state.sqrt_price_x96,
+        &sqrt_price_lower,
+        &sqrt_price_upper,
+        &position.liquidity,
+        false,
+    )
+}
+
+/// uncollected fees (tokens_owed_0, tokens_owed_1) of a position including fees earned since the last update
+pub fn position_fees(pool: &StablePool, position: &StablePosition) -> Option<(Nat, Nat)> {
+    let state = pool.concentrated.as_ref()?;
+    let (inside_0, inside_1) = fee_growth_inside(pool.pool_id, state, position.tick_lower, position.tick_upper);
+    Some((
+        nat_add(
+            &position.tokens_owed_0,
+            &fees_owed(&position.liquidity, &inside_0, &position.fee_growth_inside_0_last_x128),
+        ),
+        nat_add(
+            &position.tokens_owed_1,
+            &fees_owed(&position.liquidity, &inside_1, &position.fee_growth_inside_1_last_x128),
+        ),
+    ))
+}
+
+fn update_tick(pool_id: u32, state: &ConcentratedState, tick: i32, liquidity_delta: &BigInt, upper: bool) -> Result<StableTick, String> {
+    let mut stable_tick = tick_map::get(pool_id, tick).unwrap_or(StableTick::new(pool_id, tick));
+    if nat_is_zero(&stable_tick.liquidity_gross) {
+        // by convention, all fee growth before a tick is initialized happened below the tick
+        if tick <= state.tick {
+            stable_tick.fee_growth_outside_0_x128 = state.fee_growth_global_0_x128.clone();
+            stable_tick.fee_growth_outside_1_x128 = state.fee_growth_global_1_x128.clone();
+        }
+    }
+    stable_tick.liquidity_gross = add_liquidity_delta(&stable_tick.liquidity_gross, liquidity_delta)?;
+    stable_tick.liquidity_net = if upper {
+        Int(&stable_tick.liquidity_net.0 - liquidity_delta)
+    } else {
+        Int(&stable_tick.liquidity_net.0 + liquidity_delta)
+    };
+    Ok(stable_tick)
+}
+
+/// add (positive liquidity_delta) or remove (negative liquidity_delta) liquidity of a position
+/// fees earned by the position are moved to tokens_owed_0 and tokens_owed_1
+/// make sure to call pool_map::update and position_map::update after calling this function
+pub fn update_position(pool: &mut StablePool, position: &mut StablePosition, liquidity_delta: &BigInt, ts: u64) -> Result<(), String> {
+    let pool_id = pool.pool_id;
+    let state = pool.concentrated.as_mut().ok_or("Pool is not a concentrated liquidity pool")?;
+
+    let liquidity = add_liquidity_delta(&position.liquidity, liquidity_delta)?;
+    let tick_lower = update_tick(pool_id, state, position.tick_lower, liquidity_delta, false)?;
+    let tick_upper = update_tick(pool_id, state, position.tick_upper, liquidity_delta, true)?;
+    for stable_tick in [&tick_lower, &tick_upper] {
+        tick_map::update(stable_tick);
+    }
+
+    let (inside_0, inside_1) = fee_growth_inside(pool_id, state, position.tick_lower, position.tick_upper);
+    position.tokens_owed_0 = nat_add(
+        &position.tokens_owed_0,
+        &fees_owed(&position.liquidity, &inside_0, &position.fee_growth_inside_0_last_x128),
+    );
+    position.tokens_owed_1 = nat_add(
+        &position.tokens_owed_1,
+        &fees_owed(&position.liquidity, &inside_1, &position.fee_growth_inside_1_last_x128),
+    );
+    position.fee_growth_inside_0_last_x128 = Int(inside_0);
+    position.fee_growth_inside_1_last_x128 = Int(inside_1);
+    position.liquidity = liquidity;
+    position.ts = ts;
+
+    // ticks no longer used by any position are not needed
+    for stable_tick in [&tick_lower, &tick_upper] {
+        if nat_is_zero(&stable_tick.liquidity_gross) {
+            tick_map::remove(pool_id, stable_tick.tick);
+        }
+    }
+
+    if position.tick_lower <= state.tick && state.tick < position.tick_upper {
+        state.liquidity = add_liquidity_delta(&state.liquidity, liquidity_delta)?;
+    }
+
+    Ok(())
+}
+
+struct SwapStep {
+    liquidity: Nat,
+    amount_out: Nat,
+    cross_tick: Option<i32>,
+}
+
+/// result of walking a swap through the initialized ticks of a pool
+pub struct ConcentratedSwap {
+    pub amount_in: Nat,
+    pub amount_out: Nat,
+    sqrt_price_x96: Nat,
+    tick: i32,
+    liquidity: Nat,
+    steps: Vec<SwapStep>,
+}
+
+/// calculate a swap of a concentrated liquidity pool without updating the pool
+/// zero_for_one is true when paying token_0 and receiving token_1
+/// amount is the pay amount if exact_input, otherwise the receive amount. fees are not included
+pub fn swap(pool: &StablePool, zero_for_one: bool, amount: &Nat, exact_input: bool) -> Result<ConcentratedSwap, String> {
+    let state = pool.concentrated.as_ref().ok_or("Pool is not a concentrated liquidity pool")?;
+
+    let mut sqrt_price = state.sqrt_price_x96.clone();
+    let mut tick = state.tick;
+    let mut liquidity = state.liquidity.clone();
+    let mut amount_remaining = amount.clone();
+    let mut amount_in = nat_zero();
+    let mut amount_out = nat_zero();
+    let mut steps = Vec::new();
+
+    while !nat_is_zero(&amount_remaining) {
+        if steps.len() >= MAX_SWAP_STEPS {
+            Err("Swap crosses too many ticks".to_string())?
+        }
+
+        let next_tick = tick_map::next_initialized(pool.pool_id, tick, zero_for_one);
+        let target_tick = next_tick.unwrap_or(if zero_for_one { MIN_TICK } else { MAX_TICK });
+        let sqrt_price_target = sqrt_price_at_tick(target_tick).ok_or("Invalid tick")?;
+
+        let (sqrt_price_next, step_in, step_out) =
+            swap_step(&sqrt_price, &sqrt_price_target, &liquidity, &amount_remaining, exact_input).ok_or("Invalid swap step")?;
+        amount_remaining = if exact_input {
+            nat_subtract(&amount_remaining, &step_in).unwrap_or(nat_zero())
+        } else {
+            nat_subtract(&amount_remaining, &step_out).unwrap_or(nat_zero())
+        };
+        amount_in = nat_add(&amount_in, &step_in);
+        amount_out = nat_add(&amount_out, &step_out);
+
+        let mut step = SwapStep {
+            liquidity: liquidity.clone(),
+            amount_out: step_out,
+            cross_tick: None,
+        };
+        if sqrt_price_next == sqrt_price_target {
+            let Some(next_tick) = next_tick else {
+                // no more liquidity in this direction
+                steps.push(step);
+                sqrt_price = sqrt_price_next;
+                break;
+            };
+            let liquidity_net = tick_map::get(pool.pool_id, next_tick).map_or_else(BigInt::zero, |t| t.liquidity_net.0);
+            let liquidity_delta = if zero_for_one { -liquidity_net } else { liquidity_net };
+            liquidity = add_liquidity_delta(&liquidity, &liquidity_delta)?;
+            tick = if zero_for_one { next_tick - 1 } else { next_tick };
+            step.cross_tick = Some(next_tick);
+        } else {
+            tick = tick_at_sqrt_price(&sqrt_price_next).ok_or("Invalid price")?;
+        }
+        steps.push(step);
+        sqrt_price = sqrt_price_next;
+    }
+
+    if !nat_is_zero(&amount_remaining) {
+        Err(format!(
+            "Insufficient {} in pool",
+            if zero_for_one { pool.symbol_1() } else { pool.symbol_0() }
+        ))?
+    }
+
+    Ok(ConcentratedSwap {
+        amount_in,
+        amount_out,
+        sqrt_price_x96: sqrt_price,
+        tick,
+        liquidity,
+        steps,
+    })
+}
+
+/// apply an exact input swap to a concentrated liquidity pool
+/// lp_fee is the LPs' share of the fee in the receive token, distributed to the liquidity of each step pro-rata to amount_out
+/// make sure to call pool_map::update after calling this function
+pub fn apply_swap(pool: &mut StablePool, zero_for_one: bool, pay_amount: &Nat, lp_fee: &Nat) -> Result<(), String> {
+    let swap = swap(pool, zero_for_one, pay_amount, true)?;
+    let pool_id = pool.pool_id;
+    let state = pool.concentrated.as_mut().ok_or("Pool is not a concentrated liquidity pool")?;
+
+    for step in swap.steps.iter() {
+        if !nat_is_zero(&step.liquidity) && !nat_is_zero(&swap.amount_out) {
+            // step_fee = lp_fee * step.amount_out / amount_out
+            let step_fee = nat_divide(&nat_multiply(lp_fee, &step.amount_out), &swap.amount_out).unwrap_or(nat_zero());
+            let fee_growth = nat_divide(&nat_multiply(&step_fee, &q128()), &step.liquidity).unwrap_or(nat_zero());
+            // fees are in the receive token
+            if zero_for_one {
+                state.fee_growth_global_1_x128 = nat_add(&state.fee_growth_global_1_x128, &fee_growth);
+            } else {
+                state.fee_growth_global_0_x128 = nat_add(&state.fee_growth_global_0_x128, &fee_growth);
+            }
+        }
+        if let Some(cross_tick) = step.cross_tick {
+            // flip the fee growth outside of the crossed tick
+            if let Some(mut stable_tick) = tick_map::get(pool_id, cross_tick) {
+                stable_tick.fee_growth_outside_0_x128 =
+                    nat_subtract(&state.fee_growth_global_0_x128, &stable_tick.fee_growth_outside_0_x128).unwrap_or(nat_zero());
+                stable_tick.fee_growth_outside_1_x128 =
+                    nat_subtract(&state.fee_growth_global_1_x128, &stable_tick.fee_growth_outside_1_x128).unwrap_or(nat_zero());
+                tick_map::update(&stable_tick);
+            }
+        }
+    }
+
+    state.sqrt_price_x96 = swap.sqrt_price_x96;
+    state.tick = swap.tick;
+    state.liquidity = swap.liquidity;
+
+    Ok(())
+}
+
+/// liquidity_delta as a signed BigInt
+pub fn liquidity_delta(liquidity: &Nat, add: bool) -> BigInt {
+    let sign = if add { Sign::Plus } else { Sign::Minus };
+    BigInt::from_biguint(sign, liquidity.0.clone())
+}
@@ -0,0 +1,45 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use super::stable_pool::StablePool;
+
+use crate::helpers::fee_helpers::{dynamic_lp_fee_bps, push_price, realised_volatility_bps};
+
+/// number of recent swap prices used for the realised volatility if not specified
+pub const DEFAULT_DYNAMIC_FEE_WINDOW: u8 = 20;
+
+/// dynamic LP fee of a pool driven by the realised volatility of the prices after its last swaps
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicFee {
+    pub min_lp_fee_bps: u8,
+    pub max_lp_fee_bps: u8,
+    pub window: u8,       // number of recent swap prices kept
+    pub prices: Vec<f64>, // ring buffer of the prices after the last swaps, oldest first
+}
+
+impl DynamicFee {
+    pub fn new(min_lp_fee_bps: u8, max_lp_fee_bps: u8, window: u8) -> Self {
+        Self {
+            min_lp_fee_bps,
+            max_lp_fee_bps,
+            window,
+            prices: Vec::new(),
+        }
+    }
+
+    pub fn lp_fee_bps(&self) -> u8 {
+        dynamic_lp_fee_bps(realised_volatility_bps(&self.prices), self.min_lp_fee_bps, self.max_lp_fee_bps)
+    }
+}
+
+/// record the price of the pool after a swap for the dynamic LP fee
+/// must be called after the balances of the pool are changed
+/// make sure to call pool_map::update after calling this function
+pub fn record_price(pool: &mut StablePool) {
+    let Some(price) = pool.get_price_as_f64() else {
+        return;
+    };
+    if let Some(dynamic_fee) = pool.dynamic_fee.as_mut() {
+        push_price(&mut dynamic_fee.prices, price, dynamic_fee.window as usize);
+    }
+}
@@ -0,0 +1,44 @@
+use super::pool_map;
+use super::stable_pool::StablePool;
+
+use crate::helpers::nat_helpers::nat_is_zero;
+use crate::ic::get_time::get_time;
+use crate::ic::logging::info_log;
+use crate::stable_kong_settings::kong_settings_map;
+
+/// suspend pools whose TVL stayed below low_tvl_threshold for low_tvl_suspend_secs
+/// suspended pools only allow LPs to remove their liquidity until unsuspended by King Kong
+/// called by the stats timer after the TVL of the pools is updated
+pub fn suspend_low_tvl_pools() {
+    let kong_settings = kong_settings_map::get();
+    if nat_is_zero(&kong_settings.low_tvl_threshold) {
+        return;
+    }
+    let ts = get_time();
+    let suspend_nanosecs = kong_settings.low_tvl_suspend_secs.saturating_mul(1_000_000_000);
+
+    for pool in pool_map::get().into_iter().filter(|pool| pool.suspended_at.is_none()) {
+        let low_tvl_since = if pool.tvl < kong_settings.low_tvl_threshold {
+            Some(pool.low_tvl_since.unwrap_or(ts))
+        } else {
+            None
+        };
+        match low_tvl_since {
+            Some(low_tvl_since) if ts.saturating_sub(low_tvl_since) >= suspend_nanosecs => {
+                pool_map::update(&StablePool {
+                    low_tvl_since: Some(low_tvl_since),
+                    suspended_at: Some(ts),
+                    ..pool.clone()
+                });
+                info_log(&format!(
+                    "Pool {} suspended. TVL {} below {}",
+                    pool.symbol(),
+                    pool.tvl,
+                    kong_settings.low_tvl_threshold
+                ));
+            }
+            _ if low_tvl_since != pool.low_tvl_since => pool_map::update(&StablePool { low_tvl_since, ..pool }),
+            _ => (),
+        }
+    }
+}
@@ -1,4 +1,10 @@
 pub mod check_token_balance;
+pub mod concentrated_pool;
+pub mod dynamic_fee;
+pub mod low_tvl;
+pub mod pool_curve;
 pub mod pool_map;
+pub mod price_oracle;
+pub mod rolling_stats;
 #[allow(clippy::module_inception)]
 pub mod stable_pool;
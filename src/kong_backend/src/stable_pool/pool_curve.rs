@@ -0,0 +1,45 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// limits of the amplification coefficient of stableswap pools
+pub const MIN_AMP: u32 = 1;
+pub const MAX_AMP: u32 = 10_000;
+// limits of the tick spacing of concentrated liquidity pools
+pub const MIN_TICK_SPACING: u32 = 1;
+pub const MAX_TICK_SPACING: u32 = 16_384;
+
+/// AMM curve of a pool
+/// ConstantProduct - x * y = k
+/// StableSwap - Curve-style stableswap invariant for pegged pairs. amp is the amplification coefficient,
+///              the higher the amp the flatter the curve around the peg
+/// Concentrated - Uniswap v3 style ranged liquidity. positions are between two ticks which must be multiples of tick_spacing
+/// adding and removing liquidity is pro-rata to the pool balances for ConstantProduct and StableSwap, while
+/// Concentrated pools use per-position liquidity (see stable_position)
+#[derive(CandidType, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PoolCurve {
+    #[default]
+    ConstantProduct,
+    StableSwap {
+        amp: u32,
+    },
+    Concentrated {
+        tick_spacing: u32,
+    },
+}
+
+impl PoolCurve {
+    pub fn is_concentrated(&self) -> bool {
+        matches!(self, PoolCurve::Concentrated { .. })
+    }
+}
+
+impl fmt::Display for PoolCurve {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PoolCurve::ConstantProduct => write!(f, "ConstantProduct"),
+            PoolCurve::StableSwap { amp } => write!(f, "StableSwap (amp {})", amp),
+            PoolCurve::Concentrated { tick_spacing } => write!(f, "Concentrated (tick spacing {})", tick_spacing),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use candid::{CandidType, Nat};
+use num::{BigRational, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::stable_pool::StablePool;
+
+use crate::helpers::nat_helpers::nat_zero;
+use crate::helpers::twap_helpers::{accumulate, average_price, observation_slot, price_x64};
+use crate::stable_observation::{observation_map, stable_observation::StableObservation};
+
+// observations are recorded at most once per interval into a ring buffer per pool
+// 1,440 observations 1 minute apart keeps 24 hours of price history
+const OBSERVATION_INTERVAL_NS: u64 = 60_000_000_000;
+const OBSERVATION_CARDINALITY: u32 = 1_440;
+
+/// cumulative prices of a pool for the time-weighted average price oracle
+/// price_0 is the price of token_0 in token_1, price_1 is the price of token_1 in token_0
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct PriceCumulative {
+    pub price_0_cumulative_x64: Nat,
+    pub price_1_cumulative_x64: Nat,
+    pub ts: u64, // last time the cumulative prices were updated
+}
+
+/// current price_0 and price_1 of the pool as Q64 fixed point numbers
+fn prices_x64(pool: &StablePool) -> Option<(Nat, Nat)> {
+    let price = pool.get_price().filter(|price| !price.is_zero())?;
+    let inverse_price = BigRational::new(price.denom().clone(), price.numer().clone());
+    Some((price_x64(&price)?, price_x64(&inverse_price)?))
+}
+
+/// cumulative prices of the pool at ts, accumulating the current price since the last update
+pub fn current_price_cumulative(pool: &StablePool, ts: u64) -> Option<PriceCumulative> {
+    let price_cumulative = pool.price_cumulative.as_ref()?;
+    let elapsed = ts.saturating_sub(price_cumulative.ts);
+    Some(match prices_x64(pool) {
+        Some((price_0_x64, price_1_x64)) if elapsed > 0 => PriceCumulative {
+            price_0_cumulative_x64: accumulate(&price_cumulative.price_0_cumulative_x64, &price_0_x64, elapsed),
+            price_1_cumulative_x64: accumulate(&price_cumulative.price_1_cumulative_x64, &price_1_x64, elapsed),
+            ts,
+        },
+        _ => PriceCumulative {
+            ts: std::cmp::max(price_cumulative.ts, ts),
+            ..price_cumulative.clone()
+        },
+    })
+}
+
+/// accumulate the price of the pool up to ts and record an observation
+/// must be called before the balances of the pool are changed so the price since the last update is accumulated
+/// make sure to call pool_map::update after calling this function
+pub fn update_price_cumulative(pool: &mut StablePool, ts: u64) {
+    let price_cumulative = current_price_cumulative(pool, ts).unwrap_or(PriceCumulative {
+        price_0_cumulative_x64: nat_zero(),
+        price_1_cumulative_x64: nat_zero(),
+        ts,
+    });
+
+    // only the first update of each interval is recorded
+    let slot = observation_slot(price_cumulative.ts, OBSERVATION_INTERVAL_NS, OBSERVATION_CARDINALITY);
+    let is_recorded = observation_map::get(pool.pool_id, slot)
+        .is_some_and(|observation| observation.ts / OBSERVATION_INTERVAL_NS == price_cumulative.ts / OBSERVATION_INTERVAL_NS);
+    if !is_recorded {
+        observation_map::update(
+            slot,
+            &StableObservation {
+                pool_id: pool.pool_id,
+                price_0_cumulative_x64: price_cumulative.price_0_cumulative_x64.clone(),
+                price_1_cumulative_x64: price_cumulative.price_1_cumulative_x64.clone(),
+                ts: price_cumulative.ts,
+            },
+        );
+    }
+
+    pool.price_cumulative = Some(price_cumulative);
+}
+
+/// time-weighted average prices of the pool over at least window_secs up to ts
+/// the window starts at the most recent observation at or before ts - window_secs
+///
+/// returns (price_0, price_1, start_ts)
+pub fn twap(pool: &StablePool, window_secs: u64, ts: u64) -> Result<(f64, f64, u64), String> {
+    if window_secs == 0 {
+        Err("Window must be greater than 0".to_string())?
+    }
+    let window = window_secs.checked_mul(1_000_000_000).ok_or("Window is too large")?;
+    let end = current_price_cumulative(pool, ts).ok_or("Pool has no price history")?;
+    let start =
+        observation_map::get_at_or_before(pool.pool_id, ts.saturating_sub(window)).ok_or("Not enough price history for the window")?;
+
+    let elapsed = end.ts - start.ts;
+    let price_0 = average_price(&start.price_0_cumulative_x64, &end.price_0_cumulative_x64, elapsed).ok_or("Invalid price_0")?;
+    let price_1 = average_price(&start.price_1_cumulative_x64, &end.price_1_cumulative_x64, elapsed).ok_or("Invalid price_1")?;
+    Ok((price_0, price_1, start.ts))
+}
@@ -0,0 +1,87 @@
+use candid::Nat;
+
+use super::pool_map;
+use super::stable_pool::StablePool;
+
+use crate::helpers::nat_helpers::{nat_add, nat_zero};
+use crate::helpers::stats_helpers::{apy, stats_hour, window_start_hour};
+use crate::ic::get_time::get_time;
+use crate::stable_pool_stats::{pool_stats_map, stable_pool_stats::StablePoolStats};
+
+// swaps are recorded in hourly stats per pool. the rolling windows are the sum of the last 24, 168 and 720 hours
+// stats older than the longest window are removed
+const WINDOW_24H_HOURS: u64 = 24;
+const WINDOW_7D_HOURS: u64 = 7 * 24;
+const WINDOW_30D_HOURS: u64 = 30 * 24;
+
+/// add a swap to the pool's stats of the hour of ts and to its rolling windows
+/// volume and lp_fee are in ckUSDT. pool is not saved
+pub fn record_swap(pool: &mut StablePool, ts: u64, volume: &Nat, lp_fee: &Nat) {
+    let hour = stats_hour(ts);
+    let stats = pool_stats_map::get(pool.pool_id, hour).unwrap_or(StablePoolStats {
+        pool_id: pool.pool_id,
+        hour,
+        volume: nat_zero(),
+        lp_fee: nat_zero(),
+        num_swaps: nat_zero(),
+    });
+    pool_stats_map::update(&StablePoolStats {
+        volume: nat_add(&stats.volume, volume),
+        lp_fee: nat_add(&stats.lp_fee, lp_fee),
+        num_swaps: nat_add(&stats.num_swaps, &Nat::from(1_u128)),
+        ..stats
+    });
+
+    // the windows are recalculated from the hourly stats by update_rolling_stats()
+    pool.rolling_24h_volume = nat_add(&pool.rolling_24h_volume, volume);
+    pool.rolling_24h_lp_fee = nat_add(&pool.rolling_24h_lp_fee, lp_fee);
+    pool.rolling_24h_num_swaps = nat_add(&pool.rolling_24h_num_swaps, &Nat::from(1_u128));
+    pool.rolling_7d_volume = nat_add(&pool.rolling_7d_volume, volume);
+    pool.rolling_7d_lp_fee = nat_add(&pool.rolling_7d_lp_fee, lp_fee);
+    pool.rolling_7d_num_swaps = nat_add(&pool.rolling_7d_num_swaps, &Nat::from(1_u128));
+    pool.rolling_30d_volume = nat_add(&pool.rolling_30d_volume, volume);
+    pool.rolling_30d_lp_fee = nat_add(&pool.rolling_30d_lp_fee, lp_fee);
+    pool.rolling_30d_num_swaps = nat_add(&pool.rolling_30d_num_swaps, &Nat::from(1_u128));
+    set_rolling_apy(pool);
+}
+
+/// recalculate the rolling 24h, 7d and 30d stats of all pools from the hourly stats
+/// called by the stats timer so the windows roll forward without swaps
+pub fn update_rolling_stats() {
+    let current_hour = stats_hour(get_time());
+    let start_hour_30d = window_start_hour(current_hour, WINDOW_30D_HOURS);
+
+    for mut pool in pool_map::get() {
+        let stats = pool_stats_map::get_from_hour(pool.pool_id, start_hour_30d);
+        (pool.rolling_24h_volume, pool.rolling_24h_lp_fee, pool.rolling_24h_num_swaps) =
+            window_totals(&stats, window_start_hour(current_hour, WINDOW_24H_HOURS));
+        (pool.rolling_7d_volume, pool.rolling_7d_lp_fee, pool.rolling_7d_num_swaps) =
+            window_totals(&stats, window_start_hour(current_hour, WINDOW_7D_HOURS));
+        (pool.rolling_30d_volume, pool.rolling_30d_lp_fee, pool.rolling_30d_num_swaps) = window_totals(&stats, start_hour_30d);
+        pool.set_tvl();
+        set_rolling_apy(&mut pool);
+        pool_map::update(&pool);
+
+        pool_stats_map::remove_before(pool.pool_id, start_hour_30d);
+    }
+}
+
+/// total volume, lp_fee and num_swaps of the stats from start_hour onwards
+fn window_totals(stats: &[StablePoolStats], start_hour: u64) -> (Nat, Nat, Nat) {
+    stats
+        .iter()
+        .filter(|s| s.hour >= start_hour)
+        .fold((nat_zero(), nat_zero(), nat_zero()), |acc, s| {
+            (
+                nat_add(&acc.0, &s.volume),
+                nat_add(&acc.1, &s.lp_fee),
+                nat_add(&acc.2, &s.num_swaps),
+            )
+        })
+}
+
+fn set_rolling_apy(pool: &mut StablePool) {
+    pool.rolling_24h_apy = apy(&pool.rolling_24h_lp_fee, &pool.tvl, 1);
+    pool.rolling_7d_apy = apy(&pool.rolling_7d_lp_fee, &pool.tvl, 7);
+    pool.rolling_30d_apy = apy(&pool.rolling_30d_lp_fee, &pool.tvl, 30);
+}
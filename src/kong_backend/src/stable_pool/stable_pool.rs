@@ -1,10 +1,18 @@
 use candid::{CandidType, Nat};
 use ic_stable_structures::{storable::Bound, Storable};
 use num::BigRational;
+use num_bigint::BigInt;
+use num_traits::pow;
 use serde::{Deserialize, Serialize};
 
+use super::concentrated_pool::ConcentratedState;
+use super::dynamic_fee::DynamicFee;
+use super::pool_curve::PoolCurve;
+use super::price_oracle::PriceCumulative;
+
 use crate::helpers::math_helpers::price_rounded;
 use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_to_bigint, nat_to_decimal_precision, nat_zero};
+use crate::helpers::stableswap_helpers::stableswap_price;
 use crate::ic::ckusdt::ckusdt_amount;
 use crate::stable_token::stable_token::StableToken;
 use crate::stable_token::token::Token;
@@ -38,22 +46,66 @@ pub struct StablePool {
     pub kong_fee_1: Nat,  // Kong's share of the LP fee
     pub lp_fee_bps: u8,   // LP's fee in basis points
     pub kong_fee_bps: u8, // Kong's fee in basis points
+    #[serde(default = "nat_zero")]
+    pub collected_kong_fee_0: Nat, // Kong's fees collected to the treasury
+    #[serde(default = "nat_zero")]
+    pub collected_kong_fee_1: Nat,
     pub tvl: Nat,
     pub rolling_24h_volume: Nat,
     pub rolling_24h_lp_fee: Nat,
     pub rolling_24h_num_swaps: Nat,
     pub rolling_24h_apy: f64,
+    #[serde(default = "nat_zero")]
+    pub rolling_7d_volume: Nat,
+    #[serde(default = "nat_zero")]
+    pub rolling_7d_lp_fee: Nat,
+    #[serde(default = "nat_zero")]
+    pub rolling_7d_num_swaps: Nat,
+    #[serde(default = "zero_f64")]
+    pub rolling_7d_apy: f64,
+    #[serde(default = "nat_zero")]
+    pub rolling_30d_volume: Nat,
+    #[serde(default = "nat_zero")]
+    pub rolling_30d_lp_fee: Nat,
+    #[serde(default = "nat_zero")]
+    pub rolling_30d_num_swaps: Nat,
+    #[serde(default = "zero_f64")]
+    pub rolling_30d_apy: f64,
     pub lp_token_id: u32, // token id of the LP token
     #[serde(default = "false_bool")]
     pub is_removed: bool,
+    #[serde(default = "constant_product_curve")]
+    pub curve: PoolCurve,
+    pub concentrated: Option<ConcentratedState>, // price and liquidity state of Concentrated pools
+    pub price_cumulative: Option<PriceCumulative>, // cumulative prices for the TWAP oracle, set on the first swap
+    #[serde(default = "false_bool")]
+    pub flash_loan_enabled: bool, // whitelisted canisters can take flash loans of the pool's balances
+    #[serde(default = "nat_zero")]
+    pub flash_loan_shortfall_0: Nat, // unrepaid flash loans not covered by kong_fee_0, owed by the treasury
+    #[serde(default = "nat_zero")]
+    pub flash_loan_shortfall_1: Nat,
+    // LP fee driven by volatility instead of lp_fee_bps
+    pub dynamic_fee: Option<DynamicFee>,
+    // time the TVL fell below low_tvl_threshold, used to suspend pools
+    pub low_tvl_since: Option<u64>,
+    // time the pool was suspended for low TVL. suspended pools only allow removing liquidity
+    pub suspended_at: Option<u64>,
 }
 
 fn false_bool() -> bool {
     false
 }
 
+fn zero_f64() -> f64 {
+    0_f64
+}
+
+fn constant_product_curve() -> PoolCurve {
+    PoolCurve::ConstantProduct
+}
+
 impl StablePool {
-    pub fn new(token_id_0: u32, token_id_1: u32, lp_fee_bps: u8, kong_fee_bps: u8, lp_token_id: u32) -> Self {
+    pub fn new(token_id_0: u32, token_id_1: u32, lp_fee_bps: u8, kong_fee_bps: u8, lp_token_id: u32, curve: PoolCurve) -> Self {
         Self {
             pool_id: 0,
             token_id_0,
@@ -66,13 +118,32 @@ impl StablePool {
             kong_fee_1: nat_zero(),
             lp_fee_bps,
             kong_fee_bps,
+            collected_kong_fee_0: nat_zero(),
+            collected_kong_fee_1: nat_zero(),
             tvl: nat_zero(),
             rolling_24h_volume: nat_zero(),
             rolling_24h_lp_fee: nat_zero(),
             rolling_24h_num_swaps: nat_zero(),
             rolling_24h_apy: 0_f64,
+            rolling_7d_volume: nat_zero(),
+            rolling_7d_lp_fee: nat_zero(),
+            rolling_7d_num_swaps: nat_zero(),
+            rolling_7d_apy: 0_f64,
+            rolling_30d_volume: nat_zero(),
+            rolling_30d_lp_fee: nat_zero(),
+            rolling_30d_num_swaps: nat_zero(),
+            rolling_30d_apy: 0_f64,
             lp_token_id,
             is_removed: false,
+            curve,
+            concentrated: None,
+            price_cumulative: None,
+            flash_loan_enabled: false,
+            flash_loan_shortfall_0: nat_zero(),
+            flash_loan_shortfall_1: nat_zero(),
+            dynamic_fee: None,
+            low_tvl_since: None,
+            suspended_at: None,
         }
     }
 
@@ -132,7 +203,22 @@ impl StablePool {
         token_map::get_by_token_id(self.lp_token_id).unwrap()
     }
 
+    pub fn tick_spacing(&self) -> Option<u32> {
+        match self.curve {
+            PoolCurve::Concentrated { tick_spacing } => Some(tick_spacing),
+            _ => None,
+        }
+    }
+
     pub fn get_price(&self) -> Option<BigRational> {
+        if let Some(state) = &self.concentrated {
+            // price = sqrt_price^2 / 2^192 adjusted for the decimals of token_0 and token_1
+            let sqrt_price = nat_to_bigint(&state.sqrt_price_x96);
+            let numerator = &sqrt_price * &sqrt_price * pow(BigInt::from(10), self.token_0().decimals() as usize);
+            let denominator = (BigInt::from(1) << 192) * pow(BigInt::from(10), self.token_1().decimals() as usize);
+            return Some(BigRational::new(numerator, denominator));
+        }
+
         let reserve_0 = nat_add(&self.balance_0, &self.lp_fee_0);
         let reserve_1 = nat_add(&self.balance_1, &self.lp_fee_1);
         if nat_is_zero(&reserve_0) {
@@ -142,10 +228,22 @@ impl StablePool {
         let token_0 = self.token_0();
         let token_1 = self.token_1();
         let max_decimals = std::cmp::max(token_0.decimals(), token_1.decimals());
-        let reserve_0 = nat_to_bigint(&nat_to_decimal_precision(&reserve_0, token_0.decimals(), max_decimals));
-        let reserve_1 = nat_to_bigint(&nat_to_decimal_precision(&reserve_1, token_1.decimals(), max_decimals));
+        let reserve_0 = nat_to_decimal_precision(&reserve_0, token_0.decimals(), max_decimals);
+        let reserve_1 = nat_to_decimal_precision(&reserve_1, token_1.decimals(), max_decimals);
+
+        match self.curve {
+            PoolCurve::ConstantProduct => Some(BigRational::new(nat_to_bigint(&reserve_1), nat_to_bigint(&reserve_0))),
+            // marginal price of the stableswap invariant
+            PoolCurve::StableSwap { amp } => stableswap_price(amp, &reserve_0, &reserve_1),
+            PoolCurve::Concentrated { .. } => None,
+        }
+    }
 
-        Some(BigRational::new(reserve_1, reserve_0))
+    /// LP fee in basis points charged on swaps. lp_fee_bps unless the pool has a dynamic fee
+    pub fn effective_lp_fee_bps(&self) -> u8 {
+        self.dynamic_fee
+            .as_ref()
+            .map_or(self.lp_fee_bps, |dynamic_fee| dynamic_fee.lp_fee_bps())
     }
 
     pub fn get_price_as_f64(&self) -> Option<f64> {
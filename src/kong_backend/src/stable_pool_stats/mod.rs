@@ -0,0 +1,3 @@
+pub mod pool_stats_map;
+#[allow(clippy::module_inception)]
+pub mod stable_pool_stats;
@@ -0,0 +1,48 @@
+use super::stable_pool_stats::{StablePoolStats, StablePoolStatsId};
+
+use crate::stable_memory::POOL_STATS_MAP;
+
+/// stats of the pool from from_hour onwards, oldest first
+pub fn get_from_hour(pool_id: u32, from_hour: u64) -> Vec<StablePoolStats> {
+    POOL_STATS_MAP.with(|m| {
+        m.borrow()
+            .range(StablePoolStatsId { pool_id, hour: from_hour }..=StablePoolStatsId { pool_id, hour: u64::MAX })
+            .map(|(_, v)| v)
+            .collect()
+    })
+}
+
+pub fn get(pool_id: u32, hour: u64) -> Option<StablePoolStats> {
+    POOL_STATS_MAP.with(|m| m.borrow().get(&StablePoolStatsId { pool_id, hour }))
+}
+
+pub fn update(stats: &StablePoolStats) {
+    POOL_STATS_MAP.with(|m| {
+        m.borrow_mut().insert(
+            StablePoolStatsId {
+                pool_id: stats.pool_id,
+                hour: stats.hour,
+            },
+            stats.clone(),
+        )
+    });
+}
+
+/// remove stats of the pool older than before_hour
+pub fn remove_before(pool_id: u32, before_hour: u64) {
+    POOL_STATS_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let keys: Vec<StablePoolStatsId> = map
+            .range(
+                StablePoolStatsId { pool_id, hour: 0 }..StablePoolStatsId {
+                    pool_id,
+                    hour: before_hour,
+                },
+            )
+            .map(|(k, _)| k)
+            .collect();
+        for key in keys {
+            map.remove(&key);
+        }
+    });
+}
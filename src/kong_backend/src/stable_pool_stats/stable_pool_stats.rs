@@ -0,0 +1,44 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+/// stats are ordered by pool_id and then hour
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StablePoolStatsId {
+    pub pool_id: u32,
+    pub hour: u64, // hours since the Unix epoch
+}
+
+impl Storable for StablePoolStatsId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// swap stats of a pool within one hour. volume and lp_fee are in ckUSDT at the time of the swaps
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StablePoolStats {
+    pub pool_id: u32,
+    pub hour: u64,
+    pub volume: Nat,
+    pub lp_fee: Nat,
+    pub num_swaps: Nat,
+}
+
+impl Storable for StablePoolStats {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
@@ -0,0 +1,3 @@
+pub mod pool_update_map;
+#[allow(clippy::module_inception)]
+pub mod stable_pool_update;
@@ -0,0 +1,55 @@
+use super::stable_pool_update::{PoolUpdateStatus, StablePoolUpdate, StablePoolUpdateId};
+
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_memory::POOL_UPDATE_MAP;
+
+pub fn get_by_pool_update_id(pool_update_id: u64) -> Option<StablePoolUpdate> {
+    POOL_UPDATE_MAP.with(|m| m.borrow().get(&StablePoolUpdateId(pool_update_id)))
+}
+
+/// get all pool updates, optionally of a pool, most recent first
+pub fn get(pool_id: Option<u32>) -> Vec<StablePoolUpdate> {
+    POOL_UPDATE_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .rev()
+            .filter_map(|(_, v)| {
+                if pool_id.is_none_or(|pool_id| v.pool_id == pool_id) {
+                    Some(v)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    })
+}
+
+/// get all scheduled pool updates, oldest first
+pub fn get_scheduled() -> Vec<StablePoolUpdate> {
+    POOL_UPDATE_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .filter_map(|(_, v)| if v.status == PoolUpdateStatus::Scheduled { Some(v) } else { None })
+            .collect()
+    })
+}
+
+pub fn insert(pool_update: &StablePoolUpdate) -> u64 {
+    POOL_UPDATE_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let pool_update_id = kong_settings_map::inc_pool_update_map_idx();
+        let insert_pool_update = StablePoolUpdate {
+            pool_update_id,
+            ..pool_update.clone()
+        };
+        map.insert(StablePoolUpdateId(pool_update_id), insert_pool_update);
+        pool_update_id
+    })
+}
+
+pub fn update(pool_update: &StablePoolUpdate) {
+    POOL_UPDATE_MAP.with(|m| {
+        m.borrow_mut()
+            .insert(StablePoolUpdateId(pool_update.pool_update_id), pool_update.clone())
+    });
+}
@@ -0,0 +1,71 @@
+use candid::CandidType;
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use crate::stable_pool::pool_curve::PoolCurve;
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StablePoolUpdateId(pub u64);
+
+impl Storable for StablePoolUpdateId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PoolUpdateStatus {
+    Scheduled,
+    Applied,
+    Cancelled,
+    Failed,
+}
+
+impl std::fmt::Display for PoolUpdateStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoolUpdateStatus::Scheduled => write!(f, "Scheduled"),
+            PoolUpdateStatus::Applied => write!(f, "Applied"),
+            PoolUpdateStatus::Cancelled => write!(f, "Cancelled"),
+            PoolUpdateStatus::Failed => write!(f, "Failed"),
+        }
+    }
+}
+
+/// change of the fees and curve of a live pool
+/// updates are scheduled at effective_ts so LPs get notice and are kept as the audit log of pool parameter changes
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StablePoolUpdate {
+    pub pool_update_id: u64,
+    pub pool_id: u32,
+    pub caller: String, // principal id of the caller who scheduled the update, ie. SNS governance
+    pub status: PoolUpdateStatus,
+    pub lp_fee_bps: u8,
+    pub kong_fee_bps: u8,
+    pub curve: PoolCurve,
+    pub prev_lp_fee_bps: u8, // parameters of the pool when the update was scheduled, or applied
+    pub prev_kong_fee_bps: u8,
+    pub prev_curve: PoolCurve,
+    pub effective_ts: u64,
+    pub status_ts: Option<u64>,  // time the update was applied, cancelled or failed
+    pub message: Option<String>, // reason the update was cancelled or failed
+    pub ts: u64,
+}
+
+impl Storable for StablePoolUpdate {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
@@ -0,0 +1,3 @@
+pub mod position_map;
+#[allow(clippy::module_inception)]
+pub mod stable_position;
@@ -0,0 +1,45 @@
+use super::stable_position::{StablePosition, StablePositionId};
+
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_memory::POSITION_MAP;
+
+pub fn get_by_position_id(position_id: u64) -> Option<StablePosition> {
+    POSITION_MAP.with(|m| m.borrow().get(&StablePositionId(position_id)))
+}
+
+/// get all positions of a user
+pub fn get_by_user_id(user_id: u32) -> Vec<StablePosition> {
+    POSITION_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .filter_map(|(_, v)| if v.user_id == user_id { Some(v) } else { None })
+            .collect()
+    })
+}
+
+/// get all positions of a pool
+pub fn get_by_pool_id(pool_id: u32) -> Vec<StablePosition> {
+    POSITION_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .filter_map(|(_, v)| if v.pool_id == pool_id { Some(v) } else { None })
+            .collect()
+    })
+}
+
+pub fn insert(position: &StablePosition) -> u64 {
+    POSITION_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let position_id = kong_settings_map::inc_position_map_idx();
+        let insert_position = StablePosition {
+            position_id,
+            ..position.clone()
+        };
+        map.insert(StablePositionId(position_id), insert_position);
+        position_id
+    })
+}
+
+pub fn update(position: &StablePosition) {
+    POSITION_MAP.with(|m| m.borrow_mut().insert(StablePositionId(position.position_id), position.clone()));
+}
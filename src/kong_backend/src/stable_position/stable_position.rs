@@ -0,0 +1,67 @@
+use candid::{CandidType, Int, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::nat_helpers::nat_zero;
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StablePositionId(pub u64);
+
+impl Storable for StablePositionId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// ranged liquidity position of a concentrated liquidity pool
+/// each position has its own id and is not fungible with other positions of the same pool
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StablePosition {
+    pub position_id: u64,
+    pub user_id: u32, // user id of the position owner
+    pub pool_id: u32,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: Nat,
+    pub fee_growth_inside_0_last_x128: Int, // fee growth inside the range of token_0 at the last update
+    pub fee_growth_inside_1_last_x128: Int, // fee growth inside the range of token_1 at the last update
+    pub tokens_owed_0: Nat,                 // uncollected fees of token_0 up to the last update
+    pub tokens_owed_1: Nat,                 // uncollected fees of token_1 up to the last update
+    pub ts: u64,                            // timestamp of the last position update
+}
+
+impl StablePosition {
+    pub fn new(user_id: u32, pool_id: u32, tick_lower: i32, tick_upper: i32, ts: u64) -> Self {
+        Self {
+            position_id: 0,
+            user_id,
+            pool_id,
+            tick_lower,
+            tick_upper,
+            liquidity: nat_zero(),
+            fee_growth_inside_0_last_x128: Int::from(0),
+            fee_growth_inside_1_last_x128: Int::from(0),
+            tokens_owed_0: nat_zero(),
+            tokens_owed_1: nat_zero(),
+            ts,
+        }
+    }
+}
+
+impl Storable for StablePosition {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
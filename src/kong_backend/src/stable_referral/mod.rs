@@ -0,0 +1,3 @@
+pub mod referral_map;
+#[allow(clippy::module_inception)]
+pub mod stable_referral;
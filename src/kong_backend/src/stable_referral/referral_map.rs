@@ -0,0 +1,34 @@
+use super::stable_referral::{StableReferral, StableReferralId};
+
+use crate::stable_memory::REFERRAL_MAP;
+
+/// referral rewards of the referrer in all tokens
+pub fn get_by_user_id(user_id: u32) -> Vec<StableReferral> {
+    REFERRAL_MAP.with(|m| {
+        m.borrow()
+            .range(
+                StableReferralId { user_id, token_id: 0 }..=StableReferralId {
+                    user_id,
+                    token_id: u32::MAX,
+                },
+            )
+            .map(|(_, v)| v)
+            .collect()
+    })
+}
+
+pub fn get(user_id: u32, token_id: u32) -> Option<StableReferral> {
+    REFERRAL_MAP.with(|m| m.borrow().get(&StableReferralId { user_id, token_id }))
+}
+
+pub fn update(referral: &StableReferral) {
+    REFERRAL_MAP.with(|m| {
+        m.borrow_mut().insert(
+            StableReferralId {
+                user_id: referral.user_id,
+                token_id: referral.token_id,
+            },
+            referral.clone(),
+        )
+    });
+}
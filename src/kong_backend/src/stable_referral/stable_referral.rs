@@ -0,0 +1,45 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+/// referral rewards are ordered by the user_id of the referrer and then token_id
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableReferralId {
+    pub user_id: u32,
+    pub token_id: u32,
+}
+
+impl Storable for StableReferralId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// referral rewards of a referrer in token_id, earned from the swaps of the users they referred
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableReferral {
+    pub user_id: u32, // user_id of the referrer
+    pub token_id: u32,
+    pub volume: Nat,  // ckUSDT volume of the referees' swaps received in token_id, at the time of the swaps
+    pub earned: Nat,  // rewards earned in token_id
+    pub claimed: Nat, // rewards moved to claims
+    pub ts: u64,      // last time rewards were earned
+}
+
+impl Storable for StableReferral {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
@@ -2,8 +2,12 @@ use candid::CandidType;
 use serde::{Deserialize, Serialize};
 
 use crate::add_liquidity::add_liquidity_args::AddLiquidityArgs;
+use crate::add_liquidity::add_liquidity_single_args::AddLiquiditySingleArgs;
 use crate::add_pool::add_pool_args::AddPoolArgs;
+use crate::flash_loans::flash_loan_args::FlashLoanArgs;
+use crate::limit_orders::limit_order_args::LimitOrderArgs;
 use crate::remove_liquidity::remove_liquidity_args::RemoveLiquidityArgs;
+use crate::remove_liquidity::remove_liquidity_single_args::RemoveLiquiditySingleArgs;
 use crate::send::send_args::SendArgs;
 use crate::swap::swap_args::SwapArgs;
 
@@ -15,4 +19,8 @@ pub enum Request {
     Swap(SwapArgs),
     Claim(u64),
     Send(SendArgs),
+    LimitOrder(LimitOrderArgs),
+    AddLiquiditySingle(AddLiquiditySingleArgs),
+    RemoveLiquiditySingle(RemoveLiquiditySingleArgs),
+    FlashLoan(FlashLoanArgs),
 }
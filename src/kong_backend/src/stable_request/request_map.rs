@@ -5,6 +5,7 @@ use super::status::{Status, StatusCode};
 use crate::ic::logging::error_log;
 use crate::stable_kong_settings::kong_settings_map;
 use crate::stable_memory::REQUEST_MAP;
+use crate::webhooks::process_webhooks::{queue_webhook_delivery, reply_webhook_delivery};
 
 pub fn get_by_request_id(request_id: u64) -> Option<StableRequest> {
     REQUEST_MAP.with(|m| m.borrow().get(&StableRequestId(request_id)))
@@ -29,6 +30,11 @@ pub fn update_status(key: u64, status_code: StatusCode, message: Option<&str>) -
         let key = StableRequestId(key);
         match map.get(&key) {
             Some(mut v) => {
+                // notify the user's webhook the first time the request reaches Success or Failed
+                let is_final = |status_code: &StatusCode| matches!(status_code, StatusCode::Success | StatusCode::Failed);
+                if is_final(&status_code) && !v.statuses.iter().any(|status| is_final(&status.status_code)) {
+                    queue_webhook_delivery(v.user_id, v.request_id, &v.reply);
+                }
                 v.statuses.push(Status {
                     status_code,
                     message: message.map(|s| s.to_string()),
@@ -46,6 +52,9 @@ pub fn update_reply(key: u64, reply: Reply) -> Option<StableRequest> {
         let key = StableRequestId(key);
         match map.get(&key) {
             Some(mut v) => {
+                if !matches!(reply, Reply::Pending) {
+                    reply_webhook_delivery(v.user_id, v.request_id);
+                }
                 v.reply = reply;
                 map.insert(key, v)
             }
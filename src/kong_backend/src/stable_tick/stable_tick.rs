@@ -0,0 +1,60 @@
+use candid::{CandidType, Int, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::nat_helpers::nat_zero;
+
+/// ticks are ordered by pool_id and then tick so the initialized ticks of a pool can be walked in price order
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableTickId {
+    pub pool_id: u32,
+    pub tick: i32,
+}
+
+impl Storable for StableTickId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// initialized tick of a concentrated liquidity pool. only ticks used as a position boundary are stored
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableTick {
+    pub pool_id: u32,
+    pub tick: i32,
+    pub liquidity_gross: Nat,           // total liquidity of positions using this tick as a boundary
+    pub liquidity_net: Int,             // liquidity added when the price crosses this tick left to right
+    pub fee_growth_outside_0_x128: Nat, // fee growth per unit of liquidity of token_0 on the other side of the current tick
+    pub fee_growth_outside_1_x128: Nat, // fee growth per unit of liquidity of token_1 on the other side of the current tick
+}
+
+impl StableTick {
+    pub fn new(pool_id: u32, tick: i32) -> Self {
+        Self {
+            pool_id,
+            tick,
+            liquidity_gross: nat_zero(),
+            liquidity_net: Int::from(0),
+            fee_growth_outside_0_x128: nat_zero(),
+            fee_growth_outside_1_x128: nat_zero(),
+        }
+    }
+}
+
+impl Storable for StableTick {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
@@ -0,0 +1,40 @@
+use super::stable_tick::{StableTick, StableTickId};
+
+use crate::stable_memory::TICK_MAP;
+
+pub fn get(pool_id: u32, tick: i32) -> Option<StableTick> {
+    TICK_MAP.with(|m| m.borrow().get(&StableTickId { pool_id, tick }))
+}
+
+/// next initialized tick of the pool from tick
+/// lte = true searches down for the greatest tick <= tick, otherwise searches up for the lowest tick > tick
+pub fn next_initialized(pool_id: u32, tick: i32, lte: bool) -> Option<i32> {
+    TICK_MAP.with(|m| {
+        let map = m.borrow();
+        if lte {
+            map.range(StableTickId { pool_id, tick: i32::MIN }..=StableTickId { pool_id, tick })
+                .next_back()
+                .map(|(k, _)| k.tick)
+        } else {
+            map.range(StableTickId { pool_id, tick }..=StableTickId { pool_id, tick: i32::MAX })
+                .find(|(k, _)| k.tick > tick)
+                .map(|(k, _)| k.tick)
+        }
+    })
+}
+
+pub fn update(tick: &StableTick) {
+    TICK_MAP.with(|m| {
+        m.borrow_mut().insert(
+            StableTickId {
+                pool_id: tick.pool_id,
+                tick: tick.tick,
+            },
+            tick.clone(),
+        )
+    });
+}
+
+pub fn remove(pool_id: u32, tick: i32) -> Option<StableTick> {
+    TICK_MAP.with(|m| m.borrow_mut().remove(&StableTickId { pool_id, tick }))
+}
@@ -1,8 +1,10 @@
 use candid::{CandidType, Nat, Principal};
 use serde::{Deserialize, Serialize};
 
+use super::verification_method::VerificationMethod;
+
 use crate::chains::chains::IC_CHAIN;
-use crate::ic::ledger::{get_decimals, get_fee, get_name, get_supported_standards, get_symbol};
+use crate::ic::ledger::{get_decimals, get_fee, get_name, get_supported_standards, get_symbol, get_verification_method};
 
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub struct ICToken {
@@ -17,6 +19,7 @@ pub struct ICToken {
     pub icrc3: bool,
     #[serde(default = "false_bool")]
     pub is_removed: bool,
+    pub verification_method: Option<VerificationMethod>, // None for tokens added before it was detected, see verification_method()
 }
 
 fn false_bool() -> bool {
@@ -38,6 +41,7 @@ impl ICToken {
             }
             Err(_) => (true, false, false), // should at least support ICRC-1 if it made it this far
         };
+        let verification_method = get_verification_method(canister_id, icrc3).await;
         Ok(Self {
             token_id: 0,
             name,
@@ -49,10 +53,17 @@ impl ICToken {
             icrc2,
             icrc3,
             is_removed: false,
+            verification_method: Some(verification_method),
         })
     }
 
     pub fn chain(&self) -> String {
         IC_CHAIN.to_string()
     }
+
+    /// how transfers are verified on the token's ledger. detected when the token was added or set by kingkong
+    pub fn verification_method(&self) -> VerificationMethod {
+        self.verification_method
+            .unwrap_or_else(|| VerificationMethod::default_for(&self.canister_id))
+    }
 }
@@ -4,3 +4,4 @@ pub mod lp_token;
 pub mod stable_token;
 pub mod token;
 pub mod token_map;
+pub mod verification_method;
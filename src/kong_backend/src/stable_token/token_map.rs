@@ -161,9 +161,17 @@ pub fn get_ckusdt() -> Result<StableToken, String> {
     token_map::get_by_token_id(kong_settings_map::get().ckusdt_token_id).ok_or("ckUSDT token not found".to_string())
 }
 
-/// return ICP token
-pub fn get_icp() -> Result<StableToken, String> {
-    token_map::get_by_token_id(kong_settings_map::get().icp_token_id).ok_or("ICP token not found".to_string())
+/// return quote tokens in order of priority
+pub fn get_quote_tokens() -> Vec<StableToken> {
+    kong_settings_map::get()
+        .quote_token_ids
+        .iter()
+        .filter_map(|token_id| token_map::get_by_token_id(*token_id))
+        .collect()
+}
+
+pub fn is_quote_token_id(token_id: u32) -> bool {
+    kong_settings_map::get().quote_token_ids.contains(&token_id)
 }
 
 /// return all tokens
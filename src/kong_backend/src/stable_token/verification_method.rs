@@ -0,0 +1,39 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const ICP_CANISTER_ID: &str = "ryjl3-tyaaa-aaaaa-aaaba-cai";
+const WUMBO_CANISTER_ID: &str = "wkv3f-iiaaa-aaaap-ag73a-cai";
+const DAMONIC_CANISTER_ID: &str = "zzsnb-aaaaa-aaaap-ag66q-cai";
+const CLOWN_CANISTER_ID: &str = "iwv6l-6iaaa-aaaal-ajjjq-cai";
+
+/// how transfers to Kong are verified on the token's ledger
+#[derive(CandidType, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerificationMethod {
+    ICRC3GetBlocks,  // ICRC-3 icrc3_get_blocks
+    GetTransactions, // ICRC get_transactions, following archived transactions
+    QueryBlocks,     // ICP ledger query_blocks, falling back to archived blocks
+    Legacy,          // get_transaction returning a single legacy transaction, ie. WUMBO
+}
+
+impl VerificationMethod {
+    /// verification method of tokens added before it was detected in add_ic_token
+    pub fn default_for(canister_id: &Principal) -> Self {
+        match canister_id.to_text().as_str() {
+            ICP_CANISTER_ID => VerificationMethod::QueryBlocks,
+            WUMBO_CANISTER_ID | DAMONIC_CANISTER_ID | CLOWN_CANISTER_ID => VerificationMethod::Legacy,
+            _ => VerificationMethod::GetTransactions,
+        }
+    }
+}
+
+impl fmt::Display for VerificationMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationMethod::ICRC3GetBlocks => write!(f, "icrc3_get_blocks"),
+            VerificationMethod::GetTransactions => write!(f, "get_transactions"),
+            VerificationMethod::QueryBlocks => write!(f, "query_blocks"),
+            VerificationMethod::Legacy => write!(f, "legacy"),
+        }
+    }
+}
@@ -12,6 +12,7 @@ pub struct AddPoolTx {
     pub amount_0: Nat,
     pub amount_1: Nat,
     pub add_lp_token_amount: Nat,
+    pub position_id: Option<u64>, // position of Concentrated pools, where the LP token amount is the liquidity of the position
     pub transfer_ids: Vec<u64>,
     pub claim_ids: Vec<u64>,
     #[serde(default = "false_bool")]
@@ -32,6 +33,7 @@ impl AddPoolTx {
         amount_0: &Nat,
         amount_1: &Nat,
         add_lp_token_amount: &Nat,
+        position_id: Option<u64>,
         transfer_ids: &[u64],
         claim_ids: &[u64],
         ts: u64,
@@ -45,6 +47,7 @@ impl AddPoolTx {
             amount_0: amount_0.clone(),
             amount_1: amount_1.clone(),
             add_lp_token_amount: add_lp_token_amount.clone(),
+            position_id,
             transfer_ids: transfer_ids.to_vec(),
             claim_ids: claim_ids.to_vec(),
             is_removed: false,
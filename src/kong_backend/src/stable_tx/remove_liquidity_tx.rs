@@ -15,6 +15,7 @@ pub struct RemoveLiquidityTx {
     pub amount_1: Nat,
     pub lp_fee_1: Nat,
     pub remove_lp_token_amount: Nat,
+    pub position_id: Option<u64>, // position of Concentrated pools, where the LP token amount is the liquidity of the position
     pub transfer_ids: Vec<u64>,
     pub claim_ids: Vec<u64>,
     pub ts: u64,
@@ -31,6 +32,7 @@ impl RemoveLiquidityTx {
         amount_1: &Nat,
         lp_fee_1: &Nat,
         remove_lp_token_amount: &Nat,
+        position_id: Option<u64>,
         transfer_ids: &[u64],
         claim_ids: &[u64],
         ts: u64,
@@ -46,6 +48,7 @@ impl RemoveLiquidityTx {
             amount_1: amount_1.clone(),
             lp_fee_1: lp_fee_1.clone(),
             remove_lp_token_amount: remove_lp_token_amount.clone(),
+            position_id,
             transfer_ids: transfer_ids.to_vec(),
             claim_ids: claim_ids.to_vec(),
             ts,
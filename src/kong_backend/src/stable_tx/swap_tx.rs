@@ -21,6 +21,8 @@ pub struct SwapTx {
     pub transfer_ids: Vec<u64>,
     pub claim_ids: Vec<u64>,
     pub ts: u64,
+    // unused part of pay_amount approved for exact-output swaps
+    pub approval_headroom: Option<Nat>,
 }
 
 impl SwapTx {
@@ -39,6 +41,7 @@ impl SwapTx {
         transfer_ids: &[u64],
         claim_ids: &[u64],
         ts: u64,
+        approval_headroom: Option<&Nat>,
     ) -> Self {
         Self {
             tx_id: 0,
@@ -56,6 +59,7 @@ impl SwapTx {
             transfer_ids: transfer_ids.to_vec(),
             claim_ids: claim_ids.to_vec(),
             ts,
+            approval_headroom: approval_headroom.cloned(),
         }
     }
 }
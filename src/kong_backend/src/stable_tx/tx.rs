@@ -15,6 +15,7 @@ impl Tx for StableTx {
             StableTx::RemoveLiquidity(tx) => tx.tx_id,
             StableTx::Swap(tx) => tx.tx_id,
             StableTx::Send(tx) => tx.tx_id,
+            StableTx::CollectProtocolFees(tx) => tx.tx_id,
         }
     }
 
@@ -25,6 +26,7 @@ impl Tx for StableTx {
             StableTx::RemoveLiquidity(tx) => tx.user_id,
             StableTx::Swap(tx) => tx.user_id,
             StableTx::Send(tx) => tx.user_id,
+            StableTx::CollectProtocolFees(tx) => tx.user_id,
         }
     }
 
@@ -35,6 +37,7 @@ impl Tx for StableTx {
             StableTx::RemoveLiquidity(tx) => tx.ts,
             StableTx::Swap(tx) => tx.ts,
             StableTx::Send(tx) => tx.ts,
+            StableTx::CollectProtocolFees(tx) => tx.ts,
         }
     }
 }
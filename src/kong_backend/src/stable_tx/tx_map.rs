@@ -3,9 +3,10 @@ use std::ops::Bound;
 
 use super::add_liquidity_tx::AddLiquidityTx;
 use super::add_pool_tx::AddPoolTx;
+use super::collect_protocol_fees_tx::CollectProtocolFeesTx;
 use super::remove_liquidity_tx::RemoveLiquidityTx;
 use super::send_tx::SendTx;
-use super::stable_tx::StableTx::{AddLiquidity, AddPool, RemoveLiquidity, Send, Swap};
+use super::stable_tx::StableTx::{AddLiquidity, AddPool, CollectProtocolFees, RemoveLiquidity, Send, Swap};
 use super::stable_tx::{StableTx, StableTxId};
 use super::swap_tx::SwapTx;
 use super::tx::Tx;
@@ -41,45 +42,9 @@ pub fn get_by_user_and_token_id(
                     }
                 }
                 if let Some(token_id) = token_id {
-                    match v {
-                        StableTx::AddPool(ref add_pool_tx) => {
-                            let pool_id = add_pool_tx.pool_id;
-                            let token_0 = pool_map::get_by_pool_id(pool_id).map(|pool| pool.token_id_0)?;
-                            let token_1 = pool_map::get_by_pool_id(pool_id).map(|pool| pool.token_id_1)?;
-                            if token_0 == token_id || token_1 == token_id {
-                                return Some(v.clone());
-                            }
-                        }
-                        StableTx::AddLiquidity(ref add_liquidity_tx) => {
-                            let pool_id = add_liquidity_tx.pool_id;
-                            let token_0 = pool_map::get_by_pool_id(pool_id).map(|pool| pool.token_id_0)?;
-                            let token_1 = pool_map::get_by_pool_id(pool_id).map(|pool| pool.token_id_1)?;
-                            if token_0 == token_id || token_1 == token_id {
-                                return Some(v.clone());
-                            }
-                        }
-                        StableTx::RemoveLiquidity(ref remove_liquidity_tx) => {
-                            let pool_id = remove_liquidity_tx.pool_id;
-                            let token_0 = pool_map::get_by_pool_id(pool_id).map(|pool| pool.token_id_0)?;
-                            let token_1 = pool_map::get_by_pool_id(pool_id).map(|pool| pool.token_id_1)?;
-                            if token_0 == token_id || token_1 == token_id {
-                                return Some(v.clone());
-                            }
-                        }
-                        StableTx::Swap(ref swap_tx) => {
-                            for tx in swap_tx.txs.iter() {
-                                if tx.pay_token_id == token_id || tx.receive_token_id == token_id {
-                                    return Some(v.clone());
-                                }
-                            }
-                        }
-                        StableTx::Send(ref send_tx) => {
-                            if send_tx.token_id == token_id {
-                                return Some(v.clone());
-                            }
-                        }
+                    if !has_token_id(&v, token_id) {
+                        return None;
                     }
-                    return None;
                 }
                 Some(v.clone())
             })
@@ -88,6 +53,32 @@ pub fn get_by_user_and_token_id(
     })
 }
 
+/// true if the tx is of token_id, or of a pool of token_id
+pub fn has_token_id(tx: &StableTx, token_id: u32) -> bool {
+    match tx {
+        StableTx::Swap(ref swap_tx) => swap_tx
+            .txs
+            .iter()
+            .any(|tx| tx.pay_token_id == token_id || tx.receive_token_id == token_id),
+        StableTx::Send(ref send_tx) => send_tx.token_id == token_id,
+        _ => pool_ids(tx).iter().any(|&pool_id| {
+            pool_map::get_by_pool_id(pool_id).is_some_and(|pool| pool.token_id_0 == token_id || pool.token_id_1 == token_id)
+        }),
+    }
+}
+
+/// pools of the tx. swaps can be through several pools
+pub fn pool_ids(tx: &StableTx) -> Vec<u32> {
+    match tx {
+        StableTx::AddPool(ref add_pool_tx) => vec![add_pool_tx.pool_id],
+        StableTx::AddLiquidity(ref add_liquidity_tx) => vec![add_liquidity_tx.pool_id],
+        StableTx::RemoveLiquidity(ref remove_liquidity_tx) => vec![remove_liquidity_tx.pool_id],
+        StableTx::Swap(ref swap_tx) => swap_tx.txs.iter().map(|tx| tx.pool_id).collect(),
+        StableTx::Send(_) => Vec::new(),
+        StableTx::CollectProtocolFees(ref collect_protocol_fees_tx) => vec![collect_protocol_fees_tx.pool_id],
+    }
+}
+
 pub fn insert(tx: &StableTx) -> u64 {
     TX_MAP.with(|m| {
         let mut map = m.borrow_mut();
@@ -98,6 +89,7 @@ pub fn insert(tx: &StableTx) -> u64 {
             RemoveLiquidity(tx) => RemoveLiquidity(RemoveLiquidityTx { tx_id, ..tx.clone() }),
             Swap(tx) => Swap(SwapTx { tx_id, ..tx.clone() }),
             Send(tx) => Send(SendTx { tx_id, ..tx.clone() }),
+            CollectProtocolFees(tx) => CollectProtocolFees(CollectProtocolFeesTx { tx_id, ..tx.clone() }),
         };
         map.insert(StableTxId(tx_id), insert_tx);
         tx_id
@@ -0,0 +1,98 @@
+use candid::Nat;
+use std::collections::BTreeMap;
+
+use super::stable_user::StableUser;
+use super::user_map;
+
+use crate::helpers::fee_helpers::{fee_tier_for_volume, user_volumes};
+use crate::helpers::nat_helpers::{nat_add, nat_zero};
+use crate::helpers::stats_helpers::stats_day;
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_user_volume::stable_user_volume::StableUserVolume;
+use crate::stable_user_volume::user_volume_map;
+
+const VOLUME_WINDOW_DAYS: u64 = 30;
+
+/// recalculate the 30 day swap volume of users with daily volumes and promote or demote them to the fee tier it reaches
+/// users without daily volumes were already demoted when their last daily volume expired
+/// promotional fee levels granted by kingkong are kept until they expire
+pub fn update_fee_tiers() {
+    if not_in_maintenance_mode().is_err() {
+        return;
+    }
+
+    let fee_tiers = kong_settings_map::get().fee_tiers;
+    let ts = get_time();
+    let volumes = volumes_30d(ts);
+
+    volumes
+        .into_iter()
+        .filter_map(|(user_id, volume_30d)| {
+            let user = user_map::get_by_user_id(user_id)?;
+            let volume_fee_level = fee_tier_for_volume(&fee_tiers, &volume_30d).map_or(0, |fee_tier| fee_tier.fee_level);
+            let update_user = with_volume_fee_level(&user, volume_30d, volume_fee_level, ts);
+            if update_user.volume_30d != user.volume_30d
+                || update_user.volume_fee_level != user.volume_fee_level
+                || update_user.fee_level != user.fee_level
+                || update_user.fee_level_expires_at != user.fee_level_expires_at
+            {
+                Some(update_user)
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<StableUser>>()
+        .iter()
+        .for_each(user_map::update);
+}
+
+/// user with the new volume and volume fee level
+/// fee_level follows the volume fee level unless the user has a higher promotional fee level which has not expired.
+/// a fee level above the previous volume fee level without expiry was set by kingkong and is kept
+fn with_volume_fee_level(user: &StableUser, volume_30d: Nat, volume_fee_level: u8, ts: u64) -> StableUser {
+    let is_promotional =
+        user.fee_level > user.volume_fee_level && !matches!(user.fee_level_expires_at, Some(expires_at) if ts > expires_at);
+    let (fee_level, fee_level_expires_at) = if is_promotional && user.fee_level > volume_fee_level {
+        (user.fee_level, user.fee_level_expires_at)
+    } else {
+        (volume_fee_level, None)
+    };
+    StableUser {
+        fee_level,
+        fee_level_expires_at,
+        volume_30d,
+        volume_fee_level,
+        ..user.clone()
+    }
+}
+
+/// add the ckUSDT volume of a swap to the user's volume of the day of ts
+pub fn record_swap_volume(user_id: u32, ts: u64, volume: &Nat) {
+    let day = stats_day(ts);
+    let user_volume = user_volume_map::get(user_id, day).unwrap_or(StableUserVolume {
+        day,
+        user_id,
+        volume: nat_zero(),
+    });
+    user_volume_map::update(&StableUserVolume {
+        volume: nat_add(&user_volume.volume, volume),
+        ..user_volume
+    });
+}
+
+/// ckUSDT swap volume by user of the last 30 days including today
+/// users whose daily volumes have all expired are included with zero volume. expired daily volumes are removed
+fn volumes_30d(ts: u64) -> BTreeMap<u32, Nat> {
+    let start_day = (stats_day(ts) + 1).saturating_sub(VOLUME_WINDOW_DAYS);
+    let daily_volumes = user_volume_map::get_from_day(0);
+    let (window_volumes, expired_volumes): (Vec<StableUserVolume>, Vec<StableUserVolume>) =
+        daily_volumes.into_iter().partition(|daily_volume| daily_volume.day >= start_day);
+    let mut volumes = user_volumes(&window_volumes);
+    for expired_volume in expired_volumes {
+        volumes.entry(expired_volume.user_id).or_insert(nat_zero());
+    }
+    user_volume_map::remove_before(start_day);
+    volumes
+}
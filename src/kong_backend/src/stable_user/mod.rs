@@ -1,5 +1,7 @@
 pub mod banned_user_map;
+pub mod fee_tiers;
 pub mod principal_id_map;
+pub mod referee_count_map;
 mod referral_code;
 #[allow(clippy::module_inception)]
 pub mod stable_user;
@@ -0,0 +1,25 @@
+use crate::helpers::referral_helpers::update_referee_count;
+use crate::stable_memory::{REFEREE_COUNT_MAP, USER_MAP};
+
+pub fn create_referee_count_map() {
+    REFEREE_COUNT_MAP.with(|m| {
+        let mut referee_count_map = m.borrow_mut();
+        referee_count_map.clear();
+        USER_MAP.with(|user_map| {
+            user_map
+                .borrow()
+                .iter()
+                .for_each(|(_, user)| update_referee_count(&mut referee_count_map, None, user.referred_by));
+        });
+    });
+}
+
+/// number of users referred by user_id
+pub fn get_num_referees(user_id: u32) -> u32 {
+    REFEREE_COUNT_MAP.with(|m| m.borrow().get(&user_id).copied().unwrap_or(0))
+}
+
+/// update the referee counts when a user's referred_by changes from old_referred_by to referred_by
+pub fn update_referred_by(old_referred_by: Option<u32>, referred_by: Option<u32>) {
+    REFEREE_COUNT_MAP.with(|m| update_referee_count(&mut m.borrow_mut(), old_referred_by, referred_by));
+}
@@ -1,5 +1,6 @@
+use crate::helpers::nat_helpers::nat_zero;
 use crate::ic::id::caller_principal_id;
-use candid::CandidType;
+use candid::{CandidType, Nat};
 use ic_stable_structures::{storable::Bound, Storable};
 use serde::{Deserialize, Serialize};
 
@@ -42,7 +43,25 @@ pub struct StableUser {
     // fee level for the user. user's pays lp_fee = 100 - fee_level / 100
     // so 0 = no discount, 100 = pays no lp_fee on swaps
     pub fee_level: u8,
-    pub fee_level_expires_at: Option<u64>,
+    pub fee_level_expires_at: Option<u64>, // expiry of a promotional fee level, after which the user returns to volume_fee_level
+    #[serde(default = "nat_zero")]
+    pub volume_30d: Nat, // ckUSDT swap volume over the trailing 30 days, updated by the fee tiers timer
+    #[serde(default = "zero_u8")]
+    pub volume_fee_level: u8, // fee level of the fee tier reached by volume_30d
+}
+
+fn zero_u8() -> u8 {
+    0
+}
+
+impl StableUser {
+    /// fee level applied to swaps. a promotional fee level is only applied until it expires
+    pub fn current_fee_level(&self, ts: u64) -> u8 {
+        match self.fee_level_expires_at {
+            Some(fee_level_expires_at) if ts > fee_level_expires_at => self.volume_fee_level,
+            _ => self.fee_level,
+        }
+    }
 }
 
 impl Default for StableUser {
@@ -55,6 +74,8 @@ impl Default for StableUser {
             referred_by_expires_at: None,
             fee_level: 0,
             fee_level_expires_at: None,
+            volume_30d: nat_zero(),
+            volume_fee_level: 0,
         }
     }
 }
@@ -1,4 +1,5 @@
 use super::principal_id_map;
+use super::referee_count_map;
 use super::referral_code::{generate_referral_code, REFERRAL_INTERVAL};
 use super::stable_user::{StableUser, StableUserId};
 
@@ -87,7 +88,7 @@ pub fn insert(referred_by: Option<&str>) -> Result<u32, String> {
             // check if fee_level is expired
             if let Some(fee_level_expires_at) = user.fee_level_expires_at {
                 if now > fee_level_expires_at {
-                    user.fee_level = 0;
+                    user.fee_level = user.volume_fee_level;
                     user.fee_level_expires_at = None;
                     update = true;
                 }
@@ -121,15 +122,47 @@ pub fn insert(referred_by: Option<&str>) -> Result<u32, String> {
     };
 
     if update {
-        USER_MAP.with(|m| {
-            m.borrow_mut().insert(StableUserId(user.user_id), user.clone());
-        });
+        insert_user(&user);
         _ = archive_to_kong_data(&user);
     }
 
     Ok(user.user_id)
 }
 
+/// return user_id of principal_id, creating the user if not registered
+/// used when a principal receives LP tokens before interacting with Kong
+pub fn insert_by_principal_id(principal_id: &str) -> Result<u32, String> {
+    if let Some(user) = get_by_principal_id(principal_id)? {
+        return Ok(user.user_id);
+    }
+
+    let mut rng = get_pseudo_seed()?;
+    let user = StableUser {
+        user_id: kong_settings_map::inc_user_map_idx(),
+        principal_id: principal_id.to_string(),
+        my_referral_code: generate_referral_code(&mut rng),
+        ..Default::default()
+    };
+    principal_id_map::insert_principal_id(&user);
+    insert_user(&user);
+    _ = archive_to_kong_data(&user);
+
+    Ok(user.user_id)
+}
+
+pub fn update(user: &StableUser) {
+    insert_user(user);
+    _ = archive_to_kong_data(user);
+}
+
+/// insert user to USER_MAP and keep the referee counts of the referrers
+fn insert_user(user: &StableUser) {
+    let old_referred_by = USER_MAP
+        .with(|m| m.borrow_mut().insert(StableUserId(user.user_id), user.clone()))
+        .and_then(|old_user| old_user.referred_by);
+    referee_count_map::update_referred_by(old_referred_by, user.referred_by);
+}
+
 fn archive_to_kong_data(user: &StableUser) -> Result<(), String> {
     if !kong_settings_map::get().archive_to_kong_data {
         return Ok(());
@@ -0,0 +1,3 @@
+#[allow(clippy::module_inception)]
+pub mod stable_user_volume;
+pub mod user_volume_map;
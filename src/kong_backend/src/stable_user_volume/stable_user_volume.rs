@@ -0,0 +1,42 @@
+use candid::{CandidType, Nat};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+/// volumes are ordered by day and then user_id so the volumes of a window and the expired days are ranges
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableUserVolumeId {
+    pub day: u64, // days since the Unix epoch
+    pub user_id: u32,
+}
+
+impl Storable for StableUserVolumeId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// swap volume of a user within one day. volume is in ckUSDT at the time of the swaps
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableUserVolume {
+    pub day: u64,
+    pub user_id: u32,
+    pub volume: Nat,
+}
+
+impl Storable for StableUserVolume {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
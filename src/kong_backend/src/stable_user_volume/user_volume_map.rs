@@ -0,0 +1,48 @@
+use super::stable_user_volume::{StableUserVolume, StableUserVolumeId};
+
+use crate::stable_memory::USER_VOLUME_MAP;
+
+/// volumes of all users from from_day onwards, oldest first
+pub fn get_from_day(from_day: u64) -> Vec<StableUserVolume> {
+    USER_VOLUME_MAP.with(|m| {
+        m.borrow()
+            .range(StableUserVolumeId { day: from_day, user_id: 0 }..)
+            .map(|(_, v)| v)
+            .collect()
+    })
+}
+
+pub fn get(user_id: u32, day: u64) -> Option<StableUserVolume> {
+    USER_VOLUME_MAP.with(|m| m.borrow().get(&StableUserVolumeId { day, user_id }))
+}
+
+pub fn update(volume: &StableUserVolume) {
+    USER_VOLUME_MAP.with(|m| {
+        m.borrow_mut().insert(
+            StableUserVolumeId {
+                day: volume.day,
+                user_id: volume.user_id,
+            },
+            volume.clone(),
+        )
+    });
+}
+
+/// remove volumes of all users older than before_day
+pub fn remove_before(before_day: u64) {
+    USER_VOLUME_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let keys: Vec<StableUserVolumeId> = map
+            .range(
+                ..StableUserVolumeId {
+                    day: before_day,
+                    user_id: 0,
+                },
+            )
+            .map(|(k, _)| k)
+            .collect();
+        for key in keys {
+            map.remove(&key);
+        }
+    });
+}
@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+pub mod stable_webhook;
+pub mod stable_webhook_delivery;
+pub mod webhook_delivery_map;
+pub mod webhook_map;
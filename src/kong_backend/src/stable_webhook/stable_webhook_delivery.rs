@@ -0,0 +1,62 @@
+use candid::CandidType;
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableWebhookDeliveryId(pub u64);
+
+impl Storable for StableWebhookDeliveryId {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WebhookDeliveryStatus {
+    AwaitingReply, // request reached Success or Failed but its reply is not recorded yet
+    Pending,
+    Sent, // waiting to be acknowledged by the webhook canister
+    Failed,
+}
+
+impl std::fmt::Display for WebhookDeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookDeliveryStatus::AwaitingReply => write!(f, "Awaiting reply"),
+            WebhookDeliveryStatus::Pending => write!(f, "Pending"),
+            WebhookDeliveryStatus::Sent => write!(f, "Sent"),
+            WebhookDeliveryStatus::Failed => write!(f, "Failed"),
+        }
+    }
+}
+
+/// outbox entry of a request to notify the user's webhook of
+/// acknowledged entries are removed, entries which ran out of attempts are kept as Failed until they expire
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableWebhookDelivery {
+    pub delivery_id: u64,
+    pub user_id: u32,
+    pub request_id: u64,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u32,
+    pub message: Option<String>, // error of the last attempt
+    pub ts: u64,                 // timestamp of the last change of status
+}
+
+impl Storable for StableWebhookDelivery {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
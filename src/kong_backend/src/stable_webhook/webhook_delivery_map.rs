@@ -0,0 +1,68 @@
+use super::stable_webhook_delivery::{StableWebhookDelivery, StableWebhookDeliveryId};
+
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_memory::WEBHOOK_DELIVERY_MAP;
+
+pub fn len() -> u64 {
+    WEBHOOK_DELIVERY_MAP.with(|m| m.borrow().len())
+}
+
+/// get all deliveries of a user, most recent first
+pub fn get_by_user_id(user_id: u32) -> Vec<StableWebhookDelivery> {
+    WEBHOOK_DELIVERY_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .rev()
+            .filter_map(|(_, v)| if v.user_id == user_id { Some(v) } else { None })
+            .collect()
+    })
+}
+
+pub fn get_by_delivery_id(delivery_id: u64) -> Option<StableWebhookDelivery> {
+    WEBHOOK_DELIVERY_MAP.with(|m| m.borrow().get(&StableWebhookDeliveryId(delivery_id)))
+}
+
+/// get the delivery of a request
+pub fn get_by_request_id(request_id: u64) -> Option<StableWebhookDelivery> {
+    WEBHOOK_DELIVERY_MAP.with(|m| {
+        m.borrow()
+            .iter()
+            .find_map(|(_, v)| if v.request_id == request_id { Some(v) } else { None })
+    })
+}
+
+/// get all deliveries, oldest first
+pub fn get() -> Vec<StableWebhookDelivery> {
+    WEBHOOK_DELIVERY_MAP.with(|m| m.borrow().iter().map(|(_, v)| v).collect())
+}
+
+pub fn insert(delivery: &StableWebhookDelivery) -> u64 {
+    WEBHOOK_DELIVERY_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        let delivery_id = kong_settings_map::inc_webhook_delivery_map_idx();
+        let insert_delivery = StableWebhookDelivery {
+            delivery_id,
+            ..delivery.clone()
+        };
+        map.insert(StableWebhookDeliveryId(delivery_id), insert_delivery);
+        delivery_id
+    })
+}
+
+pub fn update(delivery: &StableWebhookDelivery) {
+    WEBHOOK_DELIVERY_MAP.with(|m| {
+        m.borrow_mut()
+            .insert(StableWebhookDeliveryId(delivery.delivery_id), delivery.clone())
+    });
+}
+
+pub fn remove(delivery_id: u64) -> Option<StableWebhookDelivery> {
+    WEBHOOK_DELIVERY_MAP.with(|m| m.borrow_mut().remove(&StableWebhookDeliveryId(delivery_id)))
+}
+
+/// remove all deliveries of a user
+pub fn remove_by_user_id(user_id: u32) {
+    for delivery in get_by_user_id(user_id) {
+        remove(delivery.delivery_id);
+    }
+}
@@ -0,0 +1,16 @@
+use super::stable_webhook::{StableWebhook, StableWebhookId};
+
+use crate::stable_memory::WEBHOOK_MAP;
+
+pub fn get_by_user_id(user_id: u32) -> Option<StableWebhook> {
+    WEBHOOK_MAP.with(|m| m.borrow().get(&StableWebhookId(user_id)))
+}
+
+/// insert or replace the user's webhook
+pub fn insert(webhook: &StableWebhook) {
+    WEBHOOK_MAP.with(|m| m.borrow_mut().insert(StableWebhookId(webhook.user_id), webhook.clone()));
+}
+
+pub fn remove(user_id: u32) -> Option<StableWebhook> {
+    WEBHOOK_MAP.with(|m| m.borrow_mut().remove(&StableWebhookId(user_id)))
+}
@@ -1,19 +1,35 @@
 use candid::Nat;
 
-use super::swap_amounts::swap_amounts;
+use super::swap_amounts::{swap_amounts_exact_output, swap_amounts_routes};
 use super::swap_calc::SwapCalc;
 
 use crate::helpers::nat_helpers::{nat_is_zero, nat_to_decimals_f64};
 use crate::stable_token::{stable_token::StableToken, token::Token};
 
+/// user_fee_level is the fee level of the user the swap is for, which is not always the caller (ie. limit order fills)
 pub fn calculate_amounts(
     pay_token: &StableToken,
     pay_amount: &Nat,
     receive_token: &StableToken,
     user_receive_amount: Option<&Nat>,
     user_max_slippage: f64,
+    exact_output: bool,
+    user_fee_level: u8,
 ) -> Result<(Nat, f64, f64, f64, Vec<SwapCalc>), String> {
-    let (receive_amount_with_fees_and_gas, price, mid_price, slippage, txs) = swap_amounts(pay_token, Some(pay_amount), receive_token)?;
+    if exact_output {
+        let user_receive_amount = user_receive_amount.ok_or("Receive amount is required for exact output swaps")?;
+        return calculate_exact_output_amounts(
+            pay_token,
+            pay_amount,
+            receive_token,
+            user_receive_amount,
+            user_max_slippage,
+            user_fee_level,
+        );
+    }
+
+    let (receive_amount_with_fees_and_gas, price, mid_price, slippage, routes) =
+        swap_amounts_routes(pay_token, Some(pay_amount), receive_token, user_fee_level)?;
 
     // make sure receive_amount is not zero
     if nat_is_zero(&receive_amount_with_fees_and_gas) {
@@ -46,5 +62,45 @@ pub fn calculate_amounts(
         ))?
     }
 
-    Ok((receive_amount_with_fees_and_gas, mid_price, price, slippage, txs))
+    // swaps of all routes are executed together
+    Ok((receive_amount_with_fees_and_gas, mid_price, price, slippage, routes.concat()))
+}
+
+/// exact-output swaps are solved again with the latest state of the pools so a better price lowers the amount swapped
+/// instead of leaving the user's surplus in the pools. only the rounding of the solver is added to the LP fees
+/// pay_amount is the maximum amount that can be swapped
+fn calculate_exact_output_amounts(
+    pay_token: &StableToken,
+    pay_amount: &Nat,
+    receive_token: &StableToken,
+    receive_amount: &Nat,
+    user_max_slippage: f64,
+    user_fee_level: u8,
+) -> Result<(Nat, f64, f64, f64, Vec<SwapCalc>), String> {
+    let (exact_pay_amount, receive_amount_with_fees_and_gas, price, mid_price, slippage, swaps) =
+        swap_amounts_exact_output(pay_token, receive_amount, receive_token, user_fee_level)?;
+
+    // check if pay_amount is enough with the latest state of the pools
+    if exact_pay_amount > *pay_amount {
+        let exact_pay_amount_f64 = nat_to_decimals_f64(pay_token.decimals(), &exact_pay_amount).unwrap_or(0_f64);
+        Err(format!(
+            "Insufficient pay amount. Requires {} {} with {}% slippage",
+            exact_pay_amount_f64,
+            pay_token.symbol(),
+            slippage
+        ))?
+    }
+
+    // check if slippage is within user's specified
+    if slippage > user_max_slippage {
+        let exact_pay_amount_f64 = nat_to_decimals_f64(pay_token.decimals(), &exact_pay_amount).unwrap_or(0_f64);
+        Err(format!(
+            "Slippage exceeded. Requires {} {} with {}% slippage",
+            exact_pay_amount_f64,
+            pay_token.symbol(),
+            slippage
+        ))?
+    }
+
+    Ok((receive_amount_with_fees_and_gas, mid_price, price, slippage, swaps))
 }
@@ -1,5 +1,6 @@
 pub mod calculate_amounts;
 pub mod return_pay_token;
+pub mod return_unused_pay_token;
 pub mod send_receive_token;
 #[allow(clippy::module_inception)]
 pub mod swap;
@@ -0,0 +1,91 @@
+use candid::Nat;
+use icrc_ledger_types::icrc1::account::Account;
+
+use super::swap_calc::SwapCalc;
+
+use crate::helpers::nat_helpers::{nat_add, nat_is_zero, nat_subtract, nat_zero};
+use crate::ic::{address::Address, transfer::icrc1_transfer};
+use crate::stable_claim::{claim_map, stable_claim::StableClaim};
+use crate::stable_pool::pool_map;
+use crate::stable_request::{request_map, status::StatusCode};
+use crate::stable_token::{stable_token::StableToken, token::Token};
+use crate::stable_transfer::{stable_transfer::StableTransfer, transfer_map, tx_id::TxId};
+
+/// returns the amount of pay_amount used by the swaps and the unused amount to be returned to the user
+/// an unused amount too small to cover the gas fee of returning it is added to the LP fees of the first pool and counted as used
+pub fn settle_unused_pay_amount(pay_token: &StableToken, pay_amount: &Nat, swaps: &[SwapCalc]) -> (Nat, Option<Nat>) {
+    let Some(first_swap) = swaps.first() else {
+        return (pay_amount.clone(), None);
+    };
+    let swap_pay_amount = first_swap.pay_amount.clone();
+    let unused_pay_amount = match nat_subtract(pay_amount, &swap_pay_amount) {
+        Some(unused_pay_amount) if !nat_is_zero(&unused_pay_amount) => unused_pay_amount,
+        _ => return (pay_amount.clone(), None),
+    };
+    if unused_pay_amount > pay_token.fee() {
+        return (swap_pay_amount, Some(unused_pay_amount));
+    }
+
+    // refresh pool with the latest state
+    if let Some(mut pool) = pool_map::get_by_pool_id(first_swap.pool_id) {
+        if first_swap.pay_token_id == pool.token_id_0 {
+            pool.lp_fee_0 = nat_add(&pool.lp_fee_0, &unused_pay_amount);
+        } else {
+            pool.lp_fee_1 = nat_add(&pool.lp_fee_1, &unused_pay_amount);
+        }
+        pool.set_tvl();
+        pool_map::update(&pool);
+    }
+    (pay_amount.clone(), None)
+}
+
+/// return the unused part of the pay amount of an exact-output swap
+#[allow(clippy::too_many_arguments)]
+pub async fn return_unused_pay_token(
+    request_id: u64,
+    user_id: u32,
+    to_principal_id: &Account,
+    pay_token: &StableToken,
+    unused_pay_amount: &Nat,
+    transfer_ids: &mut Vec<u64>,
+    claim_ids: &mut Vec<u64>,
+    ts: u64,
+) {
+    let token_id = pay_token.token_id();
+
+    request_map::update_status(request_id, StatusCode::ReturnUnusedPayToken, None);
+
+    let unused_pay_amount_with_gas = nat_subtract(unused_pay_amount, &pay_token.fee()).unwrap_or(nat_zero());
+    match icrc1_transfer(&unused_pay_amount_with_gas, to_principal_id, pay_token, None).await {
+        Ok(tx_id) => {
+            let transfer_id = transfer_map::insert(&StableTransfer {
+                transfer_id: 0,
+                request_id,
+                is_send: false,
+                amount: unused_pay_amount_with_gas,
+                token_id,
+                tx_id: TxId::BlockIndex(tx_id),
+                ts,
+            });
+            transfer_ids.push(transfer_id);
+            request_map::update_status(request_id, StatusCode::ReturnUnusedPayTokenSuccess, None);
+        }
+        Err(e) => {
+            let claim = StableClaim::new(
+                user_id,
+                token_id,
+                unused_pay_amount,
+                Some(request_id),
+                Some(Address::PrincipalId(*to_principal_id)),
+                ts,
+            );
+            let claim_id = claim_map::insert(&claim);
+            claim_ids.push(claim_id);
+            request_map::update_status(
+                request_id,
+                StatusCode::ReturnUnusedPayTokenFailed,
+                Some(&format!("Saved as claim #{}. {}", claim_id, e)),
+            );
+        }
+    };
+}
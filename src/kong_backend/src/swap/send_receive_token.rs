@@ -24,17 +24,17 @@ pub async fn send_receive_token(
     receive_amount: &Nat,
     to_address: &Address,
     transfer_ids: &mut Vec<u64>,
+    claim_ids: &mut Vec<u64>,
     mid_price: f64,
     price: f64,
     slippage: f64,
     txs: &[SwapCalc],
+    approval_headroom: Option<&Nat>,
     ts: u64,
 ) -> SwapReply {
     let pay_token_id = pay_token.token_id();
     let receive_token_id = receive_token.token_id();
 
-    let mut claim_ids = Vec::new();
-
     request_map::update_status(request_id, StatusCode::SendReceiveToken, None);
 
     // send ICP using icp_transfer or ICRC1 using icrc1_transfer
@@ -87,13 +87,14 @@ pub async fn send_receive_token(
         slippage,
         txs,
         transfer_ids,
-        &claim_ids,
+        claim_ids,
         ts,
+        approval_headroom,
     );
     let tx_id = tx_map::insert(&StableTx::Swap(swap_tx.clone()));
     let reply = match tx_map::get_by_user_and_token_id(Some(tx_id), None, None, None).first() {
         Some(StableTx::Swap(swap_tx)) => to_swap_reply(swap_tx),
-        _ => to_swap_reply_failed(request_id, pay_token, pay_amount, Some(receive_token), transfer_ids, &claim_ids, ts),
+        _ => to_swap_reply_failed(request_id, pay_token, pay_amount, Some(receive_token), transfer_ids, claim_ids, ts),
     };
     request_map::update_reply(request_id, Reply::Swap(reply.clone()));
 
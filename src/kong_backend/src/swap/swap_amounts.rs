@@ -5,19 +5,32 @@ use num_traits::ToPrimitive;
 
 use super::swap_calc::SwapCalc;
 
+use crate::flash_loans::flash_loan_lock::check_flash_loan_lock;
 use crate::helpers::math_helpers::price_rounded;
 use crate::helpers::math_helpers::round_f64;
 use crate::helpers::nat_helpers::nat_zero;
 use crate::helpers::nat_helpers::{
-    nat_add, nat_divide, nat_is_zero, nat_multiply, nat_multiply_f64, nat_subtract, nat_to_decimal_precision,
+    nat_add, nat_divide, nat_divide_ceil, nat_is_zero, nat_multiply, nat_multiply_f64, nat_subtract, nat_to_bigint,
+    nat_to_decimal_precision,
 };
+use crate::helpers::pool_helpers::{check_pool_action, PoolAction};
+use crate::helpers::split_swap_helpers::{split_allocations, take_gas_fee_once};
+use crate::helpers::stableswap_helpers::{stableswap_amount_in, stableswap_amount_out};
+use crate::ic::get_time::get_time;
+use crate::stable_kong_settings::kong_settings_map;
+use crate::stable_pool::concentrated_pool;
+use crate::stable_pool::pool_curve::PoolCurve;
 use crate::stable_pool::pool_map;
 use crate::stable_pool::stable_pool::StablePool;
 use crate::stable_token::stable_token::StableToken;
 use crate::stable_token::token::Token;
-use crate::stable_token::token_map;
 use crate::stable_user::user_map;
 
+// max number of times a route is re-solved when rounding leaves the exact-output swap short
+const MAX_EXACT_OUTPUT_ITERATIONS: usize = 8;
+// number of parts pay_amount is divided into when splitting a swap across routes
+const SPLIT_SWAP_PARTS: u32 = 10;
+
 /// calculate the receive_amount of a swap using mid price
 /// returns the receive_amount
 pub fn swap_mid_amounts(pay_token: &StableToken, pay_amount: &Nat, receive_token: &StableToken) -> Result<Nat, String> {
@@ -28,21 +41,50 @@ pub fn swap_mid_amounts(pay_token: &StableToken, pay_amount: &Nat, receive_token
 }
 
 pub fn swap_mid_price(pay_token: &StableToken, receive_token: &StableToken) -> Result<f64, String> {
-    let (_, _, mid_price, _, _) = swap_amounts(pay_token, None, receive_token)?;
+    let (_, _, mid_price, _, _) = swap_amounts(pay_token, None, receive_token, 0)?;
     Ok(mid_price)
 }
 
+/// fee level of the user, used to calculate the LP fee of the user's swaps
+pub fn user_fee_level(user_id: u32) -> u8 {
+    user_map::get_by_user_id(user_id).unwrap_or_default().current_fee_level(get_time())
+}
+
+/// fee level of the caller. anonymous and unregistered callers get the default fee level
+pub fn caller_fee_level() -> u8 {
+    user_map::get_by_caller()
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+        .current_fee_level(get_time())
+}
+
 /// calculate the receive_amount of a swap using pool price (bid/offer, fee and gas included)
 /// returns the receive_amount, price, mid_price, slippage and the pools used
 ///
 /// pay_token - pay token
 /// pay_amount - amount of pay token. pay_amount is None if only mid price is requested
 /// receive_token - receive token
+/// user_fee_level - fee level of the user swapping. see user_fee_level() and caller_fee_level()
 pub fn swap_amounts(
     pay_token: &StableToken,
     pay_amount: Option<&Nat>,
     receive_token: &StableToken,
+    user_fee_level: u8,
 ) -> Result<(Nat, f64, f64, f64, Vec<SwapCalc>), String> {
+    let (receive_amount, price, mid_price, slippage, routes) = swap_amounts_routes(pay_token, pay_amount, receive_token, user_fee_level)?;
+    Ok((receive_amount, price, mid_price, slippage, routes.concat()))
+}
+
+/// same as swap_amounts() but the pools used are grouped by route
+/// if splitting pay_amount across several routes receives more than the best single route, the split routes are returned
+#[allow(clippy::complexity)]
+pub fn swap_amounts_routes(
+    pay_token: &StableToken,
+    pay_amount: Option<&Nat>,
+    receive_token: &StableToken,
+    user_fee_level: u8,
+) -> Result<(Nat, f64, f64, f64, Vec<Vec<SwapCalc>>), String> {
     let pay_token_id = pay_token.token_id();
     let receive_token_id = receive_token.token_id();
 
@@ -54,7 +96,7 @@ pub fn swap_amounts(
     }
 
     // if pay_amount is None, user_fee_level is None as only mid_price is needed
-    let user_fee_level = pay_amount.map(|_| user_map::get_by_caller().ok().flatten().unwrap_or_default().fee_level);
+    let user_fee_level = pay_amount.map(|_| user_fee_level);
 
     // swaps stores all the swap permutations
     let mut swaps: Vec<(Nat, f64, f64, f64, Vec<SwapCalc>)> = Vec::new();
@@ -68,18 +110,27 @@ pub fn swap_amounts(
     // 3-step swap
     three_step_swaps(pay_token_id, pay_amount, receive_token_id, user_fee_level, &mut swaps)?;
 
-    let max_swap = if pay_amount.is_none() {
+    let Some(pay_amount) = pay_amount else {
         // return the swap with the highest mid_price
-        swaps
+        let (receive_amount, price, mid_price, slippage, swap) = swaps
             .into_iter()
             .max_by(|a, b| a.2.partial_cmp(&b.2).unwrap())
-            .ok_or("Invalid swap")?
-    } else {
-        // return the swap with the highest receive amount
-        swaps.into_iter().max_by(|a, b| a.0.cmp(&b.0)).ok_or("Invalid swap")?
+            .ok_or("Invalid swap")?;
+        return Ok((receive_amount, price, mid_price, slippage, vec![swap]));
     };
 
-    Ok(max_swap)
+    // return the swap with the highest receive amount
+    let routes = swaps.iter().map(|swap| swap.4.clone()).collect::<Vec<_>>();
+    let (receive_amount, price, mid_price, slippage, swap) = swaps.into_iter().max_by(|a, b| a.0.cmp(&b.0)).ok_or("Invalid swap")?;
+
+    // split pay_amount across several routes if it receives more
+    if let Some(split_swap) = split_swap_amounts(&routes, pay_amount, receive_token, user_fee_level)? {
+        if split_swap.0 > receive_amount {
+            return Ok(split_swap);
+        }
+    }
+
+    Ok((receive_amount, price, mid_price, slippage, vec![swap]))
 }
 
 /// returns (receive_amount_with_gas_and_fees, price, mid_price, slippage, swap)
@@ -91,12 +142,10 @@ fn one_step_swaps(
     user_fee_level: Option<u8>,
     swaps: &mut Vec<(Nat, f64, f64, f64, Vec<SwapCalc>)>,
 ) -> Result<(), String> {
-    let swap = if let Some(pool) = pool_map::get_by_token_ids(pay_token_id, receive_token_id) {
-        swap_amount_0(&pool, pay_amount, user_fee_level, None, None)?
-    } else if let Some(pool) = pool_map::get_by_token_ids(receive_token_id, pay_token_id) {
-        swap_amount_1(&pool, pay_amount, user_fee_level, None, None)?
-    } else {
-        return Ok(());
+    let swap = match route_pool(pay_token_id, receive_token_id, pay_amount.is_none()) {
+        Some((pool, true)) => swap_amount_0(&pool, pay_amount, user_fee_level, None, None)?,
+        Some((pool, false)) => swap_amount_1(&pool, pay_amount, user_fee_level, None, None)?,
+        None => return Ok(()),
     };
 
     if pay_amount.is_none() {
@@ -117,6 +166,9 @@ fn one_step_swaps(
     Ok(())
 }
 
+/// 2-step swaps via each quote token
+/// token0/quote -> quote/token1. make sure token0 != quote and token1 != quote
+/// pools can be in either order of tokens, ie. ckUSDT -> ICP uses the ICP/ckUSDT pool in reverse order
 #[allow(clippy::complexity)]
 fn two_step_swaps(
     pay_token_id: u32,
@@ -125,337 +177,392 @@ fn two_step_swaps(
     user_fee_level: Option<u8>,
     swaps: &mut Vec<(Nat, f64, f64, f64, Vec<SwapCalc>)>,
 ) -> Result<(), String> {
-    let ckusdt_token_id = token_map::get_ckusdt()?.token_id();
-    let icp_token_id = token_map::get_icp()?.token_id();
-
-    // test for 2-step swap via ckUSDT
-    // token0/ckUSDT -> ckUSDT/token1. make sure:
-    // 1) token0 != ckUSDT
-    // 2) token1 != ckUSDT
-    if pay_token_id != ckusdt_token_id && receive_token_id != ckusdt_token_id {
-        let pool1_ckusdt = pool_map::get_by_token_ids(pay_token_id, ckusdt_token_id);
-        let pool2_ckusdt = pool_map::get_by_token_ids(receive_token_id, ckusdt_token_id);
-        if pool1_ckusdt.is_some() && pool2_ckusdt.is_some() {
-            // 2-step swap
-            // split the LP fee between the two swaps. the "+ 1) / 2" will round up the integer
-            // 1st swap no gas fees as this is intermediate swap
-            // 2nd swap use standard gas fees
-            let pool1 = pool1_ckusdt.as_ref().unwrap();
-            let pool2 = pool2_ckusdt.as_ref().unwrap();
-            let swap1_lp_fee = (pool1.lp_fee_bps + 1) / 2;
-            // swap token0 to ckUSDT
-            let swap1 = swap_amount_0(pool1, pay_amount, user_fee_level, Some(swap1_lp_fee), Some(&nat_zero()))?;
-            let swap2_lp_fee = (pool2.lp_fee_bps + 1) / 2;
-            // swap ckUSDT to token1 (reverse order of pool)
-            let swap2 = swap_amount_1(
-                pool2,
-                Some(&swap1.receive_amount_with_fees_and_gas()),
-                user_fee_level,
-                Some(swap2_lp_fee),
-                None,
-            )?;
-            if pay_amount.is_none() {
-                // if pay_amount is None, return the mid price
-                let swap1_mid_price = swap1.get_mid_price().unwrap_or(BigRational::zero());
-                let swap2_mid_price = swap2.get_mid_price().unwrap_or(BigRational::zero());
-                let mid_price = swap1_mid_price * swap2_mid_price;
-                let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-                swaps.push((nat_zero(), mid_price_f64, mid_price_f64, 0.0, vec![swap1, swap2]));
-            } else {
-                let receive_amount = swap2.receive_amount_with_fees_and_gas();
-                let swap1_price = swap1.get_price().unwrap_or(BigRational::zero());
-                let swap2_price = swap2.get_price().unwrap_or(BigRational::zero());
-                let price = swap1_price * swap2_price;
-                let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
-                let swap1_mid_price = swap1.get_mid_price().unwrap_or(BigRational::zero());
-                let swap2_mid_price = swap2.get_mid_price().unwrap_or(BigRational::zero());
-                let mid_price = swap1_mid_price * swap2_mid_price;
-                let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-                let slippage_f64 = get_slippage(&price, &mid_price).unwrap_or(0_f64);
-                swaps.push((receive_amount, price_f64, mid_price_f64, slippage_f64, vec![swap1, swap2]));
-            }
-        }
+    let quote_token_ids = kong_settings_map::get().quote_token_ids;
+    for quote_token_id in quote_token_ids
+        .iter()
+        .filter(|quote_token_id| **quote_token_id != pay_token_id && **quote_token_id != receive_token_id)
+    {
+        let (Some(pool1), Some(pool2)) = (
+            route_pool(pay_token_id, *quote_token_id, pay_amount.is_none()),
+            route_pool(*quote_token_id, receive_token_id, pay_amount.is_none()),
+        ) else {
+            continue;
+        };
+        multi_step_swaps(&[pool1, pool2], pay_amount, user_fee_level, swaps)?;
     }
 
-    // test for 2-step swap via ICP
-    // token0/ICP -> ICP/token1. make sure:
-    // 1) token0 != ICP
-    // 2) token1 != ICP
-    if pay_token_id != icp_token_id && receive_token_id != icp_token_id {
-        let pool1_icp = pool_map::get_by_token_ids(pay_token_id, icp_token_id);
-        let pool2_icp = pool_map::get_by_token_ids(receive_token_id, icp_token_id);
-        if pool1_icp.is_some() && pool2_icp.is_some() {
-            // 2 pools: token0/ICP and token1/ICP routing token0 -> ICP -> token1
-            let pool1 = pool1_icp.as_ref().unwrap();
-            let pool2 = pool2_icp.as_ref().unwrap();
-            let swap1_lp_fee = (pool1.lp_fee_bps + 1) / 2;
-            // swap token0 to ICP
-            let swap1 = swap_amount_0(pool1, pay_amount, user_fee_level, Some(swap1_lp_fee), Some(&nat_zero()))?;
-            let swap2_lp_fee = (pool2.lp_fee_bps + 1) / 2;
-            // swap ICP to token1 (reverse order of pool)
-            let swap2 = swap_amount_1(
-                pool2,
-                Some(&swap1.receive_amount_with_fees_and_gas()),
-                user_fee_level,
-                Some(swap2_lp_fee),
-                None,
-            )?;
-            if pay_amount.is_none() {
-                // if pay_amount is None, return the mid price
-                let swap1_mid_price = swap1.get_mid_price().unwrap_or(BigRational::zero());
-                let swap2_mid_price = swap2.get_mid_price().unwrap_or(BigRational::zero());
-                let mid_price = swap1_mid_price * swap2_mid_price;
-                let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-                swaps.push((nat_zero(), mid_price_f64, mid_price_f64, 0.0, vec![swap1, swap2]));
-            } else {
-                let receive_amount = swap2.receive_amount_with_fees_and_gas();
-                let swap1_price = swap1.get_price().unwrap_or(BigRational::zero());
-                let swap2_price = swap2.get_price().unwrap_or(BigRational::zero());
-                let price = swap1_price * swap2_price;
-                let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
-                let swap1_mid_price = swap1.get_mid_price().unwrap_or(BigRational::zero());
-                let swap2_mid_price = swap2.get_mid_price().unwrap_or(BigRational::zero());
-                let mid_price = swap1_mid_price * swap2_mid_price;
-                let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-                let slippage_f64 = get_slippage(&price, &mid_price).unwrap_or(0_f64);
-                swaps.push((receive_amount, price_f64, mid_price_f64, slippage_f64, vec![swap1, swap2]));
+    Ok(())
+}
+
+/// 3-step swaps via each pair of quote tokens
+/// token0/quote1 -> quote1/quote2 -> quote2/token1. make sure token0 and token1 are not quote1 or quote2
+#[allow(clippy::complexity)]
+fn three_step_swaps(
+    pay_token_id: u32,
+    pay_amount: Option<&Nat>,
+    receive_token_id: u32,
+    user_fee_level: Option<u8>,
+    swaps: &mut Vec<(Nat, f64, f64, f64, Vec<SwapCalc>)>,
+) -> Result<(), String> {
+    let quote_token_ids = kong_settings_map::get()
+        .quote_token_ids
+        .into_iter()
+        .filter(|quote_token_id| *quote_token_id != pay_token_id && *quote_token_id != receive_token_id)
+        .collect::<Vec<u32>>();
+    for quote_token_id_1 in quote_token_ids.iter() {
+        for quote_token_id_2 in quote_token_ids.iter().filter(|quote_token_id| *quote_token_id != quote_token_id_1) {
+            let (Some(pool1), Some(pool2), Some(pool3)) = (
+                route_pool(pay_token_id, *quote_token_id_1, pay_amount.is_none()),
+                route_pool(*quote_token_id_1, *quote_token_id_2, pay_amount.is_none()),
+                route_pool(*quote_token_id_2, receive_token_id, pay_amount.is_none()),
+            ) else {
+                continue;
             };
+            multi_step_swaps(&[pool1, pool2, pool3], pay_amount, user_fee_level, swaps)?;
         }
     }
 
-    // special case where pay token is ckUSDT so need to use
-    // ckUSDT/ICP -> token1/ICP
-    // because there's no ckUSDT/ICP pool so need to use ICP/ckUSDT pool
-    let pool1_icp_ckusdt = pool_map::get_by_token_ids(icp_token_id, ckusdt_token_id);
-    let pool2_icp = pool_map::get_by_token_ids(receive_token_id, icp_token_id);
-    if pay_token_id == ckusdt_token_id && pool1_icp_ckusdt.is_some() && pool2_icp.is_some() {
-        let pool1 = pool1_icp_ckusdt.as_ref().unwrap();
-        let pool2 = pool2_icp.as_ref().unwrap();
-        let swap1_lp_fee = (pool1.lp_fee_bps + 1) / 2;
-        // swap ckUSDT to ICP (reverse order of pool)
-        let swap1 = swap_amount_1(pool1, pay_amount, user_fee_level, Some(swap1_lp_fee), Some(&nat_zero()))?;
-        let swap2_lp_fee = (pool2.lp_fee_bps + 1) / 2;
-        // swap ICP to token1 (reverse order of pool)
-        let swap2 = swap_amount_1(
-            pool2,
-            Some(&swap1.receive_amount_with_fees_and_gas()),
-            user_fee_level,
-            Some(swap2_lp_fee),
-            None,
-        )?;
-        if pay_amount.is_none() {
-            // if pay_amount is None, return the mid price
-            let swap1_mid_price = swap1.get_mid_price().unwrap_or(BigRational::zero());
-            let swap2_mid_price = swap2.get_mid_price().unwrap_or(BigRational::zero());
-            let mid_price = swap1_mid_price * swap2_mid_price;
-            let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-            swaps.push((nat_zero(), mid_price_f64, mid_price_f64, 0.0, vec![swap1, swap2]));
-        } else {
-            let receive_amount = swap2.receive_amount_with_fees_and_gas();
-            let swap1_price = swap1.get_price().unwrap_or(BigRational::zero());
-            let swap2_price = swap2.get_price().unwrap_or(BigRational::zero());
-            let price = swap1_price * swap2_price;
-            let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
-            let swap1_mid_price = swap1.get_mid_price().unwrap_or(BigRational::zero());
-            let swap2_mid_price = swap2.get_mid_price().unwrap_or(BigRational::zero());
-            let mid_price = swap1_mid_price * swap2_mid_price;
-            let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-            let slippage_f64 = get_slippage(&price, &mid_price).unwrap_or(0_f64);
-            swaps.push((receive_amount, price_f64, mid_price_f64, slippage_f64, vec![swap1, swap2]));
-        }
-    };
-
-    // special case where receieve token is ckUSDT so need to use
-    // token0/ICP -> ICP/ckUSDT
-    let pool1_icp = pool_map::get_by_token_ids(pay_token_id, icp_token_id);
-    let pool2_icp_ckusdt = pool_map::get_by_token_ids(icp_token_id, ckusdt_token_id);
-    if receive_token_id == ckusdt_token_id && pool1_icp.is_some() && pool2_icp_ckusdt.is_some() {
-        let pool1 = pool1_icp.as_ref().unwrap();
-        let pool2 = pool2_icp_ckusdt.as_ref().unwrap();
-        let swap1_lp_fee = (pool1.lp_fee_bps + 1) / 2;
-        // swap token0 to ICP
-        let swap1 = swap_amount_0(pool1, pay_amount, user_fee_level, Some(swap1_lp_fee), Some(&nat_zero()))?;
-        let swap2_lp_fee = (pool2.lp_fee_bps + 1) / 2;
-        // swap ICP to ckUSDT
-        let swap2 = swap_amount_0(
-            pool2,
-            Some(&swap1.receive_amount_with_fees_and_gas()),
-            user_fee_level,
-            Some(swap2_lp_fee),
-            None,
-        )?;
-        if pay_amount.is_none() {
-            // if pay_amount is None, return the mid price
-            let swap1_mid_price = swap1.get_mid_price().unwrap_or(BigRational::zero());
-            let swap2_mid_price = swap2.get_mid_price().unwrap_or(BigRational::zero());
-            let mid_price = swap1_mid_price * swap2_mid_price;
-            let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-            swaps.push((nat_zero(), mid_price_f64, mid_price_f64, 0.0, vec![swap1, swap2]));
-        } else {
-            let receive_amount = swap2.receive_amount_with_fees_and_gas();
-            let swap1_price = swap1.get_price().unwrap_or(BigRational::zero());
-            let swap2_price = swap2.get_price().unwrap_or(BigRational::zero());
-            let price = swap1_price * swap2_price;
-            let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
-            let swap1_mid_price = swap1.get_mid_price().unwrap_or(BigRational::zero());
-            let swap2_mid_price = swap2.get_mid_price().unwrap_or(BigRational::zero());
-            let mid_price = swap1_mid_price * swap2_mid_price;
-            let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-            let slippage_f64 = get_slippage(&price, &mid_price).unwrap_or(0_f64);
-            swaps.push((receive_amount, price_f64, mid_price_f64, slippage_f64, vec![swap1, swap2]));
-        }
-    };
-
     Ok(())
 }
 
+/// pool to swap pay token to receive token and true if the pay token is token_0 of the pool
+/// suspended pools are skipped so they do not fail the other routes of the tokens
+/// pools with a flash loan in progress are skipped unless only the mid price is needed, as the loan does not change their balances
+fn route_pool(pay_token_id: u32, receive_token_id: u32, mid_price_only: bool) -> Option<(StablePool, bool)> {
+    pool_map::get_by_token_ids(pay_token_id, receive_token_id)
+        .map(|pool| (pool, true))
+        .or_else(|| pool_map::get_by_token_ids(receive_token_id, pay_token_id).map(|pool| (pool, false)))
+        .filter(|(pool, _)| check_pool_action(pool.suspended_at, PoolAction::Swap).is_ok())
+        .filter(|(pool, _)| mid_price_only || check_flash_loan_lock(pool).is_ok())
+}
+
+/// swap through the pools of a multi-step route and add it to swaps
+/// the LP fee is split between the swaps. the "+ 1) / num_swaps" will round up the integer
+/// intermediate swaps do not take gas fees, only the last swap uses standard gas fees
 #[allow(clippy::complexity)]
-fn three_step_swaps(
-    pay_token_id: u32,
+fn multi_step_swaps(
+    pools: &[(StablePool, bool)],
     pay_amount: Option<&Nat>,
-    receive_token_id: u32,
     user_fee_level: Option<u8>,
     swaps: &mut Vec<(Nat, f64, f64, f64, Vec<SwapCalc>)>,
 ) -> Result<(), String> {
-    let ckusdt_token_id = token_map::get_ckusdt()?.token_id();
-    let icp_token_id = token_map::get_icp()?.token_id();
-    let pool2_icp_ckusdt = pool_map::get_by_token_ids(icp_token_id, ckusdt_token_id);
-
-    // token0/ckUSDT -> ckUSDT/ICP -> ICP/token1. make sure:
-    // 1) token0 != ckUSDT
-    // 2) token0 != ICP && token1 != ckUSDT
-    // 3) token1 != ICP
-    if pay_token_id != ckusdt_token_id
-        && (pay_token_id != icp_token_id && receive_token_id != ckusdt_token_id)
-        && receive_token_id != icp_token_id
-    {
-        let pool1_ckusdt = pool_map::get_by_token_ids(pay_token_id, ckusdt_token_id);
-        let pool3_icp = pool_map::get_by_token_ids(receive_token_id, icp_token_id);
-        if pool1_ckusdt.is_some() && pool2_icp_ckusdt.is_some() && pool3_icp.is_some() {
-            let pool1 = pool1_ckusdt.as_ref().unwrap();
-            let pool2 = pool2_icp_ckusdt.as_ref().unwrap();
-            let pool3 = pool3_icp.as_ref().unwrap();
-            let swap1_lp_fee = (pool1.lp_fee_bps + 1) / 3;
-            // swap token0 to ckUSDT
-            let swap1 = swap_amount_0(
-                pool1,
-                pay_amount,
-                user_fee_level,
-                Some(swap1_lp_fee),
-                Some(&nat_zero()), // swap1 do not take gas fees
-            )?;
-            let swap2_lp_fee = (pool2.lp_fee_bps + 1) / 3;
-            // swap ckUSDT to ICP (reverse order of pool)
-            let swap2 = swap_amount_1(
-                pool2,
-                Some(&swap1.receive_amount_with_fees_and_gas()),
-                user_fee_level,
-                Some(swap2_lp_fee),
-                Some(&nat_zero()), // swap2 do not take gas fees
-            )?;
-            let swap3_lp_fee = (pool3.lp_fee_bps + 1) / 3;
-            // swap ICP to token1 (reverse order of pool)
-            let swap3 = swap_amount_1(
-                pool3,
-                Some(&swap2.receive_amount_with_fees_and_gas()),
-                user_fee_level,
-                Some(swap3_lp_fee),
-                None,
-            )?;
-            if pay_amount.is_none() {
-                // if pay_amount is None, return the mid price
-                let swap1_mid_price = swap1.get_mid_price().unwrap_or(BigRational::zero());
-                let swap2_mid_price = swap2.get_mid_price().unwrap_or(BigRational::zero());
-                let swap3_mid_price = swap3.get_mid_price().unwrap_or(BigRational::zero());
-                let mid_price = swap1_mid_price * swap2_mid_price * swap3_mid_price;
-                let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-                swaps.push((nat_zero(), mid_price_f64, mid_price_f64, 0.0, vec![swap1, swap2, swap3]));
-            } else {
-                let receive_amount = swap3.receive_amount_with_fees_and_gas();
-                let swap1_price = swap1.get_price().unwrap_or(BigRational::zero());
-                let swap2_price = swap2.get_price().unwrap_or(BigRational::zero());
-                let swap3_price = swap3.get_price().unwrap_or(BigRational::zero());
-                let price = swap1_price * swap2_price * swap3_price;
-                let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
-                let swap1_mid_price = swap1.get_mid_price().unwrap_or(BigRational::zero());
-                let swap2_mid_price = swap2.get_mid_price().unwrap_or(BigRational::zero());
-                let swap3_mid_price = swap3.get_mid_price().unwrap_or(BigRational::zero());
-                let mid_price = swap1_mid_price * swap2_mid_price * swap3_mid_price;
-                let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-                let slippage_f64 = get_slippage(&price, &mid_price).unwrap_or(0_f64);
-                swaps.push((receive_amount, price_f64, mid_price_f64, slippage_f64, vec![swap1, swap2, swap3]));
-            }
+    let num_swaps = pools.len();
+    let mut route: Vec<SwapCalc> = Vec::with_capacity(num_swaps);
+    for (i, (pool, pay_token_0)) in pools.iter().enumerate() {
+        let amount = match route.last() {
+            Some(swap) => Some(swap.receive_amount_with_fees_and_gas()),
+            None => pay_amount.cloned(),
         };
+        let lp_fee = (pool.effective_lp_fee_bps() + 1) / num_swaps as u8;
+        let gas_fee = if i < num_swaps - 1 { Some(nat_zero()) } else { None };
+        let swap = if *pay_token_0 {
+            swap_amount_0(pool, amount.as_ref(), user_fee_level, Some(lp_fee), gas_fee.as_ref())?
+        } else {
+            swap_amount_1(pool, amount.as_ref(), user_fee_level, Some(lp_fee), gas_fee.as_ref())?
+        };
+        route.push(swap);
     }
 
-    // token0/ICP -> ICP/ckUSDT -> ckUSDT/token1. make sure:
-    // 1) token0 != ICP
-    // 2) token0 != ckUSDT && token1 != ICP
-    // 3) token1 != ckUSDT
-    if pay_token_id != icp_token_id
-        && (pay_token_id != ckusdt_token_id && receive_token_id != icp_token_id)
-        && receive_token_id != ckusdt_token_id
-    {
-        let pool1_icp = pool_map::get_by_token_ids(pay_token_id, icp_token_id);
-        let pool3_ckusdt = pool_map::get_by_token_ids(receive_token_id, ckusdt_token_id);
-        if pool1_icp.is_some() && pool2_icp_ckusdt.is_some() && pool3_ckusdt.is_some() {
-            let pool1 = pool1_icp.as_ref().unwrap();
-            let pool2 = pool2_icp_ckusdt.as_ref().unwrap();
-            let pool3 = pool3_ckusdt.as_ref().unwrap();
-            let swap1_lp_fee = (pool1.lp_fee_bps + 1) / 3;
-            // swap token0 to ICP
-            let swap1 = swap_amount_0(
-                pool1,
-                pay_amount,
+    let mid_price = route.iter().fold(BigRational::one(), |price, swap| {
+        price * swap.get_mid_price().unwrap_or(BigRational::zero())
+    });
+    let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
+    if pay_amount.is_none() {
+        // if pay_amount is None, return the mid price
+        swaps.push((nat_zero(), mid_price_f64, mid_price_f64, 0.0, route));
+    } else {
+        let receive_amount = route.last().ok_or("Invalid swap")?.receive_amount_with_fees_and_gas();
+        let price = route.iter().fold(BigRational::one(), |price, swap| {
+            price * swap.get_price().unwrap_or(BigRational::zero())
+        });
+        let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
+        let slippage_f64 = get_slippage(&price, &mid_price).unwrap_or(0_f64);
+        swaps.push((receive_amount, price_f64, mid_price_f64, slippage_f64, route));
+    }
+
+    Ok(())
+}
+
+/// calculate the smallest pay_amount of a swap to receive exactly receive_amount (bid/offer, fee and gas included)
+/// each route is solved backwards from receive_amount and then verified forwards with the same pool math as swap_amounts()
+/// returns the pay_amount, receive_amount, price, mid_price, slippage and the pools used
+///
+/// pay_token - pay token
+/// receive_amount - exact amount of receive token, net of LP and gas fees
+/// receive_token - receive token
+/// user_fee_level - fee level of the user swapping. see user_fee_level() and caller_fee_level()
+#[allow(clippy::complexity)]
+pub fn swap_amounts_exact_output(
+    pay_token: &StableToken,
+    receive_amount: &Nat,
+    receive_token: &StableToken,
+    user_fee_level: u8,
+) -> Result<(Nat, Nat, f64, f64, f64, Vec<SwapCalc>), String> {
+    let pay_token_id = pay_token.token_id();
+    let receive_token_id = receive_token.token_id();
+
+    // if tokens are the same pay the same amount
+    if pay_token_id == receive_token_id {
+        return Ok((receive_amount.clone(), receive_amount.clone(), 1.0, 1.0, 0.0, Vec::new()));
+    }
+
+    if nat_is_zero(receive_amount) {
+        Err("Receive amount is zero".to_string())?;
+    }
+
+    let user_fee_level = Some(user_fee_level);
+
+    // a route may fail (ie. insufficient liquidity) while others can still fill the swap
+    let mut swaps = Vec::new();
+    let mut route_error = None;
+    for route in swap_routes(pay_token_id, receive_token_id)? {
+        match exact_output_route(&route, receive_amount, user_fee_level) {
+            Ok(swap) => swaps.push(swap),
+            Err(e) => route_error = Some(e),
+        }
+    }
+
+    // return the swap with the lowest pay amount
+    swaps
+        .into_iter()
+        .min_by(|a, b| a.0.cmp(&b.0))
+        .ok_or_else(|| route_error.unwrap_or("Invalid swap".to_string()))
+}
+
+/// caps the receive amount of an exact-output swap to receive_amount
+/// any excess from rounding is added to the LP fee of the last swap of each route so it stays in the pools
+/// returns (receive_amount_with_gas_and_fees, price, mid_price, slippage, routes)
+#[allow(clippy::complexity)]
+fn cap_receive_amount(mut routes: Vec<Vec<SwapCalc>>, receive_amount: &Nat) -> Result<(Nat, f64, f64, f64, Vec<Vec<SwapCalc>>), String> {
+    let (total_receive_amount, _, _, _) = routes_amounts(&routes)?;
+    let mut excess_amount = nat_subtract(&total_receive_amount, receive_amount).ok_or("Insufficient receive amount")?;
+    for route in routes.iter_mut().rev() {
+        if nat_is_zero(&excess_amount) {
+            break;
+        }
+        let last_swap = route.last_mut().ok_or("Invalid swap")?;
+        let route_receive_amount = last_swap.receive_amount_with_fees_and_gas();
+        let route_excess_amount = std::cmp::min(excess_amount.clone(), route_receive_amount);
+        last_swap.lp_fee = nat_add(&last_swap.lp_fee, &route_excess_amount);
+        excess_amount = nat_subtract(&excess_amount, &route_excess_amount).unwrap_or(nat_zero());
+    }
+
+    let (receive_amount, price, mid_price, slippage) = routes_amounts(&routes)?;
+    Ok((receive_amount, price, mid_price, slippage, routes))
+}
+
+/// a swap in a route. use_lp_fee and use_gas_fee are the same overwrites used in swap_amount_0() and swap_amount_1()
+struct RouteStep {
+    pool: StablePool,
+    pay_token_0: bool, // true if paying token_0 of the pool
+    use_lp_fee: Option<u8>,
+    use_gas_fee: Option<Nat>,
+}
+
+/// returns all the routes from pay token to receive token
+/// the mid price permutations of one_step_swaps(), two_step_swaps() and three_step_swaps() give the pools of each route
+fn swap_routes(pay_token_id: u32, receive_token_id: u32) -> Result<Vec<Vec<RouteStep>>, String> {
+    let mut swaps: Vec<(Nat, f64, f64, f64, Vec<SwapCalc>)> = Vec::new();
+    one_step_swaps(pay_token_id, None, receive_token_id, None, &mut swaps)?;
+    two_step_swaps(pay_token_id, None, receive_token_id, None, &mut swaps)?;
+    three_step_swaps(pay_token_id, None, receive_token_id, None, &mut swaps)?;
+    swaps.iter().map(|swap| route_steps(&swap.4)).collect()
+}
+
+fn route_steps(route: &[SwapCalc]) -> Result<Vec<RouteStep>, String> {
+    let num_swaps = route.len();
+    // LP and gas fee overwrites must be the same as one_step_swaps(), two_step_swaps() and three_step_swaps()
+    route
+        .iter()
+        .enumerate()
+        .map(|(i, swap)| {
+            let pool = pool_map::get_by_pool_id(swap.pool_id).ok_or("Pool not found")?;
+            let pay_token_0 = swap.pay_token_id == pool.token_id_0;
+            let use_lp_fee = if num_swaps > 1 {
+                Some((pool.effective_lp_fee_bps() + 1) / num_swaps as u8)
+            } else {
+                None
+            };
+            // intermediate swaps do not take gas fees
+            let use_gas_fee = if i < num_swaps - 1 { Some(nat_zero()) } else { None };
+            Ok(RouteStep {
+                pool,
+                pay_token_0,
+                use_lp_fee,
+                use_gas_fee,
+            })
+        })
+        .collect()
+}
+
+/// swap pay_amount forwards through each step of a route
+fn route_swaps(steps: &[RouteStep], pay_amount: &Nat, user_fee_level: Option<u8>) -> Result<Vec<SwapCalc>, String> {
+    let mut swaps = Vec::with_capacity(steps.len());
+    let mut amount = pay_amount.clone();
+    for step in steps {
+        let swap = if step.pay_token_0 {
+            swap_amount_0(
+                &step.pool,
+                Some(&amount),
                 user_fee_level,
-                Some(swap1_lp_fee),
-                Some(&nat_zero()), // swap1 do not take gas fees
-            )?;
-            let swap2_lp_fee = (pool2.lp_fee_bps + 1) / 3;
-            // swap ICP to ckUSDT
-            let swap2 = swap_amount_0(
-                pool2,
-                Some(&swap1.receive_amount_with_fees_and_gas()),
+                step.use_lp_fee,
+                step.use_gas_fee.as_ref(),
+            )?
+        } else {
+            swap_amount_1(
+                &step.pool,
+                Some(&amount),
                 user_fee_level,
-                Some(swap2_lp_fee),
-                Some(&nat_zero()), // swap2 do not take gas fees
-            )?;
-            let swap3_lp_fee = (pool3.lp_fee_bps + 1) / 3;
-            // swap ckUSDT to token1 (reverse order of pool)
-            let swap3 = swap_amount_1(
-                pool3,
-                Some(&swap2.receive_amount_with_fees_and_gas()),
+                step.use_lp_fee,
+                step.use_gas_fee.as_ref(),
+            )?
+        };
+        amount = swap.receive_amount_with_fees_and_gas();
+        swaps.push(swap);
+    }
+    Ok(swaps)
+}
+
+/// returns (receive_amount_with_gas_and_fees, price, mid_price, slippage) of swaps grouped by route
+/// price is the pay amount weighted price of the routes and mid_price is the best mid price of the routes
+fn routes_amounts(routes: &[Vec<SwapCalc>]) -> Result<(Nat, f64, f64, f64), String> {
+    let mut receive_amount = nat_zero();
+    let mut pay_amount = BigRational::zero();
+    let mut weighted_price = BigRational::zero();
+    let mut mid_price = BigRational::zero();
+    for route in routes {
+        let first_swap = route.first().ok_or("Invalid swap")?;
+        let last_swap = route.last().ok_or("Invalid swap")?;
+        receive_amount = nat_add(&receive_amount, &last_swap.receive_amount_with_fees_and_gas());
+        let route_pay_amount = BigRational::from_integer(nat_to_bigint(&first_swap.pay_amount));
+        let route_price = route.iter().fold(BigRational::one(), |price, swap| {
+            price * swap.get_price().unwrap_or(BigRational::zero())
+        });
+        weighted_price += route_price * &route_pay_amount;
+        pay_amount += route_pay_amount;
+        let route_mid_price = route.iter().fold(BigRational::one(), |price, swap| {
+            price * swap.get_mid_price().unwrap_or(BigRational::zero())
+        });
+        if route_mid_price > mid_price {
+            mid_price = route_mid_price;
+        }
+    }
+    if pay_amount.is_zero() {
+        Err("Invalid pay amount".to_string())?
+    }
+
+    let price = weighted_price / pay_amount;
+    let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
+    let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
+    let slippage_f64 = get_slippage(&price, &mid_price).unwrap_or(0_f64);
+    Ok((receive_amount, price_f64, mid_price_f64, slippage_f64))
+}
+
+/// solve a route of swaps backwards to receive exactly receive_amount
+/// returns (pay_amount, receive_amount_with_gas_and_fees, price, mid_price, slippage, swaps)
+#[allow(clippy::complexity)]
+fn exact_output_route(
+    steps: &[RouteStep],
+    receive_amount: &Nat,
+    user_fee_level: Option<u8>,
+) -> Result<(Nat, Nat, f64, f64, f64, Vec<SwapCalc>), String> {
+    let mut target_amount = receive_amount.clone();
+    for _ in 0..MAX_EXACT_OUTPUT_ITERATIONS {
+        // solve backwards from the last swap to get the pay amount of the first swap
+        let mut pay_amount = target_amount.clone();
+        for step in steps.iter().rev() {
+            pay_amount = swap_pay_amount(
+                &step.pool,
+                step.pay_token_0,
+                &pay_amount,
                 user_fee_level,
-                Some(swap3_lp_fee),
-                None,
+                step.use_lp_fee,
+                step.use_gas_fee.as_ref(),
             )?;
-            if pay_amount.is_none() {
-                // if pay_amount is None, return the mid price
-                let swap1_mid_price = swap1.get_mid_price().unwrap_or(BigRational::zero());
-                let swap2_mid_price = swap2.get_mid_price().unwrap_or(BigRational::zero());
-                let swap3_mid_price = swap3.get_mid_price().unwrap_or(BigRational::zero());
-                let mid_price = swap1_mid_price * swap2_mid_price * swap3_mid_price;
-                let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-                swaps.push((nat_zero(), mid_price_f64, mid_price_f64, 0.0, vec![swap1, swap2, swap3]));
-            } else {
-                let receive_amount = swap3.receive_amount_with_fees_and_gas();
-                let swap1_price = swap1.get_price().unwrap_or(BigRational::zero());
-                let swap2_price = swap2.get_price().unwrap_or(BigRational::zero());
-                let swap3_price = swap3.get_price().unwrap_or(BigRational::zero());
-                let price = swap1_price * swap2_price * swap3_price;
-                let price_f64 = price_rounded(&price).ok_or("Invalid price")?;
-                let swap1_mid_price = swap1.get_mid_price().unwrap_or(BigRational::zero());
-                let swap2_mid_price = swap2.get_mid_price().unwrap_or(BigRational::zero());
-                let swap3_mid_price = swap3.get_mid_price().unwrap_or(BigRational::zero());
-                let mid_price = swap1_mid_price * swap2_mid_price * swap3_mid_price;
-                let mid_price_f64 = price_rounded(&mid_price).ok_or("Invalid mid price")?;
-                let slippage_f64 = get_slippage(&price, &mid_price).unwrap_or(0_f64);
-                swaps.push((receive_amount, price_f64, mid_price_f64, slippage_f64, vec![swap1, swap2, swap3]));
+        }
+
+        // verify forwards as rounding in each step can leave the route a few units short
+        let swaps = route_swaps(steps, &pay_amount, user_fee_level)?;
+        let amount = swaps.last().ok_or("Invalid swap")?.receive_amount_with_fees_and_gas();
+        match nat_subtract(receive_amount, &amount) {
+            // route is short, increase the target by the shortfall and solve again
+            Some(shortfall) if !nat_is_zero(&shortfall) => target_amount = nat_add(&target_amount, &shortfall),
+            _ => {
+                let (receive_amount, price, mid_price, slippage, routes) = cap_receive_amount(vec![swaps], receive_amount)?;
+                return Ok((pay_amount, receive_amount, price, mid_price, slippage, routes.concat()));
             }
-        };
+        }
     }
 
-    Ok(())
+    Err("Unable to calculate pay amount".to_string())
+}
+
+/// split pay_amount across routes that do not share any pools
+/// pay_amount is allocated in SPLIT_SWAP_PARTS parts, each to the route with the highest marginal receive amount
+/// only the last swap of the first route takes the gas fee as the receive token is sent in one transfer
+/// returns None if the best allocation uses only one route
+#[allow(clippy::complexity)]
+fn split_swap_amounts(
+    routes: &[Vec<SwapCalc>],
+    pay_amount: &Nat,
+    receive_token: &StableToken,
+    user_fee_level: Option<u8>,
+) -> Result<Option<(Nat, f64, f64, f64, Vec<Vec<SwapCalc>>)>, String> {
+    if routes.len() < 2 {
+        return Ok(None);
+    }
+    let part_amount = nat_divide(pay_amount, &Nat::from(SPLIT_SWAP_PARTS)).ok_or("Invalid pay amount")?;
+    if nat_is_zero(&part_amount) {
+        return Ok(None);
+    }
+
+    // gas fee is taken once after the split so routes are calculated without it
+    let routes = routes
+        .iter()
+        .map(|route| {
+            let mut steps = route_steps(route)?;
+            if let Some(last_step) = steps.last_mut() {
+                last_step.use_gas_fee = Some(nat_zero());
+            }
+            Ok(steps)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let route_pool_ids = routes
+        .iter()
+        .map(|steps| steps.iter().map(|step| step.pool.pool_id).collect())
+        .collect::<Vec<Vec<u32>>>();
+    let Some(allocations) = split_allocations(&route_pool_ids, pay_amount, SPLIT_SWAP_PARTS, |i, amount| {
+        let swaps = route_swaps(&routes[i], amount, user_fee_level).ok()?;
+        Some(swaps.last().map_or(nat_zero(), |swap| swap.receive_amount_with_fees_and_gas()))
+    }) else {
+        return Ok(None);
+    };
+
+    let mut split_routes = Vec::new();
+    for (steps, allocation) in routes.iter().zip(allocations.iter()) {
+        if !nat_is_zero(allocation) {
+            split_routes.push(route_swaps(steps, allocation, user_fee_level)?);
+        }
+    }
+    if split_routes.len() < 2 {
+        return Ok(None);
+    }
+    take_gas_fee_once(&mut split_routes, &receive_token.fee());
+
+    let (receive_amount, price, mid_price, slippage) = routes_amounts(&split_routes)?;
+    Ok(Some((receive_amount, price, mid_price, slippage, split_routes)))
+}
+
+/// swap pay_amount through the given state of a single pool with the user's fee level
+/// used when the receive amount stays in Kong (ie. added as liquidity) so no gas fee is taken
+pub fn pool_swap_amounts(pool: &StablePool, pay_token_0: bool, pay_amount: &Nat) -> Result<SwapCalc, String> {
+    let user_fee_level = Some(caller_fee_level());
+    if pay_token_0 {
+        swap_amount_0(pool, Some(pay_amount), user_fee_level, None, Some(&nat_zero()))
+    } else {
+        swap_amount_1(pool, Some(pay_amount), user_fee_level, None, Some(&nat_zero()))
+    }
 }
 
 /// Swap amount 0 of a given pool
@@ -467,6 +574,8 @@ fn swap_amount_0(
     use_lp_fee: Option<u8>,     // overwrite for LP fee in case of 2-legged synthetic swaps
     use_gas_fee: Option<&Nat>,  // overwrite for gas fee in case of synethetic swaps
 ) -> Result<SwapCalc, String> {
+    check_pool_action(pool.suspended_at, PoolAction::Swap)?;
+
     // Token 0
     let token_0 = pool.token_0();
     let token_id_0 = token_0.token_id();
@@ -477,7 +586,8 @@ fn swap_amount_0(
     let reserve_0 = nat_add(&pool.balance_0, &pool.lp_fee_0);
     let reserve_1 = nat_add(&pool.balance_1, &pool.lp_fee_1);
 
-    if nat_is_zero(&reserve_0) || nat_is_zero(&reserve_1) {
+    // concentrated pools only need the receive token as the price can be outside of all positions
+    if nat_is_zero(&reserve_1) || (nat_is_zero(&reserve_0) && !pool.curve.is_concentrated()) {
         return Ok(SwapCalc {
             pool_id: pool.pool_id,
             pay_token_id: token_id_0,
@@ -505,16 +615,35 @@ fn swap_amount_0(
         Some(amount) => amount,
     };
 
+    // mid price is unchanged by a flash loan but swaps have to wait until the loan is repaid
+    check_flash_loan_lock(pool)?;
+
     // convert amount_0 and pool balances to the max_decimals precision
     let max_decimals = std::cmp::max(token_0.decimals(), token_1.decimals());
     let reserve_0_in_max_decimals = nat_to_decimal_precision(&reserve_0, token_0.decimals(), max_decimals);
     let reserve_1_in_max_decimals = nat_to_decimal_precision(&reserve_1, token_1.decimals(), max_decimals);
     let amount_0_in_max_decimals = nat_to_decimal_precision(amount_0, token_0.decimals(), max_decimals);
 
-    // amount_1 = (amount_0 * reserve_1) / (reserve_0 + amount_0)
-    let numerator_in_max_decimals = nat_multiply(&amount_0_in_max_decimals, &reserve_1_in_max_decimals);
-    let denominator_in_max_decimals = nat_add(&reserve_0_in_max_decimals, &amount_0_in_max_decimals);
-    let amount_1_in_max_decimals = nat_divide(&numerator_in_max_decimals, &denominator_in_max_decimals).ok_or("Invalid amount_1")?;
+    let amount_1_in_max_decimals = match pool.curve {
+        PoolCurve::ConstantProduct => {
+            // amount_1 = (amount_0 * reserve_1) / (reserve_0 + amount_0)
+            let numerator_in_max_decimals = nat_multiply(&amount_0_in_max_decimals, &reserve_1_in_max_decimals);
+            let denominator_in_max_decimals = nat_add(&reserve_0_in_max_decimals, &amount_0_in_max_decimals);
+            nat_divide(&numerator_in_max_decimals, &denominator_in_max_decimals).ok_or("Invalid amount_1")?
+        }
+        PoolCurve::StableSwap { amp } => stableswap_amount_out(
+            amp,
+            &reserve_0_in_max_decimals,
+            &reserve_1_in_max_decimals,
+            &amount_0_in_max_decimals,
+        )
+        .ok_or("Invalid amount_1")?,
+        PoolCurve::Concentrated { .. } => {
+            // concentrated pools are priced in token precision
+            let amount_1 = concentrated_pool::swap(pool, true, amount_0, true)?.amount_out;
+            nat_to_decimal_precision(&amount_1, token_1.decimals(), max_decimals)
+        }
+    };
 
     // calculate the LP fees
     // any user fee discount. user.fee_level is 0 = 100% fee (no discount), 100 = 0% fee (max discount)
@@ -522,7 +651,7 @@ fn swap_amount_0(
     let user_lp_fee_pct = nat_subtract(&Nat::from(100_u8), &Nat::from(user_fee_level.unwrap_or(0_u8))).unwrap_or(Nat::from(100_u8));
     // user_lp_fee_bps = (user_lp_fee * user_lp_fee_pct) / 100 - user's fee level in bps with discount
     let user_lp_fee_bps = nat_divide(
-        &nat_multiply(&user_lp_fee_pct, &Nat::from(use_lp_fee.unwrap_or(pool.lp_fee_bps))),
+        &nat_multiply(&user_lp_fee_pct, &Nat::from(use_lp_fee.unwrap_or(pool.effective_lp_fee_bps()))),
         &Nat::from(100_u8),
     )
     .ok_or("Invalid LP fee")?;
@@ -559,6 +688,8 @@ fn swap_amount_1(
     use_lp_fee: Option<u8>,
     use_gas_fee: Option<&Nat>,
 ) -> Result<SwapCalc, String> {
+    check_pool_action(pool.suspended_at, PoolAction::Swap)?;
+
     // Token 0
     let token_0 = pool.token_0();
     let token_id_0 = token_0.token_id();
@@ -569,7 +700,8 @@ fn swap_amount_1(
     let reserve_0 = nat_add(&pool.balance_0, &pool.lp_fee_0);
     let reserve_1 = nat_add(&pool.balance_1, &pool.lp_fee_1);
 
-    if nat_is_zero(&reserve_0) || nat_is_zero(&reserve_1) {
+    // concentrated pools only need the receive token as the price can be outside of all positions
+    if nat_is_zero(&reserve_0) || (nat_is_zero(&reserve_1) && !pool.curve.is_concentrated()) {
         return Ok(SwapCalc {
             pool_id: pool.pool_id,
             pay_token_id: token_id_1,
@@ -597,22 +729,40 @@ fn swap_amount_1(
         Some(amount) => amount,
     };
 
+    // mid price is unchanged by a flash loan but swaps have to wait until the loan is repaid
+    check_flash_loan_lock(pool)?;
+
     // convert amount_1 and pool balances to the max_decimals precision
     let max_decimals = std::cmp::max(token_0.decimals(), token_1.decimals());
     let reserve_0_in_max_decimals = nat_to_decimal_precision(&reserve_0, token_0.decimals(), max_decimals);
     let reserve_1_in_max_decimals = nat_to_decimal_precision(&reserve_1, token_1.decimals(), max_decimals);
     let amount_1_in_max_decimals = nat_to_decimal_precision(amount_1, token_1.decimals(), max_decimals);
 
-    // amount_0 = (amount_1 * reserve_0) / (reserve_1 + amount_1)
-    let numerator_in_max_decimals = nat_multiply(&amount_1_in_max_decimals, &reserve_0_in_max_decimals);
-    let denominator_in_max_decimals = nat_add(&reserve_1_in_max_decimals, &amount_1_in_max_decimals);
-    let amount_0_in_max_decimals = nat_divide(&numerator_in_max_decimals, &denominator_in_max_decimals).ok_or("Invalid amount_0")?;
+    let amount_0_in_max_decimals = match pool.curve {
+        PoolCurve::ConstantProduct => {
+            // amount_0 = (amount_1 * reserve_0) / (reserve_1 + amount_1)
+            let numerator_in_max_decimals = nat_multiply(&amount_1_in_max_decimals, &reserve_0_in_max_decimals);
+            let denominator_in_max_decimals = nat_add(&reserve_1_in_max_decimals, &amount_1_in_max_decimals);
+            nat_divide(&numerator_in_max_decimals, &denominator_in_max_decimals).ok_or("Invalid amount_0")?
+        }
+        PoolCurve::StableSwap { amp } => stableswap_amount_out(
+            amp,
+            &reserve_1_in_max_decimals,
+            &reserve_0_in_max_decimals,
+            &amount_1_in_max_decimals,
+        )
+        .ok_or("Invalid amount_0")?,
+        PoolCurve::Concentrated { .. } => {
+            let amount_0 = concentrated_pool::swap(pool, false, amount_1, true)?.amount_out;
+            nat_to_decimal_precision(&amount_0, token_0.decimals(), max_decimals)
+        }
+    };
 
     // calculate the LP fees
     // user_lp_fee_pct = 100 - user.fee_level
     let user_lp_fee_pct = nat_subtract(&Nat::from(100_u8), &Nat::from(user_fee_level.unwrap_or(0_u8))).unwrap_or(Nat::from(100_u8));
     let user_lp_fee_bps = nat_divide(
-        &nat_multiply(&user_lp_fee_pct, &Nat::from(use_lp_fee.unwrap_or(pool.lp_fee_bps))),
+        &nat_multiply(&user_lp_fee_pct, &Nat::from(use_lp_fee.unwrap_or(pool.effective_lp_fee_bps()))),
         &Nat::from(100_u8),
     )
     .ok_or("Invalid LP fee")?;
@@ -639,6 +789,100 @@ fn swap_amount_1(
     })
 }
 
+/// Pay amount of a given pool needed to receive receive_amount after LP and gas fees
+/// this is the inverse of swap_amount_0() if pay_token_0 is true, otherwise the inverse of swap_amount_1(), rounded up
+fn swap_pay_amount(
+    pool: &StablePool,
+    pay_token_0: bool,
+    receive_amount: &Nat,
+    user_fee_level: Option<u8>,
+    use_lp_fee: Option<u8>,
+    use_gas_fee: Option<&Nat>,
+) -> Result<Nat, String> {
+    check_flash_loan_lock(pool)?;
+    check_pool_action(pool.suspended_at, PoolAction::Swap)?;
+
+    let (pay_token, pay_reserve, receive_token, receive_reserve) = if pay_token_0 {
+        (
+            pool.token_0(),
+            nat_add(&pool.balance_0, &pool.lp_fee_0),
+            pool.token_1(),
+            nat_add(&pool.balance_1, &pool.lp_fee_1),
+        )
+    } else {
+        (
+            pool.token_1(),
+            nat_add(&pool.balance_1, &pool.lp_fee_1),
+            pool.token_0(),
+            nat_add(&pool.balance_0, &pool.lp_fee_0),
+        )
+    };
+
+    if nat_is_zero(&receive_reserve) || (nat_is_zero(&pay_reserve) && !pool.curve.is_concentrated()) {
+        Err(format!("Insufficient {} in pool", receive_token.symbol()))?
+    }
+
+    // convert receive_amount plus gas fee and pool balances to the max_decimals precision
+    let max_decimals = std::cmp::max(pay_token.decimals(), receive_token.decimals());
+    let pay_reserve_in_max_decimals = nat_to_decimal_precision(&pay_reserve, pay_token.decimals(), max_decimals);
+    let receive_reserve_in_max_decimals = nat_to_decimal_precision(&receive_reserve, receive_token.decimals(), max_decimals);
+    let gas_fee = use_gas_fee.map_or_else(|| receive_token.fee(), |fee| fee.clone());
+    let net_amount_in_max_decimals = nat_to_decimal_precision(&nat_add(receive_amount, &gas_fee), receive_token.decimals(), max_decimals);
+
+    // user_lp_fee_bps = (lp_fee_bps * (100 - user.fee_level)) / 100
+    let user_lp_fee_pct = nat_subtract(&Nat::from(100_u8), &Nat::from(user_fee_level.unwrap_or(0_u8))).unwrap_or(Nat::from(100_u8));
+    let user_lp_fee_bps = nat_divide(
+        &nat_multiply(&user_lp_fee_pct, &Nat::from(use_lp_fee.unwrap_or(pool.effective_lp_fee_bps()))),
+        &Nat::from(100_u8),
+    )
+    .ok_or("Invalid LP fee")?;
+    // receive_amount before LP fee = net_amount * 10_000 / (10_000 - user_lp_fee_bps)
+    let fee_denominator = nat_subtract(&Nat::from(10_000_u128), &user_lp_fee_bps).ok_or("Invalid LP fee")?;
+    let receive_amount_in_max_decimals = nat_divide_ceil(
+        &nat_multiply(&net_amount_in_max_decimals, &Nat::from(10_000_u128)),
+        &fee_denominator,
+    )
+    .ok_or("Invalid LP fee")?;
+
+    if receive_amount_in_max_decimals >= receive_reserve_in_max_decimals {
+        Err(format!("Insufficient {} in pool", receive_token.symbol()))?
+    }
+
+    let pay_amount_in_max_decimals = match pool.curve {
+        PoolCurve::ConstantProduct => {
+            // pay_amount = (receive_amount * pay_reserve) / (receive_reserve - receive_amount)
+            let numerator_in_max_decimals = nat_multiply(&receive_amount_in_max_decimals, &pay_reserve_in_max_decimals);
+            let denominator_in_max_decimals =
+                nat_subtract(&receive_reserve_in_max_decimals, &receive_amount_in_max_decimals).ok_or("Invalid pay amount")?;
+            nat_divide_ceil(&numerator_in_max_decimals, &denominator_in_max_decimals).ok_or("Invalid pay amount")?
+        }
+        PoolCurve::StableSwap { amp } => stableswap_amount_in(
+            amp,
+            &pay_reserve_in_max_decimals,
+            &receive_reserve_in_max_decimals,
+            &receive_amount_in_max_decimals,
+        )
+        .ok_or("Invalid pay amount")?,
+        PoolCurve::Concentrated { .. } => {
+            // convert receive_amount to receive token precision rounding up
+            let mut receive_amount = nat_to_decimal_precision(&receive_amount_in_max_decimals, max_decimals, receive_token.decimals());
+            if nat_to_decimal_precision(&receive_amount, receive_token.decimals(), max_decimals) < receive_amount_in_max_decimals {
+                receive_amount = nat_add(&receive_amount, &Nat::from(1_u8));
+            }
+            let pay_amount = concentrated_pool::swap(pool, pay_token_0, &receive_amount, false)?.amount_in;
+            nat_to_decimal_precision(&pay_amount, pay_token.decimals(), max_decimals)
+        }
+    };
+
+    // convert pay_amount to pay token precision rounding up
+    let pay_amount = nat_to_decimal_precision(&pay_amount_in_max_decimals, max_decimals, pay_token.decimals());
+    if nat_to_decimal_precision(&pay_amount, pay_token.decimals(), max_decimals) < pay_amount_in_max_decimals {
+        Ok(nat_add(&pay_amount, &Nat::from(1_u8)))
+    } else {
+        Ok(pay_amount)
+    }
+}
+
 fn get_slippage(price_achieved: &BigRational, price_expected: &BigRational) -> Option<f64> {
     if price_achieved > price_expected {
         return Some(0.0); // if price is greater than expected, slippage is 0
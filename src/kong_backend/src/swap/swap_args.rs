@@ -15,4 +15,8 @@ pub struct SwapArgs {
     pub receive_address: Option<String>,
     pub max_slippage: Option<f64>,
     pub referred_by: Option<String>,
+    // if true, receive_amount is the exact amount to receive and pay_amount is the maximum amount to pay
+    pub exact_output: Option<bool>,
+    // timestamp in nanoseconds. the swap fails and the pay token is returned if executed after the deadline
+    pub deadline: Option<u64>,
 }
@@ -44,6 +44,7 @@ pub struct SwapReply {
     pub transfer_ids: Vec<TransferIdReply>,
     pub claim_ids: Vec<u64>,
     pub ts: u64,
+    pub approval_headroom: Option<Nat>, // exact-output swaps only, in pay_symbol
 }
 
 fn empty_string() -> String {
@@ -95,6 +95,7 @@ pub fn to_swap_reply(swap_tx: &SwapTx) -> SwapReply {
         transfer_ids: to_transfer_ids(&swap_tx.transfer_ids),
         claim_ids: swap_tx.claim_ids.clone(),
         ts: swap_tx.ts,
+        approval_headroom: swap_tx.approval_headroom.clone(),
     }
 }
 
@@ -134,5 +135,6 @@ pub fn to_swap_reply_failed(
         transfer_ids: to_transfer_ids(transfer_ids),
         claim_ids: claim_ids.to_vec(),
         ts,
+        approval_headroom: None,
     }
 }
@@ -3,6 +3,7 @@ use candid::Nat;
 use super::archive_to_kong_data::archive_to_kong_data;
 use super::return_pay_token::return_pay_token;
 use super::send_receive_token::send_receive_token;
+use super::swap_amounts::user_fee_level;
 use super::swap_args::SwapArgs;
 use super::swap_calc::SwapCalc;
 use super::swap_reply::SwapReply;
@@ -58,10 +59,12 @@ pub async fn swap_transfer(args: SwapArgs) -> Result<SwapReply, String> {
         &receive_amount_with_fees_and_gas,
         &to_address,
         &mut transfer_ids,
+        &mut Vec::new(),
         mid_price,
         price,
         slippage,
         &swaps,
+        None,
         ts,
     )
     .await;
@@ -112,10 +115,12 @@ pub async fn swap_transfer_async(args: SwapArgs) -> Result<u64, String> {
                 &receive_amount_with_fees_and_gas,
                 &to_address,
                 &mut transfer_ids,
+                &mut Vec::new(),
                 mid_price,
                 price,
                 slippage,
                 &swaps,
+                None,
                 ts,
             )
             .await;
@@ -223,6 +228,41 @@ async fn process_swap(
         .await;
         Err(format!("Req #{} failed. Pay amount is zero", request_id))?
     }
+    // exact-output swaps need icrc2_transfer_from to take only the required pay amount
+    if args.exact_output.unwrap_or(false) {
+        return_pay_token(
+            request_id,
+            user_id,
+            &caller_id,
+            pay_token,
+            pay_amount,
+            Some(&receive_token),
+            transfer_ids,
+            ts,
+        )
+        .await;
+        Err(format!(
+            "Req #{} failed. Exact output swaps require icrc2_approve + icrc2_transfer_from",
+            request_id
+        ))?
+    }
+
+    // the transfer could have been made well before the swap is executed
+    if args.deadline.is_some_and(|deadline| get_time() > deadline) {
+        request_map::update_status(request_id, StatusCode::DeadlineExceeded, None);
+        return_pay_token(
+            request_id,
+            user_id,
+            &caller_id,
+            pay_token,
+            pay_amount,
+            Some(&receive_token),
+            transfer_ids,
+            ts,
+        )
+        .await;
+        Err(format!("Req #{} failed. Deadline exceeded", request_id))?
+    }
 
     // use specified max slippage or use default
     let max_slippage = args.max_slippage.unwrap_or(kong_settings_map::get().default_max_slippage);
@@ -249,24 +289,32 @@ async fn process_swap(
         None => Address::PrincipalId(caller_id),
     };
 
-    let (receive_amount_with_fees_and_gas, mid_price, price, slippage, swaps) =
-        match update_liquidity_pool(request_id, pay_token, pay_amount, &receive_token, receive_amount, max_slippage) {
-            Ok((receive_amount, mid_price, price, slippage, swaps)) => (receive_amount, mid_price, price, slippage, swaps),
-            Err(e) => {
-                return_pay_token(
-                    request_id,
-                    user_id,
-                    &caller_id,
-                    pay_token,
-                    pay_amount,
-                    Some(&receive_token),
-                    transfer_ids,
-                    ts,
-                )
-                .await;
-                Err(format!("Req #{} failed. {}", request_id, e))?
-            }
-        };
+    let (receive_amount_with_fees_and_gas, mid_price, price, slippage, swaps) = match update_liquidity_pool(
+        request_id,
+        pay_token,
+        pay_amount,
+        &receive_token,
+        receive_amount,
+        max_slippage,
+        false,
+        user_fee_level(user_id),
+    ) {
+        Ok((receive_amount, mid_price, price, slippage, swaps)) => (receive_amount, mid_price, price, slippage, swaps),
+        Err(e) => {
+            return_pay_token(
+                request_id,
+                user_id,
+                &caller_id,
+                pay_token,
+                pay_amount,
+                Some(&receive_token),
+                transfer_ids,
+                ts,
+            )
+            .await;
+            Err(format!("Req #{} failed. {}", request_id, e))?
+        }
+    };
 
     request_map::update_status(request_id, StatusCode::SwapSuccess, None);
 
@@ -5,13 +5,15 @@ use std::time::Duration;
 use super::archive_to_kong_data::archive_to_kong_data;
 use super::calculate_amounts::calculate_amounts;
 use super::return_pay_token::return_pay_token;
+use super::return_unused_pay_token::{return_unused_pay_token, settle_unused_pay_amount};
 use super::send_receive_token::send_receive_token;
+use super::swap_amounts::{swap_amounts_exact_output, user_fee_level};
 use super::swap_args::SwapArgs;
 use super::swap_calc::SwapCalc;
 use super::swap_reply::SwapReply;
 use super::update_liquidity_pool::update_liquidity_pool;
 
-use crate::helpers::nat_helpers::nat_is_zero;
+use crate::helpers::nat_helpers::{nat_is_zero, nat_subtract, nat_to_decimals_f64};
 use crate::ic::address::Address;
 use crate::ic::address_helpers::get_address;
 use crate::ic::get_time::get_time;
@@ -25,13 +27,16 @@ use crate::stable_user::banned_user_map::{increase_consecutive_error, is_banned_
 use crate::stable_user::user_map;
 
 pub async fn swap_transfer_from(args: SwapArgs) -> Result<SwapReply, String> {
-    let (user_id, pay_token, pay_amount, receive_token, max_slippage, to_address) = check_arguments(&args).await?;
+    let (user_id, pay_token, pay_amount, receive_token, max_slippage, to_address, approval_headroom) = check_arguments(&args).await?;
     let ts = get_time();
     let receive_amount = args.receive_amount.clone();
+    let exact_output = args.exact_output.unwrap_or(false);
+    let deadline = args.deadline;
     let request_id = request_map::insert(&StableRequest::new(user_id, &Request::Swap(args), ts));
     let mut transfer_ids = Vec::new();
+    let mut claim_ids = Vec::new();
 
-    let (receive_amount_with_fees_and_gas, mid_price, price, slippage, swaps) = process_swap(
+    let (swap_pay_amount, receive_amount_with_fees_and_gas, mid_price, price, slippage, swaps) = process_swap(
         request_id,
         user_id,
         &pay_token,
@@ -39,7 +44,10 @@ pub async fn swap_transfer_from(args: SwapArgs) -> Result<SwapReply, String> {
         &receive_token,
         receive_amount.as_ref(),
         max_slippage,
+        exact_output,
+        deadline,
         &mut transfer_ids,
+        &mut claim_ids,
         ts,
     )
     .await
@@ -53,15 +61,17 @@ pub async fn swap_transfer_from(args: SwapArgs) -> Result<SwapReply, String> {
         request_id,
         user_id,
         &pay_token,
-        &pay_amount,
+        &swap_pay_amount,
         &receive_token,
         &receive_amount_with_fees_and_gas,
         &to_address,
         &mut transfer_ids,
+        &mut claim_ids,
         mid_price,
         price,
         slippage,
         &swaps,
+        approval_headroom.as_ref(),
         ts,
     )
     .await;
@@ -74,15 +84,18 @@ pub async fn swap_transfer_from(args: SwapArgs) -> Result<SwapReply, String> {
 }
 
 pub async fn swap_transfer_from_async(args: SwapArgs) -> Result<u64, String> {
-    let (user_id, pay_token, pay_amount, receive_token, max_slippage, to_address) = check_arguments(&args).await?;
+    let (user_id, pay_token, pay_amount, receive_token, max_slippage, to_address, approval_headroom) = check_arguments(&args).await?;
     let ts = get_time();
     let receive_amount = args.receive_amount.clone();
+    let exact_output = args.exact_output.unwrap_or(false);
+    let deadline = args.deadline;
     let request_id = request_map::insert(&StableRequest::new(user_id, &Request::Swap(args), ts));
 
     ic_cdk::spawn(async move {
         let mut transfer_ids = Vec::new();
+        let mut claim_ids = Vec::new();
 
-        let Ok((receive_amount_with_fees_and_gas, mid_price, price, slippage, swaps)) = process_swap(
+        let Ok((swap_pay_amount, receive_amount_with_fees_and_gas, mid_price, price, slippage, swaps)) = process_swap(
             request_id,
             user_id,
             &pay_token,
@@ -90,7 +103,10 @@ pub async fn swap_transfer_from_async(args: SwapArgs) -> Result<u64, String> {
             &receive_token,
             receive_amount.as_ref(),
             max_slippage,
+            exact_output,
+            deadline,
             &mut transfer_ids,
+            &mut claim_ids,
             ts,
         )
         .await
@@ -106,15 +122,17 @@ pub async fn swap_transfer_from_async(args: SwapArgs) -> Result<u64, String> {
                 request_id,
                 user_id,
                 &pay_token,
-                &pay_amount,
+                &swap_pay_amount,
                 &receive_token,
                 &receive_amount_with_fees_and_gas,
                 &to_address,
                 &mut transfer_ids,
+                &mut claim_ids,
                 mid_price,
                 price,
                 slippage,
                 &swaps,
+                approval_headroom.as_ref(),
                 ts,
             )
             .await;
@@ -128,7 +146,10 @@ pub async fn swap_transfer_from_async(args: SwapArgs) -> Result<u64, String> {
     Ok(request_id)
 }
 
-async fn check_arguments(args: &SwapArgs) -> Result<(u32, StableToken, Nat, StableToken, f64, Address), String> {
+/// returns (user_id, pay_token, pay_amount, receive_token, max_slippage, to_address, approval_headroom)
+/// for exact-output swaps pay_amount is the calculated amount to pay and approval_headroom is the unused part of args.pay_amount
+#[allow(clippy::complexity)]
+async fn check_arguments(args: &SwapArgs) -> Result<(u32, StableToken, Nat, StableToken, f64, Address, Option<Nat>), String> {
     let pay_token = token_map::get_by_token(&args.pay_token)?;
     if pay_token.is_removed() {
         Err("Pay token is suspended or removed".to_string())?;
@@ -174,9 +195,55 @@ async fn check_arguments(args: &SwapArgs) -> Result<(u32, StableToken, Nat, Stab
 
     // calculate receive_amount and swaps. do after user_id is created as it will be needed to calculate the receive_amount (user fee level)
     // no needs to store the return values as it'll be called again in process_swap
-    calculate_amounts(&pay_token, &pay_amount, &receive_token, args.receive_amount.as_ref(), max_slippage)?;
+    if !args.exact_output.unwrap_or(false) {
+        calculate_amounts(
+            &pay_token,
+            &pay_amount,
+            &receive_token,
+            args.receive_amount.as_ref(),
+            max_slippage,
+            false,
+            user_fee_level(user_id),
+        )?;
+        return Ok((user_id, pay_token, pay_amount, receive_token, max_slippage, to_address, None));
+    }
 
-    Ok((user_id, pay_token, pay_amount, receive_token, max_slippage, to_address))
+    // exact-output swap. solve for the smallest pay amount to receive exactly receive_amount
+    // pay_amount specified by the user is the maximum amount to pay
+    let receive_amount = args
+        .receive_amount
+        .as_ref()
+        .ok_or("Receive amount is required for exact output swaps")?;
+    let (exact_pay_amount, _, _, _, slippage, _) =
+        swap_amounts_exact_output(&pay_token, receive_amount, &receive_token, user_fee_level(user_id))?;
+    let Some(approval_headroom) = nat_subtract(&pay_amount, &exact_pay_amount) else {
+        let exact_pay_amount_f64 = nat_to_decimals_f64(pay_token.decimals(), &exact_pay_amount).unwrap_or(0_f64);
+        Err(format!(
+            "Insufficient pay amount. Requires {} {} with {}% slippage",
+            exact_pay_amount_f64,
+            pay_token.symbol(),
+            slippage
+        ))?
+    };
+    if slippage > max_slippage {
+        let exact_pay_amount_f64 = nat_to_decimals_f64(pay_token.decimals(), &exact_pay_amount).unwrap_or(0_f64);
+        Err(format!(
+            "Slippage exceeded. Requires {} {} with {}% slippage",
+            exact_pay_amount_f64,
+            pay_token.symbol(),
+            slippage
+        ))?
+    }
+
+    Ok((
+        user_id,
+        pay_token,
+        exact_pay_amount,
+        receive_token,
+        max_slippage,
+        to_address,
+        Some(approval_headroom),
+    ))
 }
 
 // swaps needs to be passed in to get the pool of the pay token which is needed to determine if the
@@ -190,42 +257,95 @@ async fn process_swap(
     receive_token: &StableToken,
     receive_amount: Option<&Nat>,
     max_slippage: f64,
+    exact_output: bool,
+    deadline: Option<u64>,
     transfer_ids: &mut Vec<u64>,
+    claim_ids: &mut Vec<u64>,
     ts: u64,
-) -> Result<(Nat, f64, f64, f64, Vec<SwapCalc>), String> {
+) -> Result<(Nat, Nat, f64, f64, f64, Vec<SwapCalc>), String> {
     let caller_id = caller_id();
     let kong_backend = kong_settings_map::get().kong_backend;
 
     request_map::update_status(request_id, StatusCode::Start, None);
 
+    if deadline.is_some_and(|deadline| get_time() > deadline) {
+        request_map::update_status(request_id, StatusCode::DeadlineExceeded, None);
+        Err(format!("Req #{} failed. Deadline exceeded", request_id))?
+    }
+
     transfer_from_token(request_id, &caller_id, pay_token, pay_amount, &kong_backend, transfer_ids, ts)
         .await
         .map_err(|e| format!("Pay token transfer_from failed. {}", e))?;
 
+    // deadline could have passed while waiting for transfer_from. return pay token back to user
+    if deadline.is_some_and(|deadline| get_time() > deadline) {
+        request_map::update_status(request_id, StatusCode::DeadlineExceeded, None);
+        return_pay_token(
+            request_id,
+            user_id,
+            &caller_id,
+            pay_token,
+            pay_amount,
+            Some(receive_token),
+            transfer_ids,
+            ts,
+        )
+        .await;
+        Err(format!("Req #{} failed. Deadline exceeded", request_id))?
+    }
+
     // re-calculate receive_amount and swaps with the latest pool state
-    let (receive_amount_with_fees_and_gas, mid_price, price, slippage, swaps) =
-        match update_liquidity_pool(request_id, pay_token, pay_amount, receive_token, receive_amount, max_slippage) {
-            Ok((receive_amount, mid_price, price, slippage, swaps)) => (receive_amount, mid_price, price, slippage, swaps),
-            Err(e) => {
-                // return pay token back to user
-                return_pay_token(
-                    request_id,
-                    user_id,
-                    &caller_id,
-                    pay_token,
-                    pay_amount,
-                    Some(receive_token),
-                    transfer_ids,
-                    ts,
-                )
-                .await;
-                Err(format!("Req #{} failed. {}", request_id, e))?
-            }
-        };
+    let (receive_amount_with_fees_and_gas, mid_price, price, slippage, swaps) = match update_liquidity_pool(
+        request_id,
+        pay_token,
+        pay_amount,
+        receive_token,
+        receive_amount,
+        max_slippage,
+        exact_output,
+        user_fee_level(user_id),
+    ) {
+        Ok((receive_amount, mid_price, price, slippage, swaps)) => (receive_amount, mid_price, price, slippage, swaps),
+        Err(e) => {
+            // return pay token back to user
+            return_pay_token(
+                request_id,
+                user_id,
+                &caller_id,
+                pay_token,
+                pay_amount,
+                Some(receive_token),
+                transfer_ids,
+                ts,
+            )
+            .await;
+            Err(format!("Req #{} failed. {}", request_id, e))?
+        }
+    };
 
     request_map::update_status(request_id, StatusCode::SwapSuccess, None);
 
-    Ok((receive_amount_with_fees_and_gas, mid_price, price, slippage, swaps))
+    // exact-output swaps can use less than pay_amount if the price moved in the user's favour while waiting for transfer_from
+    let (swap_pay_amount, unused_pay_amount) = if exact_output {
+        settle_unused_pay_amount(pay_token, pay_amount, &swaps)
+    } else {
+        (pay_amount.clone(), None)
+    };
+    if let Some(unused_pay_amount) = unused_pay_amount {
+        return_unused_pay_token(
+            request_id,
+            user_id,
+            &caller_id,
+            pay_token,
+            &unused_pay_amount,
+            transfer_ids,
+            claim_ids,
+            ts,
+        )
+        .await;
+    }
+
+    Ok((swap_pay_amount, receive_amount_with_fees_and_gas, mid_price, price, slippage, swaps))
 }
 
 async fn transfer_from_token(
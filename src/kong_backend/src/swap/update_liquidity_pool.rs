@@ -3,17 +3,18 @@ use candid::Nat;
 use super::calculate_amounts::calculate_amounts;
 use super::swap_calc::SwapCalc;
 
-use crate::helpers::nat_helpers::nat_divide_as_f64;
-use crate::helpers::{
-    math_helpers::round_f64,
-    nat_helpers::{nat_add, nat_divide, nat_multiply, nat_subtract, nat_zero},
-};
+use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_multiply, nat_subtract, nat_zero};
 use crate::ic::ckusdt::ckusdt_amount;
-use crate::stable_pool::pool_map;
+use crate::ic::get_time::get_time;
+use crate::ic::logging::error_log;
+use crate::referrals::referral_rewards;
+use crate::stable_pool::{concentrated_pool, dynamic_fee, pool_map, price_oracle, rolling_stats, stable_pool::StablePool};
 use crate::stable_request::request_map;
 use crate::stable_request::status::StatusCode;
 use crate::stable_token::stable_token::StableToken;
+use crate::stable_user::fee_tiers;
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_liquidity_pool(
     request_id: u64,
     pay_token: &StableToken,
@@ -21,71 +22,25 @@ pub fn update_liquidity_pool(
     receive_token: &StableToken,
     receive_amount: Option<&Nat>,
     max_slippage: f64,
+    exact_output: bool,
+    user_fee_level: u8,
 ) -> Result<(Nat, f64, f64, f64, Vec<SwapCalc>), String> {
     request_map::update_status(request_id, StatusCode::CalculatePoolAmounts, None);
 
-    match calculate_amounts(pay_token, pay_amount, receive_token, receive_amount, max_slippage) {
+    match calculate_amounts(
+        pay_token,
+        pay_amount,
+        receive_token,
+        receive_amount,
+        max_slippage,
+        exact_output,
+        user_fee_level,
+    ) {
         Ok((receive_amount_with_fees_and_gas, price, mid_price, slippage, swaps)) => {
             request_map::update_status(request_id, StatusCode::CalculatePoolAmountsSuccess, None);
 
             // update the pool, in some cases there could be multiple pools
-            request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
-            for swap in &swaps {
-                // refresh pool with the latest state
-                let mut pool = match pool_map::get_by_pool_id(swap.pool_id) {
-                    Some(pool) => pool,
-                    None => continue, // should not get here
-                };
-
-                if swap.receive_token_id == pool.token_id_1 {
-                    // user pays token_0 and receives token_1
-                    pool.balance_0 = nat_add(&pool.balance_0, &swap.pay_amount); // pay_amount is in token_0
-                    pool.balance_1 = nat_subtract(&pool.balance_1, &swap.receive_amount).unwrap_or(nat_zero()); // receive_amount is in token_1
-                    if let Ok(ckusdt_volume) = ckusdt_amount(&pool.token_1(), &swap.receive_amount) {
-                        // update 24h stats
-                        pool.rolling_24h_volume = nat_add(&pool.rolling_24h_volume, &ckusdt_volume);
-                    }
-                    // fees are in token_1. take out Kong's fee
-                    // kong_fee_1 = lp_fee * kong_fee_bps / lp_fee_bps
-                    // lp_fee_1 = lp_fee - kong_fee_1
-                    let numerator = nat_multiply(&swap.lp_fee, &Nat::from(pool.kong_fee_bps)); //swap.lp_fee is in token_1
-                    let kong_fee_1 = nat_divide(&numerator, &Nat::from(pool.lp_fee_bps)).unwrap_or(nat_zero());
-                    let lp_fee_1 = nat_subtract(&swap.lp_fee, &kong_fee_1).unwrap_or(nat_zero());
-                    pool.lp_fee_1 = nat_add(&pool.lp_fee_1, &lp_fee_1);
-                    pool.kong_fee_1 = nat_add(&pool.kong_fee_1, &kong_fee_1);
-                    if let Ok(ckusdt_lp_fee) = ckusdt_amount(&pool.token_1(), &lp_fee_1) {
-                        pool.rolling_24h_lp_fee = nat_add(&pool.rolling_24h_lp_fee, &ckusdt_lp_fee);
-                    }
-                } else {
-                    // user pays token_1 and receives token_0
-                    pool.balance_1 = nat_add(&pool.balance_1, &swap.pay_amount); // pay_amount is in token_1
-                    pool.balance_0 = nat_subtract(&pool.balance_0, &swap.receive_amount).unwrap_or(nat_zero()); // receive_amount is in token_0
-                    if let Ok(ckusdt_volume) = ckusdt_amount(&pool.token_0(), &swap.receive_amount) {
-                        pool.rolling_24h_volume = nat_add(&pool.rolling_24h_volume, &ckusdt_volume);
-                    }
-                    // fees are in token_0. take out Kong's fee
-                    // kong_fee_0 = lp_fee * kong_fee_bps / lp_fee_bps
-                    // lp_fee_0 = lp_fee - kong_fee_0
-                    let numerator = nat_multiply(&swap.lp_fee, &Nat::from(pool.kong_fee_bps)); //swap.lp_fee is in token_0
-                    let kong_fee_0 = nat_divide(&numerator, &Nat::from(pool.lp_fee_bps)).unwrap_or(nat_zero());
-                    let lp_fee_0 = nat_subtract(&swap.lp_fee, &kong_fee_0).unwrap_or(nat_zero());
-                    pool.lp_fee_0 = nat_add(&pool.lp_fee_0, &lp_fee_0);
-                    pool.kong_fee_0 = nat_add(&pool.kong_fee_0, &kong_fee_0);
-                    if let Ok(ckusdt_lp_fee) = ckusdt_amount(&pool.token_0(), &lp_fee_0) {
-                        pool.rolling_24h_lp_fee = nat_add(&pool.rolling_24h_lp_fee, &ckusdt_lp_fee);
-                    }
-                }
-                pool.set_tvl();
-                pool.rolling_24h_num_swaps = nat_add(&pool.rolling_24h_num_swaps, &Nat::from(1_u128));
-                // APY = (total_fees / total_liquidity) * 365 * 100
-                pool.rolling_24h_apy = round_f64(
-                    nat_divide_as_f64(&pool.rolling_24h_lp_fee, &pool.tvl).unwrap_or(0_f64) * 365_f64 * 100_f64,
-                    2,
-                );
-                pool_map::update(&pool);
-            }
-
-            request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
+            apply_swaps(request_id, &swaps);
 
             Ok((receive_amount_with_fees_and_gas, mid_price, price, slippage, swaps))
         }
@@ -95,3 +50,87 @@ pub fn update_liquidity_pool(
         }
     }
 }
+
+/// update the pools of the swaps with the latest state of each pool
+pub fn apply_swaps(request_id: u64, swaps: &[SwapCalc]) {
+    request_map::update_status(request_id, StatusCode::UpdatePoolAmounts, None);
+    let referrer_user_id = referral_rewards::get_referrer(request_id);
+    let user_id = request_map::get_by_request_id(request_id).map(|request| request.user_id);
+    // the first hop of each route is counted for the user's volume, the following hops are the same volume
+    let pay_token_id = swaps.first().map(|swap| swap.pay_token_id);
+    for swap in swaps {
+        // refresh pool with the latest state
+        let mut pool = match pool_map::get_by_pool_id(swap.pool_id) {
+            Some(pool) => pool,
+            None => continue, // should not get here
+        };
+        // accumulate the price before the swap changes it
+        let ts = get_time();
+        price_oracle::update_price_cumulative(&mut pool, ts);
+
+        let pay_token_0 = swap.receive_token_id == pool.token_id_1;
+        let lp_fee = swap_pool_balances(&mut pool, swap);
+        if pool.curve.is_concentrated() {
+            // move the price and distribute lp_fee to the in-range liquidity
+            if let Err(e) = concentrated_pool::apply_swap(&mut pool, pay_token_0, &swap.pay_amount, &lp_fee) {
+                error_log(&format!("Req #{}. Failed to update pool #{}. {}", request_id, pool.pool_id, e));
+            }
+        }
+
+        // price after the swap for the volatility of dynamic fee pools
+        dynamic_fee::record_price(&mut pool);
+
+        // update rolling stats. volume and fees are in the receive token
+        let receive_token = if pay_token_0 { pool.token_1() } else { pool.token_0() };
+        let ckusdt_volume = ckusdt_amount(&receive_token, &swap.receive_amount).unwrap_or(nat_zero());
+        let ckusdt_lp_fee = ckusdt_amount(&receive_token, &lp_fee).unwrap_or(nat_zero());
+        if let Some(referrer_user_id) = referrer_user_id {
+            // Kong's fee is the part of the swap's LP fee not paid to the LPs
+            let kong_fee = nat_subtract(&swap.lp_fee, &lp_fee).unwrap_or(nat_zero());
+            referral_rewards::pay_referral_fee(&mut pool, referrer_user_id, !pay_token_0, &kong_fee, &ckusdt_volume, ts);
+        }
+        if let Some(user_id) = user_id.filter(|_| Some(swap.pay_token_id) == pay_token_id) {
+            fee_tiers::record_swap_volume(user_id, ts, &ckusdt_volume);
+        }
+        pool.set_tvl();
+        rolling_stats::record_swap(&mut pool, ts, &ckusdt_volume, &ckusdt_lp_fee);
+        pool_map::update(&pool);
+    }
+    request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
+}
+
+/// update the balances and fees of the pool with the swap
+/// does not move the price of Concentrated pools. pool is not saved
+///
+/// returns the LP fee of the swap after Kong's share, in the receive token
+pub fn swap_pool_balances(pool: &mut StablePool, swap: &SwapCalc) -> Nat {
+    if swap.receive_token_id == pool.token_id_1 {
+        // user pays token_0 and receives token_1
+        pool.balance_0 = nat_add(&pool.balance_0, &swap.pay_amount); // pay_amount is in token_0
+        pool.balance_1 = nat_subtract(&pool.balance_1, &swap.receive_amount).unwrap_or(nat_zero()); // receive_amount is in token_1
+
+        // fees are in token_1. take out Kong's fee
+        // kong_fee_1 = lp_fee * kong_fee_bps / lp_fee_bps
+        // lp_fee_1 = lp_fee - kong_fee_1
+        let numerator = nat_multiply(&swap.lp_fee, &Nat::from(pool.kong_fee_bps)); //swap.lp_fee is in token_1
+        let kong_fee_1 = nat_divide(&numerator, &Nat::from(pool.lp_fee_bps)).unwrap_or(nat_zero());
+        let lp_fee_1 = nat_subtract(&swap.lp_fee, &kong_fee_1).unwrap_or(nat_zero());
+        pool.lp_fee_1 = nat_add(&pool.lp_fee_1, &lp_fee_1);
+        pool.kong_fee_1 = nat_add(&pool.kong_fee_1, &kong_fee_1);
+        lp_fee_1
+    } else {
+        // user pays token_1 and receives token_0
+        pool.balance_1 = nat_add(&pool.balance_1, &swap.pay_amount); // pay_amount is in token_1
+        pool.balance_0 = nat_subtract(&pool.balance_0, &swap.receive_amount).unwrap_or(nat_zero()); // receive_amount is in token_0
+
+        // fees are in token_0. take out Kong's fee
+        // kong_fee_0 = lp_fee * kong_fee_bps / lp_fee_bps
+        // lp_fee_0 = lp_fee - kong_fee_0
+        let numerator = nat_multiply(&swap.lp_fee, &Nat::from(pool.kong_fee_bps)); //swap.lp_fee is in token_0
+        let kong_fee_0 = nat_divide(&numerator, &Nat::from(pool.lp_fee_bps)).unwrap_or(nat_zero());
+        let lp_fee_0 = nat_subtract(&swap.lp_fee, &kong_fee_0).unwrap_or(nat_zero());
+        pool.lp_fee_0 = nat_add(&pool.lp_fee_0, &lp_fee_0);
+        pool.kong_fee_0 = nat_add(&pool.kong_fee_0, &kong_fee_0);
+        lp_fee_0
+    }
+}
@@ -2,12 +2,13 @@ use candid::Nat;
 use ic_cdk::query;
 
 use super::swap_amounts_reply::SwapAmountsReply;
-use super::swap_amounts_reply_impl::to_swap_amounts_tx_reply;
+use super::swap_amounts_reply_impl::{to_swap_amounts_route_reply, to_swap_amounts_tx_reply};
 
 use crate::ic::guards::not_in_maintenance_mode;
 use crate::stable_token::token::Token;
 use crate::stable_token::token_map;
 use crate::swap;
+use crate::swap::swap_amounts::caller_fee_level;
 
 #[query(guard = "not_in_maintenance_mode")]
 pub fn swap_amounts(pay_token: String, pay_amount: Nat, receive_token: String) -> Result<SwapAmountsReply, String> {
@@ -22,9 +23,46 @@ pub fn swap_amounts(pay_token: String, pay_amount: Nat, receive_token: String) -
     let receive_symbol = receive_token.symbol();
     let receive_address = receive_token.address();
 
-    let (receive_amount, price, mid_price, slippage, txs) =
-        swap::swap_amounts::swap_amounts(&pay_token, Some(&pay_amount), &receive_token)?;
+    let (receive_amount, price, mid_price, slippage, routes) =
+        swap::swap_amounts::swap_amounts_routes(&pay_token, Some(&pay_amount), &receive_token, caller_fee_level())?;
+    let swap_amounts_tx_reply: Vec<_> = routes.iter().flatten().filter_map(to_swap_amounts_tx_reply).collect();
+    let swap_amounts_route_reply: Vec<_> = routes.iter().map(|route| to_swap_amounts_route_reply(route)).collect();
+
+    Ok(SwapAmountsReply {
+        pay_chain,
+        pay_symbol,
+        pay_amount,
+        pay_address,
+        receive_chain,
+        receive_symbol,
+        receive_address,
+        receive_amount,
+        price,
+        mid_price,
+        slippage,
+        txs: swap_amounts_tx_reply,
+        routes: swap_amounts_route_reply,
+    })
+}
+
+/// calculates the pay_amount needed to receive exactly receive_amount of receive_token
+#[query(guard = "not_in_maintenance_mode")]
+pub fn swap_amounts_exact_output(pay_token: String, receive_token: String, receive_amount: Nat) -> Result<SwapAmountsReply, String> {
+    // Pay token
+    let pay_token = token_map::get_by_token(&pay_token)?;
+    let pay_chain = pay_token.chain();
+    let pay_symbol = pay_token.symbol();
+    let pay_address = pay_token.address();
+    // Receive token
+    let receive_token = token_map::get_by_token(&receive_token)?;
+    let receive_chain = receive_token.chain();
+    let receive_symbol = receive_token.symbol();
+    let receive_address = receive_token.address();
+
+    let (pay_amount, receive_amount, price, mid_price, slippage, txs) =
+        swap::swap_amounts::swap_amounts_exact_output(&pay_token, &receive_amount, &receive_token, caller_fee_level())?;
     let swap_amounts_tx_reply: Vec<_> = txs.iter().filter_map(to_swap_amounts_tx_reply).collect();
+    let swap_amounts_route_reply = vec![to_swap_amounts_route_reply(&txs)];
 
     Ok(SwapAmountsReply {
         pay_chain,
@@ -39,5 +77,6 @@ pub fn swap_amounts(pay_token: String, pay_amount: Nat, receive_token: String) -
         mid_price,
         slippage,
         txs: swap_amounts_tx_reply,
+        routes: swap_amounts_route_reply,
     })
 }
@@ -14,9 +14,18 @@ pub struct SwapAmountsTxReply {
     pub receive_amount: Nat,
     pub price: f64,
     pub lp_fee: Nat,
+    pub lp_fee_bps: u8, // effective LP fee of the pool
     pub gas_fee: Nat,
 }
 
+// pay_amount is split across routes, each route is a group of swaps
+#[derive(CandidType, Clone, Debug, Serialize, Deserialize)]
+pub struct SwapAmountsRouteReply {
+    pub pay_amount: Nat,
+    pub receive_amount: Nat,
+    pub txs: Vec<SwapAmountsTxReply>,
+}
+
 #[derive(CandidType, Clone, Debug, Serialize, Deserialize)]
 pub struct SwapAmountsReply {
     pub pay_chain: String,
@@ -31,4 +40,5 @@ pub struct SwapAmountsReply {
     pub mid_price: f64,
     pub slippage: f64,
     pub txs: Vec<SwapAmountsTxReply>,
+    pub routes: Vec<SwapAmountsRouteReply>,
 }
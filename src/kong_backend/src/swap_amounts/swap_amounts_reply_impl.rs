@@ -1,7 +1,8 @@
 use num::{BigRational, Zero};
 
-use super::swap_amounts_reply::SwapAmountsTxReply;
+use super::swap_amounts_reply::{SwapAmountsRouteReply, SwapAmountsTxReply};
 
+use crate::helpers::nat_helpers::nat_zero;
 use crate::stable_token::token::Token;
 use crate::stable_token::token_map;
 use crate::swap::swap_calc::SwapCalc;
@@ -31,6 +32,15 @@ pub fn to_swap_amounts_tx_reply(swap: &SwapCalc) -> Option<SwapAmountsTxReply> {
         receive_amount: swap.receive_amount_with_fees_and_gas(),
         price: price_f64,
         lp_fee: swap.lp_fee.clone(),
+        lp_fee_bps: pool.effective_lp_fee_bps(),
         gas_fee: swap.gas_fee.clone(),
     })
 }
+
+pub fn to_swap_amounts_route_reply(route: &[SwapCalc]) -> SwapAmountsRouteReply {
+    SwapAmountsRouteReply {
+        pay_amount: route.first().map_or(nat_zero(), |swap| swap.pay_amount.clone()),
+        receive_amount: route.last().map_or(nat_zero(), |swap| swap.receive_amount_with_fees_and_gas()),
+        txs: route.iter().filter_map(to_swap_amounts_tx_reply).collect(),
+    }
+}
@@ -13,5 +13,6 @@ pub struct ICReply {
     pub icrc1: bool,
     pub icrc2: bool,
     pub icrc3: bool,
+    pub verification_method: String,
     pub is_removed: bool,
 }
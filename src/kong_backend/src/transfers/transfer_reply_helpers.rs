@@ -1,6 +1,7 @@
 use crate::chains::chains::IC_CHAIN;
 use crate::stable_token::stable_token::StableToken;
 use crate::stable_token::token_map;
+use crate::stable_transfer::stable_transfer::StableTransfer;
 use crate::stable_transfer::transfer_map;
 use crate::stable_transfer::tx_id::TxId;
 
@@ -11,22 +12,23 @@ pub fn to_transfer_ids(transfer_ids: &[u64]) -> Vec<TransferIdReply> {
 }
 
 pub fn to_transfer_id(transfer_id: u64) -> Option<TransferIdReply> {
-    match transfer_map::get_by_transfer_id(transfer_id) {
-        Some(transfer) => match token_map::get_by_token_id(transfer.token_id) {
-            Some(StableToken::IC(token)) => match transfer.tx_id {
-                TxId::BlockIndex(block_index) => Some(TransferIdReply {
-                    transfer_id,
-                    transfer: TransferReply::IC(ICTransferReply {
-                        chain: IC_CHAIN.to_string(),
-                        symbol: token.symbol,
-                        is_send: transfer.is_send,
-                        amount: transfer.amount,
-                        canister_id: token.canister_id.to_string(),
-                        block_index,
-                    }),
+    to_transfer_reply(&transfer_map::get_by_transfer_id(transfer_id)?)
+}
+
+pub fn to_transfer_reply(transfer: &StableTransfer) -> Option<TransferIdReply> {
+    match token_map::get_by_token_id(transfer.token_id) {
+        Some(StableToken::IC(token)) => match transfer.tx_id {
+            TxId::BlockIndex(ref block_index) => Some(TransferIdReply {
+                transfer_id: transfer.transfer_id,
+                transfer: TransferReply::IC(ICTransferReply {
+                    chain: IC_CHAIN.to_string(),
+                    symbol: token.symbol,
+                    is_send: transfer.is_send,
+                    amount: transfer.amount.clone(),
+                    canister_id: token.canister_id.to_string(),
+                    block_index: block_index.clone(),
                 }),
-                _ => None,
-            },
+            }),
             _ => None,
         },
         _ => None,
@@ -0,0 +1,32 @@
+use ic_cdk::query;
+
+use super::twap_reply::TwapReply;
+
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_pool::{pool_map, price_oracle};
+
+/// time-weighted average price of the pool over window_secs
+/// pool can be in the format of Symbol_Symbol, Chain.Symbol_Chain.Symbol, Address_Address, or Chain.Address_Chain.Address
+#[query(guard = "not_in_maintenance_mode")]
+pub fn twap(pool: String, window_secs: u64) -> Result<TwapReply, String> {
+    let pool = pool_map::get_by_token(&pool)?;
+    if pool.is_removed {
+        Err(format!("Pool {} is removed", pool.symbol()))?
+    }
+
+    let ts = get_time();
+    let (price_0, price_1, start_ts) = price_oracle::twap(&pool, window_secs, ts)?;
+
+    Ok(TwapReply {
+        pool_id: pool.pool_id,
+        symbol: pool.symbol(),
+        symbol_0: pool.symbol_0(),
+        symbol_1: pool.symbol_1(),
+        price_0,
+        price_1,
+        window_secs: (ts - start_ts) / 1_000_000_000,
+        start_ts,
+        end_ts: ts,
+    })
+}
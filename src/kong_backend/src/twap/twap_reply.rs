@@ -0,0 +1,15 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct TwapReply {
+    pub pool_id: u32,
+    pub symbol: String,
+    pub symbol_0: String,
+    pub symbol_1: String,
+    pub price_0: f64,     // time-weighted average price of token_0 in token_1
+    pub price_1: f64,     // time-weighted average price of token_1 in token_0
+    pub window_secs: u64, // actual window of the average, at least the requested window
+    pub start_ts: u64,
+    pub end_ts: u64,
+}
@@ -1,4 +1,4 @@
-use candid::CandidType;
+use candid::{CandidType, Nat};
 use serde::{Deserialize, Serialize};
 
 #[derive(CandidType, Clone, Debug, Serialize, Deserialize)]
@@ -11,4 +11,9 @@ pub struct UserReply {
     pub referred_by_expires_at: Option<u64>,
     pub fee_level: u8,
     pub fee_level_expires_at: Option<u64>,
+    pub fee_tier: Option<String>,
+    pub volume_30d: Nat,
+    pub next_fee_tier: Option<String>,
+    pub next_fee_level: Option<u8>,
+    pub next_fee_tier_volume: Option<Nat>, // remaining 30 day volume to reach next_fee_tier
 }
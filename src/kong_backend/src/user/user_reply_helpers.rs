@@ -2,7 +2,11 @@ use candid::Principal;
 
 use super::user_reply::UserReply;
 
+use crate::helpers::fee_helpers::next_fee_tier;
+use crate::helpers::nat_helpers::nat_subtract;
+use crate::ic::get_time::get_time;
 use crate::ic::id::principal_to_account_id;
+use crate::stable_kong_settings::kong_settings_map;
 use crate::stable_user::stable_user::StableUser;
 use crate::stable_user::user_map;
 
@@ -13,6 +17,20 @@ pub fn to_user_reply(user: &StableUser) -> UserReply {
     let referred_by = user
         .referred_by
         .and_then(|referred_user| user_map::get_by_user_id(referred_user).map(|referred_user| referred_user.my_referral_code));
+    // current fee tier and the next one reachable by volume
+    let fee_tiers = kong_settings_map::get().fee_tiers;
+    let fee_level = user.current_fee_level(get_time());
+    let fee_level_expires_at = if fee_level == user.fee_level {
+        user.fee_level_expires_at
+    } else {
+        None
+    };
+    let fee_tier = fee_tiers
+        .iter()
+        .filter(|fee_tier| fee_tier.fee_level <= fee_level)
+        .max_by_key(|fee_tier| fee_tier.fee_level)
+        .map(|fee_tier| fee_tier.name.clone());
+    let next_fee_tier = next_fee_tier(&fee_tiers, &user.volume_30d, fee_level);
     UserReply {
         user_id: user.user_id,
         principal_id: user.principal_id.clone(),
@@ -20,7 +38,12 @@ pub fn to_user_reply(user: &StableUser) -> UserReply {
         my_referral_code: user.my_referral_code.clone(),
         referred_by,
         referred_by_expires_at: user.referred_by_expires_at,
-        fee_level: user.fee_level,
-        fee_level_expires_at: user.fee_level_expires_at,
+        fee_level,
+        fee_level_expires_at,
+        fee_tier,
+        volume_30d: user.volume_30d.clone(),
+        next_fee_tier: next_fee_tier.map(|fee_tier| fee_tier.name.clone()),
+        next_fee_level: next_fee_tier.map(|fee_tier| fee_tier.fee_level),
+        next_fee_tier_volume: next_fee_tier.and_then(|fee_tier| nat_subtract(&fee_tier.min_volume, &user.volume_30d)),
     }
 }
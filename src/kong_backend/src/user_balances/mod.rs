@@ -1,4 +1,5 @@
 pub mod lp_reply;
+pub mod position_reply;
 #[allow(clippy::module_inception)]
 pub mod user_balances;
 pub mod user_balances_reply;
@@ -0,0 +1,26 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Clone, Debug, Serialize, Deserialize)]
+pub struct PositionReply {
+    pub position_id: u64,
+    pub symbol: String,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub in_range: bool,
+    pub liquidity: Nat,
+    pub usd_balance: f64,
+    pub chain_0: String,
+    pub symbol_0: String,
+    pub address_0: String,
+    pub amount_0: f64,
+    pub usd_amount_0: f64,
+    pub fee_0: f64, // uncollected fees of token_0
+    pub chain_1: String,
+    pub symbol_1: String,
+    pub address_1: String,
+    pub amount_1: f64,
+    pub usd_amount_1: f64,
+    pub fee_1: f64, // uncollected fees of token_1
+    pub ts: u64,
+}
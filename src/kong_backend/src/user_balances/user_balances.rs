@@ -1,15 +1,19 @@
 use ic_cdk::query;
 
 use super::lp_reply::LPReply;
+use super::position_reply::PositionReply;
 use super::user_balances_reply::UserBalancesReply;
 
 use crate::helpers::nat_helpers::{nat_add, nat_divide, nat_multiply, nat_to_decimals_f64, nat_zero};
 use crate::ic::ckusdt::{ckusdt_amount, to_ckusdt_decimals_f64};
 use crate::ic::get_time::get_time;
 use crate::ic::guards::not_in_maintenance_mode;
-use crate::stable_lp_token::lp_token_map;
+use crate::stable_lp_token::{lp_token_map, stable_lp_token::StableLPToken};
+use crate::stable_pool::concentrated_pool::{position_amounts, position_fees};
+use crate::stable_pool::pool_map;
+use crate::stable_position::{position_map, stable_position::StablePosition};
 use crate::stable_token::lp_token::LPToken;
-use crate::stable_token::stable_token::StableToken::{IC, LP};
+use crate::stable_token::stable_token::StableToken::LP;
 use crate::stable_token::token::Token;
 use crate::stable_token::token_map;
 use crate::stable_user::user_map;
@@ -25,23 +29,30 @@ pub async fn user_balances(principal_id: String) -> Result<Vec<UserBalancesReply
     let mut user_balances = Vec::new();
     let ts = get_time();
 
-    token_map::get().iter().for_each(|token| match token {
-        LP(lp_token) => {
-            if let Some(reply) = to_user_balance_lp_token_reply(lp_token, user_id, ts) {
-                user_balances.push(reply);
+    lp_token_map::get_by_user_id(user_id)
+        .iter()
+        .for_each(|user_lp_token| match token_map::get_by_token_id(user_lp_token.token_id) {
+            Some(LP(lp_token)) if !lp_token.is_removed => {
+                if let Some(reply) = to_user_balance_lp_token_reply(&lp_token, user_lp_token, ts) {
+                    user_balances.push(reply);
+                }
             }
+            _ => (),
+        });
+
+    position_map::get_by_user_id(user_id).iter().for_each(|position| {
+        if let Some(reply) = to_user_balance_position_reply(position, ts) {
+            user_balances.push(reply);
         }
-        IC(_) => (),
     });
 
     Ok(user_balances)
 }
 
-fn to_user_balance_lp_token_reply(token: &LPToken, user_id: u32, ts: u64) -> Option<UserBalancesReply> {
+fn to_user_balance_lp_token_reply(token: &LPToken, lp_token: &StableLPToken, ts: u64) -> Option<UserBalancesReply> {
     let lp_token_id = token.token_id;
-    let lp_token = lp_token_map::get_by_token_id_by_user_id(lp_token_id, user_id)?;
     // user balance and total supply of the LP token
-    let user_lp_token_balance = lp_token.amount;
+    let user_lp_token_balance = lp_token.amount.clone();
     let lp_token_total_supply = lp_token_map::get_total_supply(lp_token_id);
     // pool of the LP token
     let pool = token.pool_of()?;
@@ -89,3 +100,50 @@ fn to_user_balance_lp_token_reply(token: &LPToken, user_id: u32, ts: u64) -> Opt
         ts,
     }))
 }
+
+/// principal and uncollected fees of a position of a Concentrated pool
+fn to_user_balance_position_reply(position: &StablePosition, ts: u64) -> Option<UserBalancesReply> {
+    let pool = pool_map::get_by_pool_id(position.pool_id)?;
+    let state = pool.concentrated.as_ref()?;
+    let (raw_amount_0, raw_amount_1) = position_amounts(&pool, position)?;
+    let (raw_fee_0, raw_fee_1) = position_fees(&pool, position)?;
+
+    let token_0 = pool.token_0();
+    let amount_0 = nat_to_decimals_f64(token_0.decimals(), &raw_amount_0)?;
+    let fee_0 = nat_to_decimals_f64(token_0.decimals(), &raw_fee_0)?;
+    let usd_amount_0 = ckusdt_amount(&token_0, &nat_add(&raw_amount_0, &raw_fee_0))
+        .and_then(|amount_0| to_ckusdt_decimals_f64(&amount_0).ok_or("Error converting amount 0 to ckUSDT".to_string()))
+        .unwrap_or(0_f64);
+
+    let token_1 = pool.token_1();
+    let amount_1 = nat_to_decimals_f64(token_1.decimals(), &raw_amount_1)?;
+    let fee_1 = nat_to_decimals_f64(token_1.decimals(), &raw_fee_1)?;
+    let usd_amount_1 = ckusdt_amount(&token_1, &nat_add(&raw_amount_1, &raw_fee_1))
+        .and_then(|amount_1| to_ckusdt_decimals_f64(&amount_1).ok_or("Error converting amount 1 to ckUSDT".to_string()))
+        .unwrap_or(0_f64);
+
+    let usd_balance = usd_amount_0 + usd_amount_1;
+
+    Some(UserBalancesReply::Position(PositionReply {
+        position_id: position.position_id,
+        symbol: pool.symbol(),
+        tick_lower: position.tick_lower,
+        tick_upper: position.tick_upper,
+        in_range: position.tick_lower <= state.tick && state.tick < position.tick_upper,
+        liquidity: position.liquidity.clone(),
+        usd_balance,
+        chain_0: token_0.chain(),
+        symbol_0: token_0.symbol(),
+        address_0: token_0.address(),
+        amount_0,
+        usd_amount_0,
+        fee_0,
+        chain_1: token_1.chain(),
+        symbol_1: token_1.symbol(),
+        address_1: token_1.address(),
+        amount_1,
+        usd_amount_1,
+        fee_1,
+        ts,
+    }))
+}
@@ -2,8 +2,10 @@ use candid::CandidType;
 use serde::{Deserialize, Serialize};
 
 use super::lp_reply::LPReply;
+use super::position_reply::PositionReply;
 
 #[derive(CandidType, Clone, Debug, Serialize, Deserialize)]
 pub enum UserBalancesReply {
-    LP(LPReply), // only return LP token balances for now
+    LP(LPReply),             // only return LP token balances for now
+    Position(PositionReply), // ranged positions of Concentrated pools
 }
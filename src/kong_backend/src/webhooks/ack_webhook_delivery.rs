@@ -0,0 +1,17 @@
+use ic_cdk::update;
+
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_user::user_map;
+use crate::stable_webhook::webhook_delivery_map;
+
+/// acknowledge a delivery to the caller's webhook so it is not sent again
+/// called by the webhook canister with the delivery_id it was notified with
+#[update(guard = "not_in_maintenance_mode")]
+fn ack_webhook_delivery(delivery_id: u64) -> Result<String, String> {
+    let user_id = user_map::get_by_caller()?.ok_or("User not found")?.user_id;
+    let delivery = webhook_delivery_map::get_by_delivery_id(delivery_id)
+        .filter(|delivery| delivery.user_id == user_id)
+        .ok_or(format!("Delivery #{} not found", delivery_id))?;
+    webhook_delivery_map::remove(delivery.delivery_id);
+    Ok(format!("Delivery #{} acknowledged", delivery_id))
+}
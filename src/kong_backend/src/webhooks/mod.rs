@@ -0,0 +1,7 @@
+pub mod ack_webhook_delivery;
+pub mod process_webhooks;
+pub mod register_webhook;
+pub mod remove_webhook;
+pub mod webhook;
+pub mod webhook_reply;
+pub mod webhook_reply_helpers;
@@ -0,0 +1,122 @@
+use ic_cdk::api::call::notify;
+
+use crate::helpers::webhook_helpers::{
+    delivery_action, queued_delivery_status, with_notify_result, with_reply_recorded, WebhookDeliveryAction,
+};
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::ic::logging::error_log;
+use crate::requests::request_reply_helpers::to_request_reply;
+use crate::stable_memory::REQUEST_ARCHIVE_MAP;
+use crate::stable_request::reply::Reply;
+use crate::stable_request::request_map;
+use crate::stable_request::stable_request::{StableRequest, StableRequestId};
+use crate::stable_webhook::stable_webhook_delivery::{StableWebhookDelivery, WebhookDeliveryStatus};
+use crate::stable_webhook::{webhook_delivery_map, webhook_map};
+
+const MAX_DELIVERIES: u64 = 10_000; // size of the outbox
+const MAX_DELIVERIES_PER_RUN: usize = 100;
+
+/// queue a delivery to the user's webhook, if the user registered one
+/// called when the request reaches Success or Failed. the delivery waits for reply if it is not recorded yet
+pub fn queue_webhook_delivery(user_id: u32, request_id: u64, reply: &Reply) {
+    if webhook_map::get_by_user_id(user_id).is_none() {
+        return;
+    }
+    if webhook_delivery_map::len() >= MAX_DELIVERIES {
+        error_log(&format!("Webhook outbox full. Dropped request_id #{}", request_id));
+        return;
+    }
+    webhook_delivery_map::insert(&StableWebhookDelivery {
+        delivery_id: 0,
+        user_id,
+        request_id,
+        status: queued_delivery_status(reply),
+        attempts: 0,
+        message: None,
+        ts: get_time(),
+    });
+}
+
+/// release the delivery of a request waiting for its reply. called when the reply is recorded
+pub fn reply_webhook_delivery(user_id: u32, request_id: u64) {
+    if webhook_map::get_by_user_id(user_id).is_none() {
+        return;
+    }
+    if let Some(delivery) = webhook_delivery_map::get_by_request_id(request_id) {
+        if delivery.status == WebhookDeliveryStatus::AwaitingReply {
+            webhook_delivery_map::update(&with_reply_recorded(&delivery));
+        }
+    }
+}
+
+/// notify webhooks of the deliveries due, oldest first
+/// notify() is one-way so the webhook canister acknowledges a delivery by calling ack_webhook_delivery(delivery_id).
+/// deliveries failing to be sent or not acknowledged are re-sent, up to MAX_ATTEMPTS times, after which they are kept
+/// as Failed until they expire
+pub fn process_webhooks() {
+    if not_in_maintenance_mode().is_err() {
+        return;
+    }
+
+    let ts = get_time();
+    let mut num_sent = 0;
+    for delivery in webhook_delivery_map::get() {
+        if num_sent >= MAX_DELIVERIES_PER_RUN {
+            break;
+        }
+        let Some(webhook) = webhook_map::get_by_user_id(delivery.user_id) else {
+            webhook_delivery_map::remove(delivery.delivery_id);
+            continue;
+        };
+        let request = get_request(delivery.request_id);
+        match delivery_action(&delivery, request.as_ref(), ts) {
+            WebhookDeliveryAction::Send => {
+                let Some(request) = request else {
+                    continue; // should not get here
+                };
+                num_sent += 1;
+                // one-way call, only errors enqueueing the message can be detected
+                let result = notify(
+                    webhook.canister_id,
+                    &webhook.method,
+                    (to_request_reply(&request), delivery.delivery_id),
+                )
+                .map_err(|e| format!("{:?}", e));
+                let update_delivery = with_notify_result(&delivery, result, ts);
+                if update_delivery.status == WebhookDeliveryStatus::Failed {
+                    log_failed_delivery(&update_delivery);
+                }
+                webhook_delivery_map::update(&update_delivery);
+            }
+            WebhookDeliveryAction::Fail(message) => {
+                let update_delivery = StableWebhookDelivery {
+                    status: WebhookDeliveryStatus::Failed,
+                    message: Some(message),
+                    ts,
+                    ..delivery
+                };
+                log_failed_delivery(&update_delivery);
+                webhook_delivery_map::update(&update_delivery);
+            }
+            WebhookDeliveryAction::Remove => {
+                webhook_delivery_map::remove(delivery.delivery_id);
+            }
+            WebhookDeliveryAction::Wait => (),
+        }
+    }
+}
+
+fn log_failed_delivery(delivery: &StableWebhookDelivery) {
+    error_log(&format!(
+        "Webhook delivery #{} of request_id #{} failed. {}",
+        delivery.delivery_id,
+        delivery.request_id,
+        delivery.message.as_deref().unwrap_or_default()
+    ));
+}
+
+/// requests older than an hour are only in the archive
+fn get_request(request_id: u64) -> Option<StableRequest> {
+    request_map::get_by_request_id(request_id).or_else(|| REQUEST_ARCHIVE_MAP.with(|m| m.borrow().get(&StableRequestId(request_id))))
+}
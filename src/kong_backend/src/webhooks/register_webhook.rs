@@ -0,0 +1,48 @@
+use candid::Principal;
+use ic_cdk::update;
+
+use super::webhook_reply::WebhookReply;
+use super::webhook_reply_helpers::to_webhook_reply;
+
+use crate::ic::get_time::get_time;
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::ic::id::caller;
+use crate::stable_user::user_map;
+use crate::stable_webhook::stable_webhook::StableWebhook;
+use crate::stable_webhook::webhook_map;
+
+const MAX_METHOD_LEN: usize = 64;
+// webhooks can only call methods meant for Kong, so Kong can't be used to call any other method of the canister
+const METHOD_PREFIX: &str = "kong_webhook_";
+
+/// register, or replace, the caller's webhook
+/// when a request of the caller reaches Success or Failed and its reply is recorded, method of canister_id is called one-way
+/// with the request's RequestReply and the delivery_id to acknowledge with ack_webhook_delivery()
+/// canister_id must be the calling canister
+#[update(guard = "not_in_maintenance_mode")]
+fn register_webhook(canister_id: String, method: String) -> Result<WebhookReply, String> {
+    let canister_id = check_arguments(&canister_id, &method)?;
+    let user_id = user_map::insert(None)?;
+    let webhook = StableWebhook {
+        user_id,
+        canister_id,
+        method,
+        ts: get_time(),
+    };
+    webhook_map::insert(&webhook);
+    Ok(to_webhook_reply(&webhook))
+}
+
+fn check_arguments(canister_id: &str, method: &str) -> Result<Principal, String> {
+    let canister_id = Principal::from_text(canister_id).map_err(|_| "Invalid canister id".to_string())?;
+    if canister_id != caller() {
+        Err("Canister id must be the caller")?
+    }
+    if !method.starts_with(METHOD_PREFIX) || method.len() == METHOD_PREFIX.len() {
+        Err(format!("Method must start with {}", METHOD_PREFIX))?
+    }
+    if method.len() > MAX_METHOD_LEN {
+        Err(format!("Method must be at most {} characters", MAX_METHOD_LEN))?
+    }
+    Ok(canister_id)
+}
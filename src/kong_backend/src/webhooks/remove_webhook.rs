@@ -0,0 +1,20 @@
+use ic_cdk::update;
+
+use super::webhook_reply::WebhookReply;
+use super::webhook_reply_helpers::to_webhook_reply;
+
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_user::user_map;
+use crate::stable_webhook::{webhook_delivery_map, webhook_map};
+
+/// remove the caller's webhook and its pending and failed deliveries
+#[update(guard = "not_in_maintenance_mode")]
+fn remove_webhook() -> Result<WebhookReply, String> {
+    let user_id = user_map::get_by_caller()?.ok_or("User not found")?.user_id;
+    let webhook = webhook_map::get_by_user_id(user_id).ok_or("Webhook not found")?;
+    // reply with the deliveries being dropped
+    let reply = to_webhook_reply(&webhook);
+    webhook_map::remove(user_id);
+    webhook_delivery_map::remove_by_user_id(user_id);
+    Ok(reply)
+}
@@ -0,0 +1,16 @@
+use ic_cdk::query;
+
+use super::webhook_reply::WebhookReply;
+use super::webhook_reply_helpers::to_webhook_reply;
+
+use crate::ic::guards::not_in_maintenance_mode;
+use crate::stable_user::user_map;
+use crate::stable_webhook::webhook_map;
+
+/// caller's webhook and its pending and failed deliveries
+#[query(guard = "not_in_maintenance_mode")]
+fn webhook() -> Result<WebhookReply, String> {
+    let user_id = user_map::get_by_caller()?.ok_or("User not found")?.user_id;
+    let webhook = webhook_map::get_by_user_id(user_id).ok_or("Webhook not found")?;
+    Ok(to_webhook_reply(&webhook))
+}
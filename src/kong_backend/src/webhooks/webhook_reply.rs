@@ -0,0 +1,20 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDeliveryReply {
+    pub delivery_id: u64,
+    pub request_id: u64,
+    pub status: String,
+    pub attempts: u32,
+    pub message: Option<String>,
+    pub ts: u64,
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookReply {
+    pub canister_id: String,
+    pub method: String,
+    pub deliveries: Vec<WebhookDeliveryReply>, // pending and failed deliveries, most recent first
+    pub ts: u64,
+}
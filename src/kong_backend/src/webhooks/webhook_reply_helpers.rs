@@ -0,0 +1,28 @@
+use super::webhook_reply::{WebhookDeliveryReply, WebhookReply};
+
+use crate::stable_webhook::stable_webhook::StableWebhook;
+use crate::stable_webhook::stable_webhook_delivery::StableWebhookDelivery;
+use crate::stable_webhook::webhook_delivery_map;
+
+pub fn to_webhook_reply(webhook: &StableWebhook) -> WebhookReply {
+    WebhookReply {
+        canister_id: webhook.canister_id.to_text(),
+        method: webhook.method.clone(),
+        deliveries: webhook_delivery_map::get_by_user_id(webhook.user_id)
+            .iter()
+            .map(to_webhook_delivery_reply)
+            .collect(),
+        ts: webhook.ts,
+    }
+}
+
+pub fn to_webhook_delivery_reply(delivery: &StableWebhookDelivery) -> WebhookDeliveryReply {
+    WebhookDeliveryReply {
+        delivery_id: delivery.delivery_id,
+        request_id: delivery.request_id,
+        status: delivery.status.to_string(),
+        attempts: delivery.attempts,
+        message: delivery.message.clone(),
+        ts: delivery.ts,
+    }
+}
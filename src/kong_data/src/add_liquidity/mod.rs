@@ -1,3 +1,4 @@
 pub mod add_liquidity_args;
 pub mod add_liquidity_reply;
 pub mod add_liquidity_reply_helpers;
+pub mod add_liquidity_single_args;
@@ -0,0 +1,2 @@
+pub mod flash_loan_args;
+pub mod flash_loan_reply;
@@ -4,9 +4,12 @@ mod canister;
 mod chains;
 mod claims;
 mod controllers;
+mod flash_loans;
 mod helpers;
 mod ic;
+mod limit_orders;
 mod pools;
+mod protocol_fees;
 mod remove_liquidity;
 mod requests;
 mod send;
@@ -0,0 +1,25 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct LimitOrderReply {
+    pub order_id: u64,
+    pub request_id: u64,
+    pub status: String,
+    pub pay_chain: String,
+    pub pay_address: String,
+    pub pay_symbol: String,
+    pub pay_amount: Nat,
+    pub remaining_pay_amount: Nat,
+    pub receive_chain: String,
+    pub receive_address: String,
+    pub receive_symbol: String,
+    pub receive_amount: Nat,
+    pub received_amount: Nat,
+    pub limit_price: f64,
+    pub to_address: String,
+    pub expires_at: Option<u64>,
+    pub fill_request_ids: Vec<u64>,
+    pub claim_id: Option<u64>,
+    pub ts: u64,
+}
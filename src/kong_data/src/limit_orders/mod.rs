@@ -0,0 +1,2 @@
+pub mod limit_order_args;
+pub mod limit_order_reply;
@@ -0,0 +1,19 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct CollectProtocolFeesReply {
+    pub tx_id: u64,
+    pub status: String,
+    pub pool_symbol: String,
+    pub treasury: String,
+    pub chain_0: String,
+    pub symbol_0: String,
+    pub amount_0: Nat,
+    pub block_id_0: Option<Nat>,
+    pub chain_1: String,
+    pub symbol_1: String,
+    pub amount_1: Nat,
+    pub block_id_1: Option<Nat>,
+    pub ts: u64,
+}
@@ -0,0 +1,32 @@
+use super::collect_protocol_fees_reply::CollectProtocolFeesReply;
+
+use crate::stable_pool::pool_map;
+use crate::stable_tx::collect_protocol_fees_tx::CollectProtocolFeesTx;
+
+pub fn to_collect_protocol_fees_reply(collect_protocol_fees_tx: &CollectProtocolFeesTx) -> CollectProtocolFeesReply {
+    let (pool_symbol, chain_0, symbol_0, chain_1, symbol_1) = match pool_map::get_by_pool_id(collect_protocol_fees_tx.pool_id) {
+        Some(pool) => (pool.symbol(), pool.chain_0(), pool.symbol_0(), pool.chain_1(), pool.symbol_1()),
+        None => (
+            "Pool symbol not found".to_string(),
+            "Pool chain_0 not found".to_string(),
+            "Pool symbol_0 not found".to_string(),
+            "Pool chain_1 not found".to_string(),
+            "Pool symbol_1 not found".to_string(),
+        ),
+    };
+    CollectProtocolFeesReply {
+        tx_id: collect_protocol_fees_tx.tx_id,
+        status: collect_protocol_fees_tx.status.to_string(),
+        pool_symbol,
+        treasury: collect_protocol_fees_tx.treasury.to_string(),
+        chain_0,
+        symbol_0,
+        amount_0: collect_protocol_fees_tx.amount_0.clone(),
+        block_id_0: collect_protocol_fees_tx.block_id_0.clone(),
+        chain_1,
+        symbol_1,
+        amount_1: collect_protocol_fees_tx.amount_1.clone(),
+        block_id_1: collect_protocol_fees_tx.block_id_1.clone(),
+        ts: collect_protocol_fees_tx.ts,
+    }
+}
@@ -0,0 +1,2 @@
+pub mod collect_protocol_fees_reply;
+pub mod collect_protocol_fees_reply_helpers;
@@ -1,3 +1,4 @@
 pub mod remove_liquidity_args;
 pub mod remove_liquidity_reply;
 pub mod remove_liquidity_reply_helpers;
+pub mod remove_liquidity_single_args;
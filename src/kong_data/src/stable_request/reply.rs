@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use crate::add_liquidity::add_liquidity_reply::AddLiquidityReply;
 use crate::add_pool::add_pool_reply::AddPoolReply;
 use crate::claims::claim_reply::ClaimReply;
+use crate::flash_loans::flash_loan_reply::FlashLoanReply;
+use crate::limit_orders::limit_order_reply::LimitOrderReply;
 use crate::remove_liquidity::remove_liquidity_reply::RemoveLiquidityReply;
 use crate::send::send_reply::SendReply;
 use crate::swap::swap_reply::SwapReply;
@@ -17,4 +19,6 @@ pub enum Reply {
     Swap(SwapReply),
     Claim(ClaimReply),
     Send(SendReply),
+    LimitOrder(LimitOrderReply),
+    FlashLoan(FlashLoanReply),
 }
@@ -57,8 +57,10 @@ pub enum StatusCode {
     ReturnToken1,
     ReturnToken1Success,
     ReturnToken1Failed,
+    LPTokenAmountBelowMinimum,
     // remove liquidity
     RemoveLiquidityFromPool,
+    RemoveAmountsBelowMinimum,
     ReturnUserLPTokenAmount,
     ReturnUserLPTokenAmountSuccess,
     ReturnUserLPTokenAmountFailed,
@@ -88,6 +90,9 @@ pub enum StatusCode {
     ReturnPayToken,
     ReturnPayTokenSuccess,
     ReturnPayTokenFailed,
+    ReturnUnusedPayToken,
+    ReturnUnusedPayTokenSuccess,
+    ReturnUnusedPayTokenFailed,
     // claim
     ClaimToken,
     ClaimTokenSuccess,
@@ -107,7 +112,19 @@ pub enum StatusCode {
     SendLPTokenToUser,
     SendLPTokenToUserSuccess,
     SendLPTokenToUserFailed,
+    // flash loan
+    FlashLoanCallback,
+    FlashLoanCallbackSuccess,
+    FlashLoanCallbackFailed,
+    VerifyFlashLoanRepayment,
+    VerifyFlashLoanRepaymentSuccess,
+    VerifyFlashLoanRepaymentFailed,
+    // pool creation fee
+    SendPoolCreationFee,
+    SendPoolCreationFeeSuccess,
+    SendPoolCreationFeeFailed,
     // general
+    DeadlineExceeded,
     Success,
     Failed,
 }
@@ -152,7 +169,9 @@ impl std::fmt::Display for StatusCode {
             StatusCode::ReturnToken1 => write!(f, "Returning token 1"),
             StatusCode::ReturnToken1Success => write!(f, "Token 1 returned"),
             StatusCode::ReturnToken1Failed => write!(f, "Failed sending token 1"),
+            StatusCode::LPTokenAmountBelowMinimum => write!(f, "LP token amount below minimum"),
             StatusCode::RemoveLiquidityFromPool => write!(f, "Remove liquidity from pool"),
+            StatusCode::RemoveAmountsBelowMinimum => write!(f, "Remove amounts below minimum"),
             StatusCode::ReturnUserLPTokenAmount => write!(f, "Returning user LP token amount"),
             StatusCode::ReturnUserLPTokenAmountSuccess => write!(f, "User LP token amount returned"),
             StatusCode::ReturnUserLPTokenAmountFailed => write!(f, "Failed returning user LP token amount"),
@@ -181,6 +200,9 @@ impl std::fmt::Display for StatusCode {
             StatusCode::ReturnPayToken => write!(f, "Returning pay token"),
             StatusCode::ReturnPayTokenSuccess => write!(f, "Pay token returned"),
             StatusCode::ReturnPayTokenFailed => write!(f, "Failing returning pay token"),
+            StatusCode::ReturnUnusedPayToken => write!(f, "Returning unused pay token"),
+            StatusCode::ReturnUnusedPayTokenSuccess => write!(f, "Unused pay token returned"),
+            StatusCode::ReturnUnusedPayTokenFailed => write!(f, "Failed returning unused pay token"),
             StatusCode::ClaimToken => write!(f, "Claiming token"),
             StatusCode::ClaimTokenSuccess => write!(f, "Token claimed"),
             StatusCode::ClaimTokenFailed => write!(f, "Failed claiming token"),
@@ -196,6 +218,16 @@ impl std::fmt::Display for StatusCode {
             StatusCode::SendLPTokenToUser => write!(f, "Sending LP token to user"),
             StatusCode::SendLPTokenToUserSuccess => write!(f, "LP token sent to user"),
             StatusCode::SendLPTokenToUserFailed => write!(f, "Failed sending LP token to user"),
+            StatusCode::FlashLoanCallback => write!(f, "Calling flash loan callback"),
+            StatusCode::FlashLoanCallbackSuccess => write!(f, "Flash loan callback returned"),
+            StatusCode::FlashLoanCallbackFailed => write!(f, "Failed calling flash loan callback"),
+            StatusCode::VerifyFlashLoanRepayment => write!(f, "Collecting flash loan repayment"),
+            StatusCode::VerifyFlashLoanRepaymentSuccess => write!(f, "Flash loan repaid"),
+            StatusCode::VerifyFlashLoanRepaymentFailed => write!(f, "Failed collecting flash loan repayment"),
+            StatusCode::SendPoolCreationFee => write!(f, "Sending pool creation fee"),
+            StatusCode::SendPoolCreationFeeSuccess => write!(f, "Pool creation fee sent"),
+            StatusCode::SendPoolCreationFeeFailed => write!(f, "Failed sending pool creation fee"),
+            StatusCode::DeadlineExceeded => write!(f, "Deadline exceeded"),
             StatusCode::Success => write!(f, "Success"),
             StatusCode::Failed => write!(f, "Failed"),
         }
@@ -0,0 +1,20 @@
+use candid::{CandidType, Nat};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::{Deserialize, Serialize};
+
+use super::status_tx::StatusTx;
+
+/// collection of Kong's share of the LP fees of a pool to the treasury
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct CollectProtocolFeesTx {
+    pub tx_id: u64,
+    pub pool_id: u32,
+    pub user_id: u32, // King Kong who collected the fees
+    pub status: StatusTx,
+    pub treasury: Account,
+    pub amount_0: Nat, // kong_fee_0 collected, including the gas fee of the transfer. if PendingReconciliation, kong_fee_0 taken out of the pool
+    pub block_id_0: Option<Nat>,
+    pub amount_1: Nat,
+    pub block_id_1: Option<Nat>,
+    pub ts: u64,
+}
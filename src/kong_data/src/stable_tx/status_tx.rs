@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 pub enum StatusTx {
     Success,
     Failed,
+    PendingReconciliation, // outcome of a transfer is unknown and has to be checked against the ledger
 }
 
 impl std::fmt::Display for StatusTx {
@@ -12,6 +13,7 @@ impl std::fmt::Display for StatusTx {
         match self {
             StatusTx::Success => write!(f, "Success"),
             StatusTx::Failed => write!(f, "Failed"),
+            StatusTx::PendingReconciliation => write!(f, "Pending reconciliation"),
         }
     }
 }
@@ -69,6 +69,14 @@ pub fn get_by_user_and_token_id(
                                 return Some(v.clone());
                             }
                         }
+                        StableTx::CollectProtocolFees(ref collect_protocol_fees_tx) => {
+                            let pool_id = collect_protocol_fees_tx.pool_id;
+                            let token_0 = pool_map::get_by_pool_id(pool_id).map(|pool| pool.token_id_0)?;
+                            let token_1 = pool_map::get_by_pool_id(pool_id).map(|pool| pool.token_id_1)?;
+                            if token_0 == token_id || token_1 == token_id {
+                                return Some(v.clone());
+                            }
+                        }
                     }
                     return None;
                 }